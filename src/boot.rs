@@ -0,0 +1,144 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Helpers for handing a device tree over to a Linux kernel.
+//!
+//! Bootloaders, kexec implementations and VMMs typically apply the same set of
+//! fixups to the `/chosen` node before jumping to the kernel: setting the
+//! command line, describing the initrd, providing entropy and restricting the
+//! memory the kernel may use. This module adds methods for these fixups to
+//! [`DeviceTree`], creating the `/chosen` node if needed.
+//!
+//! # Examples
+//!
+//! ```
+//! # use dtoolkit::model::DeviceTree;
+//! let mut tree = DeviceTree::new();
+//! tree.set_bootargs("console=ttyS0 earlycon");
+//! tree.set_initrd(0x4800_0000, 0x4880_0000).unwrap();
+//! tree.set_kaslr_seed(0x1234_5678_9abc_def0);
+//!
+//! let chosen = tree.root.child("chosen").unwrap();
+//! assert_eq!(
+//!     chosen.property("bootargs").unwrap().as_str(),
+//!     Ok("console=ttyS0 earlycon")
+//! );
+//! ```
+
+use alloc::vec::Vec;
+
+use crate::error::FdtError;
+use crate::model::property::push_cells;
+use crate::model::{DeviceTree, DeviceTreeProperty};
+use crate::standard::UefiMemoryMap;
+
+impl DeviceTree {
+    /// Sets the `bootargs` property of the `/chosen` node to the given kernel
+    /// command line.
+    pub fn set_bootargs(&mut self, bootargs: &str) {
+        let mut value = Vec::with_capacity(bootargs.len() + 1);
+        value.extend_from_slice(bootargs.as_bytes());
+        value.push(0);
+        self.chosen_node_mut()
+            .add_property(DeviceTreeProperty::new("bootargs", value));
+    }
+
+    /// Sets the `linux,initrd-start` and `linux,initrd-end` properties of the
+    /// `/chosen` node.
+    ///
+    /// The values are encoded using the root node's `#address-cells`, like
+    /// U-Boot does.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FdtError::ValueTooBig`] if one of the addresses doesn't fit
+    /// in the root node's `#address-cells`, or [`FdtError::TooManyCells`] if
+    /// `#address-cells` is more than 4.
+    pub fn set_initrd(&mut self, start: u64, end: u64) -> Result<(), FdtError> {
+        let address_cells = self.root_address_space().address_cells;
+        let mut start_value = Vec::new();
+        push_cells(&mut start_value, start, address_cells)?;
+        let mut end_value = Vec::new();
+        push_cells(&mut end_value, end, address_cells)?;
+
+        let chosen = self.chosen_node_mut();
+        chosen.add_property(DeviceTreeProperty::new("linux,initrd-start", start_value));
+        chosen.add_property(DeviceTreeProperty::new("linux,initrd-end", end_value));
+        Ok(())
+    }
+
+    /// Sets the `kaslr-seed` property of the `/chosen` node, used by the kernel
+    /// to randomize its own base address.
+    pub fn set_kaslr_seed(&mut self, seed: u64) {
+        self.chosen_node_mut()
+            .add_property(DeviceTreeProperty::new("kaslr-seed", seed.to_be_bytes()));
+    }
+
+    /// Sets the `rng-seed` property of the `/chosen` node, used by the kernel
+    /// to seed its random number generator early during boot.
+    pub fn set_rng_seed(&mut self, seed: &[u8]) {
+        self.chosen_node_mut()
+            .add_property(DeviceTreeProperty::new("rng-seed", seed));
+    }
+
+    /// Sets the `linux,usable-memory-range` property of the `/chosen` node to
+    /// the given `(address, size)` regions.
+    ///
+    /// This restricts the kernel to the given memory regions, which is
+    /// typically used for crash dump kernels. The values are encoded using the
+    /// root node's `#address-cells` and `#size-cells`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FdtError::ValueTooBig`] if one of the addresses or sizes
+    /// doesn't fit in the root node's `#address-cells` or `#size-cells`
+    /// respectively, or [`FdtError::TooManyCells`] if either is more than 4.
+    pub fn set_usable_memory_range(&mut self, regions: &[(u64, u64)]) -> Result<(), FdtError> {
+        let address_space = self.root_address_space();
+        let mut value = Vec::new();
+        for &(address, size) in regions {
+            push_cells(&mut value, address, address_space.address_cells)?;
+            push_cells(&mut value, size, address_space.size_cells)?;
+        }
+        self.chosen_node_mut()
+            .add_property(DeviceTreeProperty::new("linux,usable-memory-range", value));
+        Ok(())
+    }
+
+    /// Sets the `linux,uefi-system-table` property of the `/chosen` node to the
+    /// physical address of the UEFI system table.
+    pub fn set_uefi_system_table(&mut self, address: u64) {
+        self.chosen_node_mut().add_property(DeviceTreeProperty::new(
+            "linux,uefi-system-table",
+            address.to_be_bytes(),
+        ));
+    }
+
+    /// Sets the `linux,uefi-mmap-start`, `linux,uefi-mmap-size`,
+    /// `linux,uefi-mmap-desc-size` and `linux,uefi-mmap-desc-ver` properties of
+    /// the `/chosen` node, describing the UEFI memory map to the kernel.
+    pub fn set_uefi_memory_map(&mut self, memory_map: &UefiMemoryMap) {
+        let chosen = self.chosen_node_mut();
+        chosen.add_property(DeviceTreeProperty::new(
+            "linux,uefi-mmap-start",
+            memory_map.start.to_be_bytes(),
+        ));
+        chosen.add_property(DeviceTreeProperty::new(
+            "linux,uefi-mmap-size",
+            memory_map.size.to_be_bytes(),
+        ));
+        chosen.add_property(DeviceTreeProperty::new(
+            "linux,uefi-mmap-desc-size",
+            memory_map.descriptor_size.to_be_bytes(),
+        ));
+        chosen.add_property(DeviceTreeProperty::new(
+            "linux,uefi-mmap-desc-ver",
+            memory_map.descriptor_version.to_be_bytes(),
+        ));
+    }
+}
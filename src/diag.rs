@@ -0,0 +1,36 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Diagnostics emitted through the `log` crate.
+//!
+//! With the `log` feature enabled, the library logs the steps it takes when
+//! modifying device trees and the problems it recovers from, with the
+//! `dtoolkit` target. Without it, these macros compile to nothing.
+
+/// Logs a step taken by the library at the `debug` level.
+macro_rules! debug {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "log")]
+        ::log::debug!(target: "dtoolkit", $($arg)+);
+        #[cfg(not(feature = "log"))]
+        let _ = ::core::format_args!($($arg)+);
+    }};
+}
+
+/// Logs a problem the library recovered from at the `warn` level.
+macro_rules! warning {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "log")]
+        ::log::warn!(target: "dtoolkit", $($arg)+);
+        #[cfg(not(feature = "log"))]
+        let _ = ::core::format_args!($($arg)+);
+    }};
+}
+
+pub(crate) use debug;
+pub(crate) use warning;
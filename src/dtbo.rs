@@ -0,0 +1,442 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Android [DTB/DTBO partition] images.
+//!
+//! Android devices store device trees and overlays in a container consisting
+//! of a `dt_table_header`, followed by an array of `dt_table_entry` structures
+//! and the blobs they point to. This module provides the [`DtTable`] struct for
+//! reading such images, and (with the `write` feature) a [`DtTableBuilder`]
+//! for creating them, replacing the need for `mkdtimg`.
+//!
+//! [DTB/DTBO partition]: https://source.android.com/docs/core/architecture/dto/partitions
+
+#[cfg(feature = "write")]
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Formatter};
+use core::mem::offset_of;
+
+use zerocopy::byteorder::big_endian;
+use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned};
+
+use crate::error::{FdtErrorKind, FdtParseError};
+use crate::fdt::Fdt;
+#[cfg(feature = "write")]
+use crate::model::DeviceTree;
+
+/// Magic number of a `dt_table_header`.
+const DT_TABLE_MAGIC: u32 = 0xd7b7_ab1e;
+/// Default flash page size used by `mkdtimg`.
+#[cfg(feature = "write")]
+const DEFAULT_PAGE_SIZE: u32 = 2048;
+
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone, FromBytes, IntoBytes, Unaligned, Immutable, KnownLayout)]
+struct DtTableHeader {
+    /// Magic number of the image.
+    magic: big_endian::U32,
+    /// Total size of the image, including the header, entries and blobs.
+    total_size: big_endian::U32,
+    /// Size of the header.
+    header_size: big_endian::U32,
+    /// Size of a single entry.
+    dt_entry_size: big_endian::U32,
+    /// Number of entries.
+    dt_entry_count: big_endian::U32,
+    /// Offset of the first entry from the start of the image.
+    dt_entries_offset: big_endian::U32,
+    /// Flash page size assumed by the image.
+    page_size: big_endian::U32,
+    /// Version of the image format.
+    version: big_endian::U32,
+}
+
+#[repr(C, packed)]
+#[derive(Debug, Copy, Clone, FromBytes, IntoBytes, Unaligned, Immutable, KnownLayout)]
+struct DtTableEntryRaw {
+    /// Size of the blob.
+    dt_size: big_endian::U32,
+    /// Offset of the blob from the start of the image.
+    dt_offset: big_endian::U32,
+    /// Board ID.
+    id: big_endian::U32,
+    /// Board revision.
+    rev: big_endian::U32,
+    /// Board-specific custom fields.
+    custom: [big_endian::U32; 4],
+}
+
+/// An Android DTB/DTBO partition image.
+///
+/// # Examples
+///
+/// ```
+/// # use dtoolkit::dtbo::DtTable;
+/// # let image = include_bytes!("../tests/dtb/test_dtbo.img");
+/// let table = DtTable::new(image).unwrap();
+/// for entry in table.entries() {
+///     let entry = entry.unwrap();
+///     let fdt = entry.fdt().unwrap();
+///     println!("id {:#x} rev {:#x}: {}", entry.id(), entry.rev(), fdt);
+/// }
+/// ```
+#[derive(Clone, Copy)]
+pub struct DtTable<'a> {
+    data: &'a [u8],
+}
+
+impl Debug for DtTable<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(
+            f,
+            "DtTable {{ data: {} bytes at {:?} }}",
+            self.data.len(),
+            self.data.as_ptr()
+        )
+    }
+}
+
+impl<'a> DtTable<'a> {
+    /// Creates a new `DtTable` from the given byte slice.
+    ///
+    /// Trailing data after `total_size` bytes (e.g. the rest of a partition
+    /// dump) is ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::InvalidLength`] if `data` is too short to
+    /// contain the header or the number of bytes declared in it.
+    ///
+    /// Returns an [`FdtErrorKind::InvalidMagic`] if the `magic` field in the
+    /// header is not `0xd7b7ab1e`.
+    ///
+    /// Returns an [`FdtErrorKind::InvalidHeader`] if the header or the entry
+    /// table doesn't fit in the image.
+    pub fn new(data: &'a [u8]) -> Result<Self, FdtParseError> {
+        let Ok((header, _)) = DtTableHeader::ref_from_prefix(data) else {
+            return Err(FdtParseError::new(FdtErrorKind::InvalidLength, 0));
+        };
+
+        if header.magic.get() != DT_TABLE_MAGIC {
+            return Err(FdtParseError::new(
+                FdtErrorKind::InvalidMagic,
+                offset_of!(DtTableHeader, magic),
+            ));
+        }
+
+        let total_size = header.total_size.get() as usize;
+        if total_size > data.len() || total_size < size_of::<DtTableHeader>() {
+            return Err(FdtParseError::new(
+                FdtErrorKind::InvalidLength,
+                offset_of!(DtTableHeader, total_size),
+            ));
+        }
+        if (header.header_size.get() as usize) < size_of::<DtTableHeader>() {
+            return Err(FdtParseError::new(
+                FdtErrorKind::InvalidHeader("header size too small"),
+                offset_of!(DtTableHeader, header_size),
+            ));
+        }
+        if (header.dt_entry_size.get() as usize) < size_of::<DtTableEntryRaw>() {
+            return Err(FdtParseError::new(
+                FdtErrorKind::InvalidHeader("entry size too small"),
+                offset_of!(DtTableHeader, dt_entry_size),
+            ));
+        }
+        let entries_end = (header.dt_entry_count.get() as usize)
+            .checked_mul(header.dt_entry_size.get() as usize)
+            .and_then(|size| size.checked_add(header.dt_entries_offset.get() as usize));
+        if entries_end.is_none_or(|end| end > total_size) {
+            return Err(FdtParseError::new(
+                FdtErrorKind::InvalidHeader("entry table overflows"),
+                offset_of!(DtTableHeader, dt_entry_count),
+            ));
+        }
+
+        Ok(Self {
+            data: &data[..total_size],
+        })
+    }
+
+    fn header(self) -> &'a DtTableHeader {
+        let (header, _remaining_bytes) = DtTableHeader::ref_from_prefix(self.data)
+            .expect("new() checks if the slice is at least as big as the header");
+        header
+    }
+
+    /// Returns the underlying data slice of the image.
+    #[must_use]
+    pub fn data(self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Returns the version of the image format.
+    #[must_use]
+    pub fn version(self) -> u32 {
+        self.header().version.get()
+    }
+
+    /// Returns the flash page size the image was created for.
+    #[must_use]
+    pub fn page_size(self) -> u32 {
+        self.header().page_size.get()
+    }
+
+    /// Returns the number of entries in the image.
+    #[must_use]
+    pub fn len(self) -> usize {
+        self.header().dt_entry_count.get() as usize
+    }
+
+    /// Returns whether the image contains no entries.
+    #[must_use]
+    pub fn is_empty(self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the entry at the given index, or `None` if it is out of range.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::InvalidOffset`] if the blob the entry points
+    /// to doesn't fit in the image.
+    pub fn entry(self, index: usize) -> Option<Result<DtTableEntry<'a>, FdtParseError>> {
+        if index >= self.len() {
+            return None;
+        }
+        let header = self.header();
+        let offset =
+            header.dt_entries_offset.get() as usize + index * header.dt_entry_size.get() as usize;
+        let Some(Ok((raw, _))) = self
+            .data
+            .get(offset..)
+            .map(DtTableEntryRaw::ref_from_prefix)
+        else {
+            return Some(Err(FdtParseError::new(FdtErrorKind::InvalidOffset, offset)));
+        };
+
+        let dt_offset = raw.dt_offset.get() as usize;
+        let dt_size = raw.dt_size.get() as usize;
+        let Some(blob) = dt_offset
+            .checked_add(dt_size)
+            .and_then(|end| self.data.get(dt_offset..end))
+        else {
+            return Some(Err(FdtParseError::new(
+                FdtErrorKind::InvalidOffset,
+                offset + offset_of!(DtTableEntryRaw, dt_offset),
+            )));
+        };
+
+        Some(Ok(DtTableEntry {
+            data: blob,
+            id: raw.id.get(),
+            rev: raw.rev.get(),
+            custom: raw.custom.map(big_endian::U32::get),
+        }))
+    }
+
+    /// Returns an iterator over the entries of the image.
+    pub fn entries(self) -> impl Iterator<Item = Result<DtTableEntry<'a>, FdtParseError>> + 'a {
+        (0..self.len()).map_while(move |index| self.entry(index))
+    }
+
+    /// Creates a new [`DtTableBuilder`].
+    #[cfg(feature = "write")]
+    #[must_use]
+    pub fn builder() -> DtTableBuilder {
+        DtTableBuilder::new()
+    }
+}
+
+/// A single device tree blob stored in a [`DtTable`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct DtTableEntry<'a> {
+    data: &'a [u8],
+    id: u32,
+    rev: u32,
+    custom: [u32; 4],
+}
+
+impl<'a> DtTableEntry<'a> {
+    /// Returns the raw blob of this entry.
+    #[must_use]
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Returns the board ID of this entry.
+    #[must_use]
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+
+    /// Returns the board revision of this entry.
+    #[must_use]
+    pub fn rev(&self) -> u32 {
+        self.rev
+    }
+
+    /// Returns the board-specific custom fields of this entry.
+    ///
+    /// In version 1 images, the first field holds the entry flags (including
+    /// the compression format) instead.
+    #[must_use]
+    pub fn custom(&self) -> [u32; 4] {
+        self.custom
+    }
+
+    /// Parses the blob of this entry as an [`Fdt`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Fdt::new`]. Compressed entries will fail
+    /// with an [`FdtErrorKind::InvalidMagic`].
+    pub fn fdt(&self) -> Result<Fdt<'a>, FdtParseError> {
+        Fdt::new(self.data)
+    }
+}
+
+/// A builder for creating Android DTB/DTBO partition images.
+///
+/// # Examples
+///
+/// ```
+/// # use dtoolkit::dtbo::{DtTable, DtTableBuilder};
+/// # use dtoolkit::model::DeviceTree;
+/// let image = DtTable::builder()
+///     .entry(&DeviceTree::new(), 0x1234, 1, [0; 4])
+///     .build();
+/// let table = DtTable::new(&image).unwrap();
+/// assert_eq!(table.entry(0).unwrap().unwrap().id(), 0x1234);
+/// ```
+#[cfg(feature = "write")]
+#[derive(Debug)]
+pub struct DtTableBuilder {
+    page_size: u32,
+    entries: Vec<(Vec<u8>, DtTableEntryRaw)>,
+}
+
+#[cfg(feature = "write")]
+impl Default for DtTableBuilder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(feature = "write")]
+impl DtTableBuilder {
+    /// Creates a new, empty `DtTableBuilder`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            page_size: DEFAULT_PAGE_SIZE,
+            entries: Vec::new(),
+        }
+    }
+
+    /// Sets the flash page size recorded in the image header.
+    ///
+    /// Defaults to 2048, like `mkdtimg`.
+    #[must_use]
+    pub fn page_size(mut self, page_size: u32) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    /// Adds a device tree to the image.
+    #[must_use]
+    pub fn entry(self, tree: &DeviceTree, id: u32, rev: u32, custom: [u32; 4]) -> Self {
+        self.entry_dtb(tree.to_dtb(), id, rev, custom)
+    }
+
+    /// Adds an already serialized device tree blob to the image.
+    #[must_use]
+    pub fn entry_dtb(
+        mut self,
+        dtb: impl Into<Vec<u8>>,
+        id: u32,
+        rev: u32,
+        custom: [u32; 4],
+    ) -> Self {
+        self.entries.push((
+            dtb.into(),
+            DtTableEntryRaw {
+                dt_size: 0.into(),
+                dt_offset: 0.into(),
+                id: id.into(),
+                rev: rev.into(),
+                custom: custom.map(Into::into),
+            },
+        ));
+        self
+    }
+
+    /// Builds the image.
+    ///
+    /// Identical blobs are only stored once, like `mkdtimg` does.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the size of the image exceeds [`u32::MAX`].
+    #[must_use]
+    pub fn build(mut self) -> Vec<u8> {
+        let header_size = size_of::<DtTableHeader>();
+        let entries_size = self.entries.len() * size_of::<DtTableEntryRaw>();
+        let mut blobs: Vec<u8> = Vec::new();
+        let mut blob_offsets: Vec<(usize, usize)> = Vec::new();
+
+        for index in 0..self.entries.len() {
+            let dtb = &self.entries[index].0;
+            let existing = self.entries[..index]
+                .iter()
+                .zip(&blob_offsets)
+                .find(|((other, _), _)| other == dtb)
+                .map(|(_, &offset)| offset);
+            let (offset, size) = existing.unwrap_or_else(|| {
+                let offset = header_size + entries_size + blobs.len();
+                blobs.extend_from_slice(dtb);
+                (offset, dtb.len())
+            });
+            blob_offsets.push((offset, size));
+
+            let raw = &mut self.entries[index].1;
+            raw.dt_offset = u32::try_from(offset).expect("dt_offset exceeds u32").into();
+            raw.dt_size = u32::try_from(size).expect("dt_size exceeds u32").into();
+        }
+
+        let total_size = header_size + entries_size + blobs.len();
+        let header = DtTableHeader {
+            magic: DT_TABLE_MAGIC.into(),
+            total_size: u32::try_from(total_size)
+                .expect("total_size exceeds u32")
+                .into(),
+            header_size: u32::try_from(header_size)
+                .expect("header_size exceeds u32")
+                .into(),
+            dt_entry_size: u32::try_from(size_of::<DtTableEntryRaw>())
+                .expect("dt_entry_size exceeds u32")
+                .into(),
+            dt_entry_count: u32::try_from(self.entries.len())
+                .expect("dt_entry_count exceeds u32")
+                .into(),
+            dt_entries_offset: u32::try_from(header_size)
+                .expect("dt_entries_offset exceeds u32")
+                .into(),
+            page_size: self.page_size.into(),
+            version: 0.into(),
+        };
+
+        let mut image = Vec::with_capacity(total_size);
+        image.extend_from_slice(header.as_bytes());
+        for (_, raw) in &self.entries {
+            image.extend_from_slice(raw.as_bytes());
+        }
+        image.extend_from_slice(&blobs);
+        image
+    }
+}
@@ -7,11 +7,29 @@
 // except according to those terms.
 
 //! Error types for the `dtoolkit` crate.
+//!
+//! All errors implement [`core::error::Error`], with or without the `std`
+//! feature. Each error also has a numeric code, returned by its `code()`
+//! method, for consumers which can't afford to format error messages, such as
+//! firmware logging or FFI. Codes are stable across versions of the crate:
+//! a code is never reused for a different error, and new errors get new codes.
+//!
+//! | Codes     | Errors                                       |
+//! |-----------|----------------------------------------------|
+//! | 1–99      | [`FdtErrorKind`], the kinds of parse errors  |
+//! | 100–199   | The other variants of [`FdtError`]           |
+//! | 200–299   | `PropertyError`, from the `write` feature    |
+//! | 300–399   | `OverlayError`, from the `write` feature     |
+//! | 400–499   | `QueryError`, from the `query` feature       |
 
 use core::fmt::{self, Display, Formatter};
 
 use thiserror::Error;
 
+use crate::fdt::FdtNode;
+#[cfg(feature = "alloc")]
+use crate::path::DtPathBuf;
+
 /// An error that can occur when parsing or accessing a device tree.
 #[derive(Clone, Debug, Eq, Error, PartialEq)]
 pub enum FdtError {
@@ -30,6 +48,12 @@ pub enum FdtError {
     /// The required `/memory` node wasn't found.
     #[error("/memory node missing")]
     MemoryMissing,
+    /// A property required by another property or binding wasn't found.
+    #[error("required property {name} missing")]
+    MissingProperty {
+        /// The name of the missing property.
+        name: &'static str,
+    },
     /// The size of a prop-encoded-array property wasn't a multiple of the
     /// expected element size.
     #[error(
@@ -48,6 +72,270 @@ pub enum FdtError {
         /// The number of (32-bit) cells in the field.
         cells: usize,
     },
+    /// A fixed-capacity container ran out of space for the given kind of
+    /// items.
+    #[error("Capacity for {0} exceeded")]
+    CapacityExceeded(&'static str),
+    /// The buffer given to serialize a device tree into was too small.
+    #[error("Output buffer too small, {needed} bytes needed")]
+    BufferTooSmall {
+        /// The number of bytes needed.
+        needed: usize,
+    },
+    /// An `mtdparts` partition description couldn't be parsed.
+    #[error("Invalid mtdparts: {0}")]
+    InvalidMtdParts(&'static str),
+    /// Tried to encode a value into a prop-encoded-array field which was too
+    /// small to hold it.
+    #[error("value {value:#x} doesn't fit in a prop-encoded-array field of {cells} cells")]
+    ValueTooBig {
+        /// The value that was being encoded.
+        value: u64,
+        /// The number of (32-bit) cells in the field.
+        cells: usize,
+    },
+    /// A device tree overlay couldn't be applied.
+    #[error("Invalid overlay: {0}")]
+    InvalidOverlay(&'static str),
+    /// An `interrupt-map` property couldn't be parsed.
+    #[error("Invalid interrupt-map: {0}")]
+    InvalidInterruptMap(&'static str),
+    /// A property referred to a phandle which no node has.
+    #[error("No node with phandle {0:#x}")]
+    PhandleNotFound(u32),
+    /// A PCI address didn't have the 3 cells of the PCI bus binding.
+    #[error("PCI address was {cells} cells, but should have been 3")]
+    InvalidPciAddress {
+        /// The number of (32-bit) cells in the address.
+        cells: usize,
+    },
+    /// Tried to translate an address through a bus which doesn't map the
+    /// addresses of its children into the address space of its parent.
+    #[error("Bus at offset {node_offset:#x} isn't translatable")]
+    NotTranslatable {
+        /// The offset of the bus node in the device tree blob.
+        node_offset: usize,
+    },
+    /// The `phy-mode` or `phy-connection-type` property of a node had an
+    /// unknown value.
+    #[error("Invalid PHY mode")]
+    InvalidPhyMode,
+    /// Tried to rename a node to the name of one of its siblings.
+    #[error("A sibling node already has this name")]
+    DuplicateChild,
+    /// A node handle didn't refer to a node of the fixed-capacity tree it was
+    /// used with.
+    #[error("Invalid node handle {0}")]
+    InvalidNodeId(usize),
+}
+
+impl FdtError {
+    /// Returns the stable numeric code of this error.
+    ///
+    /// Parse errors have the code of their [`FdtErrorKind`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::error::FdtError;
+    /// assert_eq!(FdtError::MemoryMissing.code(), 103);
+    /// ```
+    #[must_use]
+    pub const fn code(&self) -> u16 {
+        match self {
+            Self::Parse(error) => error.code(),
+            Self::InvalidStatus => 100,
+            Self::CpusMissing => 101,
+            Self::CpuMissingReg => 102,
+            Self::MemoryMissing => 103,
+            Self::MissingProperty { .. } => 104,
+            Self::PropEncodedArraySizeMismatch { .. } => 105,
+            Self::TooManyCells { .. } => 106,
+            Self::CapacityExceeded(_) => 107,
+            Self::BufferTooSmall { .. } => 108,
+            Self::InvalidMtdParts(_) => 109,
+            Self::ValueTooBig { .. } => 110,
+            Self::InvalidOverlay(_) => 111,
+            Self::InvalidInterruptMap(_) => 112,
+            Self::PhandleNotFound(_) => 113,
+            Self::InvalidPciAddress { .. } => 114,
+            Self::NotTranslatable { .. } => 115,
+            Self::InvalidPhyMode => 116,
+            Self::DuplicateChild => 117,
+            Self::InvalidNodeId(_) => 118,
+        }
+    }
+
+    /// Adds the node, and optionally the property, that caused this error.
+    ///
+    /// This looks up the path of the node so that it can be included in the
+    /// error message. Without the `alloc` feature, the path is kept in a
+    /// [`ContextPath`] and truncated if it is too long.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # let dtb = include_bytes!("../tests/dtb/test_props.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let node = fdt.find_node("/test-props").unwrap().unwrap();
+    /// let error = node
+    ///     .property("u64-prop")
+    ///     .unwrap()
+    ///     .unwrap()
+    ///     .as_prop_encoded_array_dyn(&[3])
+    ///     .map(|_| ())
+    ///     .unwrap_err()
+    ///     .with_context(node, Some("u64-prop"));
+    /// assert_eq!(error.property, Some("u64-prop"));
+    /// assert!(error.to_string().starts_with("u64-prop property of node"));
+    /// ```
+    #[must_use]
+    pub fn with_context(
+        self,
+        node: FdtNode<'_>,
+        property: Option<&'static str>,
+    ) -> FdtContextError {
+        FdtContextError {
+            #[cfg(feature = "alloc")]
+            node_path: node.path().ok(),
+            #[cfg(not(feature = "alloc"))]
+            node_path: {
+                let mut path = ContextPath::default();
+                node.write_path(&mut path).ok().map(|()| path)
+            },
+            node_offset: node.offset,
+            property,
+            error: self,
+        }
+    }
+}
+
+/// An [`FdtError`] along with the node and property which caused it.
+///
+/// This is created by [`FdtError::with_context`].
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[non_exhaustive]
+pub struct FdtContextError {
+    /// The error that occurred.
+    #[source]
+    pub error: FdtError,
+    /// The offset of the node in the device tree blob.
+    pub node_offset: usize,
+    #[cfg(feature = "alloc")]
+    node_path: Option<DtPathBuf>,
+    #[cfg(not(feature = "alloc"))]
+    node_path: Option<ContextPath>,
+    /// The name of the property which caused the error, if any.
+    pub property: Option<&'static str>,
+}
+
+impl FdtContextError {
+    /// Returns the stable numeric code of the underlying error.
+    #[must_use]
+    pub const fn code(&self) -> u16 {
+        self.error.code()
+    }
+
+    /// Returns the path of the node, if it could be determined.
+    ///
+    /// Without the `alloc` feature, this is truncated to [`CONTEXT_PATH_LEN`]
+    /// bytes.
+    #[must_use]
+    pub fn node_path(&self) -> Option<&str> {
+        let path = self.node_path.as_ref()?;
+        Some(path.as_str())
+    }
+}
+
+impl Display for FdtContextError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        if let Some(property) = self.property {
+            write!(f, "{property} property of ")?;
+        }
+        if let Some(path) = &self.node_path {
+            return write!(f, "node {path}: {}", self.error);
+        }
+        write!(f, "node at offset {}: {}", self.node_offset, self.error)
+    }
+}
+
+/// The maximum length in bytes of the node path kept by an [`FdtContextError`]
+/// without the `alloc` feature.
+pub const CONTEXT_PATH_LEN: usize = 48;
+
+/// A node path kept in a fixed-size buffer, for an [`FdtContextError`] without
+/// the `alloc` feature.
+///
+/// Paths longer than [`CONTEXT_PATH_LEN`] bytes are truncated, and displayed
+/// with a trailing `...`.
+///
+/// # Examples
+///
+/// ```
+/// # use core::fmt::Write;
+/// # use dtoolkit::error::ContextPath;
+/// let mut path = ContextPath::default();
+/// write!(path, "/soc/serial@1000").unwrap();
+/// assert_eq!(path.as_str(), "/soc/serial@1000");
+/// assert!(!path.is_truncated());
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ContextPath {
+    bytes: [u8; CONTEXT_PATH_LEN],
+    // A u8 rather than a usize to keep `FdtContextError` small.
+    len: u8,
+    truncated: bool,
+}
+
+impl ContextPath {
+    /// Returns the path, or as much of it as fits.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        // Only whole characters are written to the buffer.
+        core::str::from_utf8(&self.bytes[..usize::from(self.len)]).unwrap_or_default()
+    }
+
+    /// Returns whether the path was too long to fit.
+    #[must_use]
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+}
+
+impl Default for ContextPath {
+    fn default() -> Self {
+        Self {
+            bytes: [0; CONTEXT_PATH_LEN],
+            len: 0,
+            truncated: false,
+        }
+    }
+}
+
+impl fmt::Write for ContextPath {
+    /// Appends as much of `s` as fits, never failing.
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let start = usize::from(self.len);
+        let mut len = s.len().min(CONTEXT_PATH_LEN - start);
+        while !s.is_char_boundary(len) {
+            len -= 1;
+        }
+        self.bytes[start..start + len].copy_from_slice(&s.as_bytes()[..len]);
+        self.len = u8::try_from(start + len).map_err(|_| fmt::Error)?;
+        self.truncated |= len < s.len();
+        Ok(())
+    }
+}
+
+impl Display for ContextPath {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(self.as_str())?;
+        if self.truncated {
+            f.write_str("...")?;
+        }
+        Ok(())
+    }
 }
 
 /// An error that can occur when parsing a device tree.
@@ -60,9 +348,43 @@ pub struct FdtParseError {
 }
 
 impl FdtParseError {
-    pub(crate) fn new(kind: FdtErrorKind, offset: usize) -> Self {
+    pub(crate) const fn new(kind: FdtErrorKind, offset: usize) -> Self {
         Self { offset, kind }
     }
+
+    /// Returns the stable numeric code of the kind of this error.
+    #[must_use]
+    pub const fn code(&self) -> u16 {
+        self.kind.code()
+    }
+
+    /// Adds the node, and optionally the property, that caused this error.
+    ///
+    /// See [`FdtError::with_context`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # let dtb = include_bytes!("../tests/dtb/test_props.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let node = fdt.find_node("/test-props").unwrap().unwrap();
+    /// let property = node.property("str-prop").unwrap().unwrap();
+    /// let error = property
+    ///     .as_u32()
+    ///     .unwrap_err()
+    ///     .with_context(node, Some("str-prop"));
+    /// assert_eq!(error.property, Some("str-prop"));
+    /// assert_eq!(error.node_path(), Some("/test-props"));
+    /// ```
+    #[must_use]
+    pub fn with_context(
+        self,
+        node: FdtNode<'_>,
+        property: Option<&'static str>,
+    ) -> FdtContextError {
+        FdtError::from(self).with_context(node, property)
+    }
 }
 
 impl Display for FdtParseError {
@@ -102,4 +424,121 @@ pub enum FdtErrorKind {
     /// size.
     #[error("Memory reservation block has an entry that is unaligned or has invalid size")]
     MemReserveInvalid,
+    /// Nodes were nested deeper than the given maximum depth.
+    #[error("Nodes nested deeper than the maximum depth of {0}")]
+    TooDeep(usize),
+    /// A phandle had one of the reserved values 0 or `0xffffffff`.
+    #[error("Invalid phandle {0:#x}")]
+    InvalidPhandle(u32),
+    /// The device tree had more than the given maximum number of nodes.
+    #[error("More than the maximum of {0} nodes")]
+    TooManyNodes(usize),
+    /// A property value was bigger than the given maximum size in bytes.
+    #[error("Property bigger than the maximum size of {0} bytes")]
+    PropertyTooBig(usize),
+}
+
+impl FdtErrorKind {
+    /// Returns the stable numeric code of this kind of error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::error::FdtErrorKind;
+    /// assert_eq!(FdtErrorKind::InvalidMagic.code(), 1);
+    /// ```
+    #[must_use]
+    pub const fn code(&self) -> u16 {
+        match self {
+            Self::InvalidMagic => 1,
+            Self::UnsupportedVersion(_) => 2,
+            Self::InvalidLength => 3,
+            Self::InvalidHeader(_) => 4,
+            Self::BadToken(_) => 5,
+            Self::InvalidOffset => 6,
+            Self::InvalidString => 7,
+            Self::MemReserveNotTerminated => 8,
+            Self::MemReserveInvalid => 9,
+            Self::TooDeep(_) => 10,
+            Self::InvalidPhandle(_) => 11,
+            Self::TooManyNodes(_) => 12,
+            Self::PropertyTooBig(_) => 13,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_error<E: core::error::Error>(error: &E) -> &dyn core::error::Error {
+        error
+    }
+
+    #[test]
+    fn codes() {
+        let kinds = [
+            FdtErrorKind::InvalidMagic,
+            FdtErrorKind::UnsupportedVersion(1),
+            FdtErrorKind::InvalidLength,
+            FdtErrorKind::InvalidHeader(""),
+            FdtErrorKind::BadToken(0),
+            FdtErrorKind::InvalidOffset,
+            FdtErrorKind::InvalidString,
+            FdtErrorKind::MemReserveNotTerminated,
+            FdtErrorKind::MemReserveInvalid,
+            FdtErrorKind::TooDeep(0),
+            FdtErrorKind::InvalidPhandle(0),
+            FdtErrorKind::TooManyNodes(0),
+            FdtErrorKind::PropertyTooBig(0),
+        ];
+        assert!(kinds.iter().map(FdtErrorKind::code).eq(1..=13));
+
+        let errors = [
+            FdtError::InvalidStatus,
+            FdtError::CpusMissing,
+            FdtError::CpuMissingReg,
+            FdtError::MemoryMissing,
+            FdtError::MissingProperty { name: "" },
+            FdtError::PropEncodedArraySizeMismatch { size: 0, chunk: 0 },
+            FdtError::TooManyCells { cells: 0 },
+            FdtError::CapacityExceeded(""),
+            FdtError::BufferTooSmall { needed: 0 },
+            FdtError::InvalidMtdParts(""),
+            FdtError::ValueTooBig { value: 0, cells: 0 },
+            FdtError::InvalidOverlay(""),
+            FdtError::InvalidInterruptMap(""),
+            FdtError::PhandleNotFound(0),
+            FdtError::InvalidPciAddress { cells: 0 },
+            FdtError::NotTranslatable { node_offset: 0 },
+            FdtError::InvalidPhyMode,
+            FdtError::DuplicateChild,
+            FdtError::InvalidNodeId(0),
+        ];
+        assert!(errors.iter().map(FdtError::code).eq(100..=118));
+
+        let parse_error = FdtParseError::new(FdtErrorKind::TooDeep(3), 0);
+        let error = FdtError::from(parse_error.clone());
+        assert_eq!(error.code(), 10);
+        assert_eq!(
+            assert_error(&parse_error).to_string(),
+            "Nodes nested deeper than the maximum depth of 3 at offset 0"
+        );
+        assert!(assert_error(&error).source().is_some());
+    }
+
+    #[test]
+    fn context_path() {
+        use core::fmt::Write;
+
+        let mut path = ContextPath::default();
+        write!(path, "/{}", "a".repeat(CONTEXT_PATH_LEN - 2)).unwrap();
+        assert_eq!(path.as_str().len(), CONTEXT_PATH_LEN - 1);
+        assert!(!path.is_truncated());
+        // A character which doesn't fit isn't split.
+        write!(path, "é/b").unwrap();
+        assert_eq!(path.as_str().len(), CONTEXT_PATH_LEN - 1);
+        assert!(path.is_truncated());
+        assert!(path.to_string().ends_with("a..."));
+    }
 }
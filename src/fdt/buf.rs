@@ -0,0 +1,176 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! An owned flattened device tree buffer.
+
+use alloc::sync::Arc;
+#[cfg(feature = "std")]
+use alloc::vec;
+use alloc::vec::Vec;
+use core::fmt::{self, Debug, Display, Formatter};
+#[cfg(feature = "std")]
+use std::{fs::File, io, io::Read, path::Path};
+
+#[cfg(feature = "std")]
+use zerocopy::FromBytes;
+
+use super::Fdt;
+#[cfg(feature = "std")]
+use super::FdtHeader;
+use crate::error::FdtParseError;
+
+/// A flattened device tree that owns its data.
+///
+/// Unlike [`Fdt`], which borrows the blob it was parsed from, `FdtBuf` keeps
+/// the blob in a reference-counted buffer, so it can be stored in long-lived
+/// structures and cloned cheaply. The blob is validated when the `FdtBuf` is
+/// created, and [`FdtBuf::fdt`] returns an [`Fdt`] view of it.
+///
+/// # Examples
+///
+/// ```
+/// # use dtoolkit::fdt::FdtBuf;
+/// # let dtb = include_bytes!("../../tests/dtb/test.dtb");
+/// let buf = FdtBuf::new(dtb.to_vec()).unwrap();
+/// let root = buf.fdt().root().unwrap();
+/// assert_eq!(root.name(), Ok(""));
+/// ```
+#[derive(Clone, PartialEq, Eq)]
+pub struct FdtBuf {
+    data: Arc<[u8]>,
+}
+
+impl FdtBuf {
+    /// Creates a new `FdtBuf` from the given data.
+    ///
+    /// # Errors
+    ///
+    /// This function can return the same errors as [`Fdt::new`].
+    pub fn new(data: impl Into<Arc<[u8]>>) -> Result<Self, FdtParseError> {
+        let data = data.into();
+        Fdt::new(&data)?;
+        Ok(Self { data })
+    }
+
+    /// Reads a device tree blob from the given reader.
+    ///
+    /// Only the number of bytes given by the `totalsize` field of the header
+    /// is read, so the reader may contain more data after the blob.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if reading fails, or an error of kind
+    /// [`io::ErrorKind::InvalidData`] wrapping a [`FdtParseError`] if the blob
+    /// is invalid.
+    #[cfg(feature = "std")]
+    pub fn from_reader(mut reader: impl Read) -> io::Result<Self> {
+        let mut data = vec![0; size_of::<FdtHeader>()];
+        reader.read_exact(&mut data)?;
+        let (header, _) = FdtHeader::ref_from_prefix(&data)
+            .map_err(|_| io::Error::from(io::ErrorKind::InvalidData))?;
+        let remaining = u64::from(header.totalsize()).saturating_sub(data.len() as u64);
+        reader.take(remaining).read_to_end(&mut data)?;
+        Self::new(data).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
+    /// Reads a device tree blob from the file at the given path.
+    ///
+    /// # Errors
+    ///
+    /// This function can return the same errors as [`FdtBuf::from_reader`],
+    /// as well as any error that occurs when opening the file.
+    #[cfg(feature = "std")]
+    pub fn from_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::from_reader(io::BufReader::new(File::open(path)?))
+    }
+
+    /// Returns an [`Fdt`] view of the device tree.
+    #[must_use]
+    pub fn fdt(&self) -> Fdt<'_> {
+        Fdt { data: &self.data }
+    }
+
+    /// Returns the underlying data of the device tree.
+    #[must_use]
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl AsRef<[u8]> for FdtBuf {
+    fn as_ref(&self) -> &[u8] {
+        &self.data
+    }
+}
+
+impl From<Fdt<'_>> for FdtBuf {
+    fn from(fdt: Fdt<'_>) -> Self {
+        Self {
+            data: fdt.data.into(),
+        }
+    }
+}
+
+impl TryFrom<Vec<u8>> for FdtBuf {
+    type Error = FdtParseError;
+
+    fn try_from(data: Vec<u8>) -> Result<Self, Self::Error> {
+        Self::new(data)
+    }
+}
+
+impl Debug for FdtBuf {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "FdtBuf {{ data: {} bytes }}", self.data.len())
+    }
+}
+
+impl Display for FdtBuf {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        Display::fmt(&self.fdt(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let dtb = include_bytes!("../../tests/dtb/test_children.dtb");
+        let fdt = Fdt::new(dtb).unwrap();
+        let buf = FdtBuf::from(fdt);
+        assert_eq!(buf.data(), dtb);
+        assert_eq!(buf.to_string(), fdt.to_string());
+    }
+
+    #[test]
+    fn invalid() {
+        let dtb = include_bytes!("../../tests/dtb/test.dtb");
+        assert_eq!(
+            FdtBuf::new(&dtb[..dtb.len() - 1]).unwrap_err(),
+            Fdt::new(&dtb[..dtb.len() - 1]).unwrap_err()
+        );
+    }
+
+    #[cfg(feature = "std")]
+    #[test]
+    fn from_reader() {
+        let dtb = include_bytes!("../../tests/dtb/test.dtb");
+        let mut data = dtb.to_vec();
+        data.extend_from_slice(b"trailing data");
+
+        let buf = FdtBuf::from_reader(data.as_slice()).unwrap();
+        assert_eq!(buf.data(), dtb);
+
+        let error = FdtBuf::from_reader(&dtb[..dtb.len() - 1]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+        let error = FdtBuf::from_reader(&dtb[..8]).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
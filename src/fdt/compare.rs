@@ -0,0 +1,322 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Comparing device trees by their contents rather than their encoding.
+
+use super::node::FdtChildIter;
+use super::property::FdtPropIter;
+use super::{Fdt, FdtNode, FdtProperty, MemoryReservationIter};
+use crate::error::{FdtErrorKind, FdtParseError};
+use crate::memreserve::MemoryReservation;
+
+/// The maximum depth of nodes which [`compare`] can descend into, as it keeps
+/// the nodes being compared at each level on the stack rather than
+/// allocating.
+pub const MAX_COMPARE_DEPTH: usize = 32;
+
+/// Compares two device trees, returning an iterator over the differences
+/// between them.
+///
+/// The blobs are walked in lockstep without building a
+/// [`DeviceTree`](crate::model::DeviceTree) or allocating, so this can be used
+/// on-device, for example to check which parts of a device tree a bootloader
+/// changed. Nodes are matched by their full name and properties by their name,
+/// so changes to the order of either aren't reported, nor are `FDT_NOP` tokens
+/// or the layout of the strings block.
+///
+/// The differences in the properties and children of a node are reported
+/// before those of its descendants, in the order of the nodes of `left`. Nodes
+/// nested more than [`MAX_COMPARE_DEPTH`] levels below the root aren't
+/// compared, and an
+/// [`FdtErrorKind::TooDeep`](crate::error::FdtErrorKind::TooDeep) error is
+/// returned instead. The iterator ends after returning an error.
+///
+/// # Examples
+///
+/// ```
+/// # use dtoolkit::fdt::{Difference, Fdt};
+/// # let dtb = include_bytes!("../../tests/dtb/test_props.dtb");
+/// # let other = include_bytes!("../../tests/dtb/test_children.dtb");
+/// let left = Fdt::new(dtb).unwrap();
+/// assert_eq!(dtoolkit::compare(&left, &left).count(), 0);
+///
+/// let right = Fdt::new(other).unwrap();
+/// let added = dtoolkit::compare(&left, &right).find_map(|difference| match difference {
+///     Ok(Difference::NodeOnlyInRight(node)) => Some(node),
+///     _ => None,
+/// });
+/// assert_eq!(added.unwrap().name().unwrap(), "child1");
+/// ```
+pub fn compare<'a>(
+    left: &Fdt<'a>,
+    right: &Fdt<'a>,
+) -> impl Iterator<Item = Result<Difference<'a>, FdtParseError>> + use<'a> {
+    Differences {
+        left: *left,
+        right: *right,
+        reservations: Some((
+            MemoryReservationIter::new(*left),
+            MemoryReservationIter::new(*right),
+            0,
+        )),
+        stack: [const { None }; MAX_COMPARE_DEPTH],
+        depth: 0,
+        done: false,
+    }
+}
+
+/// A difference between two device trees, as returned by [`compare`].
+///
+/// Nodes and properties are borrowed from the blob they were found in. With
+/// the `alloc` feature, [`FdtNode::path`] returns the path of a node.
+#[derive(Clone, Copy, Debug)]
+#[non_exhaustive]
+pub enum Difference<'a> {
+    /// The memory reservations at the given index differ, or only one of the
+    /// device trees has one.
+    MemoryReservation {
+        /// The index of the reservation in the memory reservation block.
+        index: usize,
+        /// The reservation of the left device tree.
+        left: Option<MemoryReservation>,
+        /// The reservation of the right device tree.
+        right: Option<MemoryReservation>,
+    },
+    /// A node of the left device tree, and all of its descendants, are missing
+    /// from the right device tree.
+    NodeOnlyInLeft(FdtNode<'a>),
+    /// A node of the right device tree, and all of its descendants, are
+    /// missing from the left device tree.
+    NodeOnlyInRight(FdtNode<'a>),
+    /// A property is only in the left device tree.
+    PropertyOnlyInLeft {
+        /// The node of the left device tree with the property.
+        node: FdtNode<'a>,
+        /// The property.
+        property: FdtProperty<'a>,
+    },
+    /// A property is only in the right device tree.
+    PropertyOnlyInRight {
+        /// The node of the right device tree with the property.
+        node: FdtNode<'a>,
+        /// The property.
+        property: FdtProperty<'a>,
+    },
+    /// A property has a different value in each device tree.
+    PropertyChanged {
+        /// The node of the left device tree with the property.
+        node: FdtNode<'a>,
+        /// The property of the left device tree.
+        left: FdtProperty<'a>,
+        /// The property of the right device tree.
+        right: FdtProperty<'a>,
+    },
+}
+
+/// The iterator returned by [`compare`].
+struct Differences<'a> {
+    left: Fdt<'a>,
+    right: Fdt<'a>,
+    /// The memory reservations left to compare, with the index of the next
+    /// one, or `None` once they have all been compared.
+    reservations: Option<(MemoryReservationIter<'a>, MemoryReservationIter<'a>, usize)>,
+    /// The pairs of nodes being compared at each level, from the roots down.
+    stack: [Option<Level<'a>>; MAX_COMPARE_DEPTH],
+    /// The number of levels in `stack`.
+    depth: usize,
+    done: bool,
+}
+
+/// A pair of matching nodes, and how far comparing them has got.
+struct Level<'a> {
+    left: FdtNode<'a>,
+    right: FdtNode<'a>,
+    step: Step<'a>,
+}
+
+/// The next thing to compare for a pair of nodes, with the properties or
+/// children left to look at.
+enum Step<'a> {
+    LeftProperties(FdtPropIter<'a>),
+    RightProperties(FdtPropIter<'a>),
+    RightChildren(FdtChildIter<'a>),
+    LeftChildren(FdtChildIter<'a>),
+}
+
+impl<'a> Step<'a> {
+    fn properties(node: FdtNode<'a>) -> FdtPropIter<'a> {
+        FdtPropIter::Start {
+            fdt: node.fdt,
+            offset: node.offset,
+        }
+    }
+}
+
+impl<'a> Differences<'a> {
+    fn next_reservation(&mut self) -> Result<Option<Difference<'a>>, FdtParseError> {
+        while let Some((left, right, next_index)) = &mut self.reservations {
+            let index = *next_index;
+            let left = left.next().transpose()?;
+            let right = right.next().transpose()?;
+            if left.is_none() && right.is_none() {
+                self.reservations = None;
+                self.push(self.left.root()?, self.right.root()?)?;
+                break;
+            }
+            *next_index += 1;
+            if left != right {
+                return Ok(Some(Difference::MemoryReservation { index, left, right }));
+            }
+        }
+        Ok(None)
+    }
+
+    fn push(&mut self, left: FdtNode<'a>, right: FdtNode<'a>) -> Result<(), FdtParseError> {
+        let level = self.stack.get_mut(self.depth).ok_or(FdtParseError::new(
+            FdtErrorKind::TooDeep(MAX_COMPARE_DEPTH),
+            left.offset,
+        ))?;
+        *level = Some(Level {
+            left,
+            right,
+            step: Step::LeftProperties(Step::properties(left)),
+        });
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn next_difference(&mut self) -> Result<Option<Difference<'a>>, FdtParseError> {
+        if let Some(difference) = self.next_reservation()? {
+            return Ok(Some(difference));
+        }
+        while let Some(index) = self.depth.checked_sub(1) {
+            let Some(level) = &mut self.stack[index] else {
+                unreachable!("levels below the depth are set");
+            };
+            let (left, right) = (level.left, level.right);
+            match &mut level.step {
+                Step::LeftProperties(properties) => {
+                    let Some(property) = properties.next().transpose()? else {
+                        level.step = Step::RightProperties(Step::properties(right));
+                        continue;
+                    };
+                    match right.property(property.name())? {
+                        None => {
+                            return Ok(Some(Difference::PropertyOnlyInLeft {
+                                node: left,
+                                property,
+                            }));
+                        }
+                        Some(other) if other.value() != property.value() => {
+                            return Ok(Some(Difference::PropertyChanged {
+                                node: left,
+                                left: property,
+                                right: other,
+                            }));
+                        }
+                        Some(_) => {}
+                    }
+                }
+                Step::RightProperties(properties) => {
+                    let Some(property) = properties.next().transpose()? else {
+                        level.step = Step::RightChildren(FdtChildIter::Start { node: right });
+                        continue;
+                    };
+                    if left.property(property.name())?.is_none() {
+                        return Ok(Some(Difference::PropertyOnlyInRight {
+                            node: right,
+                            property,
+                        }));
+                    }
+                }
+                Step::RightChildren(children) => {
+                    let Some(child) = children.next().transpose()? else {
+                        level.step = Step::LeftChildren(FdtChildIter::Start { node: left });
+                        continue;
+                    };
+                    if child_named(left, child.name()?)?.is_none() {
+                        return Ok(Some(Difference::NodeOnlyInRight(child)));
+                    }
+                }
+                Step::LeftChildren(children) => {
+                    let Some(child) = children.next().transpose()? else {
+                        self.depth -= 1;
+                        continue;
+                    };
+                    match child_named(right, child.name()?)? {
+                        Some(other) => self.push(child, other)?,
+                        None => return Ok(Some(Difference::NodeOnlyInLeft(child))),
+                    }
+                }
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<'a> Iterator for Differences<'a> {
+    type Item = Result<Difference<'a>, FdtParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let difference = self.next_difference().transpose();
+        if !matches!(difference, Some(Ok(_))) {
+            self.done = true;
+        }
+        difference
+    }
+}
+
+/// Returns the child of `node` with exactly the given name.
+pub(super) fn child_named<'a>(
+    node: FdtNode<'a>,
+    name: &str,
+) -> Result<Option<FdtNode<'a>>, FdtParseError> {
+    for child in node.children() {
+        let child = child?;
+        if child.name()? == name {
+            return Ok(Some(child));
+        }
+    }
+    Ok(None)
+}
+
+impl Fdt<'_> {
+    /// Returns whether this device tree has the same contents as `other`.
+    ///
+    /// Two blobs are semantically equal if [`compare`] finds no differences
+    /// between them: they have the same memory reservations and the same
+    /// nodes with the same properties. As with [`compare`], the order of
+    /// properties and children within a node, `FDT_NOP` tokens, the layout of
+    /// the strings block and the header fields which don't describe the tree
+    /// itself are ignored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if either device tree can't be parsed, or an
+    /// [`FdtErrorKind::TooDeep`] error if no difference is found before
+    /// reaching nodes nested more than [`MAX_COMPARE_DEPTH`] levels below the
+    /// root.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../tests/dtb/test_props.dtb");
+    /// # let other = include_bytes!("../../tests/dtb/test.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let copy = dtb.to_vec();
+    /// assert!(fdt.semantically_equals(Fdt::new(&copy).unwrap()).unwrap());
+    /// assert!(!fdt.semantically_equals(Fdt::new(other).unwrap()).unwrap());
+    /// ```
+    pub fn semantically_equals(self, other: Fdt<'_>) -> Result<bool, FdtParseError> {
+        Ok(compare(&self, &other).next().transpose()?.is_none())
+    }
+}
@@ -0,0 +1,403 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Validating device tree blobs in const contexts.
+
+use core::mem::offset_of;
+
+use super::{
+    FDT_BEGIN_NODE, FDT_END, FDT_END_NODE, FDT_MAGIC, FDT_NOP, FDT_PROP, FDT_TAGSIZE, FDT_VERSION,
+    Fdt, FdtHeader, ParseLimits,
+};
+use crate::error::{FdtErrorKind, FdtParseError};
+use crate::memreserve::MemoryReservation;
+
+/// Embeds a device tree blob in the binary, validating it at compile time.
+///
+/// The path is resolved like with [`include_bytes!`], relative to the file
+/// in which the macro is invoked. The macro evaluates to an
+/// [`Fdt<'static>`](crate::fdt::Fdt), and the build fails if the blob
+/// doesn't pass the checks of [`Fdt::new_const`].
+///
+/// # Examples
+///
+/// ```
+/// use dtoolkit::fdt::Fdt;
+/// use dtoolkit::include_dtb;
+///
+/// static FDT: Fdt<'static> = include_dtb!("../../tests/dtb/test.dtb");
+/// assert_eq!(FDT.root().unwrap().name(), Ok(""));
+/// ```
+///
+/// ```compile_fail
+/// // Not a device tree.
+/// static FDT: dtoolkit::fdt::Fdt<'static> = dtoolkit::include_dtb!("../../Cargo.toml");
+/// ```
+#[macro_export]
+macro_rules! include_dtb {
+    ($path:expr $(,)?) => {{
+        const FDT: $crate::fdt::Fdt<'static> =
+            match $crate::fdt::Fdt::new_const(::core::include_bytes!($path)) {
+                ::core::result::Result::Ok(fdt) => fdt,
+                ::core::result::Result::Err(error) => {
+                    ::core::panic!("{}", $crate::fdt::__include_dtb_error(&error))
+                }
+            };
+        FDT
+    }};
+}
+
+/// Returns a message describing the given error, for [`include_dtb!`].
+#[doc(hidden)]
+#[must_use]
+pub const fn __include_dtb_error(error: &FdtParseError) -> &'static str {
+    match error.kind {
+        FdtErrorKind::InvalidMagic => "invalid device tree blob: invalid FDT magic number",
+        FdtErrorKind::UnsupportedVersion(_) => {
+            "invalid device tree blob: FDT version is not supported"
+        }
+        FdtErrorKind::InvalidLength => "invalid device tree blob: invalid FDT length",
+        FdtErrorKind::InvalidHeader(_) => "invalid device tree blob: FDT header is invalid",
+        FdtErrorKind::BadToken(_) => "invalid device tree blob: bad FDT token",
+        FdtErrorKind::InvalidOffset => "invalid device tree blob: invalid offset in FDT",
+        FdtErrorKind::InvalidString => "invalid device tree blob: invalid string in FDT",
+        FdtErrorKind::MemReserveNotTerminated | FdtErrorKind::MemReserveInvalid => {
+            "invalid device tree blob: invalid memory reservation block"
+        }
+        FdtErrorKind::TooDeep(_) => "invalid device tree blob: nodes nested too deeply",
+        FdtErrorKind::InvalidPhandle(_) => "invalid device tree blob: invalid phandle",
+        FdtErrorKind::TooManyNodes(_) => "invalid device tree blob: too many nodes",
+        FdtErrorKind::PropertyTooBig(_) => "invalid device tree blob: property too big",
+    }
+}
+
+impl<'a> Fdt<'a> {
+    /// Creates a new `Fdt` from the given byte slice in a const context.
+    ///
+    /// In addition to the header checks done by [`Fdt::new`], this walks the
+    /// memory reservation block and the whole structure block, so that
+    /// malformed tokens, unbalanced nodes, out of bounds property values and
+    /// invalid names are reported up front. This makes it slower than
+    /// [`Fdt::new`], but it's meant to be evaluated at compile time, for
+    /// example by [`include_dtb!`](crate::include_dtb).
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Fdt::new`], as well as
+    /// [`FdtErrorKind::BadToken`], [`FdtErrorKind::InvalidOffset`],
+    /// [`FdtErrorKind::InvalidString`] and
+    /// [`FdtErrorKind::MemReserveNotTerminated`] if the memory reservation or
+    /// structure blocks are malformed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// const DTB: &[u8] = include_bytes!("../../tests/dtb/test.dtb");
+    /// const FDT: Fdt<'static> = match Fdt::new_const(DTB) {
+    ///     Ok(fdt) => fdt,
+    ///     Err(_) => panic!("invalid DTB"),
+    /// };
+    /// ```
+    pub const fn new_const(data: &'a [u8]) -> Result<Self, FdtParseError> {
+        if data.len() < size_of::<FdtHeader>() {
+            return Err(FdtParseError::new(FdtErrorKind::InvalidLength, 0));
+        }
+        let Some(magic) = read_u32(data, offset_of!(FdtHeader, magic)) else {
+            unreachable!();
+        };
+        if magic != FDT_MAGIC {
+            return Err(FdtParseError::new(
+                FdtErrorKind::InvalidMagic,
+                offset_of!(FdtHeader, magic),
+            ));
+        }
+        let version = header_field(data, offset_of!(FdtHeader, version));
+        let last_comp_version = header_field(data, offset_of!(FdtHeader, last_comp_version));
+        if last_comp_version > FDT_VERSION || version < FDT_VERSION {
+            return Err(FdtParseError::new(
+                FdtErrorKind::UnsupportedVersion(version),
+                offset_of!(FdtHeader, version),
+            ));
+        }
+        if header_field(data, offset_of!(FdtHeader, totalsize)) as usize != data.len() {
+            return Err(FdtParseError::new(
+                FdtErrorKind::InvalidLength,
+                offset_of!(FdtHeader, totalsize),
+            ));
+        }
+
+        let off_mem_rsvmap = header_field(data, offset_of!(FdtHeader, off_mem_rsvmap)) as usize;
+        let off_dt_struct = header_field(data, offset_of!(FdtHeader, off_dt_struct)) as usize;
+        let off_dt_strings = header_field(data, offset_of!(FdtHeader, off_dt_strings)) as usize;
+        let size_dt_struct = header_field(data, offset_of!(FdtHeader, size_dt_struct)) as usize;
+        let size_dt_strings = header_field(data, offset_of!(FdtHeader, size_dt_strings)) as usize;
+        let header_error = if off_mem_rsvmap > off_dt_struct {
+            Some((
+                "dt_struct not after memrsvmap",
+                offset_of!(FdtHeader, off_mem_rsvmap),
+            ))
+        } else if off_dt_struct > data.len() {
+            Some((
+                "struct offset out of bounds",
+                offset_of!(FdtHeader, off_dt_struct),
+            ))
+        } else if off_dt_strings > data.len() {
+            Some((
+                "strings offset out of bounds",
+                offset_of!(FdtHeader, off_dt_strings),
+            ))
+        } else if off_dt_struct.saturating_add(size_dt_struct) > data.len() {
+            Some((
+                "struct block overflows",
+                offset_of!(FdtHeader, size_dt_struct),
+            ))
+        } else if off_dt_strings.saturating_add(size_dt_strings) > data.len() {
+            Some((
+                "strings block overflows",
+                offset_of!(FdtHeader, size_dt_strings),
+            ))
+        } else if off_dt_struct.saturating_add(size_dt_struct) > off_dt_strings {
+            Some((
+                "strings block not after struct block",
+                offset_of!(FdtHeader, off_dt_strings),
+            ))
+        } else {
+            None
+        };
+        if let Some((message, offset)) = header_error {
+            return Err(FdtParseError::new(
+                FdtErrorKind::InvalidHeader(message),
+                offset,
+            ));
+        }
+
+        if let Err(error) = validate_mem_reservations(data, off_mem_rsvmap, off_dt_struct) {
+            return Err(error);
+        }
+        if let Err(error) = validate_struct(
+            data,
+            off_dt_struct,
+            off_dt_struct + size_dt_struct,
+            off_dt_strings,
+            off_dt_strings + size_dt_strings,
+            ParseLimits::UNLIMITED,
+        ) {
+            return Err(error);
+        }
+
+        Ok(Self { data })
+    }
+}
+
+/// Reads a header field, which is known to be in bounds.
+const fn header_field(data: &[u8], offset: usize) -> u32 {
+    match read_u32(data, offset) {
+        Some(value) => value,
+        None => unreachable!(),
+    }
+}
+
+const fn read_u32(data: &[u8], offset: usize) -> Option<u32> {
+    if offset > data.len() || data.len() - offset < FDT_TAGSIZE {
+        return None;
+    }
+    Some(u32::from_be_bytes([
+        data[offset],
+        data[offset + 1],
+        data[offset + 2],
+        data[offset + 3],
+    ]))
+}
+
+const fn validate_mem_reservations(
+    data: &[u8],
+    start: usize,
+    end: usize,
+) -> Result<(), FdtParseError> {
+    const ENTRY_SIZE: usize = size_of::<MemoryReservation>();
+
+    if !start.is_multiple_of(8) {
+        return Err(FdtParseError::new(FdtErrorKind::MemReserveInvalid, start));
+    }
+    let mut offset = start;
+    while offset + ENTRY_SIZE <= end {
+        let mut terminator = true;
+        let mut i = 0;
+        while i < ENTRY_SIZE {
+            if data[offset + i] != 0 {
+                terminator = false;
+            }
+            i += 1;
+        }
+        if terminator {
+            return Ok(());
+        }
+        offset += ENTRY_SIZE;
+    }
+    Err(FdtParseError::new(
+        FdtErrorKind::MemReserveNotTerminated,
+        start,
+    ))
+}
+
+/// Returns the offset just after the null terminator of the string starting
+/// at `start`, checking that it ends before `end` and is valid UTF-8.
+const fn validate_string(data: &[u8], start: usize, end: usize) -> Result<usize, FdtParseError> {
+    let mut offset = start;
+    while offset < end {
+        if data[offset] == 0 {
+            let (_, rest) = data.split_at(start);
+            let (string, _) = rest.split_at(offset - start);
+            if core::str::from_utf8(string).is_err() {
+                return Err(FdtParseError::new(FdtErrorKind::InvalidString, start));
+            }
+            return Ok(offset + 1);
+        }
+        offset += 1;
+    }
+    Err(FdtParseError::new(FdtErrorKind::InvalidString, start))
+}
+
+/// Walks the structure block from `start` to `end`, checking that it is
+/// well-formed and within `limits`.
+pub(super) const fn validate_struct(
+    data: &[u8],
+    start: usize,
+    end: usize,
+    strings_start: usize,
+    strings_end: usize,
+    limits: ParseLimits,
+) -> Result<(), FdtParseError> {
+    let mut offset = start;
+    let mut depth = 0usize;
+    let mut nodes = 0usize;
+    let mut seen_root = false;
+    loop {
+        let token = if offset + FDT_TAGSIZE <= end {
+            match read_u32(data, offset) {
+                Some(token) => token,
+                None => unreachable!(),
+            }
+        } else {
+            return Err(FdtParseError::new(FdtErrorKind::InvalidOffset, offset));
+        };
+        match token {
+            FDT_BEGIN_NODE => {
+                if depth == 0 && seen_root {
+                    return Err(FdtParseError::new(FdtErrorKind::BadToken(token), offset));
+                }
+                // The depth of the root node is 0.
+                if depth > limits.max_depth {
+                    return Err(FdtParseError::new(
+                        FdtErrorKind::TooDeep(limits.max_depth),
+                        offset,
+                    ));
+                }
+                if nodes == limits.max_nodes {
+                    return Err(FdtParseError::new(
+                        FdtErrorKind::TooManyNodes(limits.max_nodes),
+                        offset,
+                    ));
+                }
+                seen_root = true;
+                depth += 1;
+                nodes += 1;
+                match validate_string(data, offset + FDT_TAGSIZE, end) {
+                    Ok(name_end) => offset = name_end.next_multiple_of(FDT_TAGSIZE),
+                    Err(error) => return Err(error),
+                }
+            }
+            FDT_END_NODE => {
+                if depth == 0 {
+                    return Err(FdtParseError::new(FdtErrorKind::BadToken(token), offset));
+                }
+                depth -= 1;
+                offset += FDT_TAGSIZE;
+            }
+            FDT_PROP => {
+                if depth == 0 {
+                    return Err(FdtParseError::new(FdtErrorKind::BadToken(token), offset));
+                }
+                let (Some(len), Some(name_offset)) = (
+                    read_u32(data, offset + FDT_TAGSIZE),
+                    read_u32(data, offset + 2 * FDT_TAGSIZE),
+                ) else {
+                    return Err(FdtParseError::new(FdtErrorKind::InvalidOffset, offset));
+                };
+                if len as usize > limits.max_property_size {
+                    return Err(FdtParseError::new(
+                        FdtErrorKind::PropertyTooBig(limits.max_property_size),
+                        offset,
+                    ));
+                }
+                let value_start = offset + 3 * FDT_TAGSIZE;
+                if value_start > end || end - value_start < len as usize {
+                    return Err(FdtParseError::new(FdtErrorKind::InvalidOffset, offset));
+                }
+                let name_start = strings_start.saturating_add(name_offset as usize);
+                if let Err(error) = validate_string(data, name_start, strings_end) {
+                    return Err(error);
+                }
+                offset = (value_start + len as usize).next_multiple_of(FDT_TAGSIZE);
+            }
+            FDT_NOP => offset += FDT_TAGSIZE,
+            FDT_END => {
+                if depth != 0 || !seen_root {
+                    return Err(FdtParseError::new(FdtErrorKind::BadToken(token), offset));
+                }
+                return Ok(());
+            }
+            _ => return Err(FdtParseError::new(FdtErrorKind::BadToken(token), offset)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn valid_blobs() {
+        for dtb in [
+            &include_bytes!("../../tests/dtb/test.dtb")[..],
+            include_bytes!("../../tests/dtb/test_children.dtb"),
+            include_bytes!("../../tests/dtb/test_props.dtb"),
+            include_bytes!("../../tests/dtb/test_memreserve.dtb"),
+        ] {
+            assert_eq!(Fdt::new_const(dtb).map(Fdt::data), Ok(dtb));
+        }
+    }
+
+    #[test]
+    fn invalid_structure() {
+        let dtb = include_bytes!("../../tests/dtb/test_children.dtb");
+        let fdt = Fdt::new(dtb).unwrap();
+        let off_dt_struct = fdt.header().off_dt_struct() as usize;
+        let off_dt_strings = fdt.header().off_dt_strings() as usize;
+
+        // Corrupt the first token after the root node's name.
+        let mut data = dtb.to_vec();
+        let offset = off_dt_struct + 2 * FDT_TAGSIZE;
+        data[offset..offset + FDT_TAGSIZE].copy_from_slice(&0x42u32.to_be_bytes());
+        assert!(Fdt::new(&data).is_ok());
+        assert_eq!(
+            Fdt::new_const(&data).unwrap_err(),
+            FdtParseError::new(FdtErrorKind::BadToken(0x42), offset)
+        );
+
+        // Drop the final FDT_END token.
+        let mut data = dtb.to_vec();
+        let offset = off_dt_strings - FDT_TAGSIZE;
+        data[offset..offset + FDT_TAGSIZE].copy_from_slice(&FDT_NOP.to_be_bytes());
+        assert_eq!(
+            Fdt::new_const(&data).unwrap_err(),
+            FdtParseError::new(FdtErrorKind::InvalidOffset, off_dt_strings)
+        );
+    }
+}
@@ -0,0 +1,222 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Finding nodes whose paths match a glob pattern.
+
+use super::node::{FdtChildIter, MAX_DESCENDANTS_DEPTH};
+use super::{Fdt, FdtNode};
+use crate::error::{FdtError, FdtErrorKind, FdtParseError};
+use crate::path::{self, DtPath};
+
+/// The maximum number of segments in a pattern for [`Fdt::find_nodes`], as the
+/// segments matched so far are tracked as the bits of a u64.
+const MAX_PATTERN_SEGMENTS: usize = 63;
+
+impl<'a> Fdt<'a> {
+    /// Returns an iterator over the nodes whose paths match the given glob
+    /// pattern, such as `/soc/*/uart@*`.
+    ///
+    /// Each segment of the pattern matches one level of the device tree. A
+    /// `*` in a segment matches any sequence of characters in a node name,
+    /// and a segment which is only `**` matches any number of levels,
+    /// including none, so `/**/uart` matches every `uart` node. As with
+    /// [`find_node`](Self::find_node), a segment without a `@` is matched
+    /// against the node name without its unit address, so `/soc/*/uart`
+    /// matches `/soc/bus/uart@1000`.
+    ///
+    /// The pattern is always matched from the root node, whether or not it
+    /// starts with a `/`; aliases aren't supported. The nodes are returned
+    /// depth-first, in the order they appear in the device tree, and only the
+    /// subtrees which can match the pattern are walked.
+    ///
+    /// Nodes nested more than [`MAX_DESCENDANTS_DEPTH`] levels below the root
+    /// aren't walked, and an [`FdtErrorKind::TooDeep`] error is returned
+    /// instead. The iterator ends after returning an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FdtError::CapacityExceeded`] if the pattern has more than 63
+    /// segments, or an error if the root node can't be read. The iterator
+    /// returns an error if the device tree structure is malformed or a node
+    /// name can't be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../tests/dtb/test_traversal.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let names = |pattern| -> Vec<_> {
+    ///     fdt.find_nodes(pattern)
+    ///         .unwrap()
+    ///         .map(|node| node.unwrap().name().unwrap())
+    ///         .collect()
+    /// };
+    /// assert_eq!(names("/*"), ["a", "d"]);
+    /// assert_eq!(names("/a/*/c"), ["c"]);
+    /// assert_eq!(names("/**/c"), ["c"]);
+    /// assert_eq!(names("/a/**"), ["a", "b", "c"]);
+    /// ```
+    pub fn find_nodes<'p>(
+        self,
+        pattern: &'p str,
+    ) -> Result<impl Iterator<Item = Result<FdtNode<'a>, FdtParseError>> + use<'a, 'p>, FdtError>
+    {
+        let pattern = Pattern::new(pattern)?;
+        let root = self.root()?;
+        let states = pattern.start();
+        let mut stack = [const { None }; MAX_DESCENDANTS_DEPTH];
+        stack[0] = Some(Level {
+            children: FdtChildIter::Start { node: root },
+            states,
+        });
+        Ok(FindNodes {
+            pattern,
+            root: pattern.is_match(states).then_some(root),
+            stack,
+            depth: 1,
+            done: false,
+        })
+    }
+}
+
+/// A glob pattern for [`Fdt::find_nodes`], matched against the path of a node
+/// one segment at a time.
+///
+/// The progress of a match is tracked as a set of states, as a `**` segment
+/// can match in several ways at once: bit `i` of the set means that the first
+/// `i` segments of the pattern match the path so far.
+#[derive(Clone, Copy)]
+struct Pattern<'p> {
+    path: DtPath<'p>,
+    len: usize,
+}
+
+impl<'p> Pattern<'p> {
+    fn new(pattern: &'p str) -> Result<Self, FdtError> {
+        let path = DtPath::new(pattern);
+        let len = path.segments().count();
+        if len > MAX_PATTERN_SEGMENTS {
+            return Err(FdtError::CapacityExceeded("pattern segments"));
+        }
+        Ok(Self { path, len })
+    }
+
+    /// Returns the states for the root node.
+    fn start(self) -> u64 {
+        self.skip_globstars(1)
+    }
+
+    /// Returns the states for a child named `name` of a node with the given
+    /// states.
+    fn step(self, states: u64, name: &str) -> u64 {
+        let mut next = 0;
+        for (index, segment) in self.path.segments().enumerate() {
+            if states & (1 << index) == 0 {
+                continue;
+            }
+            if segment == "**" {
+                next |= 1 << index;
+            } else if path::glob_segment_matches(segment, name) {
+                next |= 1 << (index + 1);
+            }
+        }
+        self.skip_globstars(next)
+    }
+
+    /// Adds the states after each `**` segment which has been reached, as it
+    /// may match no levels at all.
+    fn skip_globstars(self, mut states: u64) -> u64 {
+        for (index, segment) in self.path.segments().enumerate() {
+            if segment == "**" && states & (1 << index) != 0 {
+                states |= 1 << (index + 1);
+            }
+        }
+        states
+    }
+
+    /// Returns whether a node with the given states matches the whole pattern.
+    fn is_match(self, states: u64) -> bool {
+        states & (1 << self.len) != 0
+    }
+
+    /// Returns whether the descendants of a node with the given states may
+    /// match the pattern.
+    fn can_descend(self, states: u64) -> bool {
+        states & !(1 << self.len) != 0
+    }
+}
+
+/// The iterator returned by [`Fdt::find_nodes`].
+struct FindNodes<'a, 'p> {
+    pattern: Pattern<'p>,
+    /// The root node, if it matches the pattern and hasn't been returned yet.
+    root: Option<FdtNode<'a>>,
+    /// The children being walked at each level, from the root down.
+    stack: [Option<Level<'a>>; MAX_DESCENDANTS_DEPTH],
+    /// The number of levels in `stack`.
+    depth: usize,
+    done: bool,
+}
+
+/// The children of a node being walked, with the states of the node.
+struct Level<'a> {
+    children: FdtChildIter<'a>,
+    states: u64,
+}
+
+impl<'a> FindNodes<'a, '_> {
+    fn next_node(&mut self) -> Result<Option<FdtNode<'a>>, FdtParseError> {
+        if let Some(root) = self.root.take() {
+            return Ok(Some(root));
+        }
+        while let Some(index) = self.depth.checked_sub(1) {
+            let Some(level) = &mut self.stack[index] else {
+                unreachable!("levels below the depth are set");
+            };
+            let Some(child) = level.children.next().transpose()? else {
+                self.depth = index;
+                continue;
+            };
+            let states = self.pattern.step(level.states, child.name()?);
+            if self.pattern.can_descend(states) {
+                if let Some(level) = self.stack.get_mut(self.depth) {
+                    *level = Some(Level {
+                        children: FdtChildIter::Start { node: child },
+                        states,
+                    });
+                    self.depth += 1;
+                } else if child.children().next().is_some() {
+                    return Err(FdtParseError::new(
+                        FdtErrorKind::TooDeep(MAX_DESCENDANTS_DEPTH),
+                        child.offset,
+                    ));
+                }
+            }
+            if self.pattern.is_match(states) {
+                return Ok(Some(child));
+            }
+        }
+        Ok(None)
+    }
+}
+
+impl<'a> Iterator for FindNodes<'a, '_> {
+    type Item = Result<FdtNode<'a>, FdtParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let node = self.next_node().transpose();
+        if !matches!(node, Some(Ok(_))) {
+            self.done = true;
+        }
+        node
+    }
+}
@@ -0,0 +1,503 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A lookup index over a flattened device tree.
+
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+
+use zerocopy::{FromBytes, big_endian};
+
+use super::{
+    FDT_BEGIN_NODE, FDT_END, FDT_END_NODE, FDT_PROP, FDT_TAGSIZE, Fdt, FdtNode, FdtProperty,
+    FdtToken,
+};
+use crate::error::{FdtErrorKind, FdtParseError};
+use crate::path::DtPath;
+use crate::standard::{AddressSpaceProperties, Phandle};
+
+/// A lookup index over the nodes of a flattened device tree.
+///
+/// [`Fdt::find_node`], [`Fdt::find_node_by_phandle`] and
+/// [`FdtNode::find_compatible`] walk the device tree every time they are
+/// called. `FdtIndex` is built with a single pass over the blob and then
+/// answers the same queries without walking it again, which pays off when
+/// looking up many nodes in the same tree.
+///
+/// # Examples
+///
+/// ```
+/// # use dtoolkit::fdt::{Fdt, FdtIndex};
+/// # use dtoolkit::standard::Phandle;
+/// # let dtb = include_bytes!("../../tests/dtb/test_props.dtb");
+/// let fdt = Fdt::new(dtb).unwrap();
+/// let index = FdtIndex::new(fdt).unwrap();
+/// let node = index.find_node("/standard-props").unwrap();
+/// assert_eq!(node.name(), Ok("standard-props@1"));
+/// let node = index
+///     .find_node_by_phandle(Phandle::new(0x1234).unwrap())
+///     .unwrap();
+/// assert_eq!(node.name(), Ok("standard-props@1"));
+/// let nodes = index.find_compatible("some,other");
+/// assert_eq!(nodes.len(), 1);
+/// assert_eq!(nodes[0].name(), Ok("standard-props@1"));
+/// ```
+#[derive(Debug, Clone)]
+pub struct FdtIndex<'a> {
+    root: FdtNode<'a>,
+    /// Children by the offset of their parent and their name. Each child is
+    /// also stored under its name without the unit-address, unless an earlier
+    /// sibling already claimed it.
+    children: BTreeMap<(usize, &'a str), FdtNode<'a>>,
+    phandles: BTreeMap<Phandle, FdtNode<'a>>,
+    /// Nodes whose `phandle` property has a reserved value, so they aren't in
+    /// `phandles`.
+    invalid_phandles: Vec<FdtNode<'a>>,
+    /// Nodes by each of the strings in their `compatible` property, in the
+    /// order they appear in the device tree.
+    compatibles: BTreeMap<&'a str, Vec<FdtNode<'a>>>,
+    node_count: usize,
+}
+
+impl<'a> FdtIndex<'a> {
+    /// Builds an index of all nodes in the given device tree.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the structure block is malformed, a name can't be
+    /// read, or a `#address-cells`, `#size-cells` or `phandle` property isn't
+    /// a valid u32. Nodes whose `phandle` is a reserved value are left out of
+    /// the phandle lookup and listed by [`FdtIndex::invalid_phandles`]
+    /// instead.
+    pub fn new(fdt: Fdt<'a>) -> Result<Self, FdtParseError> {
+        let root = fdt.root()?;
+        let mut index = Self {
+            root,
+            children: BTreeMap::new(),
+            phandles: BTreeMap::new(),
+            invalid_phandles: Vec::new(),
+            compatibles: BTreeMap::new(),
+            node_count: 0,
+        };
+
+        // The nodes being visited, along with the address space they define for
+        // their children.
+        let mut stack: Vec<(FdtNode<'a>, AddressSpaceProperties)> = Vec::new();
+        let mut offset = root.offset;
+        loop {
+            match fdt.read_token(offset)? {
+                FdtToken::BeginNode => {
+                    let node = if let Some(&(parent, address_space)) = stack.last() {
+                        let node = FdtNode {
+                            fdt,
+                            offset,
+                            parent_address_space: address_space,
+                        };
+                        for name in [node.name()?, node.name_without_address()?] {
+                            index.children.entry((parent.offset, name)).or_insert(node);
+                        }
+                        node
+                    } else if offset == root.offset {
+                        root
+                    } else {
+                        return Err(Self::unexpected(FDT_BEGIN_NODE, offset));
+                    };
+                    index.node_count += 1;
+                    stack.push((node, AddressSpaceProperties::default()));
+                    offset = fdt.find_string_end(offset + FDT_TAGSIZE)?;
+                    offset = Fdt::align_tag_offset(offset);
+                }
+                FdtToken::Prop => {
+                    let Some((node, address_space)) = stack.last_mut() else {
+                        return Err(Self::unexpected(FDT_PROP, offset));
+                    };
+                    let (name, value) = Self::read_property(fdt, offset)?;
+                    let value_offset = offset + 3 * FDT_TAGSIZE;
+                    if matches!(name, "#address-cells" | "#size-cells" | "phandle") {
+                        let value = big_endian::U32::ref_from_bytes(value)
+                            .map(|val| val.get())
+                            .map_err(|_e| {
+                                FdtParseError::new(FdtErrorKind::InvalidLength, value_offset)
+                            })?;
+                        match name {
+                            "#address-cells" => address_space.address_cells = value,
+                            "#size-cells" => address_space.size_cells = value,
+                            _ => {
+                                if let Some(phandle) = Phandle::new(value) {
+                                    index.phandles.entry(phandle).or_insert(*node);
+                                } else {
+                                    index.invalid_phandles.push(*node);
+                                }
+                            }
+                        }
+                    } else if name == "compatible" {
+                        index.add_compatible(*node, value);
+                    }
+                    offset = Fdt::align_tag_offset(value_offset + value.len());
+                }
+                FdtToken::EndNode => {
+                    if stack.pop().is_none() {
+                        return Err(Self::unexpected(FDT_END_NODE, offset));
+                    }
+                    offset += FDT_TAGSIZE;
+                }
+                FdtToken::Nop => offset += FDT_TAGSIZE,
+                FdtToken::End => {
+                    if !stack.is_empty() {
+                        return Err(Self::unexpected(FDT_END, offset));
+                    }
+                    return Ok(index);
+                }
+            }
+        }
+    }
+
+    /// Reads the name and value of the `FDT_PROP` token at the given offset.
+    fn read_property(fdt: Fdt<'a>, offset: usize) -> Result<(&'a str, &'a [u8]), FdtParseError> {
+        let header = fdt
+            .data
+            .get(offset + FDT_TAGSIZE..)
+            .and_then(|data| <[big_endian::U32; 2]>::ref_from_prefix(data).ok())
+            .map(|([len, nameoff], _)| (len.get() as usize, nameoff.get() as usize));
+        let Some((len, nameoff)) = header else {
+            return Err(FdtParseError::new(FdtErrorKind::InvalidLength, offset));
+        };
+        let value_offset = offset + 3 * FDT_TAGSIZE;
+        let value = fdt
+            .data
+            .get(value_offset..value_offset + len)
+            .ok_or(FdtParseError::new(
+                FdtErrorKind::InvalidLength,
+                value_offset,
+            ))?;
+        Ok((fdt.string(nameoff)?, value))
+    }
+
+    /// Adds the given node under each of the strings of its `compatible`
+    /// property, stopping at the first one that isn't valid UTF-8 like
+    /// [`FdtProperty::as_str_list`].
+    fn add_compatible(&mut self, node: FdtNode<'a>, value: &'a [u8]) {
+        // Anything after the last NUL isn't a complete string.
+        let Some(end) = value.iter().rposition(|&byte| byte == 0) else {
+            return;
+        };
+        let mut strings = value[..end].split(|&byte| byte == 0);
+        while let Some(Ok(compatible)) = strings.next().map(str::from_utf8) {
+            let nodes = self.compatibles.entry(compatible).or_default();
+            if nodes.last().is_none_or(|last| last.offset != node.offset) {
+                nodes.push(node);
+            }
+        }
+    }
+
+    fn unexpected(token: u32, offset: usize) -> FdtParseError {
+        FdtParseError::new(FdtErrorKind::BadToken(token), offset)
+    }
+
+    /// Returns the device tree this index was built from.
+    #[must_use]
+    pub fn fdt(&self) -> Fdt<'a> {
+        self.root.fdt
+    }
+
+    /// Returns the root node of the device tree.
+    #[must_use]
+    pub fn root(&self) -> FdtNode<'a> {
+        self.root
+    }
+
+    /// Returns the number of nodes in the device tree.
+    #[must_use]
+    pub fn node_count(&self) -> usize {
+        self.node_count
+    }
+
+    /// Finds a node by its path.
+    ///
+    /// This matches nodes the same way as [`Fdt::find_node`], but each path
+    /// component is looked up in the index rather than by walking the
+    /// children of its parent.
+    #[must_use]
+    pub fn find_node(&self, path: &str) -> Option<FdtNode<'a>> {
+        let mut path = DtPath::new(path);
+        let mut current_node = if path.is_absolute() {
+            self.root
+        } else {
+            let (alias, rest) = path.split_alias()?;
+            path = rest;
+            self.resolve_alias(alias)?
+        };
+        for component in path.segments() {
+            current_node = *self.children.get(&(current_node.offset, component))?;
+        }
+        Some(current_node)
+    }
+
+    /// Finds the node with the given alias, like [`Fdt::resolve_alias`] but
+    /// looking up its path in the index.
+    ///
+    /// Returns `None` if the alias can't be read.
+    #[must_use]
+    pub fn resolve_alias(&self, alias: &str) -> Option<FdtNode<'a>> {
+        let path = self.find_node("/aliases")?.property(alias).ok()??;
+        let path = path.as_str().ok()?;
+        if path.starts_with('/') {
+            self.find_node(path)
+        } else {
+            None
+        }
+    }
+
+    /// Finds the node with the given `phandle`.
+    ///
+    /// If several nodes have the same `phandle`, the first one in the device
+    /// tree is returned.
+    #[must_use]
+    pub fn find_node_by_phandle(&self, phandle: Phandle) -> Option<FdtNode<'a>> {
+        self.phandles.get(&phandle).copied()
+    }
+
+    /// Returns the nodes whose `phandle` property has a reserved value, 0 or
+    /// 0xffffffff, in the order they appear in the device tree.
+    ///
+    /// These nodes can't be found with [`FdtIndex::find_node_by_phandle`].
+    #[must_use]
+    pub fn invalid_phandles(&self) -> &[FdtNode<'a>] {
+        &self.invalid_phandles
+    }
+
+    /// Returns all nodes with a `compatible` property containing the given
+    /// string, in the order they appear in the device tree.
+    ///
+    /// Unlike [`FdtNode::find_compatible`], this searches the whole tree
+    /// rather than only the children of a node.
+    #[must_use]
+    pub fn find_compatible(&self, compatible: &str) -> &[FdtNode<'a>] {
+        self.compatibles.get(compatible).map_or(&[], Vec::as_slice)
+    }
+}
+
+/// A node of a flattened device tree with an index of its children and
+/// properties.
+///
+/// [`FdtNode::child`] and [`FdtNode::property`] scan the node's tokens on
+/// every call. `IndexedNode` scans them once, so that repeated lookups on
+/// nodes with many children, such as `/soc`, don't have to. Unlike
+/// [`FdtIndex`], it only indexes a single node rather than the whole tree.
+///
+/// # Examples
+///
+/// ```
+/// # use dtoolkit::fdt::{Fdt, IndexedNode};
+/// # let dtb = include_bytes!("../../tests/dtb/test_children.dtb");
+/// let fdt = Fdt::new(dtb).unwrap();
+/// let root = IndexedNode::new(fdt.root().unwrap()).unwrap();
+/// assert_eq!(root.child("child1").unwrap().name(), Ok("child1"));
+/// assert_eq!(root.child("child2").unwrap().name(), Ok("child2@42"));
+/// assert!(root.child("child3").is_none());
+/// ```
+#[derive(Debug, Clone)]
+pub struct IndexedNode<'a> {
+    node: FdtNode<'a>,
+    /// Children by name. Each child is also stored under its name without the
+    /// unit-address, unless an earlier sibling already claimed it.
+    children: BTreeMap<&'a str, FdtNode<'a>>,
+    properties: BTreeMap<&'a str, FdtProperty<'a>>,
+}
+
+impl<'a> IndexedNode<'a> {
+    /// Builds an index of the children and properties of the given node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the node's children or properties can't be read.
+    pub fn new(node: FdtNode<'a>) -> Result<Self, FdtParseError> {
+        let mut children = BTreeMap::new();
+        for child in node.children() {
+            let child = child?;
+            for name in [child.name()?, child.name_without_address()?] {
+                children.entry(name).or_insert(child);
+            }
+        }
+        let mut properties = BTreeMap::new();
+        for property in node.properties() {
+            let property = property?;
+            properties.entry(property.name()).or_insert(property);
+        }
+        Ok(Self {
+            node,
+            children,
+            properties,
+        })
+    }
+
+    /// Returns the node this index was built for.
+    #[must_use]
+    pub fn node(&self) -> FdtNode<'a> {
+        self.node
+    }
+
+    /// Returns a child node by its name.
+    ///
+    /// This matches children the same way as [`FdtNode::child`].
+    #[must_use]
+    pub fn child(&self, name: &str) -> Option<FdtNode<'a>> {
+        self.children.get(name).copied()
+    }
+
+    /// Returns a property by its name.
+    #[must_use]
+    pub fn property(&self, name: &str) -> Option<FdtProperty<'a>> {
+        self.properties.get(name).copied()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_linear_lookups() {
+        for dtb in [
+            &include_bytes!("../../tests/dtb/test_children.dtb")[..],
+            include_bytes!("../../tests/dtb/test_children_nested.dtb"),
+            include_bytes!("../../tests/dtb/test_props.dtb"),
+            include_bytes!("../../tests/dtb/test_traversal.dtb"),
+        ] {
+            let fdt = Fdt::new(dtb).unwrap();
+            let index = FdtIndex::new(fdt).unwrap();
+            for path in [
+                "/",
+                "/child1",
+                "/child2",
+                "/child2@42",
+                "/child2@43",
+                "/child1/child2",
+                "/a/b/c",
+                "/a/b/d",
+                "/standard-props",
+                "relative",
+            ] {
+                let expected = fdt.find_node(path).unwrap();
+                let actual = index.find_node(path);
+                assert_eq!(actual.map(|n| n.offset), expected.map(|n| n.offset));
+                assert_eq!(
+                    actual.map(|n| n.parent_address_space.address_cells),
+                    expected.map(|n| n.parent_address_space.address_cells)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn phandles() {
+        let dtb = include_bytes!("../../tests/dtb/test_props.dtb");
+        let fdt = Fdt::new(dtb).unwrap();
+        let index = FdtIndex::new(fdt).unwrap();
+        assert_eq!(index.node_count(), 3);
+
+        let phandle = Phandle::new(0x1234).unwrap();
+        let node = index.find_node_by_phandle(phandle).unwrap();
+        assert_eq!(node.name(), Ok("standard-props@1"));
+        assert_eq!(node.parent_address_space.address_cells, 2);
+        assert_eq!(
+            fdt.find_node_by_phandle(phandle).unwrap().map(|n| n.offset),
+            Some(node.offset)
+        );
+        assert!(
+            index
+                .find_node_by_phandle(Phandle::new(0x1).unwrap())
+                .is_none()
+        );
+        assert!(index.invalid_phandles().is_empty());
+    }
+
+    #[test]
+    fn reserved_phandles() {
+        use crate::model::{DeviceTree, DeviceTreeNode, DeviceTreeProperty};
+
+        let mut tree = DeviceTree::new();
+        for (name, phandle) in [("a", 0), ("b", 1), ("c", u32::MAX)] {
+            tree.root.add_child(
+                DeviceTreeNode::builder(name)
+                    .property(DeviceTreeProperty::new("phandle", phandle.to_be_bytes()))
+                    .build(),
+            );
+        }
+        let dtb = tree.to_dtb();
+        let fdt = Fdt::new(&dtb).unwrap();
+        let index = FdtIndex::new(fdt).unwrap();
+        assert_eq!(
+            index
+                .invalid_phandles()
+                .iter()
+                .map(FdtNode::name)
+                .collect::<Vec<_>>(),
+            [Ok("a"), Ok("c")]
+        );
+        let node = index.find_node_by_phandle(Phandle::new(1).unwrap());
+        assert_eq!(node.map(|n| n.name()), Some(Ok("b")));
+        let node = fdt.find_node_by_phandle(Phandle::new(1).unwrap()).unwrap();
+        assert_eq!(node.map(|n| n.name()), Some(Ok("b")));
+        assert!(
+            fdt.find_node_by_phandle(Phandle::new(2).unwrap())
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn compatibles() {
+        let dtb = include_bytes!("../../tests/dtb/test_pretty_print.dtb");
+        let fdt = Fdt::new(dtb).unwrap();
+        let index = FdtIndex::new(fdt).unwrap();
+        let root = fdt.root().unwrap();
+        assert_eq!(
+            index
+                .find_compatible("acme,coyote")
+                .first()
+                .map(|n| n.offset),
+            Some(root.offset)
+        );
+
+        let cpus = fdt.find_node("/cpus").unwrap().unwrap();
+        let expected: Vec<_> = cpus
+            .find_compatible("arm,cortex-a9")
+            .map(|n| n.unwrap().offset)
+            .collect();
+        assert!(!expected.is_empty());
+        let actual: Vec<_> = index
+            .find_compatible("arm,cortex-a9")
+            .iter()
+            .map(|n| n.offset)
+            .collect();
+        assert_eq!(actual, expected);
+        assert!(index.find_compatible("missing").is_empty());
+    }
+
+    #[test]
+    fn indexed_node_matches_linear_lookups() {
+        let dtb = include_bytes!("../../tests/dtb/test_props.dtb");
+        let fdt = Fdt::new(dtb).unwrap();
+        let node = fdt.find_node("/standard-props").unwrap().unwrap();
+        let indexed = IndexedNode::new(node).unwrap();
+        for name in ["phandle", "reg", "status", "non-existent-prop"] {
+            assert_eq!(indexed.property(name), node.property(name).unwrap());
+        }
+
+        let dtb = include_bytes!("../../tests/dtb/test_children.dtb");
+        let root = Fdt::new(dtb).unwrap().root().unwrap();
+        let indexed = IndexedNode::new(root).unwrap();
+        for name in ["child1", "child2", "child2@42", "child2@43", "child3"] {
+            assert_eq!(
+                indexed.child(name).map(|n| n.offset),
+                root.child(name).unwrap().map(|n| n.offset)
+            );
+        }
+    }
+}
@@ -0,0 +1,112 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Limits on the size of untrusted device trees.
+
+use super::Fdt;
+use super::embed::validate_struct;
+use crate::error::FdtParseError;
+
+/// Limits on the shape of a device tree, for [`Fdt::new_with_limits`].
+///
+/// The default limits allow any device tree. Set the fields which matter for
+/// the use case, such as:
+///
+/// ```
+/// # use dtoolkit::fdt::ParseLimits;
+/// let limits = ParseLimits {
+///     max_depth: 16,
+///     max_nodes: 1024,
+///     ..ParseLimits::default()
+/// };
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ParseLimits {
+    /// The maximum number of levels of nodes below the root node.
+    pub max_depth: usize,
+    /// The maximum number of nodes, including the root node.
+    pub max_nodes: usize,
+    /// The maximum size in bytes of the value of a property.
+    pub max_property_size: usize,
+}
+
+impl ParseLimits {
+    /// Limits which allow any device tree.
+    pub const UNLIMITED: Self = Self {
+        max_depth: usize::MAX,
+        max_nodes: usize::MAX,
+        max_property_size: usize::MAX,
+    };
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        Self::UNLIMITED
+    }
+}
+
+impl<'a> Fdt<'a> {
+    /// Creates a new `Fdt` from the given byte slice, checking that the whole
+    /// structure block is well-formed and within the given limits.
+    ///
+    /// [`Fdt::new`] only checks the header, and the rest of the blob is parsed
+    /// as it is accessed. Like [`Fdt::new_const`], this also walks every token
+    /// of the structure block once, in time linear in its size and without
+    /// recursing, so that later traversals of a blob from an untrusted source,
+    /// such as a guest VM, are bounded by the limits.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Fdt::new`], the errors returned by
+    /// [`Fdt::new_const`] if the structure block is malformed, or:
+    ///
+    /// - [`FdtErrorKind::TooDeep`] if nodes are nested more than `max_depth`
+    ///   levels below the root node.
+    /// - [`FdtErrorKind::TooManyNodes`] if there are more than `max_nodes`
+    ///   nodes.
+    /// - [`FdtErrorKind::PropertyTooBig`] if the value of a property is more
+    ///   than `max_property_size` bytes.
+    ///
+    /// [`FdtErrorKind::TooDeep`]: crate::error::FdtErrorKind::TooDeep
+    /// [`FdtErrorKind::TooManyNodes`]: crate::error::FdtErrorKind::TooManyNodes
+    /// [`FdtErrorKind::PropertyTooBig`]: crate::error::FdtErrorKind::PropertyTooBig
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::error::FdtErrorKind;
+    /// # use dtoolkit::fdt::{Fdt, ParseLimits};
+    /// # let dtb = include_bytes!("../../tests/dtb/test_traversal.dtb");
+    /// let limits = ParseLimits {
+    ///     max_nodes: 5,
+    ///     ..ParseLimits::default()
+    /// };
+    /// assert!(Fdt::new_with_limits(dtb, limits).is_ok());
+    /// let limits = ParseLimits {
+    ///     max_depth: 2,
+    ///     ..ParseLimits::default()
+    /// };
+    /// let error = Fdt::new_with_limits(dtb, limits).unwrap_err();
+    /// assert_eq!(error.kind, FdtErrorKind::TooDeep(2));
+    /// ```
+    pub fn new_with_limits(data: &'a [u8], limits: ParseLimits) -> Result<Self, FdtParseError> {
+        let fdt = Self::new(data)?;
+        let header = fdt.header();
+        let off_dt_struct = header.off_dt_struct() as usize;
+        let off_dt_strings = header.off_dt_strings() as usize;
+        validate_struct(
+            data,
+            off_dt_struct,
+            off_dt_struct + header.size_dt_struct() as usize,
+            off_dt_strings,
+            off_dt_strings + header.size_dt_strings() as usize,
+            limits,
+        )?;
+        Ok(fdt)
+    }
+}
@@ -15,24 +15,79 @@
 //!
 //! [Flattened Device Tree (FDT)]: https://devicetree-specification.readthedocs.io/en/latest/chapter5-flattened-format.html
 
+#[cfg(feature = "alloc")]
+mod buf;
+mod compare;
+mod embed;
+mod glob;
+#[cfg(feature = "alloc")]
+mod index;
+mod limits;
 mod node;
+mod overlay;
 mod property;
+mod scan;
+mod value;
+mod walk;
 
 use core::ffi::CStr;
 use core::fmt::{self, Debug, Display, Formatter};
+use core::iter;
 use core::mem::offset_of;
 use core::ptr;
 
 use zerocopy::byteorder::big_endian;
 use zerocopy::{FromBytes, Immutable, IntoBytes, KnownLayout, Unaligned};
 
-pub use self::node::FdtNode;
-pub use self::property::{Cells, FdtProperty};
+#[cfg(feature = "alloc")]
+pub use self::buf::FdtBuf;
+pub use self::compare::{Difference, MAX_COMPARE_DEPTH, compare};
+#[doc(hidden)]
+pub use self::embed::__include_dtb_error;
+#[cfg(feature = "alloc")]
+pub use self::index::{FdtIndex, IndexedNode};
+pub use self::limits::ParseLimits;
+pub(crate) use self::node::FdtChildIter;
+pub use self::node::{FdtNode, MAX_DESCENDANTS_DEPTH};
+pub use self::overlay::apply_overlay;
+pub(crate) use self::property::str_list;
+pub use self::property::{Cells, FdtProperty, FdtRawProperty, PropEncodedElement};
+pub use self::value::{PropertyValue, StringList, U32Array};
+pub use self::walk::{Visitor, Walk};
+use crate::diag;
 use crate::error::{FdtErrorKind, FdtParseError};
 use crate::memreserve::MemoryReservation;
+use crate::path::DtPath;
+use crate::standard::Phandle;
+
+/// The defects of a device tree blob which were tolerated by
+/// [`Fdt::new_lenient`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct ParseWarnings {
+    /// The number of bytes after the end of the blob given by its `totalsize`
+    /// field, which were ignored.
+    pub trailing_bytes: usize,
+    /// Whether the strings block is before the structure block, rather than
+    /// after it.
+    pub strings_before_struct: bool,
+}
+
+impl ParseWarnings {
+    /// Returns whether no defects were found.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
 
 /// Version of the FDT specification supported by this library.
 const FDT_VERSION: u32 = 17;
+// https://devicetree-specification.readthedocs.io/en/latest/chapter5-flattened-format.html#header
+/// Version of the FDT specification written by this library.
+pub(crate) const LAST_VERSION: u32 = 17;
+/// Oldest version of the FDT specification compatible with the written FDTs.
+pub(crate) const LAST_COMP_VERSION: u32 = 16;
 pub(crate) const FDT_TAGSIZE: usize = size_of::<u32>();
 pub(crate) const FDT_MAGIC: u32 = 0xd00d_feed;
 pub(crate) const FDT_BEGIN_NODE: u32 = 0x1;
@@ -176,6 +231,80 @@ impl<'a> Fdt<'a> {
     /// let fdt = Fdt::new(dtb).unwrap();
     /// ```
     pub fn new(data: &'a [u8]) -> Result<Self, FdtParseError> {
+        let fdt = Self::check_version(data)?;
+        if fdt.header().totalsize() as usize != data.len() {
+            return Err(FdtParseError::new(
+                FdtErrorKind::InvalidLength,
+                offset_of!(FdtHeader, totalsize),
+            ));
+        }
+
+        fdt.validate_header(None)?;
+
+        Ok(fdt)
+    }
+
+    /// Creates a new `Fdt` from the given byte slice, tolerating some common
+    /// defects of real-world blobs which [`Fdt::new`] rejects.
+    ///
+    /// This is meant for recovery and forensic tooling, rather than for
+    /// booting. The defects which are tolerated are returned as
+    /// [`ParseWarnings`], and logged with the `log` feature:
+    ///
+    /// - Data after the end of the blob given by its `totalsize` field, such
+    ///   as padding from a flash partition, is ignored.
+    /// - The strings block may come before the structure block, as long as
+    ///   they don't overlap.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`Fdt::new`], except that
+    /// [`FdtErrorKind::InvalidLength`] is only returned if `data` is shorter
+    /// than the header or its `totalsize` field.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../tests/dtb/test.dtb");
+    /// let mut data = dtb.to_vec();
+    /// data.extend_from_slice(&[0xff; 16]);
+    /// assert!(Fdt::new(&data).is_err());
+    /// let (fdt, warnings) = Fdt::new_lenient(&data).unwrap();
+    /// assert_eq!(fdt.data(), dtb);
+    /// assert_eq!(warnings.trailing_bytes, 16);
+    /// ```
+    pub fn new_lenient(data: &'a [u8]) -> Result<(Self, ParseWarnings), FdtParseError> {
+        let fdt = Self::check_version(data)?;
+        let totalsize = fdt.header().totalsize() as usize;
+        if !(size_of::<FdtHeader>()..=data.len()).contains(&totalsize) {
+            return Err(FdtParseError::new(
+                FdtErrorKind::InvalidLength,
+                offset_of!(FdtHeader, totalsize),
+            ));
+        }
+        let fdt = Fdt {
+            data: &data[..totalsize],
+        };
+        let mut warnings = ParseWarnings {
+            trailing_bytes: data.len() - totalsize,
+            ..ParseWarnings::default()
+        };
+        if warnings.trailing_bytes > 0 {
+            diag::warning!(
+                "ignoring {} bytes after the end of the FDT",
+                warnings.trailing_bytes
+            );
+        }
+
+        fdt.validate_header(Some(&mut warnings))?;
+
+        Ok((fdt, warnings))
+    }
+
+    /// Checks that `data` starts with an FDT header with the right magic
+    /// number and a supported version.
+    fn check_version(data: &'a [u8]) -> Result<Self, FdtParseError> {
         if data.len() < size_of::<FdtHeader>() {
             return Err(FdtParseError::new(FdtErrorKind::InvalidLength, 0));
         }
@@ -196,15 +325,6 @@ impl<'a> Fdt<'a> {
             ));
         }
 
-        if header.totalsize() as usize != data.len() {
-            return Err(FdtParseError::new(
-                FdtErrorKind::InvalidLength,
-                offset_of!(FdtHeader, totalsize),
-            ));
-        }
-
-        fdt.validate_header()?;
-
         Ok(fdt)
     }
 
@@ -252,7 +372,12 @@ impl<'a> Fdt<'a> {
         Fdt::new(slice)
     }
 
-    fn validate_header(self) -> Result<(), FdtParseError> {
+    /// Checks that the blocks given by the header are within the blob and in
+    /// the right order.
+    ///
+    /// With `warnings`, the defects tolerated by [`Fdt::new_lenient`] are
+    /// recorded in it rather than returned as errors.
+    fn validate_header(self, warnings: Option<&mut ParseWarnings>) -> Result<(), FdtParseError> {
         let header = self.header();
         let data = &self.data;
 
@@ -293,6 +418,13 @@ impl<'a> Fdt<'a> {
             ));
         }
         if off_dt_struct.saturating_add(size_dt_struct) > off_dt_strings {
+            if let Some(warnings) = warnings
+                && off_dt_strings.saturating_add(size_dt_strings) <= off_dt_struct
+            {
+                diag::warning!("the FDT strings block is before the struct block");
+                warnings.strings_before_struct = true;
+                return Ok(());
+            }
             return Err(FdtParseError::new(
                 FdtErrorKind::InvalidHeader("strings block not after struct block"),
                 offset_of!(FdtHeader, off_dt_strings),
@@ -334,31 +466,25 @@ impl<'a> Fdt<'a> {
     }
 
     /// Returns an iterator over the memory reservation block.
+    ///
+    /// The block is scanned up front to find its terminator, so the iterator
+    /// knows its exact length. If the block is malformed, the last item is an
+    /// error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../tests/dtb/test_memreserve.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let reservations = fdt.memory_reservations();
+    /// assert_eq!(reservations.len(), 2);
+    /// ```
+    #[must_use]
     pub fn memory_reservations(
         self,
-    ) -> impl Iterator<Item = Result<MemoryReservation, FdtParseError>> + 'a {
-        let mut offset = self.header().off_mem_rsvmap() as usize;
-        core::iter::from_fn(move || {
-            if offset >= self.header().off_dt_struct() as usize {
-                return Some(Err(FdtParseError::new(
-                    FdtErrorKind::MemReserveNotTerminated,
-                    offset,
-                )));
-            }
-
-            let reservation = match MemoryReservation::ref_from_prefix(&self.data[offset..])
-                .map_err(|_| FdtParseError::new(FdtErrorKind::MemReserveInvalid, offset))
-            {
-                Ok((reservation, _)) => *reservation,
-                Err(e) => return Some(Err(e)),
-            };
-            offset += size_of::<MemoryReservation>();
-
-            if reservation == MemoryReservation::TERMINATOR {
-                return None;
-            }
-            Some(Ok(reservation))
-        })
+    ) -> impl ExactSizeIterator<Item = Result<MemoryReservation, FdtParseError>> + 'a {
+        MemoryReservationIter::new(self)
     }
 
     /// Returns the root node of the device tree.
@@ -401,21 +527,25 @@ impl<'a> Fdt<'a> {
     /// `/cpus/cpu@0` or `/cpus/cpu@1`, while `/cpus/cpu@1` would match only the
     /// latter.
     ///
+    /// Like Linux's `of_find_node_by_path`, a path which doesn't start with `/`
+    /// starts with an alias from the `/aliases` node, so `serial0/child` finds
+    /// the `child` node of the node aliased as `serial0`.
+    ///
     /// # Performance
     ///
     /// This method traverses the device tree and its performance is linear in
     /// the number of nodes in the path. If you need to call this often,
-    /// consider using
-    /// [`DeviceTree::from_fdt`](crate::model::DeviceTree::from_fdt)
-    /// first. [`DeviceTree`](crate::model::DeviceTree) stores the nodes in a
-    /// hash map for constant-time lookup.
+    /// consider building an [`FdtIndex`] first, or using
+    /// [`DeviceTree::from_fdt`](crate::model::DeviceTree::from_fdt).
+    /// [`DeviceTree`](crate::model::DeviceTree) stores the nodes in a hash map
+    /// for constant-time lookup.
     ///
     /// # Errors
     ///
     /// Returns an [`FdtErrorKind::InvalidLength`] if the FDT structure is
     /// truncated or, an [`FdtErrorKind::BadToken`] if an unexpected token is
     /// encountered while searching, or an [`FdtErrorKind::InvalidString`] if a
-    /// node has an invalid name.
+    /// node has an invalid name or an alias has an invalid path.
     ///
     /// # Examples
     ///
@@ -437,14 +567,18 @@ impl<'a> Fdt<'a> {
     /// assert_eq!(node.name().unwrap(), "child2@42");
     /// ```
     pub fn find_node(self, path: &str) -> Result<Option<FdtNode<'a>>, FdtParseError> {
-        if !path.starts_with('/') {
+        let mut path = DtPath::new(path);
+        let mut current_node = if path.is_absolute() {
+            self.root()?
+        } else if let Some((alias, rest)) = path.split_alias()
+            && let Some(node) = self.resolve_alias(alias)?
+        {
+            path = rest;
+            node
+        } else {
             return Ok(None);
-        }
-        let mut current_node = self.root()?;
-        if path == "/" {
-            return Ok(Some(current_node));
-        }
-        for component in path.split('/').filter(|s| !s.is_empty()) {
+        };
+        for component in path.segments() {
             match current_node.child(component)? {
                 Some(node) => current_node = node,
                 None => return Ok(None),
@@ -453,6 +587,74 @@ impl<'a> Fdt<'a> {
         Ok(Some(current_node))
     }
 
+    /// Finds the node at the given absolute path, whose segments must match
+    /// the names of the nodes exactly, including their unit addresses.
+    ///
+    /// This looks nodes up as [`DeviceTree`](crate::model::DeviceTree) does,
+    /// unlike [`Fdt::find_node`], which also resolves aliases and matches
+    /// names without unit addresses.
+    #[cfg(feature = "write")]
+    pub(crate) fn find_node_exact(self, path: &str) -> Result<Option<FdtNode<'a>>, FdtParseError> {
+        let path = DtPath::new(path);
+        if !path.is_absolute() {
+            return Ok(None);
+        }
+        let mut node = self.root()?;
+        for segment in path.segments() {
+            match compare::child_named(node, segment)? {
+                Some(child) => node = child,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(node))
+    }
+
+    /// Finds the node with the given `phandle`.
+    ///
+    /// If several nodes have the same `phandle`, the first one in the device
+    /// tree is returned. Nodes whose `phandle` property has a reserved value
+    /// are skipped, as by [`FdtIndex`].
+    ///
+    /// # Performance
+    ///
+    /// This method traverses the whole device tree in the worst case. If you
+    /// need to call this often, consider building an [`FdtIndex`] first.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the device tree structure is malformed, a `phandle`
+    /// property isn't a valid u32, or nodes are nested more than
+    /// [`MAX_DESCENDANTS_DEPTH`] levels deep before the node is found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # use dtoolkit::standard::Phandle;
+    /// # let dtb = include_bytes!("../../tests/dtb/test_props.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let phandle = Phandle::new(0x1234).unwrap();
+    /// let node = fdt.find_node_by_phandle(phandle).unwrap().unwrap();
+    /// assert_eq!(node.name().unwrap(), "standard-props@1");
+    /// let phandle = Phandle::new(0x1).unwrap();
+    /// assert!(fdt.find_node_by_phandle(phandle).unwrap().is_none());
+    /// ```
+    pub fn find_node_by_phandle(
+        self,
+        phandle: Phandle,
+    ) -> Result<Option<FdtNode<'a>>, FdtParseError> {
+        let root = self.root()?;
+        for node in iter::once(Ok(root)).chain(root.descendants()) {
+            let node = node?;
+            if let Some(property) = node.property("phandle")?
+                && property.as_u32()? == phandle.get()
+            {
+                return Ok(Some(node));
+            }
+        }
+        Ok(None)
+    }
+
     pub(crate) fn read_token(self, offset: usize) -> Result<FdtToken, FdtParseError> {
         let val = big_endian::U32::ref_from_prefix(&self.data[offset..])
             .map(|(val, _)| val.get())
@@ -460,6 +662,13 @@ impl<'a> Fdt<'a> {
         FdtToken::try_from(val).map_err(|t| FdtParseError::new(FdtErrorKind::BadToken(t), offset))
     }
 
+    /// Returns the strings block of the device tree.
+    pub(crate) fn strings_block(self) -> &'a [u8] {
+        let header = self.header();
+        let start = header.off_dt_strings() as usize;
+        &self.data[start..start + header.size_dt_strings() as usize]
+    }
+
     /// Returns a string from the string block.
     pub(crate) fn string(self, string_block_offset: usize) -> Result<&'a str, FdtParseError> {
         let header = self.header();
@@ -475,6 +684,23 @@ impl<'a> Fdt<'a> {
         self.string_at_offset(str_start, Some(str_block_end))
     }
 
+    /// Returns the bytes of a string from the string block, without its NUL
+    /// terminator or checking that it is valid UTF-8.
+    pub(crate) fn string_bytes(
+        self,
+        string_block_offset: usize,
+    ) -> Result<&'a [u8], FdtParseError> {
+        let str_block_start = self.header().off_dt_strings() as usize;
+        self.strings_block()
+            .get(string_block_offset..)
+            .and_then(|data| CStr::from_bytes_until_nul(data).ok())
+            .map(CStr::to_bytes)
+            .ok_or(FdtParseError::new(
+                FdtErrorKind::InvalidString,
+                str_block_start + string_block_offset,
+            ))
+    }
+
     /// Returns a NUL-terminated string from a given offset.
     pub(crate) fn string_at_offset(
         self,
@@ -493,55 +719,50 @@ impl<'a> Fdt<'a> {
         }
     }
 
+    /// Returns the offset just past the NUL terminator of the string starting
+    /// at `start`.
     pub(crate) fn find_string_end(self, start: usize) -> Result<usize, FdtParseError> {
-        let mut offset = start;
-        loop {
-            match self.data.get(offset) {
-                Some(0) => return Ok(offset + 1),
-                Some(_) => {}
-                None => return Err(FdtParseError::new(FdtErrorKind::InvalidString, start)),
-            }
-            offset += 1;
-        }
+        // `CStr::from_bytes_until_nul` searches a word at a time rather than a
+        // byte at a time, which matters as node names are scanned on every
+        // traversal.
+        self.data
+            .get(start..)
+            .and_then(|data| CStr::from_bytes_until_nul(data).ok())
+            .map(|string| start + string.count_bytes() + 1)
+            .ok_or(FdtParseError::new(FdtErrorKind::InvalidString, start))
     }
 
+    /// Returns the offset just past the end of the node starting at `offset`,
+    /// including all of its descendants.
     pub(crate) fn next_sibling_offset(self, mut offset: usize) -> Result<usize, FdtParseError> {
-        offset += FDT_TAGSIZE; // Skip FDT_BEGIN_NODE
-
-        // Skip node name
-        offset = self.find_string_end(offset)?;
-        offset = Self::align_tag_offset(offset);
-
-        // Skip properties
+        // Walk the tokens rather than recursing into child nodes, so that deeply
+        // nested nodes can't overflow the stack.
+        let mut depth = 0usize;
         loop {
-            let token = self.read_token(offset)?;
-            match token {
+            match self.read_token(offset)? {
+                FdtToken::BeginNode => {
+                    depth += 1;
+                    offset += FDT_TAGSIZE; // Skip FDT_BEGIN_NODE
+                    offset = self.find_string_end(offset)?; // Skip node name
+                    offset = Self::align_tag_offset(offset);
+                }
                 FdtToken::Prop => {
                     offset += FDT_TAGSIZE; // skip FDT_PROP
                     offset = self.next_property_offset(offset)?;
                 }
-                FdtToken::Nop => offset += FDT_TAGSIZE,
-                _ => break,
-            }
-        }
-
-        // Skip child nodes
-        loop {
-            let token = self.read_token(offset)?;
-            match token {
-                FdtToken::BeginNode => {
-                    offset = self.next_sibling_offset(offset)?;
-                }
                 FdtToken::EndNode => {
                     offset += FDT_TAGSIZE;
-                    break;
+                    if depth <= 1 {
+                        return Ok(offset);
+                    }
+                    depth -= 1;
                 }
                 FdtToken::Nop => offset += FDT_TAGSIZE,
-                _ => {}
+                FdtToken::End => {
+                    return Err(FdtParseError::new(FdtErrorKind::BadToken(FDT_END), offset));
+                }
             }
         }
-
-        Ok(offset)
     }
 
     pub(crate) fn next_property_offset(self, mut offset: usize) -> Result<usize, FdtParseError> {
@@ -561,6 +782,86 @@ impl<'a> Fdt<'a> {
     }
 }
 
+/// An iterator over the memory reservation block of a device tree.
+struct MemoryReservationIter<'a> {
+    fdt: Fdt<'a>,
+    offset: usize,
+    /// The number of items left, including a final error if the block is
+    /// malformed.
+    remaining: usize,
+}
+
+impl<'a> MemoryReservationIter<'a> {
+    fn new(fdt: Fdt<'a>) -> Self {
+        let offset = fdt.header().off_mem_rsvmap() as usize;
+        let mut iter = Self {
+            fdt,
+            offset,
+            remaining: 0,
+        };
+        let scan = Self {
+            remaining: usize::MAX,
+            ..iter
+        };
+        for reservation in scan {
+            iter.remaining += 1;
+            if reservation.is_err() {
+                break;
+            }
+        }
+        iter
+    }
+
+    fn read(&mut self) -> Option<Result<MemoryReservation, FdtParseError>> {
+        let offset = self.offset;
+        if offset >= self.fdt.header().off_dt_struct() as usize {
+            return Some(Err(FdtParseError::new(
+                FdtErrorKind::MemReserveNotTerminated,
+                offset,
+            )));
+        }
+
+        let reservation = match MemoryReservation::ref_from_prefix(&self.fdt.data[offset..])
+            .map_err(|_| FdtParseError::new(FdtErrorKind::MemReserveInvalid, offset))
+        {
+            Ok((reservation, _)) => *reservation,
+            Err(e) => return Some(Err(e)),
+        };
+        self.offset += size_of::<MemoryReservation>();
+
+        if reservation == MemoryReservation::TERMINATOR {
+            return None;
+        }
+        Some(Ok(reservation))
+    }
+}
+
+impl Iterator for MemoryReservationIter<'_> {
+    type Item = Result<MemoryReservation, FdtParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        let item = self.read();
+        self.remaining = match item {
+            Some(Ok(_)) => self.remaining - 1,
+            _ => 0,
+        };
+        item
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl ExactSizeIterator for MemoryReservationIter<'_> {}
+
+/// Formats the device tree in DTS format.
+///
+/// As with [`FdtNode`], the precision and alternate flags limit how deep
+/// into the tree the nodes are printed.
 impl Display for Fdt<'_> {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         writeln!(f, "/dts-v1/;")?;
@@ -575,7 +876,7 @@ impl Display for Fdt<'_> {
         }
         writeln!(f)?;
         let root = self.root().map_err(|_| fmt::Error)?;
-        root.fmt_recursive(f, 0)
+        root.fmt_indented(f, 0)
     }
 }
 
@@ -640,4 +941,27 @@ mod tests {
         let result = Fdt::new(&header);
         assert!(matches!(result, Err(e) if matches!(e.kind, FdtErrorKind::UnsupportedVersion(16))));
     }
+
+    #[test]
+    fn find_string_end() {
+        let dtb = include_bytes!("../../tests/dtb/test_children.dtb");
+        let fdt = Fdt::new(dtb).unwrap();
+        let child = fdt.find_node("/child1").unwrap().unwrap();
+        let name_offset = child.offset + FDT_TAGSIZE;
+        assert_eq!(
+            fdt.find_string_end(name_offset),
+            Ok(name_offset + "child1".len() + 1)
+        );
+
+        let fdt = Fdt::new(FDT_HEADER_OK).unwrap();
+        // The last bytes of the blob are `FDT_END`, which isn't NUL-terminated.
+        assert_eq!(
+            fdt.find_string_end(FDT_HEADER_OK.len() - 1),
+            Err(FdtParseError::new(
+                FdtErrorKind::InvalidString,
+                FDT_HEADER_OK.len() - 1
+            ))
+        );
+        assert!(fdt.find_string_end(FDT_HEADER_OK.len() + 1).is_err());
+    }
 }
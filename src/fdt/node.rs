@@ -11,10 +11,18 @@
 use core::fmt::{self, Display, Formatter};
 
 use super::{FDT_TAGSIZE, Fdt, FdtToken};
-use crate::error::FdtParseError;
-use crate::fdt::property::{FdtPropIter, FdtProperty};
+use crate::error::{FdtContextError, FdtError, FdtErrorKind, FdtParseError};
+use crate::fdt::property::{FdtPropIter, FdtProperty, FdtRawPropIter, FdtRawProperty};
+use crate::path;
+#[cfg(feature = "alloc")]
+use crate::path::DtPathBuf;
 use crate::standard::AddressSpaceProperties;
 
+/// The maximum depth of nodes below a node which [`FdtNode::descendants`] can
+/// descend into, as it keeps the nodes being walked at each level on the stack
+/// rather than allocating.
+pub const MAX_DESCENDANTS_DEPTH: usize = 32;
+
 /// A node in a flattened device tree.
 #[derive(Debug, Clone, Copy)]
 pub struct FdtNode<'a> {
@@ -77,6 +85,215 @@ impl<'a> FdtNode<'a> {
         }
     }
 
+    /// Returns the path of this node.
+    ///
+    /// # Performance
+    ///
+    /// The path isn't stored in the device tree, so this walks from the root
+    /// node to find the ancestors of this node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the device tree structure is malformed or the name
+    /// of an ancestor cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../tests/dtb/test_traversal.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let node = fdt.find_node("/a/b/c").unwrap().unwrap();
+    /// assert_eq!(node.path().unwrap().as_str(), "/a/b/c");
+    /// assert_eq!(fdt.root().unwrap().path().unwrap().as_str(), "/");
+    /// ```
+    #[cfg(feature = "alloc")]
+    pub fn path(&self) -> Result<DtPathBuf, FdtParseError> {
+        let mut path = DtPathBuf::root();
+        self.visit_path(|name| path.push(name))?;
+        Ok(path)
+    }
+
+    /// Writes the path of this node to the given sink, without allocating.
+    ///
+    /// # Performance
+    ///
+    /// Like [`path`](Self::path), this walks from the root node to find the
+    /// ancestors of this node.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`fmt::Error`] if the sink returns an error, or if the device
+    /// tree structure is malformed or the name of an ancestor cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../tests/dtb/test_traversal.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let node = fdt.find_node("/a/b/c").unwrap().unwrap();
+    /// let mut path = String::new();
+    /// node.write_path(&mut path).unwrap();
+    /// assert_eq!(path, "/a/b/c");
+    /// ```
+    pub fn write_path(&self, out: &mut impl fmt::Write) -> fmt::Result {
+        let mut result = Ok(());
+        let mut root = true;
+        self.visit_path(|name| {
+            root = false;
+            if result.is_ok() {
+                result = write!(out, "/{name}");
+            }
+        })
+        .map_err(|_| fmt::Error)?;
+        result?;
+        if root {
+            out.write_char('/')?;
+        }
+        Ok(())
+    }
+
+    /// Writes the path of this node into the given buffer, without allocating,
+    /// and returns the part of the buffer which holds it.
+    ///
+    /// # Performance
+    ///
+    /// Like [`path`](Self::path), this walks from the root node to find the
+    /// ancestors of this node.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FdtError::BufferTooSmall`] with the length of the path if it
+    /// doesn't fit in `buffer`, or an error if the device tree structure is
+    /// malformed or the name of an ancestor cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::error::FdtError;
+    /// # use dtoolkit::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../tests/dtb/test_traversal.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let node = fdt.find_node("/a/b/c").unwrap().unwrap();
+    /// let mut buffer = [0; 16];
+    /// assert_eq!(node.path_into(&mut buffer).unwrap(), "/a/b/c");
+    /// assert_eq!(
+    ///     node.path_into(&mut buffer[..4]),
+    ///     Err(FdtError::BufferTooSmall { needed: 6 })
+    /// );
+    /// ```
+    pub fn path_into<'b>(&self, buffer: &'b mut [u8]) -> Result<&'b str, FdtError> {
+        let mut len = 0;
+        self.visit_path(|name| {
+            let end = len + 1 + name.len();
+            if let Some(segment) = buffer.get_mut(len..end) {
+                segment[0] = b'/';
+                segment[1..].copy_from_slice(name.as_bytes());
+            }
+            len = end;
+        })?;
+        if len == 0 {
+            if let Some(first) = buffer.first_mut() {
+                *first = b'/';
+            }
+            len = 1;
+        }
+        let path = buffer
+            .get(..len)
+            .ok_or(FdtError::BufferTooSmall { needed: len })?;
+        core::str::from_utf8(path)
+            .map_err(|_| FdtParseError::new(FdtErrorKind::InvalidString, self.offset).into())
+    }
+
+    /// Calls `visit` with the name of each ancestor of this node below the
+    /// root, from the outermost, and then with the name of this node, unless
+    /// it is the root.
+    fn visit_path(&self, mut visit: impl FnMut(&'a str)) -> Result<(), FdtParseError> {
+        let mut node = self.fdt.root()?;
+        while node.offset != self.offset {
+            let mut next = None;
+            for child in node.children() {
+                let child = child?;
+                if child.offset > self.offset {
+                    break;
+                }
+                next = Some(child);
+            }
+            node = next.ok_or(FdtParseError::new(FdtErrorKind::InvalidOffset, self.offset))?;
+            visit(node.name()?);
+        }
+        Ok(())
+    }
+
+    /// Returns the parent of this node, or `None` for the root node.
+    ///
+    /// # Performance
+    ///
+    /// Like [`path`](Self::path), this walks from the root node to find the
+    /// ancestors of this node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the device tree structure is malformed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../tests/dtb/test_traversal.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let node = fdt.find_node("/a/b/c").unwrap().unwrap();
+    /// assert_eq!(node.parent().unwrap().unwrap().name(), Ok("b"));
+    /// assert!(fdt.root().unwrap().parent().unwrap().is_none());
+    /// ```
+    pub fn parent(&self) -> Result<Option<FdtNode<'a>>, FdtParseError> {
+        let mut parent = None;
+        let mut node = self.fdt.root()?;
+        while node.offset != self.offset {
+            let mut next = None;
+            for child in node.children() {
+                let child = child?;
+                if child.offset > self.offset {
+                    break;
+                }
+                next = Some(child);
+            }
+            parent = Some(node);
+            node = next.ok_or(FdtParseError::new(FdtErrorKind::InvalidOffset, self.offset))?;
+        }
+        Ok(parent)
+    }
+
+    /// Returns the unit address of this node, which is the part of its name
+    /// after the `@` sign, if any.
+    ///
+    /// Use [`parse_unit_address`](crate::standard::parse_unit_address) to
+    /// parse it as numbers.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the name of the node cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../tests/dtb/test_children.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let root = fdt.root().unwrap();
+    /// let child = root.child("child2").unwrap().unwrap();
+    /// assert_eq!(child.unit_address().unwrap(), Some("42"));
+    /// let child = root.child("child1").unwrap().unwrap();
+    /// assert_eq!(child.unit_address().unwrap(), None);
+    /// ```
+    pub fn unit_address(&self) -> Result<Option<&'a str>, FdtParseError> {
+        Ok(self
+            .name()?
+            .split_once('@')
+            .map(|(_, unit_address)| unit_address))
+    }
+
     /// Returns a property by its name.
     ///
     /// # Performance
@@ -107,6 +324,92 @@ impl<'a> FdtNode<'a> {
         Ok(None)
     }
 
+    /// Returns the property with the given name, or an error naming the node
+    /// if it is missing.
+    ///
+    /// This is a convenience for properties which a binding requires, so that
+    /// callers don't need to turn the `None` from [`FdtNode::property`] into
+    /// an error themselves.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtError::MissingProperty`] if the node doesn't have the
+    /// property, or an [`FdtError::Parse`] if a property's name or value
+    /// cannot be read. Either way the error includes the node and property.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../tests/dtb/test_props.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let node = fdt.find_node("/test-props").unwrap().unwrap();
+    /// assert_eq!(
+    ///     node.required_property("u32-prop").unwrap().name(),
+    ///     "u32-prop"
+    /// );
+    /// let error = node.required_property("missing").unwrap_err();
+    /// assert_eq!(error.property, Some("missing"));
+    /// ```
+    pub fn required_property(
+        &self,
+        name: &'static str,
+    ) -> Result<FdtProperty<'a>, FdtContextError> {
+        match self.property(name) {
+            Ok(Some(property)) => Ok(property),
+            Ok(None) => Err(FdtError::MissingProperty { name }.with_context(*self, Some(name))),
+            Err(e) => Err(FdtError::from(e).with_context(*self, Some(name))),
+        }
+    }
+
+    /// Returns the value of the required property with the given name as a
+    /// `u32`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the property is missing, as for
+    /// [`FdtNode::required_property`], or if its value is not 4 bytes long.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../tests/dtb/test_props.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let node = fdt.find_node("/test-props").unwrap().unwrap();
+    /// assert_eq!(node.required_u32("u32-prop").unwrap(), 0x12345678);
+    /// assert!(node.required_u32("u64-prop").is_err());
+    /// ```
+    pub fn required_u32(&self, name: &'static str) -> Result<u32, FdtContextError> {
+        self.required_property(name)?
+            .as_u32()
+            .map_err(|e| FdtError::from(e).with_context(*self, Some(name)))
+    }
+
+    /// Returns the value of the required property with the given name as a
+    /// string.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the property is missing, as for
+    /// [`FdtNode::required_property`], or if its value is not a single
+    /// NUL-terminated UTF-8 string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../tests/dtb/test_props.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let node = fdt.find_node("/test-props").unwrap().unwrap();
+    /// assert_eq!(node.required_str("str-prop").unwrap(), "hello world");
+    /// ```
+    pub fn required_str(&self, name: &'static str) -> Result<&'a str, FdtContextError> {
+        self.required_property(name)?
+            .as_str()
+            .map_err(|e| FdtError::from(e).with_context(*self, Some(name)))
+    }
+
     /// Returns an iterator over the properties of this node.
     ///
     /// # Examples
@@ -130,6 +433,37 @@ impl<'a> FdtNode<'a> {
         }
     }
 
+    /// Returns an iterator over the properties of this node which doesn't
+    /// check that their names are valid UTF-8.
+    ///
+    /// This is cheaper than [`properties`](Self::properties) when the names
+    /// are only compared against known constants, such as when searching for
+    /// a handful of properties early in boot. The structure of the device
+    /// tree is still checked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../tests/dtb/test_props.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let node = fdt.find_node("/test-props").unwrap().unwrap();
+    /// let prop = node
+    ///     .raw_properties()
+    ///     .map(Result::unwrap)
+    ///     .find(|prop| prop.name() == b"u32-prop")
+    ///     .unwrap();
+    /// assert_eq!(prop.value(), [0x12, 0x34, 0x56, 0x78]);
+    /// ```
+    pub fn raw_properties(
+        &self,
+    ) -> impl Iterator<Item = Result<FdtRawProperty<'a>, FdtParseError>> + use<'a> {
+        FdtRawPropIter(FdtPropIter::Start {
+            fdt: self.fdt,
+            offset: self.offset,
+        })
+    }
+
     /// Returns a child node by its name.
     ///
     /// If the given name contains a _unit-address_ (the part after the `@`
@@ -149,6 +483,8 @@ impl<'a> FdtNode<'a> {
     /// [`DeviceTreeNode`](crate::model::DeviceTreeNode) first. Child lookup
     /// on a [`DeviceTreeNode`](crate::model::DeviceTreeNode) is a
     /// constant-time operation.
+    /// With the `alloc` feature, `IndexedNode` indexes the children of a
+    /// single node without converting the whole tree.
     ///
     /// # Errors
     ///
@@ -176,14 +512,9 @@ impl<'a> FdtNode<'a> {
     /// assert_eq!(child.name().unwrap(), "child2@42");
     /// ```
     pub fn child(&self, name: &str) -> Result<Option<FdtNode<'a>>, FdtParseError> {
-        let include_address = name.contains('@');
         for child in self.children() {
             let child = child?;
-            if if include_address {
-                child.name()? == name
-            } else {
-                child.name_without_address()? == name
-            } {
+            if path::segment_matches(name, child.name()?) {
                 return Ok(Some(child));
             }
         }
@@ -211,52 +542,187 @@ impl<'a> FdtNode<'a> {
         FdtChildIter::Start { node: *self }
     }
 
-    pub(crate) fn fmt_recursive(&self, f: &mut Formatter, indent: usize) -> fmt::Result {
-        let name = self.name().map_err(|_| fmt::Error)?;
-        if name.is_empty() {
-            writeln!(f, "{:indent$}/ {{", "", indent = indent)?;
-        } else {
-            writeln!(f, "{:indent$}{} {{", "", name, indent = indent)?;
+    /// Returns an iterator over the descendants of this node, not including
+    /// the node itself.
+    ///
+    /// The nodes are returned depth-first, with each node before its
+    /// children, in the order they appear in the device tree. As with
+    /// [`children`](Self::children), each node knows the `#address-cells` and
+    /// `#size-cells` of its parent, so its `reg` property can be decoded.
+    ///
+    /// The nodes being walked at each level are kept on the stack rather than
+    /// allocating, so nodes nested more than [`MAX_DESCENDANTS_DEPTH`] levels
+    /// below this node aren't returned, and an
+    /// [`FdtErrorKind::TooDeep`](crate::error::FdtErrorKind::TooDeep) error is
+    /// returned instead. The iterator ends after returning an error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../tests/dtb/test_traversal.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let root = fdt.root().unwrap();
+    /// let names: Vec<_> = root
+    ///     .descendants()
+    ///     .map(|node| node.unwrap().name().unwrap())
+    ///     .collect();
+    /// assert_eq!(names, ["a", "b", "c", "d"]);
+    /// ```
+    pub fn descendants(
+        &self,
+    ) -> impl Iterator<Item = Result<FdtNode<'a>, FdtParseError>> + use<'a> {
+        let mut stack = [const { None }; MAX_DESCENDANTS_DEPTH];
+        stack[0] = Some(FdtChildIter::Start { node: *self });
+        FdtDescendants {
+            stack,
+            depth: 1,
+            done: false,
         }
+    }
 
-        let mut has_properties = false;
-        for prop in self.properties() {
-            has_properties = true;
-            match prop {
-                Ok(prop) => prop.fmt(f, indent + 4)?,
-                Err(_e) => {
-                    writeln!(f, "<Error reading property>")?;
+    /// Formats this node and its descendants in DTS format.
+    ///
+    /// This walks the tokens of the structure block rather than recursing into
+    /// children, so deeply nested nodes can't overflow the stack.
+    ///
+    /// Descendants more than the formatter's precision levels below this node
+    /// are left out, or all of them with the alternate flag.
+    pub(crate) fn fmt_indented(&self, f: &mut Formatter, indent: usize) -> fmt::Result {
+        let max_depth = if f.alternate() {
+            0
+        } else {
+            f.precision().unwrap_or(usize::MAX)
+        };
+        let fdt = self.fdt;
+        let mut offset = self.offset;
+        let mut depth = 0;
+        // Whether a blank line is needed before the next child node.
+        let mut separate = false;
+        loop {
+            let indent = indent + depth * 4;
+            match fdt.read_token(offset).map_err(|_| fmt::Error)? {
+                FdtToken::BeginNode if depth > max_depth => {
+                    offset = fdt.next_sibling_offset(offset).map_err(|_| fmt::Error)?;
+                }
+                FdtToken::BeginNode => {
+                    if separate {
+                        writeln!(f)?;
+                    }
+                    let name = FdtNode::new(fdt, offset).name().map_err(|_| fmt::Error)?;
+                    if name.is_empty() {
+                        writeln!(f, "{:indent$}/ {{", "", indent = indent)?;
+                    } else {
+                        writeln!(f, "{:indent$}{} {{", "", name, indent = indent)?;
+                    }
+                    depth += 1;
+                    separate = false;
+                    offset = fdt
+                        .find_string_end(offset + FDT_TAGSIZE)
+                        .map_err(|_| fmt::Error)?;
+                    offset = Fdt::align_tag_offset(offset);
                 }
+                FdtToken::Prop => {
+                    let next_offset = fdt
+                        .next_property_offset(offset + FDT_TAGSIZE)
+                        .map_err(|_| fmt::Error)?;
+                    match FdtPropIter::try_next(fdt, &mut offset) {
+                        Some(Ok(prop)) => prop.fmt(f, indent)?,
+                        _ => writeln!(f, "<Error reading property>")?,
+                    }
+                    offset = next_offset;
+                    separate = true;
+                }
+                FdtToken::EndNode => {
+                    depth = depth.checked_sub(1).ok_or(fmt::Error)?;
+                    writeln!(f, "{:indent$}}};", "", indent = indent - 4)?;
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                    offset += FDT_TAGSIZE;
+                    separate = true;
+                }
+                FdtToken::Nop => offset += FDT_TAGSIZE,
+                FdtToken::End => return Err(fmt::Error),
             }
         }
+    }
+}
 
-        let mut first_child = true;
-        for child in self.children() {
-            if !first_child || has_properties {
-                writeln!(f)?;
-            }
+/// Formats the node and its descendants in DTS format.
+///
+/// The precision limits how many levels of descendants are included, so
+/// `{:.1}` prints the node and its children but not their children. The
+/// alternate flag, `{:#}`, prints only the node itself.
+///
+/// # Examples
+///
+/// ```
+/// # use dtoolkit::fdt::Fdt;
+/// # let dtb = include_bytes!("../../tests/dtb/test_traversal.dtb");
+/// let fdt = Fdt::new(dtb).unwrap();
+/// let node = fdt.find_node("/a").unwrap().unwrap();
+/// assert_eq!(format!("{node:#}"), "a {\n};\n");
+/// assert_eq!(format!("{node:.1}"), "a {\n    b {\n    };\n};\n");
+/// ```
+impl Display for FdtNode<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.fmt_indented(f, 0)
+    }
+}
 
-            first_child = false;
-            match child {
-                Ok(child) => child.fmt_recursive(f, indent + 4)?,
-                Err(_e) => {
-                    writeln!(f, "<Error reading child node>")?;
-                }
+/// The iterator returned by [`FdtNode::descendants`].
+struct FdtDescendants<'a> {
+    /// The children being walked at each level, from the node whose
+    /// descendants are returned down.
+    stack: [Option<FdtChildIter<'a>>; MAX_DESCENDANTS_DEPTH],
+    /// The number of levels in `stack`.
+    depth: usize,
+    done: bool,
+}
+
+impl<'a> FdtDescendants<'a> {
+    fn next_node(&mut self) -> Result<Option<FdtNode<'a>>, FdtParseError> {
+        while let Some(index) = self.depth.checked_sub(1) {
+            let Some(children) = &mut self.stack[index] else {
+                unreachable!("levels below the depth are set");
+            };
+            let Some(child) = children.next().transpose()? else {
+                self.depth = index;
+                continue;
+            };
+            if let Some(level) = self.stack.get_mut(self.depth) {
+                *level = Some(FdtChildIter::Start { node: child });
+                self.depth += 1;
+            } else if child.children().next().is_some() {
+                return Err(FdtParseError::new(
+                    FdtErrorKind::TooDeep(MAX_DESCENDANTS_DEPTH),
+                    child.offset,
+                ));
             }
+            return Ok(Some(child));
         }
-
-        writeln!(f, "{:indent$}}};", "", indent = indent)
+        Ok(None)
     }
 }
 
-impl Display for FdtNode<'_> {
-    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        self.fmt_recursive(f, 0)
+impl<'a> Iterator for FdtDescendants<'a> {
+    type Item = Result<FdtNode<'a>, FdtParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let node = self.next_node().transpose();
+        if !matches!(node, Some(Ok(_))) {
+            self.done = true;
+        }
+        node
     }
 }
 
 /// An iterator over the children of a device tree node.
-enum FdtChildIter<'a> {
+pub(crate) enum FdtChildIter<'a> {
     Start {
         node: FdtNode<'a>,
     },
@@ -274,13 +740,10 @@ impl<'a> Iterator for FdtChildIter<'a> {
     fn next(&mut self) -> Option<Self::Item> {
         match self {
             Self::Start { node } => {
-                let address_space = match node.address_space() {
-                    Ok(value) => value,
-                    Err(e) => return Some(Err(e)),
-                };
+                let fdt = node.fdt;
                 let mut offset = node.offset;
                 offset += FDT_TAGSIZE; // Skip FDT_BEGIN_NODE
-                offset = match node.fdt.find_string_end(offset) {
+                offset = match fdt.find_string_end(offset) {
                     Ok(offset) => offset,
                     Err(e) => {
                         *self = Self::Error;
@@ -288,8 +751,17 @@ impl<'a> Iterator for FdtChildIter<'a> {
                     }
                 };
                 offset = Fdt::align_tag_offset(offset);
+                // Read the address space while skipping the properties, rather than
+                // looking up `#address-cells` and `#size-cells` separately.
+                let address_space = match Self::skip_properties(fdt, &mut offset) {
+                    Ok(address_space) => address_space,
+                    Err(e) => {
+                        *self = Self::Error;
+                        return Some(Err(e));
+                    }
+                };
                 *self = Self::Running {
-                    fdt: node.fdt,
+                    fdt,
                     offset,
                     address_space,
                 };
@@ -313,6 +785,24 @@ impl<'a> Iterator for FdtChildIter<'a> {
 }
 
 impl<'a> FdtChildIter<'a> {
+    /// Advances `offset` past the properties of a node, returning the address
+    /// space they define for the node's children.
+    fn skip_properties(
+        fdt: Fdt<'a>,
+        offset: &mut usize,
+    ) -> Result<AddressSpaceProperties, FdtParseError> {
+        let mut address_space = AddressSpaceProperties::default();
+        while let Some(property) = FdtPropIter::try_next(fdt, offset) {
+            let property = property?;
+            match property.name() {
+                "#address-cells" => address_space.address_cells = property.as_u32()?,
+                "#size-cells" => address_space.size_cells = property.as_u32()?,
+                _ => {}
+            }
+        }
+        Ok(address_space)
+    }
+
     fn try_next(
         fdt: Fdt<'a>,
         offset: &mut usize,
@@ -0,0 +1,619 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Applying device tree overlays in place, without allocating.
+//!
+//! This is the equivalent of libfdt's `fdt_overlay_apply`, for bootloaders
+//! which need to merge a DTBO into the base device tree before starting the
+//! kernel but can't use [`DeviceTree`](crate::model::DeviceTree). The base
+//! blob is edited inside the buffer it was loaded into, growing into the
+//! unused space after it.
+
+use core::mem::offset_of;
+
+use zerocopy::FromBytes;
+
+use super::compare::child_named;
+use super::node::FdtChildIter;
+use super::{
+    FDT_BEGIN_NODE, FDT_END_NODE, FDT_PROP, FDT_TAGSIZE, Fdt, FdtHeader, FdtNode, FdtToken,
+    MAX_DESCENDANTS_DEPTH,
+};
+use crate::error::{FdtError, FdtErrorKind, FdtParseError};
+use crate::standard::{Phandle, PhandleNode, max_phandle};
+
+/// The maximum length of the paths added to the `__symbols__` node of the
+/// base device tree by [`apply_overlay`], as they are built on the stack.
+const MAX_SYMBOL_PATH_LEN: usize = 256;
+
+/// Applies a device tree overlay to the device tree blob at the start of
+/// `base`, returning the new size of the blob.
+///
+/// The rest of `base` after the blob is used as free space for the properties
+/// and nodes added by the overlay. The overlay is applied as by
+/// [`DeviceTree::apply_overlay`](crate::model::DeviceTree::apply_overlay):
+/// its phandles are renumbered to follow those of the base, the references
+/// listed in `__fixups__` and `__local_fixups__` are resolved, the
+/// `__overlay__` node of each fragment is merged into its target and the
+/// labels of the overlay are added to the `__symbols__` node of the base.
+///
+/// # Errors
+///
+/// Returns an error if either blob can't be parsed, if the target of a
+/// fragment or a label used by the overlay can't be found, if `base` is too
+/// small, or an [`FdtErrorKind::TooDeep`] error if nodes of either blob are
+/// nested more than [`MAX_DESCENDANTS_DEPTH`] levels deep. The targets and
+/// labels are checked before `base` is modified, so `base` is left unchanged
+/// if they can't be found, but after other errors the blob in `base` may be
+/// partially modified and should be discarded.
+///
+/// # Examples
+///
+/// ```
+/// # use dtoolkit::fdt::{Fdt, apply_overlay};
+/// # use dtoolkit::static_tree::StaticDeviceTree;
+/// let mut tree = StaticDeviceTree::<2, 0, 16>::new();
+/// tree.add_node(tree.root(), "soc").unwrap();
+/// let mut buffer = [0; 1024];
+/// tree.to_dtb(&mut buffer).unwrap();
+///
+/// let mut overlay = StaticDeviceTree::<3, 2, 64>::new();
+/// let fragment = overlay.add_node(overlay.root(), "fragment@0").unwrap();
+/// overlay
+///     .add_property_str(fragment, "target-path", "/soc")
+///     .unwrap();
+/// let contents = overlay.add_node(fragment, "__overlay__").unwrap();
+/// overlay
+///     .add_property_str(contents, "status", "okay")
+///     .unwrap();
+/// let mut dtbo = [0; 256];
+/// let dtbo_size = overlay.to_dtb(&mut dtbo).unwrap();
+///
+/// let size = apply_overlay(&mut buffer, &dtbo[..dtbo_size]).unwrap();
+/// let fdt = Fdt::new(&buffer[..size]).unwrap();
+/// let soc = fdt.find_node("/soc").unwrap().unwrap();
+/// assert_eq!(soc.required_str("status").unwrap(), "okay");
+/// ```
+pub fn apply_overlay(base: &mut [u8], overlay: &[u8]) -> Result<usize, FdtError> {
+    let overlay = Fdt::new(overlay)?;
+    let root = overlay.root()?;
+    let context = {
+        let base = FdtEditor::blob(base)?;
+        let context = OverlayContext {
+            overlay,
+            delta: max_phandle(base.root()?)?.map_or(0, Phandle::get),
+            fixups: child_named(root, "__fixups__")?,
+            local_fixups: child_named(root, "__local_fixups__")?,
+        };
+        context.validate(base, root)?;
+        context
+    };
+
+    let mut base = FdtEditor::new(base)?;
+    for fragment in root.children() {
+        let fragment = fragment?;
+        let Some(contents) = child_named(fragment, "__overlay__")? else {
+            continue;
+        };
+        let target = context.target(base.fdt(), fragment)?;
+        let local_fixups = match context.local_fixups {
+            Some(local_fixups) => match child_named(local_fixups, fragment.name()?)? {
+                Some(fragment) => child_named(fragment, "__overlay__")?,
+                None => None,
+            },
+            None => None,
+        };
+        context.merge(&mut base, target, contents, local_fixups)?;
+    }
+    if let Some(symbols) = child_named(root, "__symbols__")? {
+        context.add_symbols(&mut base, symbols)?;
+    }
+    Ok(base.totalsize())
+}
+
+/// The parts of an overlay needed to apply its fragments.
+struct OverlayContext<'o> {
+    overlay: Fdt<'o>,
+    /// The amount to add to the phandles of the overlay.
+    delta: u32,
+    fixups: Option<FdtNode<'o>>,
+    local_fixups: Option<FdtNode<'o>>,
+}
+
+impl<'o> OverlayContext<'o> {
+    /// Checks that the targets of the fragments of the overlay rooted at
+    /// `root`, and the labels listed in its `__fixups__`, can be found in
+    /// `base`.
+    fn validate(&self, base: Fdt<'_>, root: FdtNode<'o>) -> Result<(), FdtError> {
+        for fragment in root.children() {
+            let fragment = fragment?;
+            if child_named(fragment, "__overlay__")?.is_some() {
+                self.target(base, fragment)?;
+            }
+        }
+        if let Some(fixups) = self.fixups {
+            for fixup in fixups.properties() {
+                symbol_phandle(base, fixup?.name())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the offset of the node of the base device tree that the given
+    /// fragment applies to.
+    fn target(&self, base: Fdt<'_>, fragment: FdtNode<'o>) -> Result<usize, FdtError> {
+        let target = if let Some(target) = fragment.property("target")? {
+            let phandle = match self.fixup_label(fragment, "target", 0)? {
+                Some(label) => symbol_phandle(base, label)?,
+                None => target.as_u32()?,
+            };
+            let phandle =
+                Phandle::new(phandle).ok_or(FdtError::InvalidOverlay("invalid target phandle"))?;
+            base.find_node_by_phandle(phandle)?
+        } else if let Some(target_path) = fragment.property("target-path")? {
+            base.find_node(target_path.as_str()?)?
+        } else {
+            return Err(FdtError::InvalidOverlay("fragment has no target"));
+        };
+        target
+            .map(|node| node.offset)
+            .ok_or(FdtError::InvalidOverlay("fragment target not found"))
+    }
+
+    /// Shifts a phandle of the overlay past the phandles of the base device
+    /// tree.
+    fn shift_phandle(&self, phandle: u32) -> Result<u32, FdtError> {
+        phandle
+            .checked_add(self.delta)
+            .filter(|&phandle| phandle != u32::MAX)
+            .ok_or(FdtError::InvalidOverlay("phandle overflow"))
+    }
+
+    /// Returns the label of the base device tree which `__fixups__` says the
+    /// cell at `offset` in the given property of an overlay node refers to.
+    fn fixup_label(
+        &self,
+        node: FdtNode<'o>,
+        property: &str,
+        offset: usize,
+    ) -> Result<Option<&'o str>, FdtError> {
+        let Some(fixups) = self.fixups else {
+            return Ok(None);
+        };
+        for fixup in fixups.properties() {
+            let fixup = fixup?;
+            for location in fixup.as_str_list() {
+                let (path, fixup_property, fixup_offset) = parse_fixup(location)?;
+                if fixup_property == property
+                    && fixup_offset == offset
+                    && self.overlay.find_node(path)?.map(|node| node.offset) == Some(node.offset)
+                {
+                    return Ok(Some(fixup.name()));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Merges the properties and descendants of an `__overlay__` node into
+    /// the node of the base device tree at `target`.
+    ///
+    /// Nodes being added to the base only ever grow it after the nodes being
+    /// merged into, so their offsets are kept on the stack as the walk goes
+    /// down.
+    fn merge(
+        &self,
+        base: &mut FdtEditor<'_>,
+        target: usize,
+        source: FdtNode<'o>,
+        local_fixups: Option<FdtNode<'o>>,
+    ) -> Result<(), FdtError> {
+        let mut stack: [Option<MergeLevel<'o>>; MAX_DESCENDANTS_DEPTH] =
+            [const { None }; MAX_DESCENDANTS_DEPTH];
+        self.merge_properties(base, target, source, local_fixups)?;
+        stack[0] = Some(MergeLevel {
+            target,
+            children: FdtChildIter::Start { node: source },
+            local_fixups,
+        });
+        let mut depth: usize = 1;
+        while let Some(index) = depth.checked_sub(1) {
+            let Some(level) = &mut stack[index] else {
+                unreachable!("levels below the depth are set");
+            };
+            let Some(child) = level.children.next() else {
+                stack[index] = None;
+                depth = index;
+                continue;
+            };
+            let child = child?;
+            let name = child.name()?;
+            let existing = child_named(FdtNode::new(base.fdt(), level.target), name)?;
+            let offset = match existing {
+                Some(existing) => existing.offset,
+                None => base.add_subnode(level.target, name)?,
+            };
+            let local_fixups = match level.local_fixups {
+                Some(local_fixups) => child_named(local_fixups, name)?,
+                None => None,
+            };
+            self.merge_properties(base, offset, child, local_fixups)?;
+            *stack.get_mut(depth).ok_or(FdtParseError::new(
+                FdtErrorKind::TooDeep(MAX_DESCENDANTS_DEPTH),
+                child.offset,
+            ))? = Some(MergeLevel {
+                target: offset,
+                children: FdtChildIter::Start { node: child },
+                local_fixups,
+            });
+            depth += 1;
+        }
+        Ok(())
+    }
+
+    /// Merges the properties of an `__overlay__` node, or one of its
+    /// descendants, into the node of the base device tree at `target`.
+    fn merge_properties(
+        &self,
+        base: &mut FdtEditor<'_>,
+        target: usize,
+        source: FdtNode<'o>,
+        local_fixups: Option<FdtNode<'o>>,
+    ) -> Result<(), FdtError> {
+        for property in source.properties() {
+            let property = property?;
+            let name = property.name();
+            let value_offset = base.set_property(target, name, property.value())?;
+            let invalid = FdtError::InvalidOverlay("fixup outside of property");
+            let cell = |offset: usize| match offset.checked_add(FDT_TAGSIZE) {
+                Some(end) if end <= property.value().len() => Ok(value_offset + offset),
+                _ => Err(invalid.clone()),
+            };
+
+            let offsets = match local_fixups {
+                Some(local_fixups) => local_fixups.property(name)?,
+                None => None,
+            };
+            if let Some(offsets) = offsets {
+                for offset in offsets.as_u32_array()? {
+                    let offset = cell(offset as usize)?;
+                    let phandle = self.shift_phandle(base.read_u32(offset))?;
+                    base.write_u32(offset, phandle);
+                }
+            }
+            if let Some(fixups) = self.fixups {
+                for fixup in fixups.properties() {
+                    let fixup = fixup?;
+                    for location in fixup.as_str_list() {
+                        let (path, fixup_property, offset) = parse_fixup(location)?;
+                        if fixup_property == name
+                            && self.overlay.find_node(path)?.map(|node| node.offset)
+                                == Some(source.offset)
+                        {
+                            let phandle = symbol_phandle(base.fdt(), fixup.name())?;
+                            base.write_u32(cell(offset)?, phandle);
+                        }
+                    }
+                }
+            }
+            if name == "phandle" || name == "linux,phandle" {
+                let offset = cell(0)?;
+                let phandle = self.shift_phandle(base.read_u32(offset))?;
+                base.write_u32(offset, phandle);
+            }
+        }
+        Ok(())
+    }
+
+    /// Adds the labels of the overlay to the `__symbols__` node of the base
+    /// device tree, with their paths rewritten to where their nodes ended up.
+    fn add_symbols(&self, base: &mut FdtEditor<'_>, symbols: FdtNode<'o>) -> Result<(), FdtError> {
+        let root = base.fdt().root()?.offset;
+        let base_symbols = match child_named(base.fdt().root()?, "__symbols__")? {
+            Some(node) => node.offset,
+            None => base.add_subnode(root, "__symbols__")?,
+        };
+        let root = self.overlay.root()?;
+        for symbol in symbols.properties() {
+            let symbol = symbol?;
+            let path = symbol.as_str()?;
+            let Some((fragment, rest)) =
+                path.strip_prefix('/').and_then(|path| path.split_once('/'))
+            else {
+                continue;
+            };
+            let rest = match rest.strip_prefix("__overlay__") {
+                Some(rest) if rest.is_empty() || rest.starts_with('/') => rest,
+                _ => continue,
+            };
+            let Some(fragment) = child_named(root, fragment)? else {
+                continue;
+            };
+
+            let mut value = [0; MAX_SYMBOL_PATH_LEN];
+            let target = self.target(base.fdt(), fragment)?;
+            let mut len = node_path(base.fdt(), target, &mut value)?;
+            if len == 1 && !rest.is_empty() {
+                len = 0;
+            }
+            let end = len + rest.len();
+            value
+                .get_mut(len..end)
+                .ok_or(FdtError::CapacityExceeded("symbol path"))?
+                .copy_from_slice(rest.as_bytes());
+            let value = value
+                .get(..=end)
+                .ok_or(FdtError::CapacityExceeded("symbol path"))?;
+            base.set_property(base_symbols, symbol.name(), value)?;
+        }
+        Ok(())
+    }
+}
+
+/// A node of the base device tree being merged into, with the children of the
+/// overlay node being merged left to look at.
+struct MergeLevel<'o> {
+    /// The offset of the node of the base device tree.
+    target: usize,
+    children: FdtChildIter<'o>,
+    /// The node of `__local_fixups__` matching the overlay node, if any.
+    local_fixups: Option<FdtNode<'o>>,
+}
+
+/// Splits an entry of `__fixups__` into its node path, property name and
+/// offset.
+fn parse_fixup(location: &str) -> Result<(&str, &str, usize), FdtError> {
+    let invalid = FdtError::InvalidOverlay("invalid fixup");
+    let mut parts = location.rsplitn(3, ':');
+    let (Some(offset), Some(property), Some(path)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return Err(invalid);
+    };
+    let offset = offset.parse().map_err(|_| invalid)?;
+    Ok((path, property, offset))
+}
+
+/// Returns the phandle of the node of the base device tree with the given
+/// label.
+fn symbol_phandle(base: Fdt<'_>, label: &str) -> Result<u32, FdtError> {
+    let not_found = FdtError::InvalidOverlay("symbol not found");
+    let Some(symbols) = child_named(base.root()?, "__symbols__")? else {
+        return Err(not_found);
+    };
+    let Some(path) = symbols.property(label)? else {
+        return Err(not_found);
+    };
+    match base.find_node(path.as_str()?)? {
+        Some(node) => node
+            .node_phandle()?
+            .map(Phandle::get)
+            .ok_or(FdtError::InvalidOverlay("symbol has no phandle")),
+        None => Err(not_found),
+    }
+}
+
+/// Writes the path of the node at `offset`, followed by a NUL, to `path`,
+/// returning its length without the NUL.
+fn node_path(fdt: Fdt<'_>, offset: usize, path: &mut [u8]) -> Result<usize, FdtError> {
+    let mut node = fdt.root()?;
+    let mut len = 0;
+    'descend: while node.offset != offset {
+        for child in node.children() {
+            let child = child?;
+            let end = fdt.next_sibling_offset(child.offset)?;
+            if (child.offset..end).contains(&offset) {
+                let name = child.name()?;
+                let end = len + 1 + name.len();
+                let segment = path
+                    .get_mut(len..end)
+                    .ok_or(FdtError::CapacityExceeded("symbol path"))?;
+                segment[0] = b'/';
+                segment[1..].copy_from_slice(name.as_bytes());
+                node = child;
+                len = end;
+                continue 'descend;
+            }
+        }
+        return Err(FdtError::InvalidOverlay("fragment target not found"));
+    }
+    if len == 0 {
+        *path
+            .first_mut()
+            .ok_or(FdtError::CapacityExceeded("symbol path"))? = b'/';
+        len = 1;
+    }
+    *path
+        .get_mut(len)
+        .ok_or(FdtError::CapacityExceeded("symbol path"))? = 0;
+    Ok(len)
+}
+
+/// Converts an offset or size within the buffer of an [`FdtEditor`], which is
+/// at most `u32::MAX` bytes long, to a `u32`.
+fn to_u32(value: usize) -> u32 {
+    let Ok(value) = u32::try_from(value) else {
+        unreachable!("the buffer is at most u32::MAX bytes long");
+    };
+    value
+}
+
+/// A device tree blob at the start of a larger buffer, which can be edited in
+/// place.
+///
+/// The strings block is kept right after the structure block, so that both
+/// can grow into the free space at the end of the buffer.
+struct FdtEditor<'a> {
+    buf: &'a mut [u8],
+}
+
+impl<'a> FdtEditor<'a> {
+    /// Parses the blob at the start of `buf`, without editing it.
+    fn blob(buf: &[u8]) -> Result<Fdt<'_>, FdtError> {
+        let totalsize = FdtHeader::ref_from_prefix(buf)
+            .map_err(|_| FdtParseError::new(FdtErrorKind::InvalidLength, 0))?
+            .0
+            .totalsize() as usize;
+        let data = buf.get(..totalsize).ok_or(FdtParseError::new(
+            FdtErrorKind::InvalidLength,
+            offset_of!(FdtHeader, totalsize),
+        ))?;
+        Ok(Fdt::new(data)?)
+    }
+
+    /// Parses the blob at the start of `buf` and moves its strings block
+    /// right after its structure block.
+    fn new(buf: &'a mut [u8]) -> Result<Self, FdtError> {
+        let header = Self::blob(buf)?.header();
+        let struct_end = (header.off_dt_struct() + header.size_dt_struct()) as usize;
+        let strings = header.off_dt_strings() as usize;
+        let strings_size = header.size_dt_strings() as usize;
+
+        let len = buf.len().min(u32::MAX as usize);
+        let mut editor = Self {
+            buf: &mut buf[..len],
+        };
+        editor
+            .buf
+            .copy_within(strings..strings + strings_size, struct_end);
+        let header = editor.header_mut();
+        header.off_dt_strings.set(to_u32(struct_end));
+        header.totalsize.set(to_u32(struct_end + strings_size));
+        Ok(editor)
+    }
+
+    fn header_mut(&mut self) -> &mut FdtHeader {
+        let Ok((header, _)) = FdtHeader::mut_from_prefix(self.buf) else {
+            unreachable!("the header was checked when the blob was parsed");
+        };
+        header
+    }
+
+    fn totalsize(&self) -> usize {
+        self.fdt().header().totalsize() as usize
+    }
+
+    /// Returns the blob as it is now.
+    fn fdt(&self) -> Fdt<'_> {
+        let Ok((header, _)) = FdtHeader::ref_from_prefix(self.buf) else {
+            unreachable!("the header was checked when the blob was parsed");
+        };
+        Fdt {
+            data: &self.buf[..header.totalsize() as usize],
+        }
+    }
+
+    fn read_u32(&self, offset: usize) -> u32 {
+        let mut bytes = [0; 4];
+        bytes.copy_from_slice(&self.buf[offset..offset + 4]);
+        u32::from_be_bytes(bytes)
+    }
+
+    fn write_u32(&mut self, offset: usize, value: u32) {
+        self.buf[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+    }
+
+    /// Replaces `old_len` bytes of the structure block at `offset` with
+    /// `new_len` bytes, moving everything after them.
+    fn splice(&mut self, offset: usize, old_len: usize, new_len: usize) -> Result<(), FdtError> {
+        let totalsize = self.totalsize();
+        let needed = totalsize - old_len + new_len;
+        if needed > self.buf.len() {
+            return Err(FdtError::BufferTooSmall { needed });
+        }
+        self.buf
+            .copy_within(offset + old_len..totalsize, offset + new_len);
+        let header = self.header_mut();
+        let size_dt_struct = header.size_dt_struct() as usize - old_len + new_len;
+        let off_dt_strings = header.off_dt_strings() as usize - old_len + new_len;
+        header.size_dt_struct.set(to_u32(size_dt_struct));
+        header.off_dt_strings.set(to_u32(off_dt_strings));
+        header.totalsize.set(to_u32(needed));
+        Ok(())
+    }
+
+    /// Returns the offset of the given string in the strings block, adding it
+    /// if it isn't there yet.
+    fn string_offset(&mut self, string: &str) -> Result<u32, FdtError> {
+        let string = string.as_bytes();
+        let strings = self.fdt().strings_block();
+        let existing = (0..strings.len().saturating_sub(string.len())).find(|&offset| {
+            strings[offset..].starts_with(string) && strings[offset + string.len()] == 0
+        });
+        if let Some(offset) = existing {
+            return Ok(to_u32(offset));
+        }
+
+        let totalsize = self.totalsize();
+        let needed = totalsize + string.len() + 1;
+        if needed > self.buf.len() {
+            return Err(FdtError::BufferTooSmall { needed });
+        }
+        self.buf[totalsize..needed - 1].copy_from_slice(string);
+        self.buf[needed - 1] = 0;
+        let header = self.header_mut();
+        let offset = header.size_dt_strings();
+        header
+            .size_dt_strings
+            .set(offset + to_u32(string.len() + 1));
+        header.totalsize.set(to_u32(needed));
+        Ok(offset)
+    }
+
+    /// Sets a property of the node at `node`, adding it after the existing
+    /// properties if there is none with the same name, and returns the offset
+    /// of its value.
+    fn set_property(&mut self, node: usize, name: &str, value: &[u8]) -> Result<usize, FdtError> {
+        let new_len = Fdt::align_tag_offset(value.len());
+        let existing = FdtNode::new(self.fdt(), node).property(name)?;
+        let value_offset = if let Some(existing) = existing {
+            let old_len = Fdt::align_tag_offset(existing.value().len());
+            let value_offset = existing.value_offset;
+            self.splice(value_offset, old_len, new_len)?;
+            value_offset
+        } else {
+            let name_offset = self.string_offset(name)?;
+            let offset = self.properties_end(node)?;
+            self.splice(offset, 0, 3 * FDT_TAGSIZE + new_len)?;
+            self.write_u32(offset, FDT_PROP);
+            self.write_u32(offset + 2 * FDT_TAGSIZE, name_offset);
+            offset + 3 * FDT_TAGSIZE
+        };
+        self.write_u32(value_offset - 2 * FDT_TAGSIZE, to_u32(value.len()));
+        self.buf[value_offset..value_offset + value.len()].copy_from_slice(value);
+        self.buf[value_offset + value.len()..value_offset + new_len].fill(0);
+        Ok(value_offset)
+    }
+
+    /// Returns the offset just past the properties of the node at `node`.
+    fn properties_end(&self, node: usize) -> Result<usize, FdtError> {
+        let fdt = self.fdt();
+        let mut offset = fdt.find_string_end(node + FDT_TAGSIZE)?;
+        offset = Fdt::align_tag_offset(offset);
+        loop {
+            match fdt.read_token(offset)? {
+                FdtToken::Prop => offset = fdt.next_property_offset(offset + FDT_TAGSIZE)?,
+                FdtToken::Nop => offset += FDT_TAGSIZE,
+                _ => return Ok(offset),
+            }
+        }
+    }
+
+    /// Adds an empty node after the existing children of the node at
+    /// `parent`, and returns its offset.
+    fn add_subnode(&mut self, parent: usize, name: &str) -> Result<usize, FdtError> {
+        let offset = self.fdt().next_sibling_offset(parent)? - FDT_TAGSIZE;
+        let name_end = offset + FDT_TAGSIZE + name.len();
+        let end_node = Fdt::align_tag_offset(name_end + 1);
+        self.splice(offset, 0, end_node + FDT_TAGSIZE - offset)?;
+        self.write_u32(offset, FDT_BEGIN_NODE);
+        self.buf[offset + FDT_TAGSIZE..name_end].copy_from_slice(name.as_bytes());
+        self.buf[name_end..end_node].fill(0);
+        self.write_u32(end_node, FDT_END_NODE);
+        Ok(offset)
+    }
+}
@@ -14,15 +14,16 @@ use core::ops::{BitOr, Shl};
 
 use zerocopy::{FromBytes, big_endian};
 
-use super::{FDT_TAGSIZE, Fdt, FdtToken};
+use super::{FDT_TAGSIZE, Fdt, FdtToken, PropertyValue, U32Array};
 use crate::error::{FdtError, FdtErrorKind, FdtParseError};
+use crate::standard::Phandle;
 
 /// A property of a device tree node.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq)]
 pub struct FdtProperty<'a> {
     name: &'a str,
     value: &'a [u8],
-    value_offset: usize,
+    pub(super) value_offset: usize,
 }
 
 impl<'a> FdtProperty<'a> {
@@ -84,6 +85,99 @@ impl<'a> FdtProperty<'a> {
             .map_err(|_e| FdtParseError::new(FdtErrorKind::InvalidLength, self.value_offset))
     }
 
+    /// Returns the value of this property as an array of `N` bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::InvalidLength`] if the property's value is
+    /// not `N` bytes long.
+    pub(crate) fn as_byte_array<const N: usize>(&self) -> Result<[u8; N], FdtParseError> {
+        self.value
+            .try_into()
+            .map_err(|_e| FdtParseError::new(FdtErrorKind::InvalidLength, self.value_offset))
+    }
+
+    /// Returns the value of this property as a [`Phandle`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::InvalidLength`] if the property's value is
+    /// not 4 bytes long, or an [`FdtErrorKind::InvalidPhandle`] if it is one
+    /// of the reserved values.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../tests/dtb/test_props.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let node = fdt.find_node("/standard-props").unwrap().unwrap();
+    /// let prop = node.property("phandle").unwrap().unwrap();
+    /// assert_eq!(prop.as_phandle().unwrap().get(), 0x1234);
+    /// ```
+    pub fn as_phandle(&self) -> Result<Phandle, FdtParseError> {
+        let value = self.as_u32()?;
+        Phandle::new(value).ok_or(FdtParseError::new(
+            FdtErrorKind::InvalidPhandle(value),
+            self.value_offset,
+        ))
+    }
+
+    /// Returns an iterator over the value of this property as an array of
+    /// `u32`s.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::InvalidLength`] if the property's value is
+    /// not a multiple of 4 bytes long.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../tests/dtb/test_props.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let node = fdt.find_node("/test-props").unwrap().unwrap();
+    /// let prop = node.property("u64-prop").unwrap().unwrap();
+    /// assert!(prop.as_u32_array().unwrap().eq([0x11223344, 0x55667788]));
+    /// ```
+    pub fn as_u32_array(
+        &self,
+    ) -> Result<impl ExactSizeIterator<Item = u32> + use<'a>, FdtParseError> {
+        U32Array::new(self.value)
+            .map(|array| array.iter())
+            .ok_or(FdtParseError::new(
+                FdtErrorKind::InvalidLength,
+                self.value_offset,
+            ))
+    }
+
+    /// Returns an iterator over the value of this property as an array of
+    /// `u64`s.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtErrorKind::InvalidLength`] if the property's value is
+    /// not a multiple of 8 bytes long.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../tests/dtb/test_props.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let node = fdt.find_node("/test-props").unwrap().unwrap();
+    /// let prop = node.property("u64-prop").unwrap().unwrap();
+    /// assert!(prop.as_u64_array().unwrap().eq([0x1122334455667788]));
+    /// ```
+    pub fn as_u64_array(
+        &self,
+    ) -> Result<impl ExactSizeIterator<Item = u64> + use<'a>, FdtParseError> {
+        <[big_endian::U64]>::ref_from_bytes(self.value)
+            .map(|values| values.iter().map(|value| value.get()))
+            .map_err(|_e| FdtParseError::new(FdtErrorKind::InvalidLength, self.value_offset))
+    }
+
     /// Returns the value of this property as a string.
     ///
     /// # Errors
@@ -125,7 +219,61 @@ impl<'a> FdtProperty<'a> {
     /// assert_eq!(str_list.next(), None);
     /// ```
     pub fn as_str_list(&self) -> impl Iterator<Item = &'a str> + use<'a> {
-        FdtStringListIterator { value: self.value }
+        str_list(self.value)
+    }
+
+    /// Returns an iterator over the elements of this property as a
+    /// prop-encoded-array, where each element has fields of the given numbers
+    /// of cells.
+    ///
+    /// This is for layouts which are only known at runtime, such as those
+    /// which depend on the `#address-cells` or `#interrupt-cells` of another
+    /// node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an [`FdtError::PropEncodedArraySizeMismatch`] if the
+    /// property's value isn't a whole number of elements, or the elements
+    /// would be empty.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../tests/dtb/test_props.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let node = fdt.find_node("/standard-props").unwrap().unwrap();
+    /// // The child address, parent address and size of each range.
+    /// let fields = [1, 2, 1];
+    /// let ranges = node.property("ranges").unwrap().unwrap();
+    /// let mut elements = ranges.as_prop_encoded_array_dyn(&fields).unwrap();
+    /// let element = elements.next().unwrap();
+    /// assert_eq!(element.len(), 3);
+    /// assert_eq!(
+    ///     element.get(1).unwrap().to_int::<u64>().unwrap(),
+    ///     0x22220000_33330000
+    /// );
+    /// assert_eq!(element.get(2).unwrap().to_int::<u32>().unwrap(), 0x44440000);
+    /// assert!(elements.next().is_none());
+    /// ```
+    pub fn as_prop_encoded_array_dyn<'f>(
+        &self,
+        fields_cells: &'f [usize],
+    ) -> Result<impl Iterator<Item = PropEncodedElement<'a, 'f>> + use<'a, 'f>, FdtError> {
+        let chunk_cells = fields_cells.iter().sum();
+        let cells = <[big_endian::U32]>::ref_from_bytes(self.value)
+            .ok()
+            .filter(|cells| chunk_cells != 0 && cells.len().is_multiple_of(chunk_cells))
+            .ok_or(FdtError::PropEncodedArraySizeMismatch {
+                size: self.value.len(),
+                chunk: chunk_cells,
+            })?;
+        Ok(cells
+            .chunks_exact(chunk_cells)
+            .map(move |cells| PropEncodedElement {
+                cells,
+                fields_cells,
+            }))
     }
 
     pub(crate) fn as_prop_encoded_array<const N: usize>(
@@ -134,7 +282,7 @@ impl<'a> FdtProperty<'a> {
     ) -> Result<impl Iterator<Item = [Cells<'a>; N]> + use<'a, N>, FdtError> {
         let chunk_cells = fields_cells.iter().sum();
         let chunk_bytes = chunk_cells * size_of::<u32>();
-        if !self.value.len().is_multiple_of(chunk_bytes) {
+        if chunk_bytes == 0 || !self.value.len().is_multiple_of(chunk_bytes) {
             return Err(FdtError::PropEncodedArraySizeMismatch {
                 size: self.value.len(),
                 chunk: chunk_cells,
@@ -151,57 +299,31 @@ impl<'a> FdtProperty<'a> {
         }))
     }
 
+    /// Decodes the value of this property into its most likely type.
+    ///
+    /// See [`PropertyValue::decode`] for how the type is chosen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::{Fdt, PropertyValue};
+    /// # let dtb = include_bytes!("../../tests/dtb/test_props.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let node = fdt.find_node("/test-props").unwrap().unwrap();
+    /// let prop = node.property("str-prop").unwrap().unwrap();
+    /// assert_eq!(prop.decode(), PropertyValue::String("hello world"));
+    /// ```
+    #[must_use]
+    pub fn decode(&self) -> PropertyValue<'a> {
+        PropertyValue::decode(self.name, self.value)
+    }
+
     pub(crate) fn fmt(&self, f: &mut Formatter, indent: usize) -> fmt::Result {
         write!(f, "{:indent$}{}", "", self.name, indent = indent)?;
-
-        if self.value.is_empty() {
-            writeln!(f, ";")?;
-            return Ok(());
-        }
-
-        let is_printable = self
-            .value
-            .iter()
-            .all(|&ch| ch.is_ascii_graphic() || ch == b' ' || ch == 0);
-        let has_empty = self.value.windows(2).any(|window| window == [0, 0]);
-        if is_printable && self.value.ends_with(&[0]) && !has_empty {
-            let mut strings = self.as_str_list();
-            if let Some(first) = strings.next() {
-                write!(f, " = \"{first}\"")?;
-                for s in strings {
-                    write!(f, ", \"{s}\"")?;
-                }
-                writeln!(f, ";")?;
-                return Ok(());
-            }
-        }
-
-        if self.value.len().is_multiple_of(4) {
-            write!(f, " = <")?;
-            for (i, chunk) in self.value.chunks_exact(4).enumerate() {
-                if i > 0 {
-                    write!(f, " ")?;
-                }
-                let val = u32::from_be_bytes(
-                    chunk
-                        .try_into()
-                        .expect("u32::from_be_bytes() should always succeed with 4 bytes"),
-                );
-                write!(f, "0x{val:02x}")?;
-            }
-            writeln!(f, ">;")?;
-        } else {
-            write!(f, " = [")?;
-            for (i, byte) in self.value.iter().enumerate() {
-                if i > 0 {
-                    write!(f, " ")?;
-                }
-                write!(f, "{byte:02x}")?;
-            }
-            writeln!(f, "];")?;
+        match PropertyValue::guess(self.value) {
+            PropertyValue::Empty => writeln!(f, ";"),
+            value => writeln!(f, " = {value};"),
         }
-
-        Ok(())
     }
 }
 
@@ -222,6 +344,17 @@ impl<'a> Iterator for FdtPropIter<'a> {
     type Item = Result<FdtProperty<'a>, FdtParseError>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        self.next_with(|fdt, token| token.into_property(fdt))
+    }
+}
+
+impl<'a> FdtPropIter<'a> {
+    /// Advances to the next property, building the item from its token with
+    /// `f`.
+    fn next_with<T>(
+        &mut self,
+        f: impl FnOnce(Fdt<'a>, PropToken<'a>) -> Result<T, FdtParseError>,
+    ) -> Option<Result<T, FdtParseError>> {
         match self {
             Self::Start { fdt, offset } => {
                 let mut offset = *offset;
@@ -235,26 +368,33 @@ impl<'a> Iterator for FdtPropIter<'a> {
                 };
                 offset = Fdt::align_tag_offset(offset);
                 *self = Self::Running { fdt: *fdt, offset };
-                self.next()
+                self.next_with(f)
             }
-            Self::Running { fdt, offset } => match Self::try_next(*fdt, offset) {
-                Some(Ok(val)) => Some(Ok(val)),
-                Some(Err(e)) => {
+            Self::Running { fdt, offset } => {
+                let fdt = *fdt;
+                let result = Self::try_next_token(fdt, offset)?.and_then(|token| f(fdt, token));
+                if result.is_err() {
                     *self = Self::Error;
-                    Some(Err(e))
                 }
-                None => None,
-            },
+                Some(result)
+            }
             Self::Error => None,
         }
     }
-}
 
-impl<'a> FdtPropIter<'a> {
-    fn try_next(
+    pub(crate) fn try_next(
         fdt: Fdt<'a>,
         offset: &mut usize,
     ) -> Option<Result<FdtProperty<'a>, FdtParseError>> {
+        Some(Self::try_next_token(fdt, offset)?.and_then(|token| token.into_property(fdt)))
+    }
+
+    /// Reads the `FDT_PROP` token at `offset`, skipping any `FDT_NOP` tokens
+    /// before it.
+    fn try_next_token(
+        fdt: Fdt<'a>,
+        offset: &mut usize,
+    ) -> Option<Result<PropToken<'a>, FdtParseError>> {
         loop {
             let token = match fdt.read_token(*offset) {
                 Ok(token) => token,
@@ -286,13 +426,9 @@ impl<'a> FdtPropIter<'a> {
                     };
                     let prop_offset = *offset + 3 * FDT_TAGSIZE;
                     *offset = Fdt::align_tag_offset(prop_offset + len);
-                    let name = match fdt.string(nameoff) {
-                        Ok(name) => name,
-                        Err(e) => return Some(Err(e)),
-                    };
                     let value = fdt.data.get(prop_offset..prop_offset + len)?;
-                    return Some(Ok(FdtProperty {
-                        name,
+                    return Some(Ok(PropToken {
+                        nameoff,
                         value,
                         value_offset: prop_offset,
                     }));
@@ -304,6 +440,71 @@ impl<'a> FdtPropIter<'a> {
     }
 }
 
+/// An `FDT_PROP` token whose name hasn't been looked up yet.
+struct PropToken<'a> {
+    nameoff: usize,
+    value: &'a [u8],
+    value_offset: usize,
+}
+
+impl<'a> PropToken<'a> {
+    fn into_property(self, fdt: Fdt<'a>) -> Result<FdtProperty<'a>, FdtParseError> {
+        Ok(FdtProperty {
+            name: fdt.string(self.nameoff)?,
+            value: self.value,
+            value_offset: self.value_offset,
+        })
+    }
+}
+
+/// A property of a device tree node whose name has not been checked to be
+/// valid UTF-8.
+///
+/// This is returned by
+/// [`FdtNode::raw_properties`](super::FdtNode::raw_properties).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FdtRawProperty<'a> {
+    name: &'a [u8],
+    value: &'a [u8],
+}
+
+impl<'a> FdtRawProperty<'a> {
+    /// Returns the name of this property, without its NUL terminator.
+    #[must_use]
+    pub fn name(&self) -> &'a [u8] {
+        self.name
+    }
+
+    /// Returns the value of this property.
+    #[must_use]
+    pub fn value(&self) -> &'a [u8] {
+        self.value
+    }
+}
+
+/// An iterator over the properties of a device tree node which doesn't
+/// validate their names.
+pub(crate) struct FdtRawPropIter<'a>(pub(crate) FdtPropIter<'a>);
+
+impl<'a> Iterator for FdtRawPropIter<'a> {
+    type Item = Result<FdtRawProperty<'a>, FdtParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next_with(|fdt, token| {
+            Ok(FdtRawProperty {
+                name: fdt.string_bytes(token.nameoff)?,
+                value: token.value,
+            })
+        })
+    }
+}
+
+/// Returns an iterator over the strings of a stringlist value, stopping at the
+/// first one which isn't valid.
+pub(crate) fn str_list(value: &[u8]) -> impl Iterator<Item = &str> {
+    FdtStringListIterator { value }
+}
+
 struct FdtStringListIterator<'a> {
     value: &'a [u8],
 }
@@ -322,6 +523,48 @@ impl<'a> Iterator for FdtStringListIterator<'a> {
     }
 }
 
+/// An element of a prop-encoded-array property whose fields were only known
+/// at runtime.
+///
+/// This is returned by [`FdtProperty::as_prop_encoded_array_dyn`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct PropEncodedElement<'a, 'f> {
+    cells: &'a [big_endian::U32],
+    fields_cells: &'f [usize],
+}
+
+impl<'a> PropEncodedElement<'a, '_> {
+    /// Returns the number of fields in the element.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.fields_cells.len()
+    }
+
+    /// Returns whether the element has no fields.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.fields_cells.is_empty()
+    }
+
+    /// Returns the field at the given index.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<Cells<'a>> {
+        let start = self.fields_cells.get(..index)?.iter().sum();
+        let end = start + self.fields_cells.get(index)?;
+        Some(Cells(&self.cells[start..end]))
+    }
+
+    /// Returns an iterator over the fields of the element.
+    pub fn iter(&self) -> impl Iterator<Item = Cells<'a>> + use<'a, '_> {
+        let mut cells = self.cells;
+        self.fields_cells.iter().map(move |&field_cells| {
+            let field;
+            (field, cells) = cells.split_at(field_cells);
+            Cells(field)
+        })
+    }
+}
+
 /// An integer value split into several big-endian u32 parts.
 ///
 /// This is generally used in prop-encoded-array properties.
@@ -0,0 +1,105 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Searching for device tree blobs embedded in larger buffers.
+
+use zerocopy::FromBytes;
+
+use super::{FDT_MAGIC, Fdt, FdtHeader};
+use crate::diag;
+
+impl<'a> Fdt<'a> {
+    /// Returns an iterator over all valid FDTs found in the given buffer,
+    /// together with their offsets.
+    ///
+    /// This is useful for flash dumps, kernel images with appended DTBs or
+    /// multiple concatenated DTBs. Every occurrence of the FDT magic number is
+    /// treated as a candidate, and only candidates whose header passes the
+    /// same validation as [`Fdt::new`] are returned. The search continues
+    /// after the end of each blob that is found.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../tests/dtb/test.dtb");
+    /// let mut image = vec![0xff; 16];
+    /// image.extend_from_slice(dtb);
+    /// image.extend_from_slice(dtb);
+    ///
+    /// let offsets: Vec<_> = Fdt::scan(&image).map(|(offset, _fdt)| offset).collect();
+    /// assert_eq!(offsets, [16, 16 + dtb.len()]);
+    /// ```
+    pub fn scan(data: &'a [u8]) -> impl Iterator<Item = (usize, Fdt<'a>)> + 'a {
+        FdtScanner { data, offset: 0 }
+    }
+}
+
+/// An iterator over the FDTs embedded in a buffer.
+struct FdtScanner<'a> {
+    data: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> FdtScanner<'a> {
+    /// Tries to parse an FDT at the given offset of the data.
+    fn candidate(&self, offset: usize) -> Option<Fdt<'a>> {
+        let (header, _) = FdtHeader::ref_from_prefix(&self.data[offset..]).ok()?;
+        let end = offset.checked_add(header.totalsize() as usize)?;
+        Fdt::new(self.data.get(offset..end)?).ok()
+    }
+}
+
+impl<'a> Iterator for FdtScanner<'a> {
+    type Item = (usize, Fdt<'a>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let magic = FDT_MAGIC.to_be_bytes();
+        while let Some(position) = self
+            .data
+            .get(self.offset..)?
+            .windows(magic.len())
+            .position(|window| window == magic)
+        {
+            let offset = self.offset + position;
+            if let Some(fdt) = self.candidate(offset) {
+                self.offset = offset + fdt.data().len();
+                return Some((offset, fdt));
+            }
+            diag::debug!("skipping invalid FDT candidate at offset {offset:#x}");
+            self.offset = offset + 1;
+        }
+        self.offset = self.data.len();
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn skips_invalid_candidates() {
+        let dtb = include_bytes!("../../tests/dtb/test_children.dtb");
+        let mut image = FDT_MAGIC.to_be_bytes().to_vec();
+        image.extend_from_slice(&[0; 3]);
+        image.extend_from_slice(dtb);
+        image.extend_from_slice(&dtb[..dtb.len() - 1]);
+
+        let found: Vec<_> = Fdt::scan(&image).collect();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].0, 7);
+        assert_eq!(found[0].1.data(), dtb);
+    }
+
+    #[test]
+    fn empty_buffer() {
+        assert_eq!(Fdt::scan(&[]).count(), 0);
+        assert_eq!(Fdt::scan(&FDT_MAGIC.to_be_bytes()).count(), 0);
+    }
+}
@@ -0,0 +1,306 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Decoding of property values into their most likely type.
+
+use core::ffi::CStr;
+use core::fmt::{self, Display, Formatter};
+
+use zerocopy::{FromBytes, big_endian};
+
+/// The value of a property, decoded into its most likely type.
+///
+/// Property values are stored as plain bytes in a device tree, so their type
+/// has to be guessed. [`PropertyValue::decode`] uses the type of well-known
+/// properties defined by the Devicetree Specification and common bindings, and
+/// otherwise looks at the contents of the value.
+///
+/// The [`Display`] implementation formats the value in DTS syntax.
+///
+/// # Examples
+///
+/// ```
+/// # use dtoolkit::fdt::{Fdt, PropertyValue};
+/// # let dtb = include_bytes!("../../tests/dtb/test_props.dtb");
+/// let fdt = Fdt::new(dtb).unwrap();
+/// let node = fdt.find_node("/standard-props").unwrap().unwrap();
+/// let PropertyValue::StringList(compatible) =
+///     node.property("compatible").unwrap().unwrap().decode()
+/// else {
+///     panic!("compatible should be a string list");
+/// };
+/// assert!(compatible.iter().eq(["abc,def", "some,other"]));
+/// assert_eq!(
+///     node.property("phandle").unwrap().unwrap().decode(),
+///     PropertyValue::U32(0x1234)
+/// );
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PropertyValue<'a> {
+    /// A property without a value, such as `dma-coherent`.
+    Empty,
+    /// A single 32-bit integer.
+    U32(u32),
+    /// A single 64-bit integer.
+    U64(u64),
+    /// A single string.
+    String(&'a str),
+    /// A list of several strings, or a property such as `compatible` which is
+    /// defined to be a list of strings.
+    StringList(StringList<'a>),
+    /// A list of phandles, such as `interrupt-parent` or `pinctrl-0`.
+    PhandleList(U32Array<'a>),
+    /// A list of 32-bit integers.
+    U32Array(U32Array<'a>),
+    /// Bytes which don't look like any of the other types.
+    Bytes(&'a [u8]),
+}
+
+/// The type of a well-known property.
+#[derive(Clone, Copy)]
+enum KnownType {
+    U32,
+    U64,
+    U32OrU64,
+    String,
+    StringList,
+    PhandleList,
+    Bytes,
+}
+
+/// Returns the type of the property with the given name, if it is one of the
+/// well-known properties.
+fn known_type(name: &str) -> Option<KnownType> {
+    let known_type =
+        match name {
+            "#address-cells"
+            | "#size-cells"
+            | "#interrupt-cells"
+            | "#clock-cells"
+            | "#reset-cells"
+            | "#gpio-cells"
+            | "#dma-cells"
+            | "#phy-cells"
+            | "#pwm-cells"
+            | "#power-domain-cells"
+            | "#iommu-cells"
+            | "#mbox-cells"
+            | "#thermal-sensor-cells"
+            | "phandle"
+            | "linux,phandle"
+            | "virtual-reg"
+            | "cache-line-size"
+            | "cache-size"
+            | "cache-sets"
+            | "cache-block-size"
+            | "cache-level" => KnownType::U32,
+            "cpu-release-addr" => KnownType::U64,
+            "clock-frequency" | "timebase-frequency" | "linux,initrd-start"
+            | "linux,initrd-end" => KnownType::U32OrU64,
+            "model" | "status" | "device_type" | "name" | "bootargs" | "stdout-path"
+            | "stdin-path" | "enable-method" | "label" => KnownType::String,
+            "compatible" | "clock-names" | "clock-output-names" | "reg-names"
+            | "interrupt-names" | "reset-names" | "dma-names" | "phy-names" | "pinctrl-names"
+            | "power-domain-names" => KnownType::StringList,
+            "interrupt-parent" | "next-level-cache" | "memory-region" | "cpu-idle-states"
+            | "msi-parent" => KnownType::PhandleList,
+            "local-mac-address" | "mac-address" => KnownType::Bytes,
+            _ if name
+                .strip_prefix("pinctrl-")
+                .is_some_and(|index| index.bytes().all(|ch| ch.is_ascii_digit())) =>
+            {
+                KnownType::PhandleList
+            }
+            _ => return None,
+        };
+    Some(known_type)
+}
+
+impl<'a> PropertyValue<'a> {
+    /// Decodes the value of the property with the given name.
+    ///
+    /// If the property is well-known, the value is decoded as its defined
+    /// type, as long as it has a valid encoding for it. Otherwise, this falls
+    /// back to [`PropertyValue::guess`].
+    #[must_use]
+    pub fn decode(name: &str, value: &'a [u8]) -> Self {
+        let decoded = match known_type(name) {
+            _ if value.is_empty() => Some(Self::Empty),
+            Some(KnownType::U32) => Self::as_u32(value).map(Self::U32),
+            Some(KnownType::U64) => Self::as_u64(value).map(Self::U64),
+            Some(KnownType::U32OrU64) => Self::as_u32(value)
+                .map(Self::U32)
+                .or_else(|| Self::as_u64(value).map(Self::U64)),
+            Some(KnownType::String) => CStr::from_bytes_with_nul(value)
+                .ok()
+                .and_then(|string| string.to_str().ok())
+                .map(Self::String),
+            Some(KnownType::StringList) => StringList::new(value).map(Self::StringList),
+            Some(KnownType::PhandleList) => U32Array::new(value).map(Self::PhandleList),
+            Some(KnownType::Bytes) => Some(Self::Bytes(value)),
+            None => None,
+        };
+        decoded.unwrap_or_else(|| Self::guess(value))
+    }
+
+    /// Guesses the type of a property value from its contents alone.
+    ///
+    /// Values made of printable, NUL-terminated strings are decoded as strings,
+    /// values whose length is a multiple of 4 bytes as 32-bit integers, and
+    /// anything else as bytes.
+    #[must_use]
+    pub fn guess(value: &'a [u8]) -> Self {
+        if value.is_empty() {
+            return Self::Empty;
+        }
+
+        let is_printable = value
+            .iter()
+            .all(|&ch| ch.is_ascii_graphic() || ch == b' ' || ch == 0);
+        let has_empty = value.windows(2).any(|window| window == [0, 0]);
+        if is_printable
+            && !has_empty
+            && let Some(strings) = StringList::new(value)
+        {
+            let mut iter = strings.iter();
+            return match (iter.next(), iter.next()) {
+                (Some(string), None) => Self::String(string),
+                _ => Self::StringList(strings),
+            };
+        }
+
+        if let Some(value) = Self::as_u32(value) {
+            Self::U32(value)
+        } else if let Some(array) = U32Array::new(value) {
+            Self::U32Array(array)
+        } else {
+            Self::Bytes(value)
+        }
+    }
+
+    fn as_u32(value: &[u8]) -> Option<u32> {
+        big_endian::U32::ref_from_bytes(value)
+            .ok()
+            .map(|value| value.get())
+    }
+
+    fn as_u64(value: &[u8]) -> Option<u64> {
+        big_endian::U64::ref_from_bytes(value)
+            .ok()
+            .map(|value| value.get())
+    }
+}
+
+impl Display for PropertyValue<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Empty => Ok(()),
+            Self::U32(value) => write!(f, "<0x{value:02x}>"),
+            Self::U64(value) => write!(f, "/bits/ 64 <0x{value:02x}>"),
+            Self::String(string) => write!(f, "\"{string}\""),
+            Self::StringList(strings) => {
+                for (i, string) in strings.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(", ")?;
+                    }
+                    write!(f, "\"{string}\"")?;
+                }
+                Ok(())
+            }
+            Self::PhandleList(array) | Self::U32Array(array) => {
+                f.write_str("<")?;
+                for (i, value) in array.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" ")?;
+                    }
+                    write!(f, "0x{value:02x}")?;
+                }
+                f.write_str(">")
+            }
+            Self::Bytes(bytes) => {
+                f.write_str("[")?;
+                for (i, byte) in bytes.iter().enumerate() {
+                    if i > 0 {
+                        f.write_str(" ")?;
+                    }
+                    write!(f, "{byte:02x}")?;
+                }
+                f.write_str("]")
+            }
+        }
+    }
+}
+
+/// A list of NUL-terminated UTF-8 strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StringList<'a> {
+    value: &'a [u8],
+}
+
+impl<'a> StringList<'a> {
+    /// Returns the list of strings in `value`, or `None` if it isn't
+    /// NUL-terminated or a string isn't valid UTF-8.
+    fn new(value: &'a [u8]) -> Option<Self> {
+        if value.last() != Some(&0)
+            || value
+                .split(|&ch| ch == 0)
+                .any(|s| str::from_utf8(s).is_err())
+        {
+            return None;
+        }
+        Some(Self { value })
+    }
+
+    /// Returns an iterator over the strings in the list.
+    pub fn iter(&self) -> impl Iterator<Item = &'a str> + use<'a> {
+        self.value[..self.value.len() - 1]
+            .split(|&ch| ch == 0)
+            .map(|string| str::from_utf8(string).unwrap_or_default())
+    }
+}
+
+/// A list of big-endian 32-bit integers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct U32Array<'a> {
+    cells: &'a [big_endian::U32],
+}
+
+impl<'a> U32Array<'a> {
+    /// Returns the integers in `value`, or `None` if its length isn't a
+    /// multiple of 4 bytes.
+    pub(crate) fn new(value: &'a [u8]) -> Option<Self> {
+        <[big_endian::U32]>::ref_from_bytes(value)
+            .ok()
+            .map(|cells| Self { cells })
+    }
+
+    /// Returns the number of integers in the array.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    /// Returns whether the array is empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Returns the integer at the given index.
+    #[must_use]
+    pub fn get(&self, index: usize) -> Option<u32> {
+        self.cells.get(index).map(|cell| cell.get())
+    }
+
+    /// Returns an iterator over the integers in the array.
+    #[must_use]
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = u32> + use<'a> {
+        self.cells.iter().map(|cell| cell.get())
+    }
+}
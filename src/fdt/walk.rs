@@ -0,0 +1,189 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Walking a device tree with a visitor.
+
+use super::node::{FdtChildIter, MAX_DESCENDANTS_DEPTH};
+use super::{Fdt, FdtNode, FdtProperty};
+use crate::error::{FdtErrorKind, FdtParseError};
+
+/// Callbacks for the nodes and properties of a device tree, for
+/// [`Fdt::walk`] and [`FdtNode::walk`].
+///
+/// Each node is entered, then each of its properties is visited, then each of
+/// its children is walked, and then it is left. All methods do nothing and
+/// continue the walk by default, so a visitor only needs to implement the ones
+/// it is interested in.
+pub trait Visitor<'a> {
+    /// Called when entering a node, before its properties and children.
+    ///
+    /// Returning [`Walk::SkipSubtree`] skips the properties and descendants of
+    /// the node, but [`leave_node`](Self::leave_node) is still called for it.
+    fn enter_node(&mut self, node: FdtNode<'a>) -> Walk {
+        let _ = node;
+        Walk::Continue
+    }
+
+    /// Called for each property of a node, before its children.
+    ///
+    /// Returning [`Walk::SkipSubtree`] skips the rest of the properties and
+    /// the descendants of the node, but [`leave_node`](Self::leave_node) is
+    /// still called for it.
+    fn property(&mut self, node: FdtNode<'a>, property: FdtProperty<'a>) -> Walk {
+        let _ = (node, property);
+        Walk::Continue
+    }
+
+    /// Called when leaving a node, after its properties and descendants.
+    ///
+    /// Returning [`Walk::SkipSubtree`] has the same effect as
+    /// [`Walk::Continue`].
+    fn leave_node(&mut self, node: FdtNode<'a>) -> Walk {
+        let _ = node;
+        Walk::Continue
+    }
+}
+
+/// What a [`Visitor`] wants the walk to do next.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub enum Walk {
+    /// Continue walking.
+    #[default]
+    Continue,
+    /// Skip the rest of the current node.
+    SkipSubtree,
+    /// Stop walking, without calling any more methods of the visitor.
+    Stop,
+}
+
+impl<'a> Fdt<'a> {
+    /// Walks the whole device tree with the given visitor, from the root
+    /// node.
+    ///
+    /// See [`FdtNode::walk`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the device tree structure is malformed, a property
+    /// can't be read or the device tree is too deep.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::{Fdt, FdtNode, FdtProperty, Visitor, Walk};
+    /// # let dtb = include_bytes!("../../tests/dtb/test_traversal.dtb");
+    /// /// Counts the nodes which aren't under `/a/b`, and their properties.
+    /// #[derive(Default)]
+    /// struct Counter {
+    ///     nodes: usize,
+    ///     properties: usize,
+    /// }
+    ///
+    /// impl<'a> Visitor<'a> for Counter {
+    ///     fn enter_node(&mut self, node: FdtNode<'a>) -> Walk {
+    ///         if node.name() == Ok("b") {
+    ///             return Walk::SkipSubtree;
+    ///         }
+    ///         self.nodes += 1;
+    ///         Walk::Continue
+    ///     }
+    ///
+    ///     fn property(&mut self, _node: FdtNode<'a>, _property: FdtProperty<'a>) -> Walk {
+    ///         self.properties += 1;
+    ///         Walk::Continue
+    ///     }
+    /// }
+    ///
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let mut counter = Counter::default();
+    /// fdt.walk(&mut counter).unwrap();
+    /// assert_eq!((counter.nodes, counter.properties), (3, 0));
+    /// ```
+    pub fn walk(self, visitor: &mut impl Visitor<'a>) -> Result<(), FdtParseError> {
+        self.root()?.walk(visitor)
+    }
+}
+
+impl<'a> FdtNode<'a> {
+    /// Walks this node and its descendants with the given visitor.
+    ///
+    /// The nodes are walked depth-first, in the order they appear in the
+    /// device tree, and the visitor can skip subtrees or stop the walk at any
+    /// point. As with [`descendants`](Self::descendants), the nodes being
+    /// walked at each level are kept on the stack rather than allocating, so
+    /// if a node more than [`MAX_DESCENDANTS_DEPTH`] levels below this node has
+    /// children then an [`FdtErrorKind::TooDeep`] error is returned instead of
+    /// walking them.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the device tree structure is malformed, a property
+    /// can't be read or the subtree is too deep.
+    pub fn walk(&self, visitor: &mut impl Visitor<'a>) -> Result<(), FdtParseError> {
+        // The nodes whose children are being walked, with their children.
+        let mut stack: [Option<(FdtNode<'a>, FdtChildIter<'a>)>; MAX_DESCENDANTS_DEPTH] =
+            [const { None }; MAX_DESCENDANTS_DEPTH];
+        let mut depth = 0;
+        let mut next = Some(*self);
+        loop {
+            if let Some(node) = next.take() {
+                match enter(visitor, node)? {
+                    Walk::Stop => return Ok(()),
+                    Walk::Continue => {
+                        if let Some(level) = stack.get_mut(depth) {
+                            *level = Some((node, FdtChildIter::Start { node }));
+                            depth += 1;
+                        } else if node.children().next().is_some() {
+                            return Err(FdtParseError::new(
+                                FdtErrorKind::TooDeep(MAX_DESCENDANTS_DEPTH),
+                                node.offset,
+                            ));
+                        } else if visitor.leave_node(node) == Walk::Stop {
+                            return Ok(());
+                        }
+                    }
+                    Walk::SkipSubtree => {
+                        if visitor.leave_node(node) == Walk::Stop {
+                            return Ok(());
+                        }
+                    }
+                }
+            }
+            let Some(index) = depth.checked_sub(1) else {
+                return Ok(());
+            };
+            let Some((node, children)) = &mut stack[index] else {
+                unreachable!("levels below the depth are set");
+            };
+            if let Some(child) = children.next().transpose()? {
+                next = Some(child);
+            } else {
+                depth = index;
+                if visitor.leave_node(*node) == Walk::Stop {
+                    return Ok(());
+                }
+            }
+        }
+    }
+}
+
+/// Enters `node` and visits its properties, returning what to do with the rest
+/// of it.
+fn enter<'a>(visitor: &mut impl Visitor<'a>, node: FdtNode<'a>) -> Result<Walk, FdtParseError> {
+    let walk = visitor.enter_node(node);
+    if walk != Walk::Continue {
+        return Ok(walk);
+    }
+    for property in node.properties() {
+        let walk = visitor.property(node, property?);
+        if walk != Walk::Continue {
+            return Ok(walk);
+        }
+    }
+    Ok(Walk::Continue)
+}
@@ -0,0 +1,456 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Board fixups modelled after the ones applied by U-Boot.
+//!
+//! Bootloaders commonly patch the device tree with information only known at
+//! runtime: the amount of installed memory, the MAC addresses of network
+//! interfaces, and the partition layout of flash devices. This module adds
+//! [`DeviceTree`] methods equivalent to U-Boot's `fdt_fixup_memory_banks`,
+//! `fdt_fixup_ethernet` and `fdt_fixup_mtdparts`.
+
+use alloc::borrow::Cow;
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::diag;
+use crate::error::FdtError;
+use crate::model::property::push_cells;
+use crate::model::{DeviceTree, DeviceTreeNode, DeviceTreeProperty, NodeMut};
+
+impl DeviceTree {
+    /// Sets the `reg` property of the memory node to the given `(address,
+    /// size)` banks.
+    ///
+    /// The first child of the root node with `device_type = "memory"` is
+    /// updated, or a new `memory` node is created if there is none. The values
+    /// are encoded using the root node's `#address-cells` and `#size-cells`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FdtError::ValueTooBig`] if one of the addresses or sizes
+    /// doesn't fit in the root node's `#address-cells` or `#size-cells`
+    /// respectively, or [`FdtError::TooManyCells`] if either is more than 4.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::model::DeviceTree;
+    /// let mut tree = DeviceTree::new();
+    /// tree.fixup_memory(&[(0x8000_0000, 0x4000_0000)]).unwrap();
+    /// let memory = tree.root.child("memory").unwrap();
+    /// assert_eq!(
+    ///     memory.property("device_type").unwrap().as_str(),
+    ///     Ok("memory")
+    /// );
+    /// ```
+    pub fn fixup_memory(&mut self, banks: &[(u64, u64)]) -> Result<(), FdtError> {
+        let address_space = self.root_address_space();
+        let mut reg = Vec::new();
+        for &(address, size) in banks {
+            push_cells(&mut reg, address, address_space.address_cells)?;
+            push_cells(&mut reg, size, address_space.size_cells)?;
+        }
+
+        let name = self
+            .root
+            .children()
+            .find(|child| {
+                child
+                    .property("device_type")
+                    .is_some_and(|device_type| device_type.as_str() == Ok("memory"))
+            })
+            .map_or_else(
+                || String::from("memory"),
+                |child| String::from(child.name()),
+            );
+        if self.root.child(&name).is_none() {
+            self.root.add_child(DeviceTreeNode::new(name.as_str()));
+        }
+        diag::debug!("setting reg of /{name} to {} memory banks", banks.len());
+        let Some(memory) = self.root.child_mut(&name) else {
+            unreachable!("the memory node exists or was just added");
+        };
+        memory.add_property(DeviceTreeProperty::new("device_type", "memory\0"));
+        memory.add_property(DeviceTreeProperty::new("reg", reg));
+        Ok(())
+    }
+
+    /// Sets the MAC addresses of the network interfaces referenced by the
+    /// `ethernetN` aliases.
+    ///
+    /// For every `ethernetN` property of the `/aliases` node, the `env`
+    /// callback is asked for the `ethaddr` (for `ethernet0`) or `ethNaddr`
+    /// variable, like in U-Boot's environment. If it returns a valid MAC
+    /// address in the `xx:xx:xx:xx:xx:xx` format, the `mac-address` and
+    /// `local-mac-address` properties of the aliased node are set to it.
+    /// Aliases pointing to missing nodes and invalid addresses are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::model::{DeviceTree, DeviceTreeNode, DeviceTreeProperty};
+    /// let mut tree = DeviceTree::new();
+    /// tree.root.add_child(DeviceTreeNode::new("ethernet@1000"));
+    /// tree.root.add_child(
+    ///     DeviceTreeNode::builder("aliases")
+    ///         .property(DeviceTreeProperty::new("ethernet0", "/ethernet@1000\0"))
+    ///         .build(),
+    /// );
+    ///
+    /// tree.fixup_ethernet(|name| (name == "ethaddr").then(|| "02:00:00:12:34:56".into()));
+    ///
+    /// let ethernet = tree.root.child("ethernet@1000").unwrap();
+    /// assert_eq!(
+    ///     ethernet.property("mac-address").unwrap().value(),
+    ///     &[0x02, 0x00, 0x00, 0x12, 0x34, 0x56]
+    /// );
+    /// ```
+    pub fn fixup_ethernet(&mut self, mut env: impl FnMut(&str) -> Option<String>) {
+        let Some(aliases) = self.root.child("aliases") else {
+            return;
+        };
+        let targets: Vec<(String, [u8; 6])> = aliases
+            .properties()
+            .filter_map(|alias| {
+                let index = alias.name().strip_prefix("ethernet")?;
+                let variable = match index.parse::<u32>().ok()? {
+                    0 => String::from("ethaddr"),
+                    index => format!("eth{index}addr"),
+                };
+                let value = env(&variable)?;
+                let Some(mac) = parse_mac_address(&value) else {
+                    diag::warning!("ignoring invalid MAC address {value:?} in {variable}");
+                    return None;
+                };
+                Some((String::from(alias.as_str().ok()?), mac))
+            })
+            .collect();
+
+        for (path, mac) in targets {
+            if let Some(node) = self.find_node_mut(&path) {
+                diag::debug!("setting MAC address of {path}");
+                let Ok(()) = node.set_mac_address(mac);
+            } else {
+                diag::warning!("ethernet alias points to missing node {path}");
+            }
+        }
+    }
+
+    /// Adds the partitions described by a Linux `mtdparts` string to the
+    /// matching flash device nodes.
+    ///
+    /// The `mtdparts` string has the format accepted by the Linux
+    /// `cmdlinepart` parser, with or without the leading `mtdparts=`:
+    /// `<mtd-id>:<size>[@<offset>](<name>)[ro],...;<mtd-id>:...`, where the
+    /// size can be `-` to use the remaining space.
+    ///
+    /// For every `<mtd-id>` that has a matching entry in `devices`, any
+    /// existing `partitions` child of the device node is replaced by a new
+    /// `fixed-partitions` node with a `partition@<offset>` child for each
+    /// partition. Devices missing from `devices` are ignored. Partitions
+    /// without a name are labelled `Partition_<index>` like Linux does, e.g.
+    /// `Partition_000` for the first one of a device.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FdtError::InvalidMtdParts`] if the `mtdparts` string can't be
+    /// parsed or a partition doesn't fit in its device. The whole string is
+    /// parsed before any node is changed, so the tree is left unchanged on
+    /// error.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fixup::MtdDevice;
+    /// # use dtoolkit::model::{DeviceTree, DeviceTreeNode};
+    /// let mut tree = DeviceTree::new();
+    /// tree.root.add_child(DeviceTreeNode::new("flash@0"));
+    ///
+    /// let flash = MtdDevice {
+    ///     id: "nor0",
+    ///     path: "/flash@0",
+    ///     size: 0x100_0000,
+    /// };
+    /// tree.fixup_mtdparts("mtdparts=nor0:1m(u-boot)ro,-(rootfs)", &[flash])
+    ///     .unwrap();
+    ///
+    /// let partitions = tree.find_node_mut("/flash@0/partitions").unwrap();
+    /// assert!(partitions.child("partition@0").is_some());
+    /// assert!(partitions.child("partition@100000").is_some());
+    /// ```
+    pub fn fixup_mtdparts(
+        &mut self,
+        mtdparts: &str,
+        devices: &[MtdDevice],
+    ) -> Result<(), FdtError> {
+        let mtdparts = mtdparts.strip_prefix("mtdparts=").unwrap_or(mtdparts);
+        let mut fixups = Vec::new();
+        for device_parts in mtdparts.split(';').filter(|parts| !parts.is_empty()) {
+            let (id, parts) = device_parts
+                .split_once(':')
+                .ok_or(FdtError::InvalidMtdParts("missing mtd-id"))?;
+            let Some(device) = devices.iter().find(|device| device.id == id) else {
+                diag::debug!("ignoring partitions of unknown mtd-id {id}");
+                continue;
+            };
+            let partitions = parse_partitions(parts, device.size)?;
+            fixups.push((device, partitions.len(), partitions_node(&partitions)?));
+        }
+
+        for (device, count, partitions) in fixups {
+            if let Some(node) = self.find_node_mut(device.path) {
+                diag::debug!("adding {count} partitions to {}", device.path);
+                node.remove_child("partitions");
+                node.add_child(partitions);
+            } else {
+                diag::warning!("node {} of mtd-id {} not found", device.path, device.id);
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A flash device that partitions can be added to with
+/// [`DeviceTree::fixup_mtdparts`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MtdDevice<'a> {
+    /// The `<mtd-id>` used for the device in the `mtdparts` string.
+    pub id: &'a str,
+    /// The path of the device's node.
+    pub path: &'a str,
+    /// The size of the device in bytes.
+    pub size: u64,
+}
+
+/// A single partition parsed from an `mtdparts` string.
+#[derive(Debug)]
+struct MtdPartition<'a> {
+    name: Cow<'a, str>,
+    offset: u64,
+    size: u64,
+    read_only: bool,
+}
+
+fn parse_partitions(parts: &str, device_size: u64) -> Result<Vec<MtdPartition<'_>>, FdtError> {
+    let mut partitions = Vec::new();
+    let mut next_offset = 0;
+    for (index, part) in parts.split(',').enumerate() {
+        let (size, rest) = if let Some(rest) = part.strip_prefix('-') {
+            (None, rest)
+        } else {
+            let (size, rest) = parse_size(part)?;
+            (Some(size), rest)
+        };
+        let (offset, rest) = if let Some(rest) = rest.strip_prefix('@') {
+            parse_size(rest)?
+        } else {
+            (next_offset, rest)
+        };
+        let (name, rest) = if let Some(rest) = rest.strip_prefix('(') {
+            let (name, rest) = rest
+                .split_once(')')
+                .ok_or(FdtError::InvalidMtdParts("unterminated partition name"))?;
+            (Cow::Borrowed(name), rest)
+        } else {
+            (Cow::Owned(format!("Partition_{index:03}")), rest)
+        };
+        let (read_only, rest) = match rest.strip_prefix("ro") {
+            Some(rest) => (true, rest),
+            None => (false, rest),
+        };
+        // Locking isn't described in the device tree.
+        if !rest.is_empty() && rest != "lk" {
+            return Err(FdtError::InvalidMtdParts("unexpected characters"));
+        }
+
+        let size = match size {
+            Some(size) => size,
+            None => device_size
+                .checked_sub(offset)
+                .ok_or(FdtError::InvalidMtdParts("partition outside of device"))?,
+        };
+        next_offset = offset
+            .checked_add(size)
+            .filter(|&end| end <= device_size)
+            .ok_or(FdtError::InvalidMtdParts("partition outside of device"))?;
+        partitions.push(MtdPartition {
+            name,
+            offset,
+            size,
+            read_only,
+        });
+    }
+    Ok(partitions)
+}
+
+/// Parses a number with an optional `k`, `m` or `g` suffix from the start of
+/// the given string, returning it with the rest of the string.
+fn parse_size(s: &str) -> Result<(u64, &str), FdtError> {
+    let (digits, radix) = match s.strip_prefix("0x").or_else(|| s.strip_prefix("0X")) {
+        Some(hex) => (hex, 16),
+        None => (s, 10),
+    };
+    let end = digits
+        .find(|c: char| !c.is_digit(radix))
+        .unwrap_or(digits.len());
+    let value = u64::from_str_radix(&digits[..end], radix)
+        .map_err(|_| FdtError::InvalidMtdParts("invalid number"))?;
+    let rest = &digits[end..];
+    let (shift, rest) = match rest.as_bytes().first() {
+        Some(b'k' | b'K') => (10, &rest[1..]),
+        Some(b'm' | b'M') => (20, &rest[1..]),
+        Some(b'g' | b'G') => (30, &rest[1..]),
+        _ => (0, rest),
+    };
+    let value = value
+        .checked_mul(1 << shift)
+        .ok_or(FdtError::InvalidMtdParts("number too big"))?;
+    Ok((value, rest))
+}
+
+fn partitions_node(partitions: &[MtdPartition]) -> Result<DeviceTreeNode, FdtError> {
+    let cells: u32 = if partitions
+        .iter()
+        .all(|partition| partition.offset <= u32::MAX.into() && partition.size <= u32::MAX.into())
+    {
+        1
+    } else {
+        2
+    };
+
+    let mut node = DeviceTreeNode::builder("partitions")
+        .property(DeviceTreeProperty::new("compatible", "fixed-partitions\0"))
+        .property(DeviceTreeProperty::new(
+            "#address-cells",
+            cells.to_be_bytes(),
+        ))
+        .property(DeviceTreeProperty::new("#size-cells", cells.to_be_bytes()))
+        .build();
+    for partition in partitions {
+        let mut reg = Vec::new();
+        push_cells(&mut reg, partition.offset, cells)?;
+        push_cells(&mut reg, partition.size, cells)?;
+
+        let mut label = String::from(partition.name.as_ref());
+        label.push('\0');
+        let mut child = DeviceTreeNode::builder(format!("partition@{:x}", partition.offset))
+            .property(DeviceTreeProperty::new("label", label))
+            .property(DeviceTreeProperty::new("reg", reg));
+        if partition.read_only {
+            child = child.property(DeviceTreeProperty::new("read-only", []));
+        }
+        node.add_child(child.build());
+    }
+    Ok(node)
+}
+
+/// Parses a MAC address in the `xx:xx:xx:xx:xx:xx` format.
+fn parse_mac_address(s: &str) -> Option<[u8; 6]> {
+    let mut mac = [0; 6];
+    let mut parts = s.split(':');
+    for byte in &mut mac {
+        let part = parts.next()?;
+        if part.is_empty() || part.len() > 2 {
+            return None;
+        }
+        *byte = u8::from_str_radix(part, 16).ok()?;
+    }
+    parts.next().is_none().then_some(mac)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mac_address() {
+        assert_eq!(
+            parse_mac_address("00:1a:2B:3c:4d:5e"),
+            Some([0x00, 0x1a, 0x2b, 0x3c, 0x4d, 0x5e])
+        );
+        assert_eq!(parse_mac_address("00:1a:2b:3c:4d"), None);
+        assert_eq!(parse_mac_address("00:1a:2b:3c:4d:5e:6f"), None);
+        assert_eq!(parse_mac_address("00:1a:2b:3c:4d:5g"), None);
+        assert_eq!(parse_mac_address("001:a:2b:3c:4d:5e"), None);
+    }
+
+    #[test]
+    fn mtdparts() {
+        let partitions =
+            parse_partitions("512k(spl)ro,0x100000@1m(u-boot)lk,-(rootfs)", 0x80_0000).unwrap();
+        let layout: Vec<_> = partitions
+            .iter()
+            .map(|p| (p.name.as_ref(), p.offset, p.size, p.read_only))
+            .collect();
+        assert_eq!(
+            layout,
+            [
+                ("spl", 0, 0x8_0000, true),
+                ("u-boot", 0x10_0000, 0x10_0000, false),
+                ("rootfs", 0x20_0000, 0x60_0000, false),
+            ]
+        );
+
+        assert_eq!(
+            parse_partitions("1m(a),1m(b)", 0x10_0000).unwrap_err(),
+            FdtError::InvalidMtdParts("partition outside of device")
+        );
+        assert_eq!(
+            parse_partitions("1m(a", 0x10_0000).unwrap_err(),
+            FdtError::InvalidMtdParts("unterminated partition name")
+        );
+        assert_eq!(
+            parse_partitions("(a)", 0x10_0000).unwrap_err(),
+            FdtError::InvalidMtdParts("invalid number")
+        );
+
+        let names: Vec<_> = parse_partitions("1m,1m(b),-", 0x30_0000)
+            .unwrap()
+            .into_iter()
+            .map(|p| p.name)
+            .collect();
+        assert_eq!(names, ["Partition_000", "b", "Partition_002"]);
+    }
+
+    #[test]
+    fn mtdparts_error_leaves_tree_unchanged() {
+        let mut tree = DeviceTree::new();
+        tree.root.add_child(DeviceTreeNode::new("flash@0"));
+        tree.root.add_child(DeviceTreeNode::new("flash@1"));
+        let devices = [
+            MtdDevice {
+                id: "nor0",
+                path: "/flash@0",
+                size: 0x10_0000,
+            },
+            MtdDevice {
+                id: "nor1",
+                path: "/flash@1",
+                size: 0x10_0000,
+            },
+        ];
+        let original = tree.clone();
+
+        assert_eq!(
+            tree.fixup_mtdparts("nor0:-(a);nor1:2m(b)", &devices),
+            Err(FdtError::InvalidMtdParts("partition outside of device"))
+        );
+        assert_eq!(tree, original);
+
+        tree.fixup_mtdparts("nor0:-", &devices).unwrap();
+        let partition = tree
+            .find_node_mut("/flash@0/partitions/partition@0")
+            .unwrap();
+        assert_eq!(
+            partition.property("label").unwrap().as_str(),
+            Ok("Partition_000")
+        );
+    }
+}
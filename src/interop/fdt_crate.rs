@@ -0,0 +1,56 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Conversions to the [`fdt`](::fdt) crate.
+
+use crate::fdt::{Fdt, FdtNode};
+
+impl<'a> TryFrom<Fdt<'a>> for ::fdt::Fdt<'a> {
+    type Error = ::fdt::FdtError;
+
+    /// Parses the same blob with the `fdt` crate.
+    fn try_from(fdt: Fdt<'a>) -> Result<Self, Self::Error> {
+        ::fdt::Fdt::new(fdt.data())
+    }
+}
+
+impl<'a> FdtNode<'a> {
+    /// Returns the node of the given [`fdt::Fdt`](::fdt::Fdt) corresponding
+    /// to this node.
+    ///
+    /// `fdt` must have been created from the same blob as this node, for
+    /// example with [`TryFrom<Fdt>`](TryFrom), otherwise `None` is returned.
+    ///
+    /// # Performance
+    ///
+    /// This method iterates through all nodes of the device tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../tests/dtb/test_children.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let other = fdt::Fdt::try_from(fdt).unwrap();
+    ///
+    /// let node = fdt.find_node("/child1").unwrap().unwrap();
+    /// let other_node = node.to_fdt_crate_node(&other).unwrap();
+    /// assert_eq!(other_node.name, "child1");
+    /// ```
+    #[must_use]
+    pub fn to_fdt_crate_node<'b>(
+        &self,
+        fdt: &'b ::fdt::Fdt<'a>,
+    ) -> Option<::fdt::node::FdtNode<'b, 'a>> {
+        let name = self.name().ok()?;
+        // Both crates return node names borrowed from the blob, so the node can
+        // be identified by the address of its name.
+        fdt.all_nodes()
+            .find(|node| core::ptr::eq(node.name.as_ptr(), name.as_ptr()))
+    }
+}
@@ -0,0 +1,94 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Conversions to and from the [`fdt_rs`] crate.
+
+use fdt_rs::base::DevTree;
+use fdt_rs::error::DevTreeError;
+
+use crate::error::{FdtErrorKind, FdtParseError};
+use crate::fdt::Fdt;
+
+impl<'a> TryFrom<Fdt<'a>> for DevTree<'a> {
+    type Error = DevTreeError;
+
+    /// Parses the same blob with the `fdt-rs` crate.
+    ///
+    /// `fdt-rs` requires the blob to be 4-byte aligned, so this fails with
+    /// [`DevTreeError::InvalidParameter`] if it isn't.
+    fn try_from(fdt: Fdt<'a>) -> Result<Self, Self::Error> {
+        let data = fdt.data();
+        if data.as_ptr().align_offset(align_of::<u32>()) != 0 {
+            return Err(DevTreeError::InvalidParameter("FDT is not 4-byte aligned"));
+        }
+        #[expect(
+            unsafe_code,
+            reason = "fdt-rs only provides an unsafe constructor, whose requirements are checked \
+            here."
+        )]
+        // SAFETY: The buffer is 32-bit aligned, as checked above, and its length is
+        // exactly the `totalsize` from the header, as validated by `Fdt::new`.
+        unsafe {
+            DevTree::new(data)
+        }
+    }
+}
+
+impl<'a> TryFrom<DevTree<'a>> for Fdt<'a> {
+    type Error = FdtParseError;
+
+    /// Parses the blob of the `fdt-rs` device tree with `dtoolkit`.
+    fn try_from(tree: DevTree<'a>) -> Result<Self, Self::Error> {
+        let data = tree
+            .buf()
+            .get(..tree.totalsize())
+            .ok_or(FdtParseError::new(FdtErrorKind::InvalidLength, 0))?;
+        Fdt::new(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[repr(C, align(4))]
+    struct Aligned<T: ?Sized>(T);
+
+    static DTB: &Aligned<[u8]> = &Aligned(*include_bytes!("../../tests/dtb/test_children.dtb"));
+
+    #[test]
+    fn round_trip() {
+        let fdt = Fdt::new(&DTB.0).unwrap();
+        let tree = DevTree::try_from(fdt).unwrap();
+        assert_eq!(tree.totalsize(), DTB.0.len());
+        assert_eq!(
+            tree.root().unwrap().unwrap().name(),
+            Ok(fdt.root().unwrap().name().unwrap())
+        );
+
+        let fdt = Fdt::try_from(tree).unwrap();
+        assert_eq!(fdt.data(), &DTB.0);
+    }
+
+    #[test]
+    fn unaligned() {
+        let mut data = vec![0; DTB.0.len() + 1];
+        let offset = if data.as_ptr().align_offset(align_of::<u32>()) == 0 {
+            1
+        } else {
+            0
+        };
+        let slice = &mut data[offset..][..DTB.0.len()];
+        slice.copy_from_slice(&DTB.0);
+        let fdt = Fdt::new(slice).unwrap();
+        assert_eq!(
+            DevTree::try_from(fdt).unwrap_err(),
+            DevTreeError::InvalidParameter("FDT is not 4-byte aligned")
+        );
+    }
+}
@@ -0,0 +1,28 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Conversions to and from other device tree crates.
+//!
+//! These make it possible to migrate a code base to `dtoolkit` incrementally:
+//! a blob parsed by one crate can be handed to code written against another
+//! without copying or re-reading it. The conversions only share the
+//! underlying blob, so they are cheap, but each crate still validates the
+//! header itself.
+//!
+//! - With the `fdt` feature, [`Fdt`](crate::fdt::Fdt) can be converted to
+//!   [`fdt::Fdt`], and an [`FdtNode`](crate::fdt::FdtNode) can be looked up in
+//!   it with [`FdtNode::to_fdt_crate_node`].
+//! - With the `fdt-rs` feature, [`Fdt`](crate::fdt::Fdt) can be converted to
+//!   and from `fdt_rs::base::DevTree`.
+//!
+//! [`FdtNode::to_fdt_crate_node`]: crate::fdt::FdtNode::to_fdt_crate_node
+
+#[cfg(feature = "fdt")]
+mod fdt_crate;
+#[cfg(feature = "fdt-rs")]
+mod fdt_rs;
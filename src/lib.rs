@@ -19,6 +19,37 @@
 //! you don't need the Device Tree manipulation functionality, the library is
 //! also no-`alloc`-compatible.
 //!
+//! ## Features
+//!
+//! - `alloc`: enables [`FdtBuf`](fdt::FdtBuf), an owned counterpart of
+//!   [`Fdt`](fdt::Fdt), and [`FdtIndex`](fdt::FdtIndex) and
+//!   [`IndexedNode`](fdt::IndexedNode) for repeated lookups, and
+//!   [`DtPathBuf`](path::DtPathBuf) for owned node paths.
+//! - `std`: implies `alloc` and enables reading device trees from files and
+//!   other `std::io::Read` sources, and with `write`, reading a
+//!   [`DeviceTree`](model::DeviceTree) from a file and writing it to a
+//!   `std::io::Write`.
+//! - `write`: implies `alloc` and enables the read-write API in [`model`].
+//! - `arbitrary`: implies `write` and implements `arbitrary::Arbitrary` for
+//!   [`DeviceTree`](model::DeviceTree) and its nodes and properties, generating
+//!   structurally valid trees for fuzzing.
+//! - `fdt` and `fdt-rs`: enable conversions to and from the `fdt` and `fdt-rs`
+//!   crates, see the `interop` module.
+//! - `log`: logs the changes made by fixups and the problems recovered from
+//!   through the [`log`](https://docs.rs/log) crate, with the `dtoolkit`
+//!   target.
+//! - `query`: implies `alloc` and enables the [`query`] module, a small query
+//!   language for selecting nodes of an [`Fdt`](fdt::Fdt) or a
+//!   [`DeviceTree`](model::DeviceTree).
+//! - `serde`: implies `write` and implements `serde::Serialize` and
+//!   `serde::Deserialize` for [`DeviceTree`](model::DeviceTree), its nodes and
+//!   properties, and [`MemoryReservation`](memreserve::MemoryReservation), with
+//!   property values stored as strings, cells or bytes.
+//! - `test-utils`: implies `write` and enables the `test_utils` module, with a
+//!   builder for test blobs and an `assert_tree_eq!` macro.
+//! - `wasm`: implies `alloc` and enables `wasm-bindgen` bindings for use in web
+//!   applications, see the `wasm` module.
+//!
 //! ## Read-Only API
 //!
 //! The read-only API is centered around the [`Fdt`](fdt::Fdt) struct, which
@@ -77,12 +108,32 @@
 #![deny(unsafe_code)]
 #![cfg_attr(docsrs, feature(doc_cfg))]
 
-#[cfg(feature = "write")]
+#[cfg(feature = "alloc")]
 extern crate alloc;
+#[cfg(all(feature = "std", not(test)))]
+extern crate std;
 
+#[cfg(feature = "write")]
+pub mod boot;
+mod diag;
+pub mod dtbo;
 pub mod error;
 pub mod fdt;
+#[cfg(feature = "write")]
+pub mod fixup;
+#[cfg(any(feature = "fdt", feature = "fdt-rs"))]
+pub mod interop;
 pub mod memreserve;
 #[cfg(feature = "write")]
 pub mod model;
+pub mod path;
+#[cfg(feature = "query")]
+pub mod query;
 pub mod standard;
+pub mod static_tree;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+pub use fdt::compare;
@@ -54,4 +54,105 @@ impl MemoryReservation {
     pub const fn size(&self) -> u64 {
         self.size.get()
     }
+
+    /// Returns the address just past the end of the reserved memory region,
+    /// saturating at `u64::MAX`.
+    #[must_use]
+    pub const fn end(&self) -> u64 {
+        self.address().saturating_add(self.size())
+    }
+
+    /// Returns whether this reservation and the given region have any address
+    /// in common.
+    #[must_use]
+    pub const fn overlaps(&self, address: u64, size: u64) -> bool {
+        self.address() < address.saturating_add(size) && address < self.end()
+    }
+
+    /// Returns whether this reservation doesn't overlap any of the given
+    /// `(address, size)` regions, such as those from
+    /// [`Memory::regions`](crate::standard::Memory::regions), in which case it
+    /// doesn't reserve any RAM and is probably a mistake.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::memreserve::MemoryReservation;
+    /// let memory = [(0x8000_0000, 0x4000_0000)];
+    /// assert!(!MemoryReservation::new(0x8000_0000, 0x1000).is_outside(memory));
+    /// assert!(MemoryReservation::new(0x1000, 0x1000).is_outside(memory));
+    /// ```
+    #[must_use]
+    pub fn is_outside(&self, regions: impl IntoIterator<Item = (u64, u64)>) -> bool {
+        !regions
+            .into_iter()
+            .any(|(address, size)| self.overlaps(address, size))
+    }
+}
+
+/// Sorts memory reservations by address, and then by size.
+pub fn sort_reservations(reservations: &mut [MemoryReservation]) {
+    reservations.sort_unstable_by_key(|reservation| (reservation.address(), reservation.size()));
+}
+
+/// Returns the pairs of memory reservations which overlap each other, in the
+/// order in which they appear in `reservations`.
+///
+/// Each pair is only returned once. Reservations which are merely adjacent
+/// don't overlap.
+pub fn overlapping_reservations(
+    reservations: &[MemoryReservation],
+) -> impl Iterator<Item = (MemoryReservation, MemoryReservation)> + use<'_> {
+    reservations
+        .iter()
+        .enumerate()
+        .flat_map(move |(index, first)| {
+            reservations[index + 1..]
+                .iter()
+                .filter(|second| first.overlaps(second.address(), second.size()))
+                .map(|second| (*first, *second))
+        })
+}
+
+/// Sorts memory reservations and merges those which overlap or are adjacent,
+/// and removes empty ones, which would otherwise terminate the memory
+/// reservation block when written.
+///
+/// The merged reservations are moved to the start of `reservations`, and their
+/// number is returned, so that a `Vec` can be truncated to it.
+///
+/// # Examples
+///
+/// ```
+/// # use dtoolkit::memreserve::{MemoryReservation, coalesce_reservations};
+/// let mut reservations = vec![
+///     MemoryReservation::new(0x2000, 0x1000),
+///     MemoryReservation::new(0x1000, 0x1000),
+///     MemoryReservation::new(0x8000, 0),
+///     MemoryReservation::new(0x2800, 0x1000),
+/// ];
+/// let len = coalesce_reservations(&mut reservations);
+/// reservations.truncate(len);
+/// assert_eq!(reservations, [MemoryReservation::new(0x1000, 0x2800)]);
+/// ```
+pub fn coalesce_reservations(reservations: &mut [MemoryReservation]) -> usize {
+    sort_reservations(reservations);
+    let mut len: usize = 0;
+    for index in 0..reservations.len() {
+        let reservation = reservations[index];
+        if reservation.size() == 0 {
+            continue;
+        }
+        match len.checked_sub(1).map(|last| &mut reservations[last]) {
+            Some(last) if reservation.address() <= last.end() => {
+                let end = last.end().max(reservation.end());
+                *last = MemoryReservation::new(last.address(), end - last.address());
+            }
+            _ => {
+                reservations[len] = reservation;
+                len += 1;
+            }
+        }
+    }
+    len
 }
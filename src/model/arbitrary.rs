@@ -0,0 +1,130 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! [`Arbitrary`] implementations for fuzzing.
+//!
+//! The generated trees are structurally valid: node and property names only
+//! use the characters allowed by the Devicetree Specification, siblings have
+//! distinct names, nesting is bounded and memory reservations are never empty.
+//! They can therefore be serialized and parsed back without losing anything.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use arbitrary::{Arbitrary, Result, Unstructured};
+
+use super::{DeviceTree, DeviceTreeNode, DeviceTreeProperty};
+use crate::memreserve::MemoryReservation;
+
+/// The maximum number of levels of nodes generated below a node.
+const MAX_DEPTH: usize = 8;
+/// The maximum number of children or properties generated for a node.
+const MAX_ENTRIES: u8 = 8;
+/// The maximum length of a node or property name, as recommended by the
+/// Devicetree Specification.
+const MAX_NAME_LEN: usize = 31;
+
+/// Characters allowed in node names, besides the `@` before the unit address.
+const NODE_NAME_CHARS: &[u8] =
+    b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789,._+-";
+/// Characters allowed in property names.
+const PROPERTY_NAME_CHARS: &[u8] =
+    b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789,._+?#-";
+
+/// Returns a name made of between 1 and `max_len` of the given characters,
+/// starting with a letter.
+fn name(u: &mut Unstructured<'_>, chars: &[u8], max_len: usize) -> Result<String> {
+    let len = u.int_in_range(1..=max_len)?;
+    let mut name = String::with_capacity(len);
+    name.push(char::from(*u.choose(&chars[..52])?));
+    for _ in 1..len {
+        name.push(char::from(*u.choose(chars)?));
+    }
+    Ok(name)
+}
+
+fn arbitrary_node(u: &mut Unstructured<'_>, name: String, depth: usize) -> Result<DeviceTreeNode> {
+    let mut node = DeviceTreeNode::new(name);
+    for _ in 0..u.int_in_range(0..=MAX_ENTRIES)? {
+        node.add_property(u.arbitrary()?);
+    }
+    if depth < MAX_DEPTH {
+        for _ in 0..u.int_in_range(0..=MAX_ENTRIES)? {
+            let name = node_name(u)?;
+            node.add_child(arbitrary_node(u, name, depth + 1)?);
+        }
+    }
+    Ok(node)
+}
+
+/// Returns a node name, with a unit address half of the time.
+fn node_name(u: &mut Unstructured<'_>) -> Result<String> {
+    let mut name = name(u, NODE_NAME_CHARS, MAX_NAME_LEN - 9)?;
+    if u.arbitrary()? {
+        let unit_address: u32 = u.arbitrary()?;
+        name = alloc::format!("{name}@{unit_address:x}");
+    }
+    Ok(name)
+}
+
+impl<'a> Arbitrary<'a> for DeviceTreeProperty {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let name = name(u, PROPERTY_NAME_CHARS, MAX_NAME_LEN)?;
+        let value: Vec<u8> = u.arbitrary()?;
+        Ok(Self::new(name, value))
+    }
+}
+
+impl<'a> Arbitrary<'a> for DeviceTreeNode {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let name = node_name(u)?;
+        arbitrary_node(u, name, 0)
+    }
+}
+
+impl<'a> Arbitrary<'a> for MemoryReservation {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        // An empty reservation would terminate the memory reservation block.
+        Ok(Self::new(u.arbitrary()?, u.int_in_range(1..=u64::MAX)?))
+    }
+}
+
+impl<'a> Arbitrary<'a> for DeviceTree {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut tree = DeviceTree::new();
+        tree.root = arbitrary_node(u, String::from("/"), 0)?;
+        tree.memory_reservations = u.arbitrary()?;
+        Ok(tree)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fdt::Fdt;
+
+    #[test]
+    fn round_trip() {
+        let mut seed = 0x1234_5678_u32;
+        let data: Vec<u8> = (0..4096)
+            .map(|_| {
+                seed ^= seed << 13;
+                seed ^= seed >> 17;
+                seed ^= seed << 5;
+                seed.to_le_bytes()[0]
+            })
+            .collect();
+        let mut u = Unstructured::new(&data);
+        while !u.is_empty() {
+            let tree: DeviceTree = u.arbitrary().unwrap();
+            let dtb = tree.to_dtb();
+            let fdt = Fdt::new(&dtb).unwrap();
+            assert_eq!(DeviceTree::from_fdt(&fdt).unwrap(), tree);
+        }
+    }
+}
@@ -0,0 +1,178 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Recording the changes made by applying an overlay, so that they can be
+//! undone.
+
+use alloc::string::ToString;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use super::overlay::{node_at, node_at_mut};
+use super::{DeviceTree, DeviceTreeProperty, OverlayError};
+use crate::path::{DtPath, DtPathBuf};
+
+/// The changes made to a [`DeviceTree`] by applying an overlay with
+/// [`DeviceTree::apply_overlay_with_changeset`], which can be undone with
+/// [`DeviceTree::revert`].
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct OverlayChangeset {
+    changes: Vec<Change>,
+}
+
+/// A single change made by applying an overlay.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) enum Change {
+    /// A node was added, along with its descendants.
+    AddNode { path: DtPathBuf },
+    /// A property was added or replaced.
+    SetProperty {
+        path: DtPathBuf,
+        name: Arc<str>,
+        old: Option<DeviceTreeProperty>,
+    },
+    /// A label was added to a node.
+    AddLabel { path: DtPathBuf, label: Arc<str> },
+}
+
+impl Change {
+    /// Returns the path of the node the change was made to.
+    fn path(&self) -> &DtPathBuf {
+        match self {
+            Self::AddNode { path }
+            | Self::SetProperty { path, .. }
+            | Self::AddLabel { path, .. } => path,
+        }
+    }
+}
+
+impl OverlayChangeset {
+    /// Records a change, in the order they were made.
+    pub(super) fn push(&mut self, change: Change) {
+        self.changes.push(change);
+    }
+
+    /// Returns whether applying the overlay didn't change anything.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Returns the paths of the nodes added by the overlay.
+    ///
+    /// The descendants of added nodes aren't listed separately.
+    pub fn added_nodes(&self) -> impl Iterator<Item = DtPath<'_>> {
+        self.changes.iter().filter_map(|change| match change {
+            Change::AddNode { path } => Some(path.as_path()),
+            _ => None,
+        })
+    }
+
+    /// Returns the paths of the nodes and the names of the properties added
+    /// or replaced by the overlay, along with their previous values.
+    ///
+    /// Properties of added nodes aren't listed.
+    pub fn changed_properties(
+        &self,
+    ) -> impl Iterator<Item = (DtPath<'_>, &str, Option<&DeviceTreeProperty>)> {
+        self.changes.iter().filter_map(|change| match change {
+            Change::SetProperty { path, name, old } => {
+                Some((path.as_path(), &**name, old.as_ref()))
+            }
+            _ => None,
+        })
+    }
+}
+
+impl DeviceTree {
+    /// Undoes the changes made by applying an overlay with
+    /// [`DeviceTree::apply_overlay_with_changeset`].
+    ///
+    /// Added nodes are removed, and added or replaced properties and labels
+    /// are restored to what they were before the overlay was applied,
+    /// including those of the `__symbols__` node. Changes made to the tree
+    /// since then are kept, unless they were made to nodes or properties the
+    /// overlay added or replaced.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`OverlayError::StaleChangeset`] if a node changed by the
+    /// overlay has since been removed or moved, in which case the tree is
+    /// left unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # use dtoolkit::model::{DeviceTree, DeviceTreeNode, DeviceTreeProperty, Overlay, OverlayTarget};
+    /// let mut tree = DeviceTree::new();
+    /// tree.root.add_child(DeviceTreeNode::new("soc"));
+    /// let original = tree.clone();
+    ///
+    /// let mut overlay = Overlay::new();
+    /// overlay.add_fragment(
+    ///     OverlayTarget::Path("/soc".into()),
+    ///     DeviceTreeNode::builder("soc")
+    ///         .property(DeviceTreeProperty::new("status", "okay\0"))
+    ///         .child(DeviceTreeNode::new("uart"))
+    ///         .build(),
+    /// );
+    /// let dtbo = overlay.to_dtbo();
+    ///
+    /// let changeset = tree
+    ///     .apply_overlay_with_changeset(&Fdt::new(&dtbo).unwrap())
+    ///     .unwrap();
+    /// let added = changeset.added_nodes().next().unwrap();
+    /// assert_eq!(added.as_str(), "/soc/uart");
+    /// tree.revert(changeset).unwrap();
+    /// assert_eq!(tree, original);
+    /// ```
+    pub fn revert(&mut self, changeset: OverlayChangeset) -> Result<(), OverlayError> {
+        if let Some(change) = changeset
+            .changes
+            .iter()
+            .find(|change| node_at(&self.root, change.path().as_str()).is_none())
+        {
+            return Err(OverlayError::StaleChangeset {
+                path: change.path().to_string(),
+            });
+        }
+
+        for change in changeset.changes.into_iter().rev() {
+            match change {
+                Change::AddNode { path } => {
+                    let (Some(parent), Some(name)) =
+                        (path.as_path().parent(), path.as_path().name())
+                    else {
+                        continue;
+                    };
+                    if let Some(parent) = node_at_mut(&mut self.root, parent.as_str()) {
+                        parent.remove_child(name);
+                    }
+                }
+                Change::SetProperty { path, name, old } => {
+                    let Some(node) = node_at_mut(&mut self.root, path.as_str()) else {
+                        continue;
+                    };
+                    match old {
+                        Some(old) => node.add_property(old),
+                        None => {
+                            node.remove_property(&name);
+                        }
+                    }
+                }
+                Change::AddLabel { path, label } => {
+                    if let Some(node) = node_at_mut(&mut self.root, path.as_str()) {
+                        node.remove_label(&label);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Sharing of node and property names between the nodes of a device tree.
+
+use alloc::collections::BTreeMap;
+use alloc::sync::Arc;
+
+/// A pool of names read from a flattened device tree.
+///
+/// Property names such as `reg`, `compatible` or `status` appear on most nodes
+/// of a device tree. Interning them while converting a tree lets all
+/// properties with the same name share a single allocation, which also makes
+/// comparing them cheap as equal names usually point to the same string.
+#[derive(Debug, Default)]
+pub(super) struct NamePool<'a> {
+    names: BTreeMap<&'a str, Arc<str>>,
+}
+
+impl<'a> NamePool<'a> {
+    /// Returns the shared copy of `name`, allocating it if this is the first
+    /// time it was seen.
+    pub(super) fn intern(&mut self, name: &'a str) -> Arc<str> {
+        self.names
+            .entry(name)
+            .or_insert_with(|| name.into())
+            .clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shares_equal_names() {
+        let mut pool = NamePool::default();
+        let reg = pool.intern("reg");
+        let status = pool.intern("status");
+        assert!(Arc::ptr_eq(&reg, &pool.intern("reg")));
+        assert!(!Arc::ptr_eq(&reg, &status));
+        assert_eq!(&*status, "status");
+    }
+}
@@ -0,0 +1,484 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A device tree which is only parsed as far as it is accessed.
+
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::mem;
+
+use indexmap::IndexMap;
+use twox_hash::xxhash64;
+use zerocopy::IntoBytes;
+
+use super::intern::NamePool;
+use super::node::{NodeSource, default_hash_state};
+use super::writer::{self, StringMap};
+use super::{DeviceTree, DeviceTreeNode, DeviceTreeProperty};
+use crate::error::{FdtError, FdtParseError};
+use crate::fdt::{FDT_END, FDT_END_NODE, Fdt, FdtBuf, FdtHeader, FdtNode};
+use crate::memreserve::MemoryReservation;
+use crate::path::DtPath;
+
+/// A mutable device tree which keeps the nodes that haven't been accessed as
+/// references into the blob it was parsed from.
+///
+/// Unlike [`DeviceTree::from_fdt`], which converts the whole blob up front,
+/// [`LazyDeviceTree::from_fdt`] only copies it. A node's properties and the
+/// names of its children are parsed the first time the node is accessed, and
+/// the subtrees that were never accessed are copied as is by
+/// [`LazyDeviceTree::to_dtb`]. This suits tools that change a handful of
+/// nodes in a large tree.
+///
+/// # Examples
+///
+/// ```
+/// # use dtoolkit::{fdt::Fdt, model::{DeviceTreeProperty, LazyDeviceTree}};
+/// # let dtb = include_bytes!("../../tests/dtb/test_children.dtb");
+/// let fdt = Fdt::new(dtb).unwrap();
+/// let mut tree = LazyDeviceTree::from_fdt(&fdt).unwrap();
+/// let node = tree.find_node_mut("/child2@42").unwrap().unwrap();
+/// node.add_property(DeviceTreeProperty::new("status", "okay\0"))
+///     .unwrap();
+///
+/// let dtb = tree.to_dtb().unwrap();
+/// let node = Fdt::new(&dtb)
+///     .unwrap()
+///     .find_node("/child2")
+///     .unwrap()
+///     .unwrap();
+/// assert!(node.property("status").unwrap().is_some());
+/// ```
+#[derive(Clone, Debug)]
+pub struct LazyDeviceTree {
+    /// The memory reservations for this device tree.
+    pub memory_reservations: Vec<MemoryReservation>,
+    source: FdtBuf,
+    root: LazyNode,
+}
+
+impl LazyDeviceTree {
+    /// Creates a new `LazyDeviceTree` from a copy of the given `Fdt`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the root node or the memory reservations of the
+    /// `Fdt` cannot be parsed.
+    pub fn from_fdt(fdt: &Fdt<'_>) -> Result<Self, FdtParseError> {
+        let source = FdtBuf::from(*fdt);
+        let root = LazyNode::unvisited(&source, source.fdt().root()?)?;
+        Ok(Self {
+            memory_reservations: fdt.memory_reservations().collect::<Result<_, _>>()?,
+            source,
+            root,
+        })
+    }
+
+    /// Returns the root node.
+    pub fn root(&mut self) -> &mut LazyNode {
+        &mut self.root
+    }
+
+    /// Finds a node by its path and returns a mutable reference to it.
+    ///
+    /// As with [`DeviceTree::find_node_mut`], names must match exactly,
+    /// including their unit-address. Only the nodes along the path are parsed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a node along the path cannot be parsed.
+    pub fn find_node_mut(&mut self, path: &str) -> Result<Option<&mut LazyNode>, FdtParseError> {
+        let path = DtPath::new(path);
+        if !path.is_absolute() {
+            return Ok(None);
+        }
+        let mut current_node = &mut self.root;
+        for component in path.segments() {
+            match current_node.child_mut(component)? {
+                Some(node) => current_node = node,
+                None => return Ok(None),
+            }
+        }
+        Ok(Some(current_node))
+    }
+
+    /// Serializes the device tree to a flattened device tree blob.
+    ///
+    /// Subtrees which haven't been accessed are copied from the original blob,
+    /// along with its strings block.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a node which hasn't been accessed was swapped in
+    /// from another `LazyDeviceTree` and can't be parsed, or
+    /// [`FdtError::CapacityExceeded`] if the offset of a property name in the
+    /// strings block doesn't fit in a u32.
+    ///
+    /// # Panics
+    ///
+    /// This may panic if any of the lengths written to the DTB (block sizes,
+    /// property value length, etc.) exceed [`u32::MAX`].
+    pub fn to_dtb(&self) -> Result<Vec<u8>, FdtError> {
+        let mut string_map = StringMap::new(self.source.fdt().strings_block());
+        let mut dtb = vec![0; size_of::<FdtHeader>()];
+
+        writer::write_memory_reservations(&mut dtb, &self.memory_reservations);
+        let off_dt_struct = dtb.len();
+        self.root.write(&mut dtb, &mut string_map, &self.source)?;
+        dtb.extend_from_slice(&FDT_END.to_be_bytes());
+        let off_dt_strings = dtb.len();
+        string_map.write_string_block(&mut dtb);
+
+        let header = writer::header(
+            off_dt_struct - size_of::<FdtHeader>(),
+            off_dt_strings - off_dt_struct,
+            dtb.len() - off_dt_strings,
+        );
+        dtb[..size_of::<FdtHeader>()].copy_from_slice(header.as_bytes());
+        Ok(dtb)
+    }
+
+    /// Parses the rest of the device tree and converts it to a [`DeviceTree`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a node which hasn't been accessed yet cannot be
+    /// parsed.
+    pub fn into_device_tree(self) -> Result<DeviceTree, FdtParseError> {
+        let mut tree = DeviceTree::new();
+        tree.root = self.root.into_node()?;
+        tree.memory_reservations = self.memory_reservations;
+        tree.source = Some(self.source);
+        Ok(tree)
+    }
+}
+
+/// A node of a [`LazyDeviceTree`].
+///
+/// Accessing the properties or children of a node parses them from the
+/// source blob the first time, so most methods take `&mut self` and can fail.
+#[derive(Clone, Debug)]
+pub struct LazyNode {
+    name: Arc<str>,
+    state: LazyState,
+}
+
+/// The children of a visited [`LazyNode`], by name.
+type LazyChildren = IndexMap<Arc<str>, LazyNode, xxhash64::State>;
+
+#[derive(Clone, Debug)]
+enum LazyState {
+    /// The node hasn't been accessed, and is still only in the source blob.
+    Unvisited(NodeSource),
+    /// The node was added as a [`DeviceTreeNode`], and its children haven't
+    /// been accessed yet.
+    Node(DeviceTreeNode),
+    /// The node has been accessed. `head` holds its name and properties, but
+    /// no children.
+    Visited {
+        head: DeviceTreeNode,
+        children: LazyChildren,
+    },
+}
+
+impl LazyNode {
+    fn unvisited(source: &FdtBuf, node: FdtNode<'_>) -> Result<Self, FdtParseError> {
+        let end = node.fdt.next_sibling_offset(node.offset)?;
+        Ok(Self {
+            name: node.name()?.into(),
+            state: LazyState::Unvisited(NodeSource {
+                buf: source.clone(),
+                range: node.offset..end,
+            }),
+        })
+    }
+
+    /// Returns the name of this node.
+    #[must_use]
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Parses the properties and children of this node if needed, and returns
+    /// them.
+    fn visit(&mut self) -> Result<(&mut DeviceTreeNode, &mut LazyChildren), FdtParseError> {
+        match &mut self.state {
+            LazyState::Unvisited(source) => {
+                let fdt = source.buf.fdt();
+                let node = FdtNode::new(fdt, source.range.start);
+                let head = DeviceTreeNode::without_children(node, &mut NamePool::default())?;
+                let mut children = IndexMap::with_hasher(default_hash_state());
+                for child in node.children() {
+                    let child = LazyNode::unvisited(&source.buf, child?)?;
+                    children.insert(child.name.clone(), child);
+                }
+                self.state = LazyState::Visited { head, children };
+            }
+            LazyState::Node(node) => {
+                let mut head = mem::take(node);
+                head.source = None;
+                let mut children = IndexMap::with_hasher(default_hash_state());
+                let head_children = mem::replace(
+                    &mut head.children,
+                    IndexMap::with_hasher(default_hash_state()),
+                );
+                for (name, child) in head_children {
+                    let child = LazyNode {
+                        name: name.clone(),
+                        state: LazyState::Node(child),
+                    };
+                    children.insert(name, child);
+                }
+                self.state = LazyState::Visited { head, children };
+            }
+            LazyState::Visited { .. } => {}
+        }
+        match &mut self.state {
+            LazyState::Visited { head, children } => Ok((head, children)),
+            _ => unreachable!("the node was just visited"),
+        }
+    }
+
+    /// Finds a property by its name and returns a reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the properties of this node cannot be parsed.
+    pub fn property(&mut self, name: &str) -> Result<Option<&DeviceTreeProperty>, FdtParseError> {
+        Ok(self.visit()?.0.property(name))
+    }
+
+    /// Returns an iterator over the properties of this node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the properties of this node cannot be parsed.
+    pub fn properties(
+        &mut self,
+    ) -> Result<impl ExactSizeIterator<Item = &DeviceTreeProperty>, FdtParseError> {
+        Ok(self.visit()?.0.properties())
+    }
+
+    /// Finds a property by its name and returns a mutable reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the properties of this node cannot be parsed.
+    pub fn property_mut(
+        &mut self,
+        name: &str,
+    ) -> Result<Option<&mut DeviceTreeProperty>, FdtParseError> {
+        Ok(self.visit()?.0.property_mut(name))
+    }
+
+    /// Adds a property to this node, replacing any property with the same
+    /// name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the properties of this node cannot be parsed.
+    pub fn add_property(&mut self, property: DeviceTreeProperty) -> Result<(), FdtParseError> {
+        self.visit()?.0.add_property(property);
+        Ok(())
+    }
+
+    /// Removes a property from this node by its name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the properties of this node cannot be parsed.
+    pub fn remove_property(
+        &mut self,
+        name: &str,
+    ) -> Result<Option<DeviceTreeProperty>, FdtParseError> {
+        Ok(self.visit()?.0.remove_property(name))
+    }
+
+    /// Returns an iterator over the children of this node.
+    ///
+    /// The children themselves aren't parsed until they are accessed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the children of this node cannot be parsed.
+    pub fn children_mut(
+        &mut self,
+    ) -> Result<impl ExactSizeIterator<Item = &mut LazyNode>, FdtParseError> {
+        Ok(self.visit()?.1.values_mut())
+    }
+
+    /// Finds a child by its name and returns a mutable reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the children of this node cannot be parsed.
+    pub fn child_mut(&mut self, name: &str) -> Result<Option<&mut LazyNode>, FdtParseError> {
+        Ok(self.visit()?.1.get_mut(name))
+    }
+
+    /// Adds a child to this node, replacing any child with the same name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the children of this node cannot be parsed.
+    pub fn add_child(&mut self, child: DeviceTreeNode) -> Result<(), FdtParseError> {
+        let child = LazyNode {
+            name: child.name().into(),
+            state: LazyState::Node(child),
+        };
+        self.visit()?.1.insert(child.name.clone(), child);
+        Ok(())
+    }
+
+    /// Removes a child from this node by its name, parsing the rest of it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the children of this node or the removed child
+    /// cannot be parsed.
+    pub fn remove_child(&mut self, name: &str) -> Result<Option<DeviceTreeNode>, FdtParseError> {
+        self.visit()?
+            .1
+            .shift_remove(name)
+            .map(LazyNode::into_node)
+            .transpose()
+    }
+
+    /// Parses the rest of this node and converts it to a [`DeviceTreeNode`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a node which hasn't been accessed yet cannot be
+    /// parsed.
+    pub fn into_node(self) -> Result<DeviceTreeNode, FdtParseError> {
+        match self.state {
+            LazyState::Unvisited(source) => DeviceTreeNode::from_fdt_node(
+                FdtNode::new(source.buf.fdt(), source.range.start),
+                usize::MAX,
+                Some(&source.buf),
+            ),
+            LazyState::Node(node) => Ok(node),
+            LazyState::Visited { mut head, children } => {
+                for (_, child) in children {
+                    head.add_child(child.into_node()?);
+                }
+                Ok(head)
+            }
+        }
+    }
+
+    /// Writes this node and its descendants to `dtb`, copying the subtrees
+    /// which haven't been accessed from `source`.
+    fn write(
+        &self,
+        dtb: &mut Vec<u8>,
+        string_map: &mut StringMap,
+        source: &FdtBuf,
+    ) -> Result<(), FdtError> {
+        let mut stack = Vec::new();
+        let mut next = Some(self);
+        loop {
+            if let Some(node) = next {
+                match &node.state {
+                    LazyState::Unvisited(node_source) if node_source.buf == *source => {
+                        dtb.extend_from_slice(&source.data()[node_source.range.clone()]);
+                    }
+                    LazyState::Unvisited(_) => {
+                        // The node was swapped in from another tree, so its property
+                        // names refer to another strings block.
+                        let node = node.clone().into_node()?;
+                        Self::write_node(dtb, string_map, &node)?;
+                    }
+                    LazyState::Node(node) => Self::write_node(dtb, string_map, node)?,
+                    LazyState::Visited { head, children } => {
+                        writer::write_node_head(dtb, head, |name| string_map.insert(name))?;
+                        stack.push(children.values());
+                    }
+                }
+            }
+            let Some(children) = stack.last_mut() else {
+                return Ok(());
+            };
+            next = children.next();
+            if next.is_none() {
+                stack.pop();
+                dtb.extend_from_slice(&FDT_END_NODE.to_be_bytes());
+            }
+        }
+    }
+
+    fn write_node(
+        dtb: &mut Vec<u8>,
+        string_map: &mut StringMap,
+        node: &DeviceTreeNode,
+    ) -> Result<(), FdtError> {
+        let mut result = Ok(());
+        writer::walk(node, |node| {
+            if result.is_err() {
+                return false;
+            }
+            if let Some(node) = node {
+                result = writer::write_node_head(dtb, node, |name| string_map.insert(name));
+            } else {
+                dtb.extend_from_slice(&FDT_END_NODE.to_be_bytes());
+            }
+            result.is_ok()
+        });
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn unmodified_round_trip() {
+        let dtb = include_bytes!("../../tests/dtb/test_props.dtb");
+        let tree = LazyDeviceTree::from_fdt(&Fdt::new(dtb).unwrap()).unwrap();
+        assert_eq!(tree.to_dtb().unwrap(), dtb);
+    }
+
+    #[test]
+    fn edits() {
+        let dtb = include_bytes!("../../tests/dtb/test_children_nested.dtb");
+        let fdt = Fdt::new(dtb).unwrap();
+        let mut lazy = LazyDeviceTree::from_fdt(&fdt).unwrap();
+        let mut eager = DeviceTree::from_fdt(&fdt).unwrap();
+
+        let property = DeviceTreeProperty::new("new-prop", 1u32.to_be_bytes());
+        lazy.find_node_mut("/child1/child2")
+            .unwrap()
+            .unwrap()
+            .add_property(property.clone())
+            .unwrap();
+        eager
+            .find_node_mut("/child1/child2")
+            .unwrap()
+            .add_property(property);
+        assert!(matches!(
+            lazy.root().child_mut("child3").unwrap().unwrap().state,
+            LazyState::Unvisited(_)
+        ));
+
+        let child = lazy.root().remove_child("child3").unwrap().unwrap();
+        lazy.find_node_mut("/child1")
+            .unwrap()
+            .unwrap()
+            .add_child(child.clone())
+            .unwrap();
+        eager.root.remove_child("child3");
+        eager.find_node_mut("/child1").unwrap().add_child(child);
+
+        let new_dtb = lazy.to_dtb().unwrap();
+        let new_tree = DeviceTree::from_fdt(&Fdt::new(&new_dtb).unwrap()).unwrap();
+        assert_eq!(new_tree, eager);
+        assert_eq!(lazy.into_device_tree().unwrap(), eager);
+    }
+}
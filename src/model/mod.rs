@@ -12,18 +12,48 @@
 //! [`DeviceTreeProperty`] structs, which can be used to create or modify a
 //! device tree in memory. The [`DeviceTree`] can then be serialized to a
 //! flattened device tree blob.
+//!
+//! [`LazyDeviceTree`] offers a similar API for editing a few nodes of a large
+//! blob, only parsing the nodes which are accessed.
 
+use alloc::string::String;
+use alloc::vec;
 use alloc::vec::Vec;
-use core::fmt::Display;
+use core::fmt::{self, Debug, Display, Formatter};
+#[cfg(feature = "std")]
+use std::{io, path::Path};
 
 use crate::error::FdtParseError;
-use crate::fdt::Fdt;
+use crate::fdt::{Fdt, FdtBuf, FdtNode};
 use crate::memreserve::MemoryReservation;
+use crate::path::DtPath;
+use crate::standard::AddressSpaceProperties;
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+mod changeset;
+mod intern;
+mod lazy;
+mod naming;
 mod node;
-mod property;
+mod node_mut;
+mod overlay;
+mod overlay_builder;
+pub(crate) mod property;
+mod references;
+#[cfg(feature = "serde")]
+mod serde;
+mod standard;
+mod symbols;
 mod writer;
-pub use node::{DeviceTreeNode, DeviceTreeNodeBuilder};
+pub use changeset::OverlayChangeset;
+pub use lazy::{LazyDeviceTree, LazyNode};
+pub use node::{Children, DeviceTreeNode, DeviceTreeNodeBuilder};
+pub use node_mut::NodeMut;
+pub use overlay::OverlayError;
+pub use overlay_builder::{Overlay, OverlayTarget};
 pub use property::DeviceTreeProperty;
+pub use references::PhandleReference;
+pub use standard::{Chosen, Cpu, Cpus};
 
 /// A mutable, in-memory representation of a device tree.
 ///
@@ -38,13 +68,19 @@ pub use property::DeviceTreeProperty;
 /// tree.root.add_child(DeviceTreeNode::new("child"));
 /// let child = tree.find_node_mut("/child").unwrap();
 /// ```
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Clone)]
 #[non_exhaustive]
 pub struct DeviceTree {
     /// The root node for this device tree.
     pub root: DeviceTreeNode,
     /// The memory reservations for this device tree.
     pub memory_reservations: Vec<MemoryReservation>,
+    /// The blob this device tree was parsed from, if any. Its strings block and
+    /// the encoding of unmodified nodes are reused when serializing.
+    source: Option<FdtBuf>,
+    /// Whether this device tree is an overlay, so that unresolved phandle
+    /// references are listed in `__fixups__`.
+    overlay: bool,
 }
 
 impl DeviceTree {
@@ -61,6 +97,8 @@ impl DeviceTree {
         Self {
             root: DeviceTreeNode::new("/"),
             memory_reservations: Vec::new(),
+            source: None,
+            overlay: false,
         }
     }
 
@@ -79,16 +117,73 @@ impl DeviceTree {
     ///
     /// Returns an error if the root node of the `Fdt` cannot be parsed.
     pub fn from_fdt(fdt: &Fdt<'_>) -> Result<Self, FdtParseError> {
-        let root = DeviceTreeNode::try_from(fdt.root()?)?;
+        Self::from_fdt_with_max_depth(fdt, usize::MAX)
+    }
+
+    /// Creates a new `DeviceTree` from a `Fdt`, rejecting nodes nested more
+    /// than `max_depth` levels below the root.
+    ///
+    /// Only the conversion itself is guaranteed not to recurse. Other
+    /// operations on the resulting tree, such as cloning, comparing or
+    /// dropping it, may use stack space proportional to its depth, so callers
+    /// handling untrusted blobs should bound the nesting of the tree with this
+    /// rather than [`DeviceTree::from_fdt`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::{fdt::Fdt, model::DeviceTree};
+    /// # let dtb = include_bytes!("../../tests/dtb/test_traversal.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// assert!(DeviceTree::from_fdt_with_max_depth(&fdt, 3).is_ok());
+    /// assert!(DeviceTree::from_fdt_with_max_depth(&fdt, 2).is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `Fdt` cannot be parsed, or an
+    /// [`FdtErrorKind::TooDeep`](crate::error::FdtErrorKind::TooDeep) error if
+    /// nodes are nested too deeply.
+    pub fn from_fdt_with_max_depth(fdt: &Fdt<'_>, max_depth: usize) -> Result<Self, FdtParseError> {
+        let buf = FdtBuf::from(*fdt);
+        let mut root = DeviceTreeNode::from_fdt_node(buf.fdt().root()?, max_depth, Some(&buf))?;
+        symbols::attach_labels(&mut root);
+        let overlay = references::is_overlay(&root);
         let memory_reservations: Result<Vec<_>, _> = fdt.memory_reservations().collect();
         Ok(DeviceTree {
             root,
             memory_reservations: memory_reservations?,
+            source: Some(buf),
+            overlay,
         })
     }
 
+    /// Reads a device tree from the device tree blob in the file at the given
+    /// path.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use dtoolkit::model::DeviceTree;
+    /// let tree = DeviceTree::from_dtb_file("board.dtb").unwrap();
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`FdtBuf::from_file`], or an error of kind
+    /// [`io::ErrorKind::InvalidData`] wrapping a [`FdtParseError`] if the
+    /// nodes of the blob can't be parsed.
+    #[cfg(feature = "std")]
+    pub fn from_dtb_file(path: impl AsRef<Path>) -> io::Result<Self> {
+        let buf = FdtBuf::from_file(path)?;
+        Self::from_fdt(&buf.fdt()).map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+    }
+
     /// Finds a node by its path and returns a mutable reference to it.
     ///
+    /// Like [`Fdt::find_node`], a path which doesn't start with `/` starts
+    /// with an alias from the `/aliases` node.
+    ///
     /// # Performance
     ///
     /// This method traverses the device tree, but since child lookup is a
@@ -98,21 +193,30 @@ impl DeviceTree {
     /// # Examples
     ///
     /// ```
-    /// # use dtoolkit::model::{DeviceTree, DeviceTreeNode};
+    /// # use dtoolkit::model::{DeviceTree, DeviceTreeNode, DeviceTreeProperty};
     /// let mut tree = DeviceTree::new();
     /// tree.root.add_child(DeviceTreeNode::new("child"));
     /// let child = tree.find_node_mut("/child").unwrap();
     /// assert_eq!(child.name(), "child");
+    ///
+    /// tree.root.add_child(
+    ///     DeviceTreeNode::builder("aliases")
+    ///         .property(DeviceTreeProperty::new("mine", "/child\0"))
+    ///         .build(),
+    /// );
+    /// assert_eq!(tree.find_node_mut("mine").unwrap().name(), "child");
     /// ```
     pub fn find_node_mut(&mut self, path: &str) -> Option<&mut DeviceTreeNode> {
-        if !path.starts_with('/') {
-            return None;
-        }
-        let mut current_node = &mut self.root;
-        if path == "/" {
-            return Some(current_node);
-        }
-        for component in path.split('/').filter(|s| !s.is_empty()) {
+        let mut path = DtPath::new(path);
+        let mut current_node = if path.is_absolute() {
+            &mut self.root
+        } else {
+            let (alias, rest) = path.split_alias()?;
+            let alias_path = String::from(self.alias_path(alias)?);
+            path = rest;
+            self.find_node_mut(&alias_path)?
+        };
+        for component in path.segments() {
             match current_node.child_mut(component) {
                 Some(node) => current_node = node,
                 None => return None,
@@ -120,18 +224,139 @@ impl DeviceTree {
         }
         Some(current_node)
     }
+
+    /// Returns the absolute path given for an alias by the `/aliases` node.
+    fn alias_path(&self, alias: &str) -> Option<&str> {
+        let path = self.root.child("aliases")?.property(alias)?.as_str().ok()?;
+        path.starts_with('/').then_some(path)
+    }
+
+    /// Returns whether this device tree has the same contents as the given
+    /// flattened device tree.
+    ///
+    /// Like [`Fdt::semantically_equals`] and `==` between two `DeviceTree`s,
+    /// this compares the memory reservations and the names and properties of
+    /// nodes, ignoring the order of properties and children and how the blob
+    /// is encoded. The trees are walked without recursing, so deeply nested
+    /// nodes can't overflow the stack.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `fdt` can't be parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # use dtoolkit::model::{DeviceTree, DeviceTreeProperty};
+    /// # let dtb = include_bytes!("../../tests/dtb/test_props.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let mut tree = DeviceTree::from_fdt(&fdt).unwrap();
+    /// assert!(tree.semantically_equals(&fdt).unwrap());
+    ///
+    /// tree.root
+    ///     .add_property(DeviceTreeProperty::new("new-prop", []));
+    /// assert!(!tree.semantically_equals(&fdt).unwrap());
+    /// ```
+    pub fn semantically_equals(&self, fdt: &Fdt<'_>) -> Result<bool, FdtParseError> {
+        let reservations = fdt.memory_reservations();
+        if reservations.len() != self.memory_reservations.len() {
+            return Ok(false);
+        }
+        for (reservation, other) in self.memory_reservations.iter().zip(reservations) {
+            if *reservation != other? {
+                return Ok(false);
+            }
+        }
+        node_equals_fdt_node(&self.root, fdt.root()?)
+    }
+
+    /// Returns the `/chosen` node, creating it if it doesn't exist yet.
+    pub(crate) fn chosen_node_mut(&mut self) -> &mut DeviceTreeNode {
+        if self.root.child("chosen").is_none() {
+            self.root.add_child(DeviceTreeNode::new("chosen"));
+        }
+        self.root
+            .child_mut("chosen")
+            .expect("the /chosen node was just added")
+    }
+
+    /// Returns the `#address-cells` and `#size-cells` properties of the root
+    /// node, falling back to the defaults if they are missing or malformed.
+    pub(crate) fn root_address_space(&self) -> AddressSpaceProperties {
+        self.root.address_space()
+    }
 }
 
+/// Returns whether a node has the same properties and descendants as a node
+/// of a flattened device tree, in any order. The names of the nodes
+/// themselves are compared by the caller, as the root node is named
+/// differently in the two representations.
+fn node_equals_fdt_node(
+    node: &DeviceTreeNode,
+    fdt_node: FdtNode<'_>,
+) -> Result<bool, FdtParseError> {
+    let mut stack = vec![(node, fdt_node)];
+    while let Some((node, fdt_node)) = stack.pop() {
+        let mut property_count = 0;
+        for property in fdt_node.properties() {
+            let property = property?;
+            match node.property(property.name()) {
+                Some(other) if other.value() == property.value() => property_count += 1,
+                _ => return Ok(false),
+            }
+        }
+        if property_count != node.properties().len() {
+            return Ok(false);
+        }
+
+        let mut child_count = 0;
+        for fdt_child in fdt_node.children() {
+            let fdt_child = fdt_child?;
+            let Some(child) = node.child(fdt_child.name()?) else {
+                return Ok(false);
+            };
+            child_count += 1;
+            stack.push((child, fdt_child));
+        }
+        if child_count != node.children().len() {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
+
+impl Debug for DeviceTree {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeviceTree")
+            .field("root", &self.root)
+            .field("memory_reservations", &self.memory_reservations)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Device trees are compared by their contents, regardless of the blob they
+/// were parsed from.
+impl PartialEq for DeviceTree {
+    fn eq(&self, other: &Self) -> bool {
+        self.root == other.root && self.memory_reservations == other.memory_reservations
+    }
+}
+
+impl Eq for DeviceTree {}
+
 impl Default for DeviceTree {
     fn default() -> Self {
         Self::new()
     }
 }
 
+/// Formats the device tree in DTS format, with the same depth-limiting flags
+/// as [`Fdt`]'s `Display` implementation.
 impl Display for DeviceTree {
-    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
-        Fdt::new(&self.to_dtb())
-            .expect("DeviceTree::to_dtb() should always generate a valid FDT")
-            .fmt(f)
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let dtb = self.to_dtb();
+        let fdt = Fdt::new(&dtb).expect("DeviceTree::to_dtb() should always generate a valid FDT");
+        Display::fmt(&fdt, f)
     }
 }
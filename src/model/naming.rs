@@ -0,0 +1,173 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Keeping the unit addresses of generated nodes in sync with their `reg`.
+//!
+//! The Devicetree Specification requires the unit address of a node with a
+//! `reg` property to be the address of its first region, as in
+//! `memory@80000000`, `cpu@1` or `framebuffer@a0000000` under
+//! `/reserved-memory`, and dtc warns about nodes which don't follow this.
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use super::{DeviceTreeNode, DeviceTreeProperty};
+use crate::diag;
+use crate::error::FdtError;
+use crate::model::property::push_cells;
+use crate::standard::AddressSpaceProperties;
+
+impl DeviceTreeNode {
+    /// Returns the `#address-cells` and `#size-cells` properties of this node,
+    /// which describe the `reg` of its children, falling back to the defaults
+    /// if they are missing or malformed.
+    pub(crate) fn address_space(&self) -> AddressSpaceProperties {
+        let default = AddressSpaceProperties::default();
+        let cells = |name, default| match self.property(name).map(DeviceTreeProperty::as_u32) {
+            Some(Ok(cells)) => cells,
+            Some(Err(_)) => {
+                diag::warning!("ignoring malformed {name} property of node {}", self.name);
+                default
+            }
+            None => default,
+        };
+        AddressSpaceProperties {
+            address_cells: cells("#address-cells", default.address_cells),
+            size_cells: cells("#size-cells", default.size_cells),
+        }
+    }
+
+    /// Adds a child with a `reg` property describing the given `(address,
+    /// size)` regions, named `name@<address>` after the first region.
+    ///
+    /// The regions are encoded using this node's `#address-cells` and
+    /// `#size-cells`. If `regions` is empty, the child is named `name` and
+    /// has no `reg` property. An existing child with the same name is
+    /// replaced.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FdtError::ValueTooBig`] if an address or size doesn't fit in
+    /// the number of cells of this node, or [`FdtError::TooManyCells`] if
+    /// `#address-cells` or `#size-cells` is more than 4.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+    /// let mut cpus = DeviceTreeNode::builder("cpus")
+    ///     .property(DeviceTreeProperty::new(
+    ///         "#address-cells",
+    ///         1u32.to_be_bytes(),
+    ///     ))
+    ///     .property(DeviceTreeProperty::new("#size-cells", 0u32.to_be_bytes()))
+    ///     .build();
+    /// let cpu = cpus.add_child_with_reg("cpu", &[(0x101, 0)]).unwrap();
+    /// assert_eq!(cpu.name(), "cpu@101");
+    /// assert_eq!(cpu.property("reg").unwrap().as_u32(), Ok(0x101));
+    /// ```
+    pub fn add_child_with_reg(
+        &mut self,
+        name: &str,
+        regions: &[(u64, u64)],
+    ) -> Result<&mut DeviceTreeNode, FdtError> {
+        let reg = self.encode_reg(regions)?;
+        let mut child = DeviceTreeNode::new(name_with_unit_address(name, regions));
+        if let Some(reg) = reg {
+            child.add_property(reg);
+        }
+        let name = child.name.clone();
+        self.add_child(child);
+        let Some(child) = self.children.get_mut(&name) else {
+            unreachable!("the child was just added");
+        };
+        Ok(child)
+    }
+
+    /// Sets the `reg` property of the child with the given name, and renames
+    /// the child so that its unit address matches the first region.
+    ///
+    /// The child keeps its position among its siblings. The regions are
+    /// encoded as for [`DeviceTreeNode::add_child_with_reg`]. Returns the
+    /// renamed child, or `None` if there is no child with the given name.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FdtError::ValueTooBig`] if an address or size doesn't fit in
+    /// the number of cells of this node, [`FdtError::TooManyCells`] if
+    /// `#address-cells` or `#size-cells` is more than 4, or
+    /// [`FdtError::DuplicateChild`] if another child already has the new
+    /// name. The child is left unchanged in any case.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::model::DeviceTreeNode;
+    /// let mut root = DeviceTreeNode::new("/");
+    /// root.add_child_with_reg("memory", &[(0x4000_0000, 0x1000_0000)])
+    ///     .unwrap();
+    /// let memory = root
+    ///     .set_child_reg("memory@40000000", &[(0x8000_0000, 0x2000_0000)])
+    ///     .unwrap()
+    ///     .unwrap();
+    /// assert_eq!(memory.name(), "memory@80000000");
+    /// assert!(root.child("memory@40000000").is_none());
+    /// ```
+    pub fn set_child_reg(
+        &mut self,
+        name: &str,
+        regions: &[(u64, u64)],
+    ) -> Result<Option<&mut DeviceTreeNode>, FdtError> {
+        let reg = self.encode_reg(regions)?;
+        let Some(index) = self.children.get_index_of(name) else {
+            return Ok(None);
+        };
+        let base_name = name.split_once('@').map_or(name, |(name, _)| name);
+        let new_name = name_with_unit_address(base_name, regions);
+        if new_name != name && self.children.contains_key(new_name.as_str()) {
+            return Err(FdtError::DuplicateChild);
+        }
+        self.source = None;
+        let Some((_, mut child)) = self.children.shift_remove_index(index) else {
+            unreachable!("the child was just found");
+        };
+        match reg {
+            Some(reg) => child.add_property(reg),
+            None => {
+                child.remove_property("reg");
+            }
+        }
+        child.name = new_name.into();
+        self.children.shift_insert(index, child.name.clone(), child);
+        Ok(self.children.get_index_mut(index).map(|(_, child)| child))
+    }
+
+    /// Encodes the given regions as the value of a `reg` property of a child
+    /// of this node, or returns `None` if there are no regions.
+    fn encode_reg(&self, regions: &[(u64, u64)]) -> Result<Option<DeviceTreeProperty>, FdtError> {
+        if regions.is_empty() {
+            return Ok(None);
+        }
+        let address_space = self.address_space();
+        let mut reg = Vec::new();
+        for &(address, size) in regions {
+            push_cells(&mut reg, address, address_space.address_cells)?;
+            push_cells(&mut reg, size, address_space.size_cells)?;
+        }
+        Ok(Some(DeviceTreeProperty::new("reg", reg)))
+    }
+}
+
+/// Returns `name` with the unit address of the first region, if any.
+fn name_with_unit_address(name: &str, regions: &[(u64, u64)]) -> String {
+    match regions.first() {
+        Some((address, _)) => format!("{name}@{address:x}"),
+        None => String::from(name),
+    }
+}
@@ -6,38 +6,97 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use alloc::borrow::ToOwned;
-use alloc::string::{String, ToString};
+use alloc::string::String;
+use alloc::sync::Arc;
 use alloc::vec::Vec;
+use core::fmt::{self, Debug, Formatter};
+use core::iter::FusedIterator;
+use core::mem;
+use core::ops::Range;
 
 use indexmap::IndexMap;
 use twox_hash::xxhash64;
 
+use super::intern::NamePool;
 use super::property::DeviceTreeProperty;
-use crate::error::FdtParseError;
-use crate::fdt::FdtNode;
+use super::references::PhandleReference;
+use crate::error::{FdtErrorKind, FdtParseError};
+use crate::fdt::{FdtBuf, FdtNode};
+use crate::standard::Phandle;
 
 /// A mutable, in-memory representation of a device tree node.
 ///
 /// Children and properties are stored in [`IndexMap`]s, which provide O(1)
-/// lookups by name while preserving insertion order.
-#[derive(Debug, Clone, PartialEq, Eq)]
+/// lookups by name while preserving insertion order. Nodes converted from the
+/// same flattened device tree share the allocations of equal names.
+#[derive(Clone)]
 pub struct DeviceTreeNode {
-    name: String,
-    pub(super) properties: IndexMap<String, DeviceTreeProperty, xxhash64::State>,
-    pub(super) children: IndexMap<String, DeviceTreeNode, xxhash64::State>,
+    pub(super) name: Arc<str>,
+    pub(super) properties: IndexMap<Arc<str>, DeviceTreeProperty, xxhash64::State>,
+    pub(super) children: IndexMap<Arc<str>, DeviceTreeNode, xxhash64::State>,
+    /// The labels of this node, which are written to the `__symbols__` node
+    /// with its current path when serializing.
+    pub(super) labels: Vec<Arc<str>>,
+    /// The labels which have been removed from this node, whose entries are
+    /// dropped from the `__symbols__` node when serializing.
+    pub(super) removed_labels: Vec<Arc<str>>,
+    /// The references from cells of the properties of this node to labelled
+    /// nodes, which haven't been resolved yet.
+    pub(super) references: Vec<PhandleReference>,
+    /// Where this node was parsed from, as long as neither it nor any of its
+    /// descendants have been modified since.
+    pub(super) source: Option<NodeSource>,
+}
+
+/// The encoding of an unmodified node in the blob it was parsed from.
+#[derive(Clone, Debug)]
+pub(super) struct NodeSource {
+    /// The blob the node was parsed from.
+    pub(super) buf: FdtBuf,
+    /// The range of the node's `FDT_BEGIN_NODE` to `FDT_END_NODE` tokens in
+    /// the blob.
+    pub(super) range: Range<usize>,
 }
 
 impl Default for DeviceTreeNode {
     fn default() -> Self {
         Self {
-            name: String::new(),
+            name: Arc::default(),
             properties: IndexMap::with_hasher(default_hash_state()),
             children: IndexMap::with_hasher(default_hash_state()),
+            labels: Vec::new(),
+            removed_labels: Vec::new(),
+            references: Vec::new(),
+            source: None,
         }
     }
 }
 
+impl Debug for DeviceTreeNode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DeviceTreeNode")
+            .field("name", &self.name)
+            .field("properties", &self.properties)
+            .field("children", &self.children)
+            .field("labels", &self.labels)
+            .field("references", &self.references)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Nodes are compared by their contents, regardless of where they were
+/// parsed from. Labels aren't compared, as they only affect the `__symbols__`
+/// node, which is compared like any other.
+impl PartialEq for DeviceTreeNode {
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name
+            && self.properties == other.properties
+            && self.children == other.children
+    }
+}
+
+impl Eq for DeviceTreeNode {}
+
 impl DeviceTreeNode {
     /// Creates a new [`DeviceTreeNode`] with the given name.
     ///
@@ -51,7 +110,7 @@ impl DeviceTreeNode {
     #[must_use]
     pub fn new(name: impl Into<String>) -> Self {
         Self {
-            name: name.into(),
+            name: name.into().into(),
             ..Default::default()
         }
     }
@@ -76,13 +135,39 @@ impl DeviceTreeNode {
         &self.name
     }
 
+    /// Returns the unit address of this node, which is the part of its name
+    /// after the `@` sign, if any.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::model::DeviceTreeNode;
+    /// let node = DeviceTreeNode::new("memory@80000000");
+    /// assert_eq!(node.unit_address(), Some("80000000"));
+    /// assert_eq!(DeviceTreeNode::new("chosen").unit_address(), None);
+    /// ```
+    #[must_use]
+    pub fn unit_address(&self) -> Option<&str> {
+        self.name
+            .split_once('@')
+            .map(|(_, unit_address)| unit_address)
+    }
+
     /// Returns an iterator over the properties of this node.
-    pub fn properties(&self) -> impl Iterator<Item = &DeviceTreeProperty> {
+    #[must_use]
+    pub fn properties(
+        &self,
+    ) -> impl DoubleEndedIterator<Item = &DeviceTreeProperty> + ExactSizeIterator + FusedIterator
+    {
         self.properties.values()
     }
 
     /// Returns a mutable iterator over the properties of this node.
-    pub fn properties_mut(&mut self) -> impl Iterator<Item = &mut DeviceTreeProperty> {
+    pub fn properties_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = &mut DeviceTreeProperty> + ExactSizeIterator + FusedIterator
+    {
+        self.source = None;
         self.properties.values_mut()
     }
 
@@ -124,6 +209,7 @@ impl DeviceTreeNode {
     /// ```
     #[must_use]
     pub fn property_mut(&mut self, name: &str) -> Option<&mut DeviceTreeProperty> {
+        self.source = None;
         self.properties.get_mut(name)
     }
 
@@ -142,7 +228,9 @@ impl DeviceTreeNode {
     /// assert_eq!(node.property("my-prop").unwrap().value(), &[1, 2, 3, 4]);
     /// ```
     pub fn add_property(&mut self, property: DeviceTreeProperty) {
-        self.properties.insert(property.name().to_owned(), property);
+        self.source = None;
+        self.properties
+            .insert(property.shared_name().clone(), property);
     }
 
     /// Removes a property from this node by its name.
@@ -163,16 +251,55 @@ impl DeviceTreeNode {
     /// assert!(node.property("my-prop").is_none());
     /// ```
     pub fn remove_property(&mut self, name: &str) -> Option<DeviceTreeProperty> {
+        self.source = None;
         self.properties.shift_remove(name)
     }
 
+    /// Sets the `phandle` property of this node, replacing any existing one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::model::DeviceTreeNode;
+    /// # use dtoolkit::standard::Phandle;
+    /// let mut node = DeviceTreeNode::new("my-node");
+    /// node.set_phandle(Phandle::new(0x42).unwrap());
+    /// assert_eq!(node.property("phandle").unwrap().as_u32(), Ok(0x42));
+    /// ```
+    pub fn set_phandle(&mut self, phandle: Phandle) {
+        self.add_property(DeviceTreeProperty::new(
+            "phandle",
+            phandle.get().to_be_bytes(),
+        ));
+    }
+
     /// Returns an iterator over the children of this node.
-    pub fn children(&self) -> impl Iterator<Item = &DeviceTreeNode> {
-        self.children.values()
+    ///
+    /// This is the same as iterating over `&node`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::model::DeviceTreeNode;
+    /// let mut node = DeviceTreeNode::new("my-node");
+    /// node.add_child(DeviceTreeNode::new("first"));
+    /// node.add_child(DeviceTreeNode::new("second"));
+    /// assert_eq!(node.children().next_back().unwrap().name(), "second");
+    /// for child in &node {
+    ///     assert!(child.children().next().is_none());
+    /// }
+    /// ```
+    #[must_use]
+    pub fn children(&self) -> Children<'_> {
+        Children(self.children.values())
     }
 
     /// Returns a mutable iterator over the children of this node.
-    pub fn children_mut(&mut self) -> impl Iterator<Item = &mut DeviceTreeNode> {
+    pub fn children_mut(
+        &mut self,
+    ) -> impl DoubleEndedIterator<Item = &mut DeviceTreeNode> + ExactSizeIterator + FusedIterator
+    {
+        self.source = None;
         self.children.values_mut()
     }
 
@@ -214,6 +341,7 @@ impl DeviceTreeNode {
     /// ```
     #[must_use]
     pub fn child_mut(&mut self, name: &str) -> Option<&mut DeviceTreeNode> {
+        self.source = None;
         self.children.get_mut(name)
     }
 
@@ -232,7 +360,8 @@ impl DeviceTreeNode {
     /// assert_eq!(node.child("child").unwrap().name(), "child");
     /// ```
     pub fn add_child(&mut self, child: DeviceTreeNode) {
-        self.children.insert(child.name().to_owned(), child);
+        self.source = None;
+        self.children.insert(child.name.clone(), child);
     }
 
     /// Removes a child from this node by its name.
@@ -253,6 +382,7 @@ impl DeviceTreeNode {
     /// assert!(node.child("child").is_none());
     /// ```
     pub fn remove_child(&mut self, name: &str) -> Option<DeviceTreeNode> {
+        self.source = None;
         self.children.shift_remove(name)
     }
 }
@@ -261,35 +391,141 @@ impl<'a> TryFrom<FdtNode<'a>> for DeviceTreeNode {
     type Error = FdtParseError;
 
     fn try_from(node: FdtNode<'a>) -> Result<Self, Self::Error> {
-        let name = node.name()?.to_string();
+        Self::from_fdt_node(node, usize::MAX, None)
+    }
+}
+
+impl DeviceTreeNode {
+    /// Converts the given node and its descendants, failing if they are nested
+    /// more than `max_depth` levels below it.
+    ///
+    /// If `source` is given, it must hold the blob `node` belongs to, and the
+    /// converted nodes remember their encoding in it so that they can be
+    /// copied as is when serialized.
+    ///
+    /// This uses an explicit stack rather than recursion, so deeply nested
+    /// nodes can't overflow the call stack.
+    pub(super) fn from_fdt_node(
+        node: FdtNode<'_>,
+        max_depth: usize,
+        source: Option<&FdtBuf>,
+    ) -> Result<Self, FdtParseError> {
+        let mut names = NamePool::default();
+        // The ancestors of the node being converted, with their offsets and
+        // remaining children.
+        let mut stack = Vec::new();
+        let mut current = (
+            Self::without_children(node, &mut names)?,
+            node.offset,
+            node.children(),
+        );
+        loop {
+            if let Some(child) = current.2.next() {
+                let child = child?;
+                if stack.len() >= max_depth {
+                    return Err(FdtParseError::new(
+                        FdtErrorKind::TooDeep(max_depth),
+                        child.offset,
+                    ));
+                }
+                let parent = mem::replace(
+                    &mut current,
+                    (
+                        Self::without_children(child, &mut names)?,
+                        child.offset,
+                        child.children(),
+                    ),
+                );
+                stack.push(parent);
+                continue;
+            }
+
+            if let Some(buf) = source {
+                let start = current.1;
+                current.0.source = Some(NodeSource {
+                    buf: buf.clone(),
+                    range: start..node.fdt.next_sibling_offset(start)?,
+                });
+            }
+            if let Some(parent) = stack.pop() {
+                let (child, _, _) = mem::replace(&mut current, parent);
+                current.0.children.insert(child.name.clone(), child);
+            } else {
+                return Ok(current.0);
+            }
+        }
+    }
+
+    /// Converts the name and properties of the given node, interning the
+    /// names in `names`.
+    pub(super) fn without_children<'a>(
+        node: FdtNode<'a>,
+        names: &mut NamePool<'a>,
+    ) -> Result<Self, FdtParseError> {
+        let name = names.intern(node.name()?);
         let properties = node
             .properties()
-            .map(|property| property?.try_into())
-            .collect::<Result<Vec<DeviceTreeProperty>, _>>()?;
+            .map(|property| Ok(DeviceTreeProperty::from_fdt_property(property?, names)))
+            .collect::<Result<Vec<_>, FdtParseError>>()?;
         let mut property_map =
             IndexMap::with_capacity_and_hasher(properties.len(), default_hash_state());
         for property in properties {
-            property_map.insert(property.name().to_owned(), property);
-        }
-
-        let children_vec: Vec<DeviceTreeNode> = node
-            .children()
-            .map(|child| child?.try_into())
-            .collect::<Result<Vec<_>, _>>()?;
-        let mut children =
-            IndexMap::with_capacity_and_hasher(children_vec.len(), default_hash_state());
-        for child in children_vec {
-            children.insert(child.name().to_owned(), child);
+            property_map.insert(property.shared_name().clone(), property);
         }
 
         Ok(DeviceTreeNode {
             name,
             properties: property_map,
-            children,
+            children: IndexMap::with_hasher(default_hash_state()),
+            labels: Vec::new(),
+            removed_labels: Vec::new(),
+            references: Vec::new(),
+            source: None,
         })
     }
 }
 
+#[expect(
+    clippy::into_iter_without_iter,
+    reason = "The children are iterated with `children`, as a node also has properties"
+)]
+impl<'a> IntoIterator for &'a DeviceTreeNode {
+    type Item = &'a DeviceTreeNode;
+    type IntoIter = Children<'a>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.children()
+    }
+}
+
+/// An iterator over the children of a [`DeviceTreeNode`].
+///
+/// This is returned by [`DeviceTreeNode::children`].
+#[derive(Clone, Debug)]
+pub struct Children<'a>(indexmap::map::Values<'a, Arc<str>, DeviceTreeNode>);
+
+impl<'a> Iterator for Children<'a> {
+    type Item = &'a DeviceTreeNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.0.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.0.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for Children<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.0.next_back()
+    }
+}
+
+impl ExactSizeIterator for Children<'_> {}
+
+impl FusedIterator for Children<'_> {}
+
 /// A builder for creating [`DeviceTreeNode`]s.
 #[derive(Debug, Default)]
 pub struct DeviceTreeNodeBuilder {
@@ -324,6 +560,6 @@ impl DeviceTreeNodeBuilder {
     }
 }
 
-fn default_hash_state() -> xxhash64::State {
+pub(super) fn default_hash_state() -> xxhash64::State {
     xxhash64::State::with_seed(0xC001_C0DE)
 }
@@ -0,0 +1,133 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Setters for standard properties shared by the mutable node types.
+
+use alloc::vec::Vec;
+use core::convert::Infallible;
+
+use super::{DeviceTreeNode, DeviceTreeProperty, LazyNode};
+use crate::error::FdtParseError;
+use crate::standard::{Phandle, Status};
+
+/// A node whose properties can be changed.
+///
+/// This is implemented by both [`DeviceTreeNode`] and [`LazyNode`], so that
+/// fixups can be written once and applied to either kind of tree. Only
+/// [`NodeMut::set_property`] needs to be implemented; the setters for standard
+/// properties encode their value and call it.
+///
+/// # Examples
+///
+/// ```
+/// # use dtoolkit::model::{DeviceTreeNode, NodeMut};
+/// # use dtoolkit::standard::Status;
+/// fn disable<N: NodeMut>(node: &mut N) -> Result<(), N::Error> {
+///     node.set_status(Status::Disabled)
+/// }
+///
+/// let mut node = DeviceTreeNode::new("serial@1000");
+/// disable(&mut node).unwrap();
+/// assert_eq!(node.property("status").unwrap().as_str(), Ok("disabled"));
+/// ```
+pub trait NodeMut {
+    /// The error returned if the node can't be modified.
+    type Error;
+
+    /// Adds a property to this node, replacing any property with the same
+    /// name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the existing properties of the node can't be read.
+    fn set_property(&mut self, property: DeviceTreeProperty) -> Result<(), Self::Error>;
+
+    /// Sets the `status` property of this node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the existing properties of the node can't be read.
+    fn set_status(&mut self, status: Status) -> Result<(), Self::Error> {
+        self.set_property(DeviceTreeProperty::new(
+            "status",
+            string_value(status.as_str()),
+        ))
+    }
+
+    /// Sets the `compatible` property of this node to the given list of
+    /// strings, from most to least specific.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the existing properties of the node can't be read.
+    fn set_compatible(&mut self, compatible: &[&str]) -> Result<(), Self::Error> {
+        let mut value = Vec::new();
+        for compatible in compatible {
+            value.extend_from_slice(compatible.as_bytes());
+            value.push(0);
+        }
+        self.set_property(DeviceTreeProperty::new("compatible", value))
+    }
+
+    /// Sets the `phandle` property of this node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the existing properties of the node can't be read.
+    fn set_phandle(&mut self, phandle: Phandle) -> Result<(), Self::Error> {
+        self.set_property(DeviceTreeProperty::new(
+            "phandle",
+            phandle.get().to_be_bytes(),
+        ))
+    }
+
+    /// Sets the `model` property of this node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the existing properties of the node can't be read.
+    fn set_model(&mut self, model: &str) -> Result<(), Self::Error> {
+        self.set_property(DeviceTreeProperty::new("model", string_value(model)))
+    }
+
+    /// Sets the MAC address of this network device, in both its `mac-address`
+    /// and `local-mac-address` properties as bootloaders do.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the existing properties of the node can't be read.
+    fn set_mac_address(&mut self, mac: [u8; 6]) -> Result<(), Self::Error> {
+        self.set_property(DeviceTreeProperty::new("mac-address", mac))?;
+        self.set_property(DeviceTreeProperty::new("local-mac-address", mac))
+    }
+}
+
+/// Returns the value of a property holding a single string.
+fn string_value(string: &str) -> Vec<u8> {
+    let mut value = Vec::with_capacity(string.len() + 1);
+    value.extend_from_slice(string.as_bytes());
+    value.push(0);
+    value
+}
+
+impl NodeMut for DeviceTreeNode {
+    type Error = Infallible;
+
+    fn set_property(&mut self, property: DeviceTreeProperty) -> Result<(), Self::Error> {
+        self.add_property(property);
+        Ok(())
+    }
+}
+
+impl NodeMut for LazyNode {
+    type Error = FdtParseError;
+
+    fn set_property(&mut self, property: DeviceTreeProperty) -> Result<(), Self::Error> {
+        self.add_property(property)
+    }
+}
@@ -0,0 +1,588 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Applying device tree overlays.
+//!
+//! An overlay is a device tree blob, usually compiled by `dtc -@` from a
+//! `/plugin/` source, with a `fragment@N` node for each part of the base tree
+//! it changes. Each fragment names the node it applies to with a `target`
+//! phandle or a `target-path`, and has an `__overlay__` child holding the
+//! properties and nodes to merge into it. References to labels of the base
+//! tree are listed in `__fixups__`, and references between nodes of the
+//! overlay itself in `__local_fixups__`.
+
+use alloc::collections::btree_map::BTreeMap;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+
+use thiserror::Error;
+
+use super::changeset::Change;
+use super::{DeviceTree, DeviceTreeNode, DeviceTreeProperty, OverlayChangeset};
+use crate::diag;
+use crate::error::FdtParseError;
+use crate::fdt::Fdt;
+use crate::path::{DtPath, DtPathBuf};
+use crate::standard::{Phandle, PhandleNode, max_phandle};
+
+/// An error that can occur when applying an overlay.
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[non_exhaustive]
+pub enum OverlayError {
+    /// The overlay couldn't be parsed.
+    #[error("{0}")]
+    Parse(#[from] FdtParseError),
+    /// A fragment has neither a `target` nor a `target-path` property.
+    #[error("fragment {fragment} has no target or target-path property")]
+    MissingTarget {
+        /// The name of the fragment.
+        fragment: String,
+    },
+    /// The node targeted by a fragment isn't in the base tree.
+    #[error("target of fragment {fragment} not found")]
+    TargetNotFound {
+        /// The name of the fragment.
+        fragment: String,
+    },
+    /// A label referenced by the overlay isn't in the `__symbols__` node of
+    /// the base tree.
+    #[error("symbol {label} not found")]
+    UnresolvedSymbol {
+        /// The label.
+        label: String,
+    },
+    /// An entry of `__fixups__` or `__local_fixups__` doesn't refer to a cell
+    /// of a property of the overlay.
+    #[error("invalid fixup {fixup}")]
+    InvalidFixup {
+        /// The fixup, as `path:property:offset`.
+        fixup: String,
+    },
+    /// A property used to apply the overlay has an invalid value.
+    #[error("invalid {property} property of node {node}")]
+    InvalidProperty {
+        /// The name or path of the node.
+        node: String,
+        /// The name of the property.
+        property: String,
+    },
+    /// Renumbering the phandles of the overlay to follow those of the base
+    /// tree ran out of phandle values.
+    #[error("phandles of the overlay overflow")]
+    PhandleOverflow,
+    /// The cells of a property referring to other nodes don't match the
+    /// number of cells those nodes expect, as given by their `#…-cells`
+    /// property.
+    #[error("cells of {property} property of node {node} don't match its phandles")]
+    CellCountMismatch {
+        /// The path of the node.
+        node: String,
+        /// The name of the property.
+        property: String,
+    },
+    /// A node changed by applying an overlay was removed or moved before the
+    /// changes were reverted.
+    #[error("node {path} changed by the overlay no longer exists")]
+    StaleChangeset {
+        /// The path of the node.
+        path: String,
+    },
+}
+
+impl OverlayError {
+    /// Returns the stable numeric code of this error.
+    ///
+    /// Parse errors have the code of their
+    /// [`FdtErrorKind`](crate::error::FdtErrorKind). See the
+    /// [`error`](crate::error) module for the ranges of codes.
+    #[must_use]
+    pub const fn code(&self) -> u16 {
+        match self {
+            Self::Parse(error) => error.code(),
+            Self::MissingTarget { .. } => 300,
+            Self::TargetNotFound { .. } => 301,
+            Self::UnresolvedSymbol { .. } => 302,
+            Self::InvalidFixup { .. } => 303,
+            Self::InvalidProperty { .. } => 304,
+            Self::PhandleOverflow => 305,
+            Self::CellCountMismatch { .. } => 306,
+            Self::StaleChangeset { .. } => 307,
+        }
+    }
+}
+
+impl DeviceTree {
+    /// Applies a device tree overlay to this tree.
+    ///
+    /// The phandles of the overlay are renumbered to follow the largest
+    /// phandle of this tree, and the references to them listed in
+    /// `__local_fixups__` are updated to match. References to labels of this
+    /// tree listed in `__fixups__` are resolved using its `__symbols__` node.
+    /// Then the `__overlay__` node of each fragment is merged into the node
+    /// given by its `target` phandle or its absolute `target-path`: properties
+    /// replace those with the same name, and children are merged into those
+    /// with the same name or added. Finally, the labels of the overlay are
+    /// added to the `__symbols__` node of this tree, with their paths rewritten
+    /// to where their nodes ended up, and attached to those nodes as
+    /// [labels](DeviceTreeNode::labels).
+    ///
+    /// All targets are resolved before anything is merged, so this tree is
+    /// left unchanged if an error is returned.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the overlay can't be parsed, if the target of a
+    /// fragment can't be found, or if a fixup can't be resolved or applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # use dtoolkit::model::{DeviceTree, DeviceTreeNode, DeviceTreeProperty};
+    /// let mut tree = DeviceTree::new();
+    /// tree.root.add_child(DeviceTreeNode::new("soc"));
+    ///
+    /// let mut overlay = DeviceTree::new();
+    /// overlay.root.add_child(
+    ///     DeviceTreeNode::builder("fragment@0")
+    ///         .property(DeviceTreeProperty::new("target-path", "/soc\0"))
+    ///         .child(
+    ///             DeviceTreeNode::builder("__overlay__")
+    ///                 .property(DeviceTreeProperty::new("status", "okay\0"))
+    ///                 .build(),
+    ///         )
+    ///         .build(),
+    /// );
+    /// let dtbo = overlay.to_dtb();
+    ///
+    /// tree.apply_overlay(&Fdt::new(&dtbo).unwrap()).unwrap();
+    /// let soc = tree.find_node_mut("/soc").unwrap();
+    /// assert_eq!(soc.property("status").unwrap().as_str(), Ok("okay"));
+    /// ```
+    pub fn apply_overlay(&mut self, overlay: &Fdt<'_>) -> Result<(), OverlayError> {
+        self.apply_overlay_with_changeset(overlay).map(drop)
+    }
+
+    /// Applies a device tree overlay to this tree like
+    /// [`DeviceTree::apply_overlay`], returning the changes it made so that
+    /// they can be undone with [`DeviceTree::revert`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`DeviceTree::apply_overlay`], in which case
+    /// this tree is left unchanged.
+    pub fn apply_overlay_with_changeset(
+        &mut self,
+        overlay: &Fdt<'_>,
+    ) -> Result<OverlayChangeset, OverlayError> {
+        let mut overlay = DeviceTree::from_fdt(overlay)?.root;
+
+        let Ok(max) = max_phandle(&self.root);
+        let delta = max.map_or(0, Phandle::get);
+        if let Some(local_fixups) = overlay.remove_child("__local_fixups__") {
+            apply_local_fixups(&mut overlay, &local_fixups, delta)?;
+        }
+        renumber_phandles(&mut overlay, delta)?;
+        if let Some(fixups) = overlay.remove_child("__fixups__") {
+            self.apply_fixups(&mut overlay, &fixups)?;
+        }
+
+        let symbols = overlay.remove_child("__symbols__");
+        let mut fragments = Vec::new();
+        for mut fragment in overlay.children.into_values() {
+            let Some(contents) = fragment.remove_child("__overlay__") else {
+                diag::debug!(
+                    "ignoring overlay node {} without __overlay__",
+                    fragment.name
+                );
+                continue;
+            };
+            let target = self.fragment_target(&fragment)?;
+            diag::debug!("applying overlay {} to {target}", fragment.name);
+            fragments.push((fragment.name, target, contents));
+        }
+        let symbols = match symbols {
+            Some(symbols) => translate_symbols(&symbols, &fragments)?,
+            None => Vec::new(),
+        };
+
+        let mut changeset = OverlayChangeset::default();
+        for (_, target, contents) in fragments {
+            let Some(node) = self.find_node_mut(target.as_str()) else {
+                unreachable!("the targets of all fragments were found");
+            };
+            merge_node(node, contents, target, &mut changeset);
+        }
+        for symbol in &symbols {
+            if let Ok(path) = symbol.as_str()
+                && let Some(node) = node_at_mut(&mut self.root, path)
+                && !node.labels().any(|label| label == symbol.name())
+            {
+                node.add_label(symbol.name());
+                changeset.push(Change::AddLabel {
+                    path: DtPathBuf::from(path),
+                    label: symbol.shared_name().clone(),
+                });
+            }
+        }
+        if !symbols.is_empty() {
+            let symbols_node = DtPathBuf::from("/__symbols__");
+            if self.root.child("__symbols__").is_none() {
+                self.root.add_child(DeviceTreeNode::new("__symbols__"));
+                changeset.push(Change::AddNode {
+                    path: symbols_node.clone(),
+                });
+            }
+            let Some(base_symbols) = self.root.child_mut("__symbols__") else {
+                unreachable!("the __symbols__ node was just added");
+            };
+            for symbol in symbols {
+                changeset.push(Change::SetProperty {
+                    path: symbols_node.clone(),
+                    name: symbol.shared_name().clone(),
+                    old: base_symbols.property(symbol.name()).cloned(),
+                });
+                base_symbols.add_property(symbol);
+            }
+        }
+        Ok(changeset)
+    }
+
+    /// Applies several device tree overlays to this tree, in order, as if by
+    /// calling [`DeviceTree::apply_overlay`] for each of them.
+    ///
+    /// Either all of the overlays are applied or none of them are: if one
+    /// fails, the changes made by those before it are reverted, so this tree
+    /// is left unchanged. Later overlays may refer to labels added by earlier
+    /// ones.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first error returned by applying one of the overlays.
+    pub fn apply_overlays(&mut self, overlays: &[Fdt<'_>]) -> Result<(), OverlayError> {
+        let mut changesets = Vec::with_capacity(overlays.len());
+        for overlay in overlays {
+            match self.apply_overlay_with_changeset(overlay) {
+                Ok(changeset) => changesets.push(changeset),
+                Err(error) => {
+                    for changeset in changesets.into_iter().rev() {
+                        if self.revert(changeset).is_err() {
+                            unreachable!("the tree wasn't changed since the overlay was applied");
+                        }
+                    }
+                    return Err(error);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the path of the node in this tree that the given fragment
+    /// applies to.
+    fn fragment_target(&self, fragment: &DeviceTreeNode) -> Result<DtPathBuf, OverlayError> {
+        let not_found = || OverlayError::TargetNotFound {
+            fragment: fragment.name().to_string(),
+        };
+        if let Some(target) = fragment.property("target") {
+            let phandle = target
+                .as_u32()
+                .map_err(|_| invalid_property(fragment.name(), "target"))?;
+            find_phandle(&self.root, phandle).ok_or_else(not_found)
+        } else if let Some(target_path) = fragment.property("target-path") {
+            let target_path = target_path
+                .as_str()
+                .map_err(|_| invalid_property(fragment.name(), "target-path"))?;
+            match node_at(&self.root, target_path) {
+                Some(_) => Ok(DtPathBuf::from(target_path)),
+                None => Err(not_found()),
+            }
+        } else {
+            Err(OverlayError::MissingTarget {
+                fragment: fragment.name().to_string(),
+            })
+        }
+    }
+
+    /// Resolves the references of the overlay to labels of this tree.
+    ///
+    /// Each property of `__fixups__` is named after a label, and lists the
+    /// `path:property:offset` locations of the cells to set to the phandle of
+    /// its node.
+    fn apply_fixups(
+        &self,
+        overlay: &mut DeviceTreeNode,
+        fixups: &DeviceTreeNode,
+    ) -> Result<(), OverlayError> {
+        for fixup in fixups.properties() {
+            let label = fixup.name();
+            let phandle = self.symbol_phandle(label)?;
+            for location in fixup.value().split(|&byte| byte == 0) {
+                if location.is_empty() {
+                    continue;
+                }
+                let location =
+                    str::from_utf8(location).map_err(|_| invalid_property("__fixups__", label))?;
+                let invalid = || OverlayError::InvalidFixup {
+                    fixup: location.to_string(),
+                };
+                let mut parts = location.rsplitn(3, ':');
+                let (Some(offset), Some(property), Some(path)) =
+                    (parts.next(), parts.next(), parts.next())
+                else {
+                    return Err(invalid());
+                };
+                let offset = offset.parse().map_err(|_| invalid())?;
+                let property = node_at_mut(overlay, path)
+                    .and_then(|node| node.property_mut(property))
+                    .ok_or_else(invalid)?;
+                update_cell(property, offset, |_| Some(phandle)).ok_or_else(invalid)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the phandle of the node with the given label.
+    fn symbol_phandle(&self, label: &str) -> Result<u32, OverlayError> {
+        let unresolved = || OverlayError::UnresolvedSymbol {
+            label: label.to_string(),
+        };
+        let path = self
+            .root
+            .child("__symbols__")
+            .and_then(|symbols| symbols.property(label))
+            .ok_or_else(unresolved)?
+            .as_str()
+            .map_err(|_| invalid_property("__symbols__", label))?;
+        let node = node_at(&self.root, path).ok_or_else(unresolved)?;
+        let Ok(phandle) = node.node_phandle();
+        phandle.map(Phandle::get).ok_or_else(unresolved)
+    }
+}
+
+pub(super) fn invalid_property(node: &str, property: &str) -> OverlayError {
+    OverlayError::InvalidProperty {
+        node: node.to_string(),
+        property: property.to_string(),
+    }
+}
+
+/// Returns the node at the given absolute path below `root`.
+pub(super) fn node_at<'a>(root: &'a DeviceTreeNode, path: &str) -> Option<&'a DeviceTreeNode> {
+    let path = DtPath::new(path);
+    if !path.is_absolute() {
+        return None;
+    }
+    path.segments()
+        .try_fold(root, |node, segment| node.child(segment))
+}
+
+/// Returns the node at the given absolute path below `root`, mutably.
+pub(super) fn node_at_mut<'a>(
+    root: &'a mut DeviceTreeNode,
+    path: &str,
+) -> Option<&'a mut DeviceTreeNode> {
+    let path = DtPath::new(path);
+    if !path.is_absolute() {
+        return None;
+    }
+    path.segments()
+        .try_fold(root, |node, segment| node.child_mut(segment))
+}
+
+/// Returns the path of the first node of the tree with the given phandle.
+fn find_phandle(root: &DeviceTreeNode, phandle: u32) -> Option<DtPathBuf> {
+    let has_phandle = |node: &DeviceTreeNode| matches!(node.node_phandle(), Ok(Some(found)) if found.get() == phandle);
+    let mut path = DtPathBuf::root();
+    if has_phandle(root) {
+        return Some(path);
+    }
+    let mut stack = vec![root.children()];
+    while let Some(children) = stack.last_mut() {
+        if let Some(child) = children.next() {
+            path.push(child.name());
+            if has_phandle(child) {
+                return Some(path);
+            }
+            stack.push(child.children());
+        } else {
+            stack.pop();
+            path.pop();
+        }
+    }
+    None
+}
+
+/// Adds `delta` to the phandles of a node and its descendants.
+fn renumber_phandles(node: &mut DeviceTreeNode, delta: u32) -> Result<(), OverlayError> {
+    if delta == 0 {
+        return Ok(());
+    }
+    let mut stack = vec![node];
+    while let Some(node) = stack.pop() {
+        for name in ["phandle", "linux,phandle"] {
+            if let Some(property) = node.property_mut(name) {
+                update_cell(property, 0, |phandle| {
+                    phandle
+                        .checked_add(delta)
+                        .filter(|&phandle| phandle != u32::MAX)
+                })
+                .ok_or(OverlayError::PhandleOverflow)?;
+            }
+        }
+        stack.extend(node.children_mut());
+    }
+    Ok(())
+}
+
+/// Adds `delta` to the cells listed in `local_fixups`, which mirrors the
+/// structure of `node` with a property listing the offsets of the phandles in
+/// each property which refers to another node of the overlay.
+fn apply_local_fixups(
+    node: &mut DeviceTreeNode,
+    local_fixups: &DeviceTreeNode,
+    delta: u32,
+) -> Result<(), OverlayError> {
+    let mut stack = vec![(node, local_fixups)];
+    while let Some((node, local_fixups)) = stack.pop() {
+        let name = node.name.clone();
+        for fixup in local_fixups.properties() {
+            let invalid = || OverlayError::InvalidFixup {
+                fixup: alloc::format!("{name}:{}", fixup.name()),
+            };
+            let offsets = fixup.as_u32_array().map_err(|_| invalid())?;
+            let mut cells = Vec::with_capacity(offsets.len());
+            cells.extend(offsets);
+            let Some(property) = node.properties.get_mut(fixup.name()) else {
+                return Err(invalid());
+            };
+            for offset in cells {
+                let mut overflow = false;
+                update_cell(property, offset as usize, |phandle| {
+                    let renumbered = phandle.checked_add(delta).and_then(Phandle::new);
+                    overflow = renumbered.is_none();
+                    renumbered.map(Phandle::get)
+                })
+                .ok_or_else(|| {
+                    if overflow {
+                        OverlayError::PhandleOverflow
+                    } else {
+                        invalid()
+                    }
+                })?;
+            }
+        }
+        node.source = None;
+        if let Some(missing) = local_fixups
+            .children()
+            .find(|fixups| node.child(fixups.name()).is_none())
+        {
+            return Err(OverlayError::InvalidFixup {
+                fixup: missing.name().to_string(),
+            });
+        }
+        for child in node.children.values_mut() {
+            if let Some(fixups) = local_fixups.child(child.name()) {
+                stack.push((child, fixups));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Replaces the big-endian cell at the given byte offset of a property with
+/// the result of `update`, returning `None` if the offset is out of bounds or
+/// `update` fails.
+pub(super) fn update_cell(
+    property: &mut DeviceTreeProperty,
+    offset: usize,
+    update: impl FnOnce(u32) -> Option<u32>,
+) -> Option<()> {
+    let mut value = property.value().to_vec();
+    let cell: &mut [u8; 4] = value
+        .get_mut(offset..offset.checked_add(4)?)?
+        .try_into()
+        .ok()?;
+    *cell = update(u32::from_be_bytes(*cell))?.to_be_bytes();
+    property.set_value(value);
+    Some(())
+}
+
+/// Returns the properties to add to the `__symbols__` node of the base tree
+/// for the labels of the overlay, with paths into a fragment's `__overlay__`
+/// node rewritten to be relative to its target.
+fn translate_symbols(
+    symbols: &DeviceTreeNode,
+    fragments: &[(alloc::sync::Arc<str>, DtPathBuf, DeviceTreeNode)],
+) -> Result<Vec<DeviceTreeProperty>, OverlayError> {
+    let mut translated = Vec::new();
+    for symbol in symbols.properties() {
+        let path = symbol
+            .as_str()
+            .map_err(|_| invalid_property("__symbols__", symbol.name()))?;
+        let mut segments = DtPath::new(path).segments();
+        let fragment = segments.next();
+        let target = fragments
+            .iter()
+            .find(|(name, _, _)| Some(&**name) == fragment)
+            .map(|(_, target, _)| target);
+        let (Some(target), Some("__overlay__")) = (target, segments.next()) else {
+            diag::debug!("ignoring overlay symbol {} for {path}", symbol.name());
+            continue;
+        };
+        let mut path = target.clone();
+        for segment in segments {
+            path.push(segment);
+        }
+        let mut value = String::from(path.as_str());
+        value.push('\0');
+        translated.push(DeviceTreeProperty::new(symbol.name(), value));
+    }
+    Ok(translated)
+}
+
+/// Merges the properties and children of `source` into `target`, recording
+/// the changes given the path of `target`.
+///
+/// The changes to each node are recorded before those to its descendants.
+fn merge_node(
+    target: &mut DeviceTreeNode,
+    source: DeviceTreeNode,
+    path: DtPathBuf,
+    changeset: &mut OverlayChangeset,
+) {
+    let mut stack = vec![(target, source, path)];
+    while let Some((target, source, path)) = stack.pop() {
+        for property in source.properties.into_values() {
+            changeset.push(Change::SetProperty {
+                path: path.clone(),
+                name: property.shared_name().clone(),
+                old: target.property(property.name()).cloned(),
+            });
+            target.add_property(property);
+        }
+        let mut merged = BTreeMap::new();
+        for child in source.children.into_values() {
+            if target.child(child.name()).is_some() {
+                merged.insert(child.name.clone(), child);
+            } else {
+                let mut child_path = path.clone();
+                child_path.push(child.name());
+                changeset.push(Change::AddNode { path: child_path });
+                target.add_child(child);
+            }
+        }
+        for existing in target.children_mut().rev() {
+            if let Some(child) = merged.remove(existing.name()) {
+                let mut child_path = path.clone();
+                child_path.push(child.name());
+                stack.push((existing, child, child_path));
+            }
+        }
+    }
+}
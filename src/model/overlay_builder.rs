@@ -0,0 +1,392 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Building overlays without spelling out their fragments.
+
+use alloc::collections::btree_map::BTreeMap;
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use super::overlay::{invalid_property, node_at};
+use super::references::collect_references;
+use super::symbols::label_paths;
+use super::{DeviceTree, DeviceTreeNode, DeviceTreeProperty, OverlayError, PhandleReference};
+use crate::fdt::{Fdt, FdtNode};
+use crate::path::DtPathBuf;
+
+/// The node of the base device tree that a fragment of an [`Overlay`] applies
+/// to.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum OverlayTarget {
+    /// The node with the given absolute path, written as `target-path`.
+    Path(String),
+    /// The node with the given label, written as a `target` phandle which is
+    /// listed in `__fixups__` to be resolved against the `__symbols__` of the
+    /// base device tree.
+    Label(String),
+}
+
+/// A device tree overlay, made of fragments which each apply a subtree to a
+/// node of the base device tree.
+///
+/// This hides the `fragment@N` and `__overlay__` nodes that overlays are made
+/// of. Labels and [phandle
+/// references](DeviceTreeNode::add_phandle_reference) in the subtrees are
+/// turned into `__symbols__`, `__fixups__` and `__local_fixups__` when the
+/// overlay is serialized.
+///
+/// # Examples
+///
+/// ```
+/// # use dtoolkit::fdt::Fdt;
+/// # use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty, Overlay, OverlayTarget};
+/// let mut overlay = Overlay::new();
+/// overlay.add_fragment(
+///     OverlayTarget::Label("uart0".into()),
+///     DeviceTreeNode::builder("uart")
+///         .property(DeviceTreeProperty::new("status", "okay\0"))
+///         .build(),
+/// );
+/// let dtbo = overlay.to_dtbo();
+///
+/// let fdt = Fdt::new(&dtbo).unwrap();
+/// let fragment = fdt.find_node("/fragment@0/__overlay__").unwrap().unwrap();
+/// assert_eq!(fragment.required_str("status").unwrap(), "okay");
+/// let fixups = fdt.find_node("/__fixups__").unwrap().unwrap();
+/// assert_eq!(
+///     fixups.required_str("uart0").unwrap(),
+///     "/fragment@0:target:0"
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct Overlay {
+    tree: DeviceTree,
+    fragments: usize,
+}
+
+impl Default for Overlay {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Overlay {
+    /// Creates an overlay without any fragments.
+    #[must_use]
+    pub fn new() -> Self {
+        Self {
+            tree: DeviceTree::new_overlay(),
+            fragments: 0,
+        }
+    }
+
+    /// Adds a fragment which applies the properties and children of `subtree`
+    /// to the given target, and returns its `__overlay__` node.
+    ///
+    /// The name of `subtree` is ignored.
+    pub fn add_fragment(
+        &mut self,
+        target: OverlayTarget,
+        mut subtree: DeviceTreeNode,
+    ) -> &mut DeviceTreeNode {
+        let mut fragment = DeviceTreeNode::new(format!("fragment@{}", self.fragments));
+        self.fragments += 1;
+        match target {
+            OverlayTarget::Path(path) => {
+                let mut value = path.into_bytes();
+                value.push(0);
+                fragment.add_property(DeviceTreeProperty::new("target-path", value));
+            }
+            OverlayTarget::Label(label) => {
+                fragment.add_property(DeviceTreeProperty::new("target", u32::MAX.to_be_bytes()));
+                fragment.add_phandle_reference("target", 0, &label);
+            }
+        }
+        subtree.name = "__overlay__".into();
+        subtree.source = None;
+        fragment.add_child(subtree);
+
+        let name = fragment.name.clone();
+        self.tree.root.add_child(fragment);
+        let Some(contents) = self
+            .tree
+            .root
+            .child_mut(&name)
+            .and_then(|fragment| fragment.child_mut("__overlay__"))
+        else {
+            unreachable!("the fragment was just added");
+        };
+        contents
+    }
+
+    /// Returns the device tree of this overlay, with a `fragment@N` node for
+    /// each fragment.
+    #[must_use]
+    pub fn tree(&self) -> &DeviceTree {
+        &self.tree
+    }
+
+    /// Returns the device tree of this overlay.
+    #[must_use]
+    pub fn into_tree(self) -> DeviceTree {
+        self.tree
+    }
+
+    /// Serializes this overlay to a `.dtbo` blob.
+    #[must_use]
+    pub fn to_dtbo(&self) -> Vec<u8> {
+        self.tree.to_dtb()
+    }
+
+    /// Checks that this overlay can be applied to the given base tree,
+    /// without changing either of them.
+    ///
+    /// This checks that the `target-path` of each fragment is in the base
+    /// tree, and that the label of each [phandle
+    /// reference](DeviceTreeNode::add_phandle_reference) is either a label of
+    /// the overlay or in the `__symbols__` node of the base tree, for a node
+    /// with a phandle. The label target of a fragment must be in the base
+    /// tree, as that is the only place it is looked up when the overlay is
+    /// applied. For properties made of phandles followed by arguments,
+    /// such as `clocks` or `*-gpios`, it also checks that each phandle is
+    /// followed by as many cells as the `#…-cells` property of its node says.
+    /// Phandles which aren't given by a reference can't be checked, so the
+    /// rest of their property is skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error that [`DeviceTree::apply_overlay`] would return for
+    /// a missing target or label, [`OverlayError::CellCountMismatch`] if the
+    /// cells of a property don't match its phandles, or an error if the base
+    /// tree can't be parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # use dtoolkit::model::{DeviceTree, DeviceTreeNode, Overlay, OverlayError, OverlayTarget};
+    /// let mut base = DeviceTree::new();
+    /// base.root.add_child(DeviceTreeNode::new("soc"));
+    /// let dtb = base.to_dtb();
+    /// let base = Fdt::new(&dtb).unwrap();
+    ///
+    /// let mut overlay = Overlay::new();
+    /// overlay.add_fragment(OverlayTarget::Path("/soc".into()), DeviceTreeNode::new("soc"));
+    /// assert_eq!(overlay.validate(&base), Ok(()));
+    ///
+    /// overlay.add_fragment(OverlayTarget::Label("uart0".into()), DeviceTreeNode::new("uart"));
+    /// assert_eq!(
+    ///     overlay.validate(&base),
+    ///     Err(OverlayError::UnresolvedSymbol {
+    ///         label: "uart0".into()
+    ///     })
+    /// );
+    /// ```
+    pub fn validate(&self, base: &Fdt<'_>) -> Result<(), OverlayError> {
+        let root = &self.tree.root;
+        for fragment in root.children() {
+            let Some(target_path) = fragment.property("target-path") else {
+                continue;
+            };
+            let target_path = target_path
+                .as_str()
+                .map_err(|_| invalid_property(fragment.name(), "target-path"))?;
+            if base.find_node_exact(target_path)?.is_none() {
+                return Err(OverlayError::TargetNotFound {
+                    fragment: fragment.name().to_string(),
+                });
+            }
+        }
+
+        let labels = label_paths(root);
+        let validator = Validator {
+            root,
+            labels,
+            base: *base,
+            symbols: base.find_node_exact("/__symbols__")?,
+        };
+        let mut references = collect_references(root);
+        for (path, reference) in &references {
+            if is_fragment_target(path, reference) {
+                validator.resolve_base(reference.label())?;
+            } else {
+                validator.resolve(reference.label())?;
+            }
+        }
+
+        references.sort_by(|(a_path, a), (b_path, b)| {
+            (a_path.as_str(), a.property()).cmp(&(b_path.as_str(), b.property()))
+        });
+        for group in references
+            .chunk_by(|(a_path, a), (b_path, b)| a_path == b_path && a.property() == b.property())
+        {
+            validator.check_cells(group)?;
+        }
+        Ok(())
+    }
+}
+
+/// The node a phandle reference of an overlay refers to.
+enum Target<'a, 'b> {
+    /// A labelled node of the overlay.
+    Overlay(&'a DeviceTreeNode),
+    /// A node of the base tree listed in its `__symbols__` node.
+    Base(FdtNode<'b>),
+}
+
+impl Target<'_, '_> {
+    /// Returns the value of the given `#…-cells` property of the node.
+    fn cells(&self, name: &str) -> Result<Option<u32>, OverlayError> {
+        match self {
+            Self::Overlay(node) => node
+                .property(name)
+                .map(|property| {
+                    property
+                        .as_u32()
+                        .map_err(|_| invalid_property(node.name(), name))
+                })
+                .transpose(),
+            Self::Base(node) => Ok(node
+                .property(name)?
+                .map(|property| property.as_u32())
+                .transpose()?),
+        }
+    }
+}
+
+/// Returns whether the reference is the `target` of a fragment, which is
+/// always looked up in the base tree when the overlay is applied.
+fn is_fragment_target(path: &DtPathBuf, reference: &PhandleReference) -> bool {
+    reference.property() == "target"
+        && path
+            .as_path()
+            .parent()
+            .is_some_and(|parent| parent.is_root())
+}
+
+/// Checks the references of an [`Overlay`] against a base tree.
+struct Validator<'a, 'b> {
+    root: &'a DeviceTreeNode,
+    labels: BTreeMap<Arc<str>, DtPathBuf>,
+    base: Fdt<'b>,
+    symbols: Option<FdtNode<'b>>,
+}
+
+impl<'a, 'b> Validator<'a, 'b> {
+    /// Returns the node with the given label, looking in the overlay first.
+    fn resolve(&self, label: &str) -> Result<Target<'a, 'b>, OverlayError> {
+        if let Some(path) = self.labels.get(label) {
+            let Some(node) = node_at(self.root, path.as_str()) else {
+                unreachable!("labels were collected from the overlay");
+            };
+            return Ok(Target::Overlay(node));
+        }
+        self.resolve_base(label)
+    }
+
+    /// Returns the node of the base tree with the given label, as the target
+    /// of a fragment must be.
+    fn resolve_base(&self, label: &str) -> Result<Target<'a, 'b>, OverlayError> {
+        let unresolved = || OverlayError::UnresolvedSymbol {
+            label: label.to_string(),
+        };
+        let Some(symbol) = self
+            .symbols
+            .map(|symbols| symbols.property(label))
+            .transpose()?
+            .flatten()
+        else {
+            return Err(unresolved());
+        };
+        let path = symbol
+            .as_str()
+            .map_err(|_| invalid_property("__symbols__", label))?;
+        let node = self.base.find_node_exact(path)?.ok_or_else(unresolved)?;
+        if node.property("phandle")?.is_none() && node.property("linux,phandle")?.is_none() {
+            return Err(unresolved());
+        }
+        Ok(Target::Base(node))
+    }
+
+    /// Checks the references from cells of a single property.
+    fn check_cells(
+        &self,
+        references: &[(DtPathBuf, PhandleReference)],
+    ) -> Result<(), OverlayError> {
+        let Some((path, first)) = references.first() else {
+            return Ok(());
+        };
+        let len = node_at(self.root, path.as_str())
+            .and_then(|node| node.property(first.property()))
+            .map_or(0, |property| property.value().len());
+        for (_, reference) in references {
+            if reference.offset().saturating_add(4) > len {
+                return Err(OverlayError::InvalidFixup {
+                    fixup: format!("{path}:{}:{}", reference.property(), reference.offset()),
+                });
+            }
+        }
+        let Some(cells_name) = cells_property(first.property()) else {
+            return Ok(());
+        };
+
+        let mut offset = 0;
+        let mut checked = 0;
+        while offset < len {
+            let Some((_, reference)) = references
+                .iter()
+                .find(|(_, reference)| reference.offset() == offset)
+            else {
+                break;
+            };
+            let Some(cells) = self.resolve(reference.label())?.cells(cells_name)? else {
+                break;
+            };
+            checked += 1;
+            offset = offset
+                .saturating_add(4)
+                .saturating_add((cells as usize).saturating_mul(4));
+        }
+        let skipped = references
+            .iter()
+            .filter(|(_, reference)| reference.offset() < offset)
+            .count()
+            != checked;
+        if offset > len || skipped {
+            return Err(OverlayError::CellCountMismatch {
+                node: path.to_string(),
+                property: first.property().to_string(),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Returns the name of the property giving the number of cells after each
+/// phandle of the given property, if it is made of phandles followed by
+/// arguments.
+fn cells_property(name: &str) -> Option<&'static str> {
+    let cells = match name {
+        "clocks" | "assigned-clocks" => "#clock-cells",
+        "resets" => "#reset-cells",
+        "dmas" => "#dma-cells",
+        "phys" => "#phy-cells",
+        "pwms" => "#pwm-cells",
+        "power-domains" => "#power-domain-cells",
+        "iommus" => "#iommu-cells",
+        "mboxes" => "#mbox-cells",
+        "interrupts-extended" => "#interrupt-cells",
+        "thermal-sensors" => "#thermal-sensor-cells",
+        "gpios" => "#gpio-cells",
+        _ if name.ends_with("-gpios") => "#gpio-cells",
+        _ => return None,
+    };
+    Some(cells)
+}
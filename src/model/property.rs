@@ -6,12 +6,16 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use alloc::string::{String, ToString};
+use alloc::string::String;
+use alloc::sync::Arc;
 use alloc::vec::Vec;
 use core::{fmt, str};
 
-use crate::error::FdtParseError;
-use crate::fdt::FdtProperty;
+use smallvec::SmallVec;
+
+use super::intern::NamePool;
+use crate::error::{FdtError, FdtParseError};
+use crate::fdt::{FdtProperty, PropertyValue};
 
 /// An error that can occur when parsing a property.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -33,11 +37,29 @@ impl fmt::Display for PropertyError {
 
 impl core::error::Error for PropertyError {}
 
+impl PropertyError {
+    /// Returns the stable numeric code of this error.
+    ///
+    /// See the [`error`](crate::error) module for the ranges of codes.
+    #[must_use]
+    pub const fn code(self) -> u16 {
+        match self {
+            Self::InvalidLength => 200,
+            Self::InvalidString => 201,
+        }
+    }
+}
+
+/// The number of bytes of a property value stored without a separate heap
+/// allocation. Most properties are a few cells or a short string, so this
+/// covers the majority of values in typical device trees.
+const INLINE_VALUE_LEN: usize = 16;
+
 /// A mutable, in-memory representation of a device tree property.
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct DeviceTreeProperty {
-    name: String,
-    value: Vec<u8>,
+    name: Arc<str>,
+    value: SmallVec<[u8; INLINE_VALUE_LEN]>,
 }
 
 impl DeviceTreeProperty {
@@ -52,10 +74,19 @@ impl DeviceTreeProperty {
     /// assert_eq!(prop.value(), &[1, 2, 3, 4]);
     /// ```
     #[must_use]
-    pub fn new(name: impl Into<String>, value: impl Into<Vec<u8>>) -> Self {
+    pub fn new(name: impl Into<String>, value: impl AsRef<[u8]>) -> Self {
         Self {
-            name: name.into(),
-            value: value.into(),
+            name: name.into().into(),
+            value: SmallVec::from_slice(value.as_ref()),
+        }
+    }
+
+    /// Converts the given property, sharing its name with other properties
+    /// converted with the same pool.
+    pub(super) fn from_fdt_property<'a>(prop: FdtProperty<'a>, names: &mut NamePool<'a>) -> Self {
+        Self {
+            name: names.intern(prop.name()),
+            value: SmallVec::from_slice(prop.value()),
         }
     }
 
@@ -65,6 +96,12 @@ impl DeviceTreeProperty {
         &self.name
     }
 
+    /// Returns the name of this property, which may be shared with other
+    /// properties.
+    pub(super) fn shared_name(&self) -> &Arc<str> {
+        &self.name
+    }
+
     /// Returns the value of this property.
     #[must_use]
     pub fn value(&self) -> &[u8] {
@@ -81,8 +118,25 @@ impl DeviceTreeProperty {
     /// prop.set_value(vec![5, 6, 7, 8]);
     /// assert_eq!(prop.value(), &[5, 6, 7, 8]);
     /// ```
-    pub fn set_value(&mut self, value: impl Into<Vec<u8>>) {
-        self.value = value.into();
+    pub fn set_value(&mut self, value: impl AsRef<[u8]>) {
+        self.value = SmallVec::from_slice(value.as_ref());
+    }
+
+    /// Decodes the value of this property into its most likely type.
+    ///
+    /// See [`PropertyValue::decode`] for how the type is chosen.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::PropertyValue;
+    /// # use dtoolkit::model::DeviceTreeProperty;
+    /// let prop = DeviceTreeProperty::new("#address-cells", 2u32.to_be_bytes());
+    /// assert_eq!(prop.decode(), PropertyValue::U32(2));
+    /// ```
+    #[must_use]
+    pub fn decode(&self) -> PropertyValue<'_> {
+        PropertyValue::decode(&self.name, &self.value)
     }
 
     /// Returns the value of this property as a `u32`.
@@ -106,6 +160,71 @@ impl DeviceTreeProperty {
             .map_err(|_| PropertyError::InvalidLength)
     }
 
+    /// Returns the value of this property as a `u64`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::model::DeviceTreeProperty;
+    /// let prop = DeviceTreeProperty::new("my-prop", 0x1_0000_0002u64.to_be_bytes());
+    /// assert_eq!(prop.as_u64(), Ok(0x1_0000_0002));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the property's value is not 8 bytes long.
+    pub fn as_u64(&self) -> Result<u64, PropertyError> {
+        self.value
+            .as_slice()
+            .try_into()
+            .map(u64::from_be_bytes)
+            .map_err(|_| PropertyError::InvalidLength)
+    }
+
+    /// Returns an iterator over the value of this property as an array of
+    /// `u32`s.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::model::DeviceTreeProperty;
+    /// let prop = DeviceTreeProperty::new("my-prop", vec![0, 0, 0, 1, 0, 0, 0, 2]);
+    /// assert!(prop.as_u32_array().unwrap().eq([1, 2]));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the property's value is not a multiple of 4 bytes
+    /// long.
+    pub fn as_u32_array(&self) -> Result<impl ExactSizeIterator<Item = u32>, PropertyError> {
+        match self.value.as_chunks() {
+            (chunks, []) => Ok(chunks.iter().copied().map(u32::from_be_bytes)),
+            _ => Err(PropertyError::InvalidLength),
+        }
+    }
+
+    /// Returns an iterator over the value of this property as an array of
+    /// `u64`s.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::model::DeviceTreeProperty;
+    /// let prop = DeviceTreeProperty::new("my-prop", 0x1_0000_0002u64.to_be_bytes());
+    /// assert!(prop.as_u64_array().unwrap().eq([0x1_0000_0002]));
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the property's value is not a multiple of 8 bytes
+    /// long.
+    pub fn as_u64_array(&self) -> Result<impl ExactSizeIterator<Item = u64>, PropertyError> {
+        match self.value.as_chunks() {
+            (chunks, []) => Ok(chunks.iter().copied().map(u64::from_be_bytes)),
+            _ => Err(PropertyError::InvalidLength),
+        }
+    }
+
     /// Returns the value of this property as a string.
     ///
     /// # Examples
@@ -129,8 +248,34 @@ impl<'a> TryFrom<FdtProperty<'a>> for DeviceTreeProperty {
     type Error = FdtParseError;
 
     fn try_from(prop: FdtProperty<'a>) -> Result<Self, Self::Error> {
-        let name = prop.name().to_string();
-        let value = prop.value().to_vec();
-        Ok(DeviceTreeProperty { name, value })
+        Ok(Self::from_fdt_property(prop, &mut NamePool::default()))
+    }
+}
+
+/// The largest number of cells [`push_cells`] encodes, so that a malformed
+/// `#address-cells` or `#size-cells` can't make it write gigabytes of zeros.
+const MAX_PUSHED_CELLS: usize = 4;
+
+/// Appends the given value to `value` as a big-endian number of `cells` 32-bit
+/// cells.
+///
+/// Returns [`FdtError::TooManyCells`] if `cells` is more than
+/// [`MAX_PUSHED_CELLS`].
+pub(crate) fn push_cells(value: &mut Vec<u8>, number: u64, cells: u32) -> Result<(), FdtError> {
+    let cells = cells as usize;
+    if cells > MAX_PUSHED_CELLS {
+        return Err(FdtError::TooManyCells { cells });
+    }
+    if cells < 2 && number >> (32 * cells) != 0 {
+        return Err(FdtError::ValueTooBig {
+            value: number,
+            cells,
+        });
+    }
+    let bytes = number.to_be_bytes();
+    for _ in 2..cells {
+        value.extend_from_slice(&0u32.to_be_bytes());
     }
+    value.extend_from_slice(&bytes[bytes.len() - cells.min(2) * size_of::<u32>()..]);
+    Ok(())
 }
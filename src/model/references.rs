@@ -0,0 +1,347 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! References from property cells to labelled nodes, and the `__fixups__` and
+//! `__local_fixups__` nodes that describe them in overlays.
+//!
+//! In a DTS source, `<&label>` is replaced by the phandle of the labelled
+//! node. When compiling an overlay, the label may be in the base tree the
+//! overlay will be applied to, so `dtc -@` writes a placeholder and lists the
+//! cell under the label in `__fixups__`. References to nodes of the overlay
+//! itself are resolved, but listed in `__local_fixups__`, as the phandles of
+//! the overlay are renumbered when it is applied.
+
+use alloc::sync::Arc;
+use alloc::vec;
+use alloc::vec::Vec;
+use core::convert::Infallible;
+use core::iter;
+use core::ptr;
+
+use super::overlay::{node_at_mut, update_cell};
+use super::symbols::label_paths;
+use super::writer::walk;
+use super::{DeviceTree, DeviceTreeNode, DeviceTreeProperty};
+use crate::diag;
+use crate::path::DtPathBuf;
+use crate::standard::{Phandle, PhandleNode, max_phandle};
+
+/// A reference from a cell of a property to the node with a given label,
+/// which is replaced by the phandle of that node when the tree is serialized.
+///
+/// References are added with [`DeviceTreeNode::add_phandle_reference`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct PhandleReference {
+    property: Arc<str>,
+    offset: usize,
+    label: Arc<str>,
+}
+
+impl PhandleReference {
+    /// Returns the name of the property with the reference.
+    #[must_use]
+    pub fn property(&self) -> &str {
+        &self.property
+    }
+
+    /// Returns the offset in bytes of the cell in the value of the property.
+    #[must_use]
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the label of the node referred to.
+    #[must_use]
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+}
+
+impl DeviceTreeNode {
+    /// Records that the 32-bit cell at byte `offset` of the given property
+    /// refers to the node with the given [label](DeviceTreeNode::labels).
+    ///
+    /// When the tree is serialized, the cell is set to the phandle of the
+    /// labelled node, which is given one if it doesn't have one yet. If no
+    /// node of the tree has the label and the tree is an
+    /// [overlay](DeviceTree::is_overlay), the cell is listed in `__fixups__`
+    /// to be resolved against the base tree instead, like `<&label>` in a DTS
+    /// source compiled with `dtc -@`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+    /// let mut node = DeviceTreeNode::new("serial@1000");
+    /// node.add_property(DeviceTreeProperty::new("clocks", [0; 4]));
+    /// node.add_phandle_reference("clocks", 0, "uart_clk");
+    /// assert_eq!(
+    ///     node.phandle_references().next().unwrap().label(),
+    ///     "uart_clk"
+    /// );
+    /// ```
+    pub fn add_phandle_reference(&mut self, property: &str, offset: usize, label: &str) {
+        self.source = None;
+        self.references.push(PhandleReference {
+            property: property.into(),
+            offset,
+            label: label.into(),
+        });
+    }
+
+    /// Returns the references to labelled nodes from cells of the properties
+    /// of this node which haven't been resolved yet.
+    #[must_use]
+    pub fn phandle_references(&self) -> impl ExactSizeIterator<Item = &PhandleReference> {
+        self.references.iter()
+    }
+}
+
+impl DeviceTree {
+    /// Creates an empty overlay.
+    ///
+    /// This is the same as [`DeviceTree::new`], except that unresolved
+    /// [phandle references](DeviceTreeNode::add_phandle_reference) are listed
+    /// in `__fixups__` when serializing.
+    #[must_use]
+    pub fn new_overlay() -> Self {
+        let mut tree = Self::new();
+        tree.overlay = true;
+        tree
+    }
+
+    /// Returns whether this tree is an overlay.
+    ///
+    /// Trees parsed from a blob are overlays if they have a `__fixups__` or
+    /// `__local_fixups__` node, or a fragment with an `__overlay__` node.
+    #[must_use]
+    pub fn is_overlay(&self) -> bool {
+        self.overlay
+    }
+
+    /// Sets whether this tree is an overlay.
+    pub fn set_overlay(&mut self, overlay: bool) {
+        self.overlay = overlay;
+    }
+
+    /// Replaces the [phandle references](DeviceTreeNode::add_phandle_reference)
+    /// of the nodes of this tree with the phandles of the nodes they refer to.
+    ///
+    /// Labelled nodes without a phandle are given one. In an overlay, each
+    /// resolved reference is listed in `__local_fixups__`, and references to
+    /// labels which no node has are set to `0xffffffff` and listed in
+    /// `__fixups__`. In other trees, they are left unchanged. References to
+    /// cells outside of their property are ignored. [`DeviceTree::to_dtb`]
+    /// does this automatically, so this only needs to be called to inspect
+    /// the result.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::model::{DeviceTree, DeviceTreeNode, DeviceTreeProperty};
+    /// let mut overlay = DeviceTree::new_overlay();
+    /// let mut node = DeviceTreeNode::new("consumer");
+    /// node.add_property(DeviceTreeProperty::new("clocks", [0; 4]));
+    /// node.add_phandle_reference("clocks", 0, "uart_clk");
+    /// overlay.root.add_child(node);
+    /// overlay.resolve_phandle_references();
+    ///
+    /// let fixups = overlay.root.child("__fixups__").unwrap();
+    /// assert_eq!(
+    ///     fixups.property("uart_clk").unwrap().as_str(),
+    ///     Ok("/consumer:clocks:0")
+    /// );
+    /// ```
+    pub fn resolve_phandle_references(&mut self) {
+        let references = collect_references(&self.root);
+        if references.is_empty() {
+            return;
+        }
+        let labels = label_paths(&self.root);
+        let Ok(max) = max_phandle(&self.root);
+        let mut next_phandle = max.map_or(0, Phandle::get).saturating_add(1);
+
+        for (path, reference) in references {
+            let phandle = match labels.get(&reference.label) {
+                Some(target) => {
+                    let Some(node) = node_at_mut(&mut self.root, target.as_str()) else {
+                        unreachable!("labels were collected from the tree");
+                    };
+                    let Ok(existing) = (&*node).node_phandle();
+                    if let Some(phandle) = existing {
+                        Some(phandle.get())
+                    } else {
+                        let Some(phandle) = Phandle::new(next_phandle) else {
+                            diag::warning!("ran out of phandles for label {}", reference.label);
+                            continue;
+                        };
+                        node.set_phandle(phandle);
+                        next_phandle += 1;
+                        Some(phandle.get())
+                    }
+                }
+                None if self.overlay => None,
+                None => {
+                    diag::warning!("reference to missing label {}", reference.label);
+                    continue;
+                }
+            };
+
+            let Some(node) = node_at_mut(&mut self.root, path.as_str()) else {
+                unreachable!("references were collected from the tree");
+            };
+            let written = node.property_mut(&reference.property).and_then(|property| {
+                update_cell(property, reference.offset, |_| {
+                    Some(phandle.unwrap_or(u32::MAX))
+                })
+            });
+            if written.is_none() {
+                diag::warning!(
+                    "ignoring reference to {} outside of {path}:{}",
+                    reference.label,
+                    reference.property
+                );
+                continue;
+            }
+            if !self.overlay {
+                continue;
+            }
+            match phandle {
+                Some(_) => self.add_local_fixup(&path, &reference),
+                None => self.add_fixup(&path, &reference),
+            }
+        }
+        clear_references(&mut self.root);
+    }
+
+    /// Lists a reference to a node of this overlay in `__local_fixups__`.
+    fn add_local_fixup(&mut self, path: &DtPathBuf, reference: &PhandleReference) {
+        let mut node = child_or_insert(&mut self.root, "__local_fixups__");
+        for segment in path.as_path().segments() {
+            node = child_or_insert(node, segment);
+        }
+        let mut value = node
+            .property(&reference.property)
+            .map(|property| property.value().to_vec())
+            .unwrap_or_default();
+        let offset = u32::try_from(reference.offset).unwrap_or(u32::MAX);
+        if !value.chunks(4).any(|cell| cell == offset.to_be_bytes()) {
+            value.extend_from_slice(&offset.to_be_bytes());
+        }
+        node.add_property(DeviceTreeProperty::new(&*reference.property, value));
+    }
+
+    /// Lists a reference to a label of the base tree in `__fixups__`.
+    fn add_fixup(&mut self, path: &DtPathBuf, reference: &PhandleReference) {
+        let fixups = child_or_insert(&mut self.root, "__fixups__");
+        let location = alloc::format!("{path}:{}:{}", reference.property, reference.offset);
+        let mut value = fixups
+            .property(&reference.label)
+            .map(|property| property.value().to_vec())
+            .unwrap_or_default();
+        if !value
+            .split(|&byte| byte == 0)
+            .any(|existing| existing == location.as_bytes())
+        {
+            value.extend_from_slice(location.as_bytes());
+            value.push(0);
+        }
+        fixups.add_property(DeviceTreeProperty::new(&*reference.label, value));
+    }
+}
+
+/// Returns whether a tree parsed from a blob is an overlay.
+pub(super) fn is_overlay(root: &DeviceTreeNode) -> bool {
+    root.child("__fixups__").is_some()
+        || root.child("__local_fixups__").is_some()
+        || root
+            .children()
+            .any(|child| child.child("__overlay__").is_some())
+}
+
+/// Returns whether any node of the tree has an unresolved phandle reference.
+pub(super) fn has_references(root: &DeviceTreeNode) -> bool {
+    let mut found = false;
+    walk(root, |node| {
+        if let Some(node) = node {
+            found |= !node.references.is_empty();
+        }
+        !found
+    });
+    found
+}
+
+/// Returns the phandle references of the nodes of the tree, in depth-first
+/// order, along with the paths of their nodes.
+pub(super) fn collect_references(root: &DeviceTreeNode) -> Vec<(DtPathBuf, PhandleReference)> {
+    let mut references = Vec::new();
+    let mut path = DtPathBuf::root();
+    walk(root, |node| {
+        let Some(node) = node else {
+            path.pop();
+            return true;
+        };
+        if !ptr::eq(node, root) {
+            path.push(node.name());
+        }
+        for reference in &node.references {
+            references.push((path.clone(), reference.clone()));
+        }
+        true
+    });
+    references
+}
+
+/// Forgets the phandle references of the nodes of the tree, without counting
+/// it as a modification of the nodes which have none.
+fn clear_references(root: &mut DeviceTreeNode) {
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        node.references.clear();
+        stack.extend(node.children.values_mut());
+    }
+}
+
+/// Returns the child of `node` with the given name, adding it if needed.
+fn child_or_insert<'a>(node: &'a mut DeviceTreeNode, name: &str) -> &'a mut DeviceTreeNode {
+    if node.child(name).is_none() {
+        node.add_child(DeviceTreeNode::new(name));
+    }
+    let Some(child) = node.child_mut(name) else {
+        unreachable!("the child was just added");
+    };
+    child
+}
+
+/// Malformed `phandle` properties, and those with a reserved value, are
+/// ignored.
+impl PhandleNode for &DeviceTreeNode {
+    type Error = Infallible;
+
+    fn node_phandle(self) -> Result<Option<Phandle>, Infallible> {
+        Ok(self
+            .property("phandle")
+            .or_else(|| self.property("linux,phandle"))
+            .and_then(|property| property.as_u32().ok())
+            .and_then(Phandle::new))
+    }
+
+    fn descendant_nodes(self) -> impl Iterator<Item = Result<Self, Infallible>> {
+        let mut stack = vec![self.children()];
+        iter::from_fn(move || {
+            loop {
+                let children = stack.last_mut()?;
+                if let Some(child) = children.next() {
+                    stack.push(child.children());
+                    return Some(Ok(child));
+                }
+                stack.pop();
+            }
+        })
+    }
+}
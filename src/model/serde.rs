@@ -0,0 +1,274 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! [`Serialize`] and [`Deserialize`] implementations, for storing device trees
+//! in formats such as JSON or TOML.
+//!
+//! A tree is a struct with its `memory_reservations` and `root` node, and a
+//! node is a struct with its `name`, `labels`, `properties` and `children`, in
+//! order. Empty lists are omitted, and default to empty when deserializing.
+//!
+//! A property is a struct with its `name` and `value`. The value is decoded as
+//! by [`PropertyValue::decode`] and stored as one of the variants of `Value`,
+//! so that strings and cells are readable, and it is omitted for properties
+//! without a value. Decoding and encoding doesn't change the bytes of a value.
+//!
+//! Deserializing a node with two properties or two children of the same name
+//! fails, rather than silently keeping only the last of them.
+//!
+//! Phandle references which haven't been resolved yet aren't serialized, so
+//! call [`DeviceTree::resolve_phandle_references`] first to keep them.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use serde::de::{Deserialize, Deserializer, Error};
+use serde::ser::{Serialize, SerializeStruct, Serializer};
+
+use super::{DeviceTree, DeviceTreeNode, DeviceTreeProperty, references};
+use crate::fdt::PropertyValue;
+use crate::memreserve::MemoryReservation;
+
+/// The typed value of a property.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+enum Value {
+    /// A single NUL-terminated string.
+    String(String),
+    /// Several NUL-terminated strings.
+    Strings(Vec<String>),
+    /// Big-endian 32-bit cells.
+    Cells(Vec<u32>),
+    /// Bytes which aren't strings or cells.
+    Bytes(Vec<u8>),
+}
+
+impl Value {
+    /// Returns the typed value of the given property, or `None` if it is
+    /// empty.
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "64-bit values are split into two cells"
+    )]
+    fn new(property: &DeviceTreeProperty) -> Option<Self> {
+        let value = match PropertyValue::decode(property.name(), property.value()) {
+            PropertyValue::Empty => return None,
+            PropertyValue::String(string) => Self::String(string.into()),
+            PropertyValue::StringList(strings) => {
+                Self::Strings(strings.iter().map(Into::into).collect())
+            }
+            PropertyValue::U32(value) => Self::Cells(alloc::vec![value]),
+            PropertyValue::U64(value) => {
+                Self::Cells(alloc::vec![(value >> 32) as u32, value as u32])
+            }
+            PropertyValue::PhandleList(array) | PropertyValue::U32Array(array) => {
+                Self::Cells(array.iter().collect())
+            }
+            PropertyValue::Bytes(bytes) => Self::Bytes(bytes.into()),
+        };
+        Some(value)
+    }
+
+    fn into_bytes(self) -> Vec<u8> {
+        match self {
+            Self::String(string) => {
+                let mut bytes = Vec::from(string);
+                bytes.push(0);
+                bytes
+            }
+            Self::Strings(strings) => strings
+                .iter()
+                .flat_map(|string| string.bytes().chain([0]))
+                .collect(),
+            Self::Cells(cells) => cells.iter().flat_map(|cell| cell.to_be_bytes()).collect(),
+            Self::Bytes(bytes) => bytes,
+        }
+    }
+}
+
+/// Serializes the items returned by a function as a sequence, without
+/// collecting them.
+struct Seq<F>(F);
+
+impl<F, I> Serialize for Seq<F>
+where
+    F: Fn() -> I,
+    I: IntoIterator<Item: Serialize>,
+{
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq((self.0)())
+    }
+}
+
+/// Serializes a field of `state` with the items returned by `items`, or skips
+/// it if there are none.
+fn serialize_seq_field<S, F, I>(
+    state: &mut S,
+    key: &'static str,
+    len: usize,
+    items: F,
+) -> Result<(), S::Error>
+where
+    S: SerializeStruct,
+    F: Fn() -> I,
+    I: IntoIterator<Item: Serialize>,
+{
+    if len == 0 {
+        state.skip_field(key)
+    } else {
+        state.serialize_field(key, &Seq(items))
+    }
+}
+
+impl Serialize for DeviceTreeProperty {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("DeviceTreeProperty", 2)?;
+        state.serialize_field("name", self.name())?;
+        match Value::new(self) {
+            Some(value) => state.serialize_field("value", &value)?,
+            None => state.skip_field("value")?,
+        }
+        state.end()
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename = "DeviceTreeProperty", deny_unknown_fields)]
+struct PropertyData {
+    name: String,
+    #[serde(default)]
+    value: Option<Value>,
+}
+
+impl<'de> Deserialize<'de> for DeviceTreeProperty {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let PropertyData { name, value } = PropertyData::deserialize(deserializer)?;
+        Ok(Self::new(
+            name,
+            value.map(Value::into_bytes).unwrap_or_default(),
+        ))
+    }
+}
+
+impl Serialize for DeviceTreeNode {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("DeviceTreeNode", 4)?;
+        state.serialize_field("name", self.name())?;
+        serialize_seq_field(&mut state, "labels", self.labels.len(), || self.labels())?;
+        serialize_seq_field(&mut state, "properties", self.properties.len(), || {
+            self.properties()
+        })?;
+        serialize_seq_field(&mut state, "children", self.children.len(), || {
+            self.children()
+        })?;
+        state.end()
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename = "DeviceTreeNode", deny_unknown_fields)]
+struct NodeData {
+    name: String,
+    #[serde(default)]
+    labels: Vec<String>,
+    #[serde(default)]
+    properties: Vec<DeviceTreeProperty>,
+    #[serde(default)]
+    children: Vec<DeviceTreeNode>,
+}
+
+impl<'de> Deserialize<'de> for DeviceTreeNode {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let data = NodeData::deserialize(deserializer)?;
+        let mut node = Self::new(data.name);
+        for label in data.labels {
+            node.add_label(label);
+        }
+        for property in data.properties {
+            if node.property(property.name()).is_some() {
+                return Err(D::Error::custom(format_args!(
+                    "duplicate property `{}` in node `{}`",
+                    property.name(),
+                    node.name()
+                )));
+            }
+            node.add_property(property);
+        }
+        for child in data.children {
+            if node.child(child.name()).is_some() {
+                return Err(D::Error::custom(format_args!(
+                    "duplicate child `{}` in node `{}`",
+                    child.name(),
+                    node.name()
+                )));
+            }
+            node.add_child(child);
+        }
+        Ok(node)
+    }
+}
+
+impl Serialize for MemoryReservation {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("MemoryReservation", 2)?;
+        state.serialize_field("address", &self.address())?;
+        state.serialize_field("size", &self.size())?;
+        state.end()
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename = "MemoryReservation", deny_unknown_fields)]
+struct MemoryReservationData {
+    address: u64,
+    size: u64,
+}
+
+impl<'de> Deserialize<'de> for MemoryReservation {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let MemoryReservationData { address, size } =
+            MemoryReservationData::deserialize(deserializer)?;
+        Ok(Self::new(address, size))
+    }
+}
+
+impl Serialize for DeviceTree {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("DeviceTree", 2)?;
+        serialize_seq_field(
+            &mut state,
+            "memory_reservations",
+            self.memory_reservations.len(),
+            || &self.memory_reservations,
+        )?;
+        state.serialize_field("root", &self.root)?;
+        state.end()
+    }
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename = "DeviceTree", deny_unknown_fields)]
+struct TreeData {
+    #[serde(default)]
+    memory_reservations: Vec<MemoryReservation>,
+    root: DeviceTreeNode,
+}
+
+impl<'de> Deserialize<'de> for DeviceTree {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let TreeData {
+            memory_reservations,
+            root,
+        } = TreeData::deserialize(deserializer)?;
+        let mut tree = DeviceTree::new();
+        tree.overlay = references::is_overlay(&root);
+        tree.root = root;
+        tree.memory_reservations = memory_reservations;
+        Ok(tree)
+    }
+}
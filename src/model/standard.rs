@@ -0,0 +1,307 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Typed wrappers for standard nodes of a [`DeviceTree`], as
+//! [`crate::standard`] has for a flattened device tree.
+
+use core::ops::Deref;
+
+use super::property::PropertyError;
+use super::{DeviceTree, DeviceTreeNode, DeviceTreeProperty};
+use crate::fdt::Cells;
+use crate::standard::{ChosenNode, CpuNodes, StandardNode, StandardProperty, UefiMemoryMap};
+
+impl DeviceTree {
+    /// Returns the `/chosen` node, if there is one.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::model::{DeviceTree, DeviceTreeNode, DeviceTreeProperty};
+    /// let mut tree = DeviceTree::new();
+    /// assert!(tree.chosen().is_none());
+    /// tree.root.add_child(
+    ///     DeviceTreeNode::builder("chosen")
+    ///         .property(DeviceTreeProperty::new(
+    ///             "linux,uefi-system-table",
+    ///             0x8000_0000u64.to_be_bytes(),
+    ///         ))
+    ///         .build(),
+    /// );
+    /// let chosen = tree.chosen().unwrap();
+    /// assert_eq!(chosen.uefi_system_table(), Ok(Some(0x8000_0000)));
+    /// ```
+    #[must_use]
+    pub fn chosen(&self) -> Option<Chosen<'_>> {
+        Some(Chosen {
+            node: self.root.child("chosen")?,
+        })
+    }
+
+    /// Returns the `/cpus` node, if there is one.
+    ///
+    /// This should always be included in a valid device tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::model::{DeviceTree, DeviceTreeNode, DeviceTreeProperty};
+    /// let mut tree = DeviceTree::new();
+    /// tree.root.add_child(
+    ///     DeviceTreeNode::builder("cpus")
+    ///         .property(DeviceTreeProperty::new("#address-cells", 1u32.to_be_bytes()))
+    ///         .property(DeviceTreeProperty::new("#size-cells", 0u32.to_be_bytes()))
+    ///         .child(
+    ///             DeviceTreeNode::builder("cpu@1")
+    ///                 .property(DeviceTreeProperty::new("reg", 1u32.to_be_bytes()))
+    ///                 .build(),
+    ///         )
+    ///         .build(),
+    /// );
+    /// let cpu = tree.cpus().unwrap().cpus().next().unwrap();
+    /// let cpu = cpu.unwrap();
+    /// let id = cpu.ids().unwrap().unwrap().next().unwrap();
+    /// assert_eq!(id.to_int::<u32>(), Ok(1));
+    /// ```
+    #[must_use]
+    pub fn cpus(&self) -> Option<Cpus<'_>> {
+        Some(Cpus {
+            node: self.root.child("cpus")?,
+        })
+    }
+}
+
+/// Typed wrapper for the `/chosen` node of a [`DeviceTree`].
+#[derive(Clone, Copy, Debug)]
+pub struct Chosen<'a> {
+    node: &'a DeviceTreeNode,
+}
+
+impl Deref for Chosen<'_> {
+    type Target = DeviceTreeNode;
+
+    fn deref(&self) -> &Self::Target {
+        self.node
+    }
+}
+
+impl Chosen<'_> {
+    /// Returns the physical address of the UEFI system table, from the
+    /// `linux,uefi-system-table` property.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value isn't a valid u64.
+    pub fn uefi_system_table(&self) -> Result<Option<u64>, PropertyError> {
+        ChosenNode(self.node).uefi_system_table()
+    }
+
+    /// Returns the UEFI memory map passed by the EFI stub, from the
+    /// `linux,uefi-mmap-*` properties.
+    ///
+    /// Returns `None` if there is no `linux,uefi-mmap-start` property.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a value has the wrong size, or only some of the
+    /// properties are present.
+    pub fn uefi_memory_map(&self) -> Result<Option<UefiMemoryMap>, PropertyError> {
+        ChosenNode(self.node).uefi_memory_map()
+    }
+}
+
+/// Typed wrapper for the `/cpus` node of a [`DeviceTree`].
+#[derive(Clone, Copy, Debug)]
+pub struct Cpus<'a> {
+    node: &'a DeviceTreeNode,
+}
+
+impl Deref for Cpus<'_> {
+    type Target = DeviceTreeNode;
+
+    fn deref(&self) -> &Self::Target {
+        self.node
+    }
+}
+
+impl<'a> Cpus<'a> {
+    /// Returns an iterator over the `/cpus/cpu@*` nodes.
+    pub fn cpus(&self) -> impl Iterator<Item = Result<Cpu<'a>, PropertyError>> + use<'a> {
+        CpuNodes::children_of(self.node).map(|nodes| nodes.map(|nodes| Cpu { nodes }))
+    }
+}
+
+/// Typed wrapper for a `/cpus/cpu` node of a [`DeviceTree`].
+#[derive(Clone, Copy, Debug)]
+pub struct Cpu<'a> {
+    nodes: CpuNodes<&'a DeviceTreeNode>,
+}
+
+impl Deref for Cpu<'_> {
+    type Target = DeviceTreeNode;
+
+    fn deref(&self) -> &Self::Target {
+        self.nodes.node
+    }
+}
+
+impl<'a> Cpu<'a> {
+    /// Returns an iterator over the IDs of the CPU, from the standard `reg`
+    /// property, or `None` if it has no `reg` property.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `#address-cells` or `#size-cells` property of
+    /// the `/cpus` node isn't a valid u32, or the size of the `reg` value isn't
+    /// a multiple of the size of an entry.
+    pub fn ids(&self) -> Result<Option<impl Iterator<Item = Cells<'a>> + use<'a>>, PropertyError> {
+        self.nodes.ids()
+    }
+
+    /// Returns the value of the standard `timebase-frequency` property, in Hz,
+    /// from the CPU node or else the `/cpus` node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value isn't a valid u32 or u64.
+    pub fn timebase_frequency(&self) -> Result<Option<u64>, PropertyError> {
+        self.nodes.timebase_frequency()
+    }
+
+    /// Returns the value of the standard `enable-method` property, which
+    /// describes how to start the CPU if it isn't already running, such as
+    /// `psci` or `spin-table`.
+    ///
+    /// # Errors
+    ///
+    /// This doesn't currently return an error, but has the same signature as
+    /// [`standard::Cpu::enable_method`](crate::standard::Cpu::enable_method).
+    pub fn enable_method(
+        &self,
+    ) -> Result<Option<impl Iterator<Item = &'a str> + use<'a>>, PropertyError> {
+        self.nodes.enable_method()
+    }
+
+    /// Returns the value of the standard `cpu-release-addr` property, which is
+    /// the address a secondary CPU spins on with the `spin-table` enable
+    /// method.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value isn't a valid u64.
+    pub fn cpu_release_addr(&self) -> Result<Option<u64>, PropertyError> {
+        self.nodes.cpu_release_addr()
+    }
+
+    /// Returns the value of the RISC-V `riscv,isa` property, the ISA string of
+    /// the hart such as `rv64imafdc_zicsr`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value isn't a valid string.
+    pub fn riscv_isa(&self) -> Result<Option<&'a str>, PropertyError> {
+        self.nodes.riscv_isa()
+    }
+
+    /// Returns the value of the RISC-V `riscv,isa-extensions` property, which
+    /// lists the extensions supported by the hart, such as `i`, `m` and
+    /// `zicsr`.
+    ///
+    /// # Errors
+    ///
+    /// This doesn't currently return an error, but has the same signature as
+    /// [`standard::Cpu::riscv_isa_extensions`](crate::standard::Cpu::riscv_isa_extensions).
+    pub fn riscv_isa_extensions(
+        &self,
+    ) -> Result<Option<impl Iterator<Item = &'a str> + use<'a>>, PropertyError> {
+        self.nodes.riscv_isa_extensions()
+    }
+
+    /// Returns whether the RISC-V `riscv,isa-extensions` property lists the
+    /// given extension.
+    ///
+    /// # Errors
+    ///
+    /// This doesn't currently return an error, but has the same signature as
+    /// [`standard::Cpu::has_riscv_isa_extension`](crate::standard::Cpu::has_riscv_isa_extension).
+    pub fn has_riscv_isa_extension(&self, extension: &str) -> Result<bool, PropertyError> {
+        self.nodes.has_riscv_isa_extension(extension)
+    }
+
+    /// Returns the value of the standard `mmu-type` property, such as
+    /// `riscv,sv39` or `riscv,none`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the value isn't a valid string.
+    pub fn mmu_type(&self) -> Result<Option<&'a str>, PropertyError> {
+        self.nodes.mmu_type()
+    }
+
+    /// Returns the `interrupt-controller` child of a RISC-V hart, which is its
+    /// local interrupt controller.
+    ///
+    /// # Errors
+    ///
+    /// This doesn't currently return an error, but has the same signature as
+    /// [`standard::Cpu::hart_interrupt_controller`](crate::standard::Cpu::hart_interrupt_controller).
+    pub fn hart_interrupt_controller(&self) -> Result<Option<&'a DeviceTreeNode>, PropertyError> {
+        self.nodes.hart_interrupt_controller()
+    }
+}
+
+impl<'a> StandardNode<'a> for &'a DeviceTreeNode {
+    type Property = &'a DeviceTreeProperty;
+    type Error = PropertyError;
+    type ValueError = PropertyError;
+
+    fn node_name_without_address(self) -> Result<&'a str, PropertyError> {
+        let name = self.name();
+        Ok(name.split_once('@').map_or(name, |(name, _)| name))
+    }
+
+    fn child_nodes(self) -> impl Iterator<Item = Result<Self, PropertyError>> + use<'a> {
+        self.children().map(Ok)
+    }
+
+    fn named_child(self, name: &str) -> Result<Option<Self>, PropertyError> {
+        Ok(self.child(name))
+    }
+
+    fn node_property(self, name: &str) -> Result<Option<&'a DeviceTreeProperty>, PropertyError> {
+        Ok(self.property(name))
+    }
+
+    fn missing_property(_name: &'static str) -> PropertyError {
+        PropertyError::InvalidLength
+    }
+
+    fn size_mismatch(_size: usize, _chunk: usize) -> PropertyError {
+        PropertyError::InvalidLength
+    }
+}
+
+impl<'a> StandardProperty<'a> for &'a DeviceTreeProperty {
+    type Error = PropertyError;
+
+    fn property_value(self) -> &'a [u8] {
+        self.value()
+    }
+
+    fn property_u32(self) -> Result<u32, PropertyError> {
+        self.as_u32()
+    }
+
+    fn property_u64(self) -> Result<u64, PropertyError> {
+        self.as_u64()
+    }
+
+    fn property_str(self) -> Result<&'a str, PropertyError> {
+        self.as_str()
+    }
+}
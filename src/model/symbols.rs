@@ -0,0 +1,224 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Keeping the `__symbols__` node in sync with the nodes it refers to.
+//!
+//! Blobs compiled with `dtc -@` have a `__symbols__` node mapping each label
+//! of the source to the path of its node, so that overlays can refer to them.
+//! When a tree is parsed, the labels are attached to their nodes, so that they
+//! follow the nodes when they are renamed or moved and the `__symbols__` node
+//! can be regenerated when the tree is serialized.
+
+use alloc::collections::btree_map::BTreeMap;
+use alloc::collections::btree_set::BTreeSet;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+use core::ptr;
+
+use super::overlay::node_at;
+use super::writer::walk;
+use super::{DeviceTree, DeviceTreeNode, DeviceTreeProperty};
+use crate::diag;
+use crate::path::{DtPath, DtPathBuf};
+
+impl DeviceTreeNode {
+    /// Returns the labels of this node.
+    ///
+    /// Labels are read from the `__symbols__` node when a tree is parsed, and
+    /// written back to it with the current path of the node when it is
+    /// serialized.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::model::DeviceTreeNode;
+    /// let mut node = DeviceTreeNode::new("serial@1000");
+    /// node.add_label("uart0");
+    /// assert!(node.labels().eq(["uart0"]));
+    /// ```
+    #[must_use]
+    pub fn labels(&self) -> impl ExactSizeIterator<Item = &str> {
+        self.labels.iter().map(|label| &**label)
+    }
+
+    /// Adds a label to this node, if it doesn't have it yet.
+    ///
+    /// Labels must be unique within a tree. If another node has the same
+    /// label, which of them ends up in the `__symbols__` node is unspecified.
+    pub fn add_label(&mut self, label: impl Into<String>) {
+        let label = label.into();
+        self.removed_labels.retain(|removed| **removed != *label);
+        if !self.labels.iter().any(|existing| **existing == *label) {
+            self.labels.push(label.into());
+        }
+    }
+
+    /// Removes a label from this node, returning whether it had it.
+    ///
+    /// The label is removed from the `__symbols__` node when the tree is next
+    /// serialized, unless another node has it.
+    pub fn remove_label(&mut self, label: &str) -> bool {
+        let Some(index) = self.labels.iter().position(|existing| **existing == *label) else {
+            return false;
+        };
+        let label = self.labels.remove(index);
+        self.removed_labels.push(label);
+        true
+    }
+}
+
+impl DeviceTree {
+    /// Rewrites the `__symbols__` node to match the labels of the nodes of
+    /// this tree.
+    ///
+    /// Each label is mapped to the current path of its node. Entries of
+    /// `__symbols__` for labels which no node has any more are removed if the
+    /// label was [removed](DeviceTreeNode::remove_label) from its node or
+    /// their path no longer exists, and kept otherwise. [`DeviceTree::to_dtb`]
+    /// does this automatically, so this only needs to be called to inspect
+    /// the result.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::model::{DeviceTree, DeviceTreeNode};
+    /// let mut tree = DeviceTree::new();
+    /// let mut serial = DeviceTreeNode::new("serial@1000");
+    /// serial.add_label("uart0");
+    /// tree.root.add_child(serial);
+    /// tree.update_symbols();
+    ///
+    /// let symbols = tree.root.child("__symbols__").unwrap();
+    /// assert_eq!(
+    ///     symbols.property("uart0").unwrap().as_str(),
+    ///     Ok("/serial@1000")
+    /// );
+    /// ```
+    pub fn update_symbols(&mut self) {
+        if let Some(symbols) = self.updated_symbols() {
+            self.root.add_child(symbols);
+        }
+    }
+
+    /// Returns the `__symbols__` node matching the labels of the nodes of
+    /// this tree, or `None` if the current one already does.
+    pub(super) fn updated_symbols(&self) -> Option<DeviceTreeNode> {
+        let labels = collect_labels(&self.root);
+        let existing = self.root.child("__symbols__");
+        if labels.is_empty() && existing.is_none() {
+            return None;
+        }
+        let label_names: BTreeSet<&str> = labels.iter().map(|(label, _)| &**label).collect();
+        let removed_labels = collect_removed_labels(&self.root);
+
+        let mut symbols = DeviceTreeNode::new("__symbols__");
+        for property in existing.into_iter().flat_map(DeviceTreeNode::properties) {
+            if label_names.contains(property.name()) {
+                symbols.add_property(property.clone());
+                continue;
+            }
+            if removed_labels.contains(property.name()) {
+                diag::debug!("removing symbol {}", property.name());
+                continue;
+            }
+            match property.as_str() {
+                Ok(path) if node_at(&self.root, path).is_some() => {
+                    symbols.add_property(property.clone());
+                }
+                _ => diag::debug!("removing stale symbol {}", property.name()),
+            }
+        }
+        for (label, path) in &labels {
+            let mut value = String::from(path.as_str());
+            value.push('\0');
+            symbols.add_property(DeviceTreeProperty::new(&**label, value));
+        }
+
+        if existing == Some(&symbols) {
+            None
+        } else {
+            Some(symbols)
+        }
+    }
+}
+
+/// Returns the labels of the nodes of the tree, in depth-first order, along
+/// with the paths of their nodes.
+pub(super) fn collect_labels(root: &DeviceTreeNode) -> Vec<(Arc<str>, DtPathBuf)> {
+    let mut labels = Vec::new();
+    let mut path = DtPathBuf::root();
+    walk(root, |node| {
+        let Some(node) = node else {
+            path.pop();
+            return true;
+        };
+        if !ptr::eq(node, root) {
+            if path.as_path().is_root() && node.name() == "__symbols__" {
+                return false;
+            }
+            path.push(node.name());
+        }
+        for label in &node.labels {
+            labels.push((label.clone(), path.clone()));
+        }
+        true
+    });
+    labels
+}
+
+/// Maps each label of the tree to the path of the first node which has it.
+pub(super) fn label_paths(root: &DeviceTreeNode) -> BTreeMap<Arc<str>, DtPathBuf> {
+    let mut paths = BTreeMap::new();
+    for (label, path) in collect_labels(root) {
+        paths.entry(label).or_insert(path);
+    }
+    paths
+}
+
+/// Returns the labels which have been removed from the nodes of the tree.
+fn collect_removed_labels(root: &DeviceTreeNode) -> BTreeSet<&str> {
+    let mut labels = BTreeSet::new();
+    walk(root, |node| {
+        if let Some(node) = node {
+            labels.extend(node.removed_labels.iter().map(|label| &**label));
+        }
+        true
+    });
+    labels
+}
+
+/// Attaches the labels listed in the `__symbols__` node of `root` to the nodes
+/// they refer to.
+///
+/// This doesn't count as a modification of the nodes, so their encoding in
+/// the blob they were parsed from can still be reused.
+pub(super) fn attach_labels(root: &mut DeviceTreeNode) {
+    let Some(symbols) = root.children.get("__symbols__") else {
+        return;
+    };
+    let symbols: Vec<_> = symbols
+        .properties()
+        .filter_map(|property| {
+            let path = property.as_str().ok()?;
+            Some((property.shared_name().clone(), String::from(path)))
+        })
+        .collect();
+    for (label, path) in symbols {
+        let path = DtPath::new(&path);
+        let node = path.is_absolute().then_some(&mut *root).and_then(|root| {
+            path.segments()
+                .try_fold(root, |node, segment| node.children.get_mut(segment))
+        });
+        match node {
+            Some(node) if !node.labels.contains(&label) => node.labels.push(label),
+            Some(_) => {}
+            None => diag::debug!("symbol {label} refers to missing node {path}"),
+        }
+    }
+}
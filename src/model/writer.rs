@@ -9,37 +9,100 @@
 use alloc::borrow::ToOwned;
 use alloc::collections::btree_map::BTreeMap;
 use alloc::string::String;
+use alloc::vec;
 use alloc::vec::Vec;
+use core::convert::Infallible;
+use core::ops::Bound;
+use core::ptr;
+#[cfg(feature = "std")]
+use std::io;
 
 use zerocopy::IntoBytes;
 
+use crate::error::FdtError;
 use crate::fdt::{
     FDT_BEGIN_NODE, FDT_END, FDT_END_NODE, FDT_MAGIC, FDT_PROP, FDT_TAGSIZE, Fdt, FdtHeader,
+    LAST_COMP_VERSION, LAST_VERSION,
 };
 use crate::memreserve::MemoryReservation;
-use crate::model::{DeviceTree, DeviceTreeNode, DeviceTreeProperty};
-
-// https://devicetree-specification.readthedocs.io/en/latest/chapter5-flattened-format.html#header
-const LAST_VERSION: u32 = 17;
-const LAST_COMP_VERSION: u32 = 16;
+use crate::model::{DeviceTree, DeviceTreeNode, DeviceTreeProperty, references};
 
 impl DeviceTree {
     /// Serializes the [`DeviceTree`] to a flattened device tree blob.
     ///
+    /// # Performance
+    ///
+    /// If the device tree was created with [`DeviceTree::from_fdt`], nodes
+    /// that haven't been modified since, along with all their descendants, are
+    /// copied from the original blob rather than encoded again. Any mutable
+    /// access to a node, such as through [`DeviceTreeNode::child_mut`], counts
+    /// as a modification of it, so small edits to a large tree only re-encode
+    /// the nodes on the path to the edited one.
+    ///
+    /// # Symbols and fixups
+    ///
+    /// [Phandle references](DeviceTreeNode::add_phandle_reference) are
+    /// resolved as by [`DeviceTree::resolve_phandle_references`], which adds
+    /// `__fixups__` and `__local_fixups__` nodes to overlays.
+    ///
+    /// The `__symbols__` node is updated to match the
+    /// [labels](DeviceTreeNode::labels) of the nodes, as by
+    /// [`DeviceTree::update_symbols`], so that it stays valid after labelled
+    /// nodes are renamed, moved or removed.
+    ///
     /// # Panics
     ///
     /// This may panic if any of the lengths written to the DTB (block sizes,
     /// property value length, etc.) exceed [`u32::MAX`].
     #[must_use]
     pub fn to_dtb(&self) -> Vec<u8> {
-        let mut string_map = StringMap::new();
-        let header = self.generate_header(&mut string_map);
+        if references::has_references(&self.root) {
+            let mut tree = self.clone();
+            tree.resolve_phandle_references();
+            return tree.write_dtb(tree.updated_symbols().as_ref());
+        }
+        self.write_dtb(self.updated_symbols().as_ref())
+    }
+
+    /// Serializes the [`DeviceTree`] to a flattened device tree blob, as by
+    /// [`DeviceTree::to_dtb`], and writes it to the given writer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::model::DeviceTree;
+    /// let tree = DeviceTree::new();
+    /// let mut dtb = Vec::new();
+    /// tree.write_dtb_to(&mut dtb).unwrap();
+    /// assert_eq!(dtb, tree.to_dtb());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns any error that occurs when writing.
+    ///
+    /// # Panics
+    ///
+    /// Panics in the same cases as [`DeviceTree::to_dtb`].
+    #[cfg(feature = "std")]
+    pub fn write_dtb_to(&self, mut writer: impl io::Write) -> io::Result<()> {
+        writer.write_all(&self.to_dtb())
+    }
+
+    /// Encodes this tree, with `symbols` in place of its `__symbols__` node if
+    /// given.
+    fn write_dtb(&self, symbols: Option<&DeviceTreeNode>) -> Vec<u8> {
+        let source = self.source.as_ref();
+        let mut string_map = StringMap::new(source.map_or(&[], |buf| buf.fdt().strings_block()));
+        let header = self
+            .generate_header(&mut string_map, symbols)
+            .unwrap_or_else(|error| panic!("{error}"));
 
         let mut dtb = Vec::with_capacity(header.totalsize() as usize);
         dtb.extend_from_slice(header.as_bytes());
 
-        self.write_memory_reservations(&mut dtb);
-        self.write_root(&mut dtb, &string_map);
+        write_memory_reservations(&mut dtb, &self.memory_reservations);
+        self.write_root(&mut dtb, &string_map, symbols);
         string_map.write_string_block(&mut dtb);
 
         debug_assert_eq!(
@@ -53,169 +116,394 @@ impl DeviceTree {
 
     /// Calculate all needed sizes (so that we can pre-allocate the buffer) and
     /// return [`FdtHeader`].
-    #[must_use]
-    fn generate_header(&self, string_map: &mut StringMap) -> FdtHeader {
+    fn generate_header(
+        &self,
+        string_map: &mut StringMap,
+        symbols: Option<&DeviceTreeNode>,
+    ) -> Result<FdtHeader, FdtError> {
         // entries + terminator
         let mem_reservations_size =
             (self.memory_reservations.len() + 1) * size_of::<MemoryReservation>();
         // +FDT_TAGSIZE for FDT_END
-        let dt_struct_size = Self::calculate_node_size(string_map, &self.root) + FDT_TAGSIZE;
-        let dt_strings_size = string_map.next_offset as usize;
-
-        let header_size = size_of::<FdtHeader>();
-        let off_mem_rsvmap = header_size;
-        let off_dt_struct = off_mem_rsvmap + mem_reservations_size;
-        let off_dt_strings = off_dt_struct + dt_struct_size;
-        let totalsize = off_dt_strings + dt_strings_size;
-
-        let size_dt_strings = totalsize - off_dt_strings;
-        let size_dt_struct = off_dt_strings - off_dt_struct;
-
-        FdtHeader {
-            magic: FDT_MAGIC.into(),
-            totalsize: u32::try_from(totalsize)
-                .expect("totalsize exceeds u32")
-                .into(),
-            off_dt_struct: u32::try_from(off_dt_struct)
-                .expect("off_dt_struct exceeds u32")
-                .into(),
-            off_dt_strings: u32::try_from(off_dt_strings)
-                .expect("off_dt_strings exceeds u32")
-                .into(),
-            off_mem_rsvmap: u32::try_from(off_mem_rsvmap)
-                .expect("off_mem_rsvmap exceeds u32")
-                .into(),
-            version: LAST_VERSION.into(),
-            last_comp_version: LAST_COMP_VERSION.into(),
-            boot_cpuid_phys: 0u32.into(),
-            size_dt_strings: u32::try_from(size_dt_strings)
-                .expect("size_dt_strings exceeds u32")
-                .into(),
-            size_dt_struct: u32::try_from(size_dt_struct)
-                .expect("size_dt_struct exceeds u32")
-                .into(),
-        }
+        let dt_struct_size = self.calculate_node_size(string_map, symbols)? + FDT_TAGSIZE;
+        let dt_strings_size = string_map.size();
+
+        Ok(header(
+            mem_reservations_size,
+            dt_struct_size,
+            dt_strings_size,
+        ))
     }
 
-    fn calculate_node_size(string_map: &mut StringMap, node: &DeviceTreeNode) -> usize {
+    fn calculate_node_size(
+        &self,
+        string_map: &mut StringMap,
+        symbols: Option<&DeviceTreeNode>,
+    ) -> Result<usize, FdtError> {
         let mut size = 0;
-        size += FDT_TAGSIZE; // FDT_BEGIN_NODE
-
-        // name + null terminator + padding
-        let name_len = node.name().len() + 1;
-        size += Fdt::align_tag_offset(name_len);
+        let mut result = Ok(());
+        self.walk_nodes(symbols, |node| {
+            if result.is_err() {
+                return false;
+            }
+            let Some(node) = node else {
+                size += FDT_TAGSIZE; // FDT_END_NODE
+                return false;
+            };
+            if let Some(encoded) = self.unmodified_encoding(node, symbols) {
+                size += encoded.len();
+                return false;
+            }
+
+            size += FDT_TAGSIZE; // FDT_BEGIN_NODE
+
+            // name + null terminator + padding
+            let name_len = node.name().len() + 1;
+            size += Fdt::align_tag_offset(name_len);
+
+            for prop in node.properties() {
+                match Self::calculate_prop_size(string_map, prop) {
+                    Ok(prop_size) => size += prop_size,
+                    Err(error) => {
+                        result = Err(error);
+                        return false;
+                    }
+                }
+            }
+            true
+        });
+        result.map(|()| size)
+    }
 
-        for prop in node.properties() {
-            size += Self::calculate_prop_size(string_map, prop);
+    /// Returns the encoding of the given node and its descendants in the blob
+    /// this device tree was parsed from, if they haven't been modified since.
+    ///
+    /// Nodes moved over from another device tree aren't reused, as their
+    /// property names refer to a different strings block, and neither is the
+    /// root node if its `__symbols__` node is replaced with `symbols`.
+    fn unmodified_encoding<'a>(
+        &'a self,
+        node: &'a DeviceTreeNode,
+        symbols: Option<&DeviceTreeNode>,
+    ) -> Option<&'a [u8]> {
+        if symbols.is_some() && ptr::eq(node, &raw const self.root) {
+            return None;
         }
-
-        for child in node.children() {
-            size += Self::calculate_node_size(string_map, child);
+        let node_source = node.source.as_ref()?;
+        if Some(&node_source.buf) == self.source.as_ref() {
+            node_source.buf.data().get(node_source.range.clone())
+        } else {
+            None
         }
-
-        size += FDT_TAGSIZE; // FDT_END_NODE
-        size
     }
 
-    fn calculate_prop_size(string_map: &mut StringMap, prop: &DeviceTreeProperty) -> usize {
+    fn calculate_prop_size(
+        string_map: &mut StringMap,
+        prop: &DeviceTreeProperty,
+    ) -> Result<usize, FdtError> {
         let mut size = 0;
         size += FDT_TAGSIZE; // FDT_PROP
         size += size_of::<u32>(); // len
         size += size_of::<u32>(); // nameoff
 
         // ensure the name is in the map
-        string_map.insert(prop.name());
+        string_map.insert(prop.name())?;
 
         // value + padding
         size += Fdt::align_tag_offset(prop.value().len());
-        size
+        Ok(size)
     }
 
-    fn write_memory_reservations(&self, dtb: &mut Vec<u8>) {
-        for reservation in &self.memory_reservations {
-            dtb.extend_from_slice(reservation.as_bytes());
-        }
-        dtb.extend_from_slice(MemoryReservation::TERMINATOR.as_bytes());
-    }
-
-    fn write_root(&self, dtb: &mut Vec<u8>, string_map: &StringMap) {
-        Self::write_node(dtb, string_map, &self.root);
+    fn write_root(
+        &self,
+        dtb: &mut Vec<u8>,
+        string_map: &StringMap,
+        symbols: Option<&DeviceTreeNode>,
+    ) {
+        self.write_node(dtb, string_map, symbols);
         dtb.extend_from_slice(&FDT_END.to_be_bytes());
     }
 
-    fn write_node(dtb: &mut Vec<u8>, string_map: &StringMap, node: &DeviceTreeNode) {
-        dtb.extend_from_slice(&FDT_BEGIN_NODE.to_be_bytes());
-        dtb.extend_from_slice(node.name().as_bytes());
-        dtb.push(0);
-        Self::align(dtb);
+    fn write_node(
+        &self,
+        dtb: &mut Vec<u8>,
+        string_map: &StringMap,
+        symbols: Option<&DeviceTreeNode>,
+    ) {
+        self.walk_nodes(symbols, |node| {
+            let Some(node) = node else {
+                dtb.extend_from_slice(&FDT_END_NODE.to_be_bytes());
+                return false;
+            };
+            if let Some(encoded) = self.unmodified_encoding(node, symbols) {
+                dtb.extend_from_slice(encoded);
+                return false;
+            }
+
+            let Ok(()) = write_node_head(dtb, node, |name| {
+                Ok::<_, Infallible>(string_map.get_offset(name))
+            });
+            true
+        });
+    }
 
-        for prop in node.properties() {
-            Self::write_prop(dtb, string_map, prop);
+    /// Visits the nodes of this tree as by [`walk`], with `symbols` in place
+    /// of the `__symbols__` node if given, or after the other children of the
+    /// root node if there is none.
+    fn walk_nodes<'a>(
+        &'a self,
+        symbols: Option<&'a DeviceTreeNode>,
+        mut visit: impl FnMut(Option<&'a DeviceTreeNode>) -> bool,
+    ) {
+        let Some(symbols) = symbols else {
+            return walk(&self.root, visit);
+        };
+        if !visit(Some(&self.root)) {
+            return;
         }
-
-        for child in node.children() {
-            Self::write_node(dtb, string_map, child);
+        let mut replaced = false;
+        for child in self.root.children() {
+            if child.name() == "__symbols__" {
+                replaced = true;
+                walk(symbols, &mut visit);
+            } else {
+                walk(child, &mut visit);
+            }
         }
+        if !replaced {
+            walk(symbols, &mut visit);
+        }
+        visit(None);
+    }
+}
 
-        dtb.extend_from_slice(&FDT_END_NODE.to_be_bytes());
+/// Builds the header of a blob with blocks of the given sizes, laid out one
+/// after the other.
+pub(super) fn header(
+    mem_reservations_size: usize,
+    dt_struct_size: usize,
+    dt_strings_size: usize,
+) -> FdtHeader {
+    let header_size = size_of::<FdtHeader>();
+    let off_mem_rsvmap = header_size;
+    let off_dt_struct = off_mem_rsvmap + mem_reservations_size;
+    let off_dt_strings = off_dt_struct + dt_struct_size;
+    let totalsize = off_dt_strings + dt_strings_size;
+
+    let size_dt_strings = totalsize - off_dt_strings;
+    let size_dt_struct = off_dt_strings - off_dt_struct;
+
+    FdtHeader {
+        magic: FDT_MAGIC.into(),
+        totalsize: u32::try_from(totalsize)
+            .expect("totalsize exceeds u32")
+            .into(),
+        off_dt_struct: u32::try_from(off_dt_struct)
+            .expect("off_dt_struct exceeds u32")
+            .into(),
+        off_dt_strings: u32::try_from(off_dt_strings)
+            .expect("off_dt_strings exceeds u32")
+            .into(),
+        off_mem_rsvmap: u32::try_from(off_mem_rsvmap)
+            .expect("off_mem_rsvmap exceeds u32")
+            .into(),
+        version: LAST_VERSION.into(),
+        last_comp_version: LAST_COMP_VERSION.into(),
+        boot_cpuid_phys: 0u32.into(),
+        size_dt_strings: u32::try_from(size_dt_strings)
+            .expect("size_dt_strings exceeds u32")
+            .into(),
+        size_dt_struct: u32::try_from(size_dt_struct)
+            .expect("size_dt_struct exceeds u32")
+            .into(),
     }
+}
 
-    fn write_prop(dtb: &mut Vec<u8>, string_map: &StringMap, prop: &DeviceTreeProperty) {
-        let name_offset = string_map.get_offset(prop.name());
+pub(super) fn write_memory_reservations(dtb: &mut Vec<u8>, reservations: &[MemoryReservation]) {
+    for reservation in reservations {
+        dtb.extend_from_slice(reservation.as_bytes());
+    }
+    dtb.extend_from_slice(MemoryReservation::TERMINATOR.as_bytes());
+}
 
-        dtb.extend_from_slice(&FDT_PROP.to_be_bytes());
-        dtb.extend_from_slice(
-            &u32::try_from(prop.value().len())
-                .expect("property value length exceeds u32")
-                .to_be_bytes(),
-        );
-        dtb.extend_from_slice(&name_offset.to_be_bytes());
-        dtb.extend_from_slice(prop.value());
-        Self::align(dtb);
+/// Writes the `FDT_BEGIN_NODE` token, name and properties of the given node,
+/// looking up the offsets of property names with `name_offset`.
+pub(super) fn write_node_head<E>(
+    dtb: &mut Vec<u8>,
+    node: &DeviceTreeNode,
+    mut name_offset: impl FnMut(&str) -> Result<u32, E>,
+) -> Result<(), E> {
+    dtb.extend_from_slice(&FDT_BEGIN_NODE.to_be_bytes());
+    dtb.extend_from_slice(node.name().as_bytes());
+    dtb.push(0);
+    align(dtb);
+
+    for prop in node.properties() {
+        write_prop(dtb, name_offset(prop.name())?, prop);
     }
+    Ok(())
+}
 
-    fn align(vec: &mut Vec<u8>) {
-        let len = vec.len();
-        let new_len = Fdt::align_tag_offset(len);
-        vec.resize(new_len, 0);
+fn write_prop(dtb: &mut Vec<u8>, name_offset: u32, prop: &DeviceTreeProperty) {
+    dtb.extend_from_slice(&FDT_PROP.to_be_bytes());
+    dtb.extend_from_slice(
+        &u32::try_from(prop.value().len())
+            .expect("property value length exceeds u32")
+            .to_be_bytes(),
+    );
+    dtb.extend_from_slice(&name_offset.to_be_bytes());
+    dtb.extend_from_slice(prop.value());
+    align(dtb);
+}
+
+fn align(vec: &mut Vec<u8>) {
+    let len = vec.len();
+    let new_len = Fdt::align_tag_offset(len);
+    vec.resize(new_len, 0);
+}
+
+/// Visits `root` and its descendants in depth-first order without recursing.
+///
+/// `visit` is called with each node when entering it, and returns whether to
+/// descend into the node. If it does, `visit` is called with `None` after the
+/// node's descendants, when leaving it. Otherwise the node's descendants are
+/// skipped.
+pub(super) fn walk<'a>(
+    root: &'a DeviceTreeNode,
+    mut visit: impl FnMut(Option<&'a DeviceTreeNode>) -> bool,
+) {
+    if !visit(Some(root)) {
+        return;
+    }
+    let mut stack = vec![root.children()];
+    while let Some(children) = stack.last_mut() {
+        if let Some(child) = children.next() {
+            if visit(Some(child)) {
+                stack.push(child.children());
+            }
+        } else {
+            stack.pop();
+            visit(None);
+        }
     }
 }
 
-struct StringMap {
-    string_map: BTreeMap<String, u32>,
-    next_offset: u32,
+/// The strings block being built, mapping property names to their offsets.
+///
+/// If the device tree was parsed from a blob, its strings block is kept as is
+/// and names found in it reuse their original offsets, including names
+/// sharing the tail of a longer string as `dtc` lays them out. Other names are
+/// appended in the order they first appear.
+pub(super) struct StringMap<'a> {
+    source: &'a [u8],
+    /// The offset of the first occurrence of each string in `source`.
+    source_offsets: BTreeMap<&'a [u8], usize>,
+    /// The end offset of each string in `source`, keyed by the string
+    /// reversed, so that the strings ending with a name sort right after it.
+    ///
+    /// This is only built once a name isn't found among the whole strings.
+    source_tails: Option<BTreeMap<Vec<u8>, usize>>,
+    offsets: BTreeMap<String, u32>,
+    next_offset: usize,
 }
 
-impl StringMap {
+impl<'a> StringMap<'a> {
     #[must_use]
-    fn new() -> Self {
+    pub(super) fn new(source: &'a [u8]) -> Self {
+        let mut source_offsets = BTreeMap::new();
+        for (string, start) in Self::source_strings(source) {
+            source_offsets.entry(string).or_insert(start);
+        }
         Self {
-            string_map: BTreeMap::new(),
-            next_offset: 0,
+            source,
+            source_offsets,
+            source_tails: None,
+            offsets: BTreeMap::new(),
+            next_offset: source.len(),
         }
     }
 
-    fn insert(&mut self, key: &str) {
-        if !self.string_map.contains_key(key) {
+    /// Returns the NUL-terminated strings of `source`, along with their
+    /// offsets.
+    fn source_strings(source: &[u8]) -> impl Iterator<Item = (&[u8], usize)> {
+        let mut start = 0;
+        core::iter::from_fn(move || {
+            let len = source[start..].iter().position(|&byte| byte == 0)?;
+            let string = (&source[start..start + len], start);
+            start += len + 1;
+            Some(string)
+        })
+    }
+
+    /// Adds the given name to the strings block if it isn't there yet, and
+    /// returns its offset.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FdtError::CapacityExceeded`] if the offset of the name
+    /// doesn't fit in a u32.
+    pub(super) fn insert(&mut self, key: &str) -> Result<u32, FdtError> {
+        if let Some(&offset) = self.offsets.get(key) {
+            return Ok(offset);
+        }
+        let offset = if let Some(offset) = self.source_offset(key.as_bytes()) {
+            offset
+        } else {
             let offset = self.next_offset;
-            self.string_map.insert(key.to_owned(), offset);
-            self.next_offset = u32::try_from(self.next_offset as usize + key.len() + 1)
-                .expect("string block length exceeds u32");
+            self.next_offset += key.len() + 1;
+            offset
+        };
+        let offset =
+            u32::try_from(offset).map_err(|_| FdtError::CapacityExceeded("strings block"))?;
+        self.offsets.insert(key.to_owned(), offset);
+        Ok(offset)
+    }
+
+    /// Returns the offset of a string in `source` which is equal to or ends
+    /// with `key`, if any.
+    fn source_offset(&mut self, key: &[u8]) -> Option<usize> {
+        if let Some(&offset) = self.source_offsets.get(key) {
+            return Some(offset);
         }
+        let source = self.source;
+        let tails = self.source_tails.get_or_insert_with(|| {
+            Self::source_strings(source)
+                .map(|(string, start)| {
+                    (string.iter().rev().copied().collect(), start + string.len())
+                })
+                .collect()
+        });
+        let reversed: Vec<u8> = key.iter().rev().copied().collect();
+        let (string, &end) = tails
+            .range::<[u8], _>((Bound::Included(reversed.as_slice()), Bound::Unbounded))
+            .next()?;
+        string.starts_with(&reversed).then(|| end - key.len())
+    }
+
+    /// Returns the size of the strings block.
+    pub(super) fn size(&self) -> usize {
+        self.next_offset
     }
 
     #[must_use]
     fn get_offset(&self, key: &str) -> u32 {
         *self
-            .string_map
+            .offsets
             .get(key)
             .expect("the key should have been inserted at the size calculation step")
     }
 
-    fn write_string_block(self, dtb: &mut Vec<u8>) {
-        // write the strings in the order when they appear, mimicking the behavior
-        // of `dtc` (Device Tree Compiler)
-        let mut items: Vec<_> = self.string_map.into_iter().collect();
+    pub(super) fn write_string_block(self, dtb: &mut Vec<u8>) {
+        dtb.extend_from_slice(self.source);
+
+        // write the new strings in the order when they appear, mimicking the
+        // behavior of `dtc` (Device Tree Compiler)
+        let source_len = self.source.len();
+        let mut items: Vec<_> = self
+            .offsets
+            .into_iter()
+            .filter(|(_s, offset)| *offset as usize >= source_len)
+            .collect();
         items.sort_unstable_by_key(|(_s, offset)| *offset);
 
         for (s, _offset) in items {
@@ -224,3 +512,72 @@ impl StringMap {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reuses_unmodified_nodes() {
+        let dtb = include_bytes!("../../tests/dtb/test_props.dtb");
+        let mut tree = DeviceTree::from_fdt(&Fdt::new(dtb).unwrap()).unwrap();
+        assert_eq!(
+            tree.unmodified_encoding(&tree.root, None),
+            Some(&dtb[56..492])
+        );
+        let symbols = DeviceTreeNode::new("__symbols__");
+        assert!(
+            tree.unmodified_encoding(&tree.root, Some(&symbols))
+                .is_none()
+        );
+
+        tree.find_node_mut("/standard-props@1")
+            .unwrap()
+            .property_mut("model")
+            .unwrap()
+            .set_value("Other Model\0");
+        assert!(tree.unmodified_encoding(&tree.root, None).is_none());
+        let test_props = tree.root.child("test-props").unwrap();
+        assert!(tree.unmodified_encoding(test_props, None).is_some());
+        let standard_props = tree.root.child("standard-props@1").unwrap();
+        assert!(tree.unmodified_encoding(standard_props, None).is_none());
+
+        let new_dtb = tree.to_dtb();
+        let new_tree = DeviceTree::from_fdt(&Fdt::new(&new_dtb).unwrap()).unwrap();
+        assert_eq!(new_tree, tree);
+    }
+
+    #[test]
+    fn encodes_nodes_from_other_trees() {
+        let dtb = include_bytes!("../../tests/dtb/test_strings.dtb");
+        let mut other = DeviceTree::from_fdt(&Fdt::new(dtb).unwrap()).unwrap();
+        let node = other.root.remove_child("node").unwrap();
+
+        let dtb = include_bytes!("../../tests/dtb/test_props.dtb");
+        let mut tree = DeviceTree::from_fdt(&Fdt::new(dtb).unwrap()).unwrap();
+        tree.root.add_child(node);
+        assert!(
+            tree.unmodified_encoding(tree.root.child("node").unwrap(), None)
+                .is_none()
+        );
+
+        let new_dtb = tree.to_dtb();
+        let new_tree = DeviceTree::from_fdt(&Fdt::new(&new_dtb).unwrap()).unwrap();
+        assert_eq!(new_tree, tree);
+    }
+    #[test]
+    fn string_map_reuses_tails() {
+        let mut string_map = StringMap::new(b"foo-reg\0reg\0bar\0");
+        assert_eq!(string_map.insert("reg"), Ok(8));
+        assert_eq!(string_map.insert("-reg"), Ok(3));
+        assert_eq!(string_map.insert("ar"), Ok(13));
+        assert_eq!(string_map.insert("oo"), Ok(16));
+        assert_eq!(string_map.insert("o-re"), Ok(19));
+        assert_eq!(string_map.insert("oo"), Ok(16));
+        assert_eq!(string_map.size(), 24);
+
+        let mut dtb = Vec::new();
+        string_map.write_string_block(&mut dtb);
+        assert_eq!(dtb, b"foo-reg\0reg\0bar\0oo\0o-re\0");
+    }
+}
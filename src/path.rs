@@ -0,0 +1,408 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Paths of nodes in a device tree.
+//!
+//! [`DtPath`] borrows a path and interprets it without allocating, ignoring
+//! repeated and trailing slashes. With the `alloc` feature, `DtPathBuf` is
+//! an owned path which is always kept in its normalized form.
+
+#[cfg(feature = "alloc")]
+use alloc::string::{String, ToString};
+use core::fmt::{self, Display, Formatter};
+
+/// A borrowed path of a node in a device tree, such as `/soc/serial@1000`.
+///
+/// Empty segments are ignored, so `//soc/serial@1000/` refers to the same
+/// node as `/soc/serial@1000`. Paths which don't start with `/` are relative.
+///
+/// # Examples
+///
+/// ```
+/// # use dtoolkit::path::DtPath;
+/// let path = DtPath::new("//soc/serial@1000/");
+/// assert!(path.is_absolute());
+/// assert!(path.segments().eq(["soc", "serial@1000"]));
+/// assert_eq!(path.name(), Some("serial@1000"));
+/// assert_eq!(path.parent(), Some(DtPath::new("/soc")));
+/// assert_eq!(path.to_string(), "/soc/serial@1000");
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct DtPath<'a> {
+    path: &'a str,
+}
+
+impl<'a> DtPath<'a> {
+    /// Creates a path from the given string.
+    #[must_use]
+    pub const fn new(path: &'a str) -> Self {
+        Self { path }
+    }
+
+    /// Returns the path as it was given, without normalizing it.
+    #[must_use]
+    pub fn as_str(&self) -> &'a str {
+        self.path
+    }
+
+    /// Returns whether the path starts at the root node.
+    #[must_use]
+    pub fn is_absolute(&self) -> bool {
+        self.path.starts_with('/')
+    }
+
+    /// Returns whether this is the path of the root node.
+    #[must_use]
+    pub fn is_root(&self) -> bool {
+        self.is_absolute() && self.segments().next().is_none()
+    }
+
+    /// Returns an iterator over the node names in the path, skipping empty
+    /// segments.
+    #[must_use]
+    pub fn segments(&self) -> impl DoubleEndedIterator<Item = &'a str> + use<'a> {
+        self.path.split('/').filter(|segment| !segment.is_empty())
+    }
+
+    /// Returns the name of the node the path refers to, or `None` for the root
+    /// node or an empty path.
+    #[must_use]
+    pub fn name(&self) -> Option<&'a str> {
+        self.segments().next_back()
+    }
+
+    /// Returns the path of the parent of the node, or `None` for the root node
+    /// or a relative path with a single segment.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::path::DtPath;
+    /// assert_eq!(DtPath::new("/soc").parent(), Some(DtPath::new("/")));
+    /// assert_eq!(DtPath::new("/").parent(), None);
+    /// assert_eq!(DtPath::new("a/b").parent(), Some(DtPath::new("a")));
+    /// assert_eq!(DtPath::new("a").parent(), None);
+    /// ```
+    #[must_use]
+    pub fn parent(&self) -> Option<Self> {
+        let trimmed = self.path.trim_end_matches('/');
+        if trimmed.is_empty() {
+            return None;
+        }
+        let parent = match trimmed.rfind('/') {
+            Some(end) => trimmed[..end].trim_end_matches('/'),
+            None => return None,
+        };
+        Some(Self::new(if parent.is_empty() { "/" } else { parent }))
+    }
+
+    /// Splits a relative path into the alias it starts with and the rest of
+    /// the path, or returns `None` for an absolute or empty path.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::path::DtPath;
+    /// let (alias, rest) = DtPath::new("serial0/child").split_alias().unwrap();
+    /// assert_eq!(alias, "serial0");
+    /// assert!(rest.segments().eq(["child"]));
+    /// assert!(DtPath::new("/serial0").split_alias().is_none());
+    /// ```
+    #[must_use]
+    pub fn split_alias(&self) -> Option<(&'a str, Self)> {
+        if self.is_absolute() {
+            return None;
+        }
+        let (alias, rest) = self.path.split_once('/').unwrap_or((self.path, ""));
+        (!alias.is_empty()).then(|| (alias, Self::new(rest)))
+    }
+
+    /// Returns whether this path matches the path of the given node,
+    /// following the lookup rules of
+    /// [`Fdt::find_node`](crate::fdt::Fdt::find_node).
+    ///
+    /// A segment of this path without a unit-address matches a node with the
+    /// same name and any unit-address, while a segment with a unit-address
+    /// must match exactly.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::path::DtPath;
+    /// let node = DtPath::new("/soc/serial@1000");
+    /// assert!(DtPath::new("/soc/serial").matches(node));
+    /// assert!(DtPath::new("/soc/serial@1000").matches(node));
+    /// assert!(!DtPath::new("/soc/serial@2000").matches(node));
+    /// assert!(!DtPath::new("/soc").matches(node));
+    /// ```
+    #[must_use]
+    pub fn matches(&self, node_path: DtPath<'_>) -> bool {
+        let mut segments = self.segments();
+        let mut node_segments = node_path.segments();
+        self.is_absolute() == node_path.is_absolute()
+            && segments
+                .by_ref()
+                .zip(node_segments.by_ref())
+                .all(|(segment, node_segment)| segment_matches(segment, node_segment))
+            && segments.next().is_none()
+            && node_segments.next().is_none()
+    }
+
+    /// Returns this path with `path` appended to it, or `path` itself if it is
+    /// absolute.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::path::DtPath;
+    /// let soc = DtPath::new("/soc/");
+    /// assert_eq!(soc.join("serial@1000").as_str(), "/soc/serial@1000");
+    /// assert_eq!(soc.join("/cpus").as_str(), "/cpus");
+    /// ```
+    #[cfg(feature = "alloc")]
+    #[must_use]
+    pub fn join(&self, path: &str) -> DtPathBuf {
+        let mut joined = DtPathBuf::from(*self);
+        joined.push(path);
+        joined
+    }
+}
+
+/// Returns whether a path segment matches the name of a node, following the
+/// lookup rules for unit-addresses.
+pub(crate) fn segment_matches(segment: &str, name: &str) -> bool {
+    if segment.contains('@') {
+        name == segment
+    } else {
+        name.split_once('@').map_or(name, |(name, _)| name) == segment
+    }
+}
+
+/// Returns whether a path segment with `*` wildcards matches the name of a
+/// node, following the same lookup rules for unit-addresses as
+/// [`segment_matches`].
+pub(crate) fn glob_segment_matches(segment: &str, name: &str) -> bool {
+    let name = if segment.contains('@') {
+        name
+    } else {
+        name.split_once('@').map_or(name, |(name, _)| name)
+    };
+    glob_matches(segment.as_bytes(), name.as_bytes())
+}
+
+/// Returns whether `text` matches `pattern`, where `*` matches any sequence of
+/// bytes.
+fn glob_matches(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    // The position in the pattern after the last `*`, and the position in the
+    // text it has matched up to.
+    let mut star = None;
+    while t < text.len() {
+        if pattern.get(p) == Some(&b'*') {
+            p += 1;
+            star = Some((p, t));
+        } else if pattern.get(p) == Some(&text[t]) {
+            p += 1;
+            t += 1;
+        } else if let Some((star_p, star_t)) = star {
+            // Let the last `*` match one more byte and try again.
+            p = star_p;
+            t = star_t + 1;
+            star = Some((star_p, t));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&byte| byte == b'*')
+}
+
+/// Paths are equal if they have the same segments, regardless of repeated or
+/// trailing slashes.
+impl PartialEq for DtPath<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.is_absolute() == other.is_absolute() && self.segments().eq(other.segments())
+    }
+}
+
+impl Eq for DtPath<'_> {}
+
+/// Formats the path in its normalized form.
+impl Display for DtPath<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let mut segments = self.segments();
+        match segments.next() {
+            Some(first) if self.is_absolute() => write!(f, "/{first}")?,
+            Some(first) => f.write_str(first)?,
+            None if self.is_absolute() => return f.write_str("/"),
+            None => return Ok(()),
+        }
+        for segment in segments {
+            write!(f, "/{segment}")?;
+        }
+        Ok(())
+    }
+}
+
+impl<'a> From<&'a str> for DtPath<'a> {
+    fn from(path: &'a str) -> Self {
+        Self::new(path)
+    }
+}
+
+/// An owned, normalized path of a node in a device tree.
+///
+/// # Examples
+///
+/// ```
+/// # use dtoolkit::path::DtPathBuf;
+/// let mut path = DtPathBuf::from("/soc//");
+/// path.push("serial@1000/");
+/// assert_eq!(path.as_str(), "/soc/serial@1000");
+/// assert!(path.pop());
+/// assert_eq!(path.as_str(), "/soc");
+/// ```
+#[cfg(feature = "alloc")]
+#[derive(Clone, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct DtPathBuf {
+    path: String,
+}
+
+#[cfg(feature = "alloc")]
+impl DtPathBuf {
+    /// Returns the path of the root node.
+    #[must_use]
+    pub fn root() -> Self {
+        Self {
+            path: String::from("/"),
+        }
+    }
+
+    /// Returns the normalized path.
+    #[must_use]
+    pub fn as_str(&self) -> &str {
+        &self.path
+    }
+
+    /// Borrows this path as a [`DtPath`].
+    #[must_use]
+    pub fn as_path(&self) -> DtPath<'_> {
+        DtPath::new(&self.path)
+    }
+
+    /// Appends the segments of `path` to this path, or replaces it if `path`
+    /// is absolute.
+    pub fn push(&mut self, path: &str) {
+        let path = DtPath::new(path);
+        if path.is_absolute() {
+            self.path.clear();
+            self.path.push('/');
+        }
+        for segment in path.segments() {
+            if !self.path.is_empty() && !self.path.ends_with('/') {
+                self.path.push('/');
+            }
+            self.path.push_str(segment);
+        }
+    }
+
+    /// Removes the last segment of this path, returning whether there was
+    /// one to remove.
+    pub fn pop(&mut self) -> bool {
+        match self.as_path().parent() {
+            Some(parent) => {
+                let len = parent.as_str().len();
+                self.path.truncate(len);
+                true
+            }
+            None if !self.as_path().is_root() && !self.path.is_empty() => {
+                self.path.clear();
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<DtPath<'_>> for DtPathBuf {
+    fn from(path: DtPath<'_>) -> Self {
+        Self {
+            path: path.to_string(),
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl From<&str> for DtPathBuf {
+    fn from(path: &str) -> Self {
+        DtPath::new(path).into()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl Display for DtPathBuf {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(&self.path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize() {
+        for (path, normalized) in [
+            ("/", "/"),
+            ("//", "/"),
+            ("", ""),
+            ("/a//b/", "/a/b"),
+            ("a/b//", "a/b"),
+        ] {
+            assert_eq!(DtPath::new(path).to_string(), normalized);
+            assert_eq!(DtPath::new(path), DtPath::new(normalized));
+        }
+        assert_ne!(DtPath::new("/a"), DtPath::new("a"));
+    }
+
+    #[test]
+    fn glob() {
+        assert!(glob_matches(b"uart@*", b"uart@1000"));
+        assert!(glob_matches(b"*", b""));
+        assert!(glob_matches(b"*@*0", b"uart@1000"));
+        assert!(glob_matches(b"a*b*c", b"axxbyybc"));
+        assert!(!glob_matches(b"a*b*c", b"axxbyyb"));
+        assert!(!glob_matches(b"uart", b"uart@1000"));
+        assert!(glob_segment_matches("uart", "uart@1000"));
+        assert!(glob_segment_matches("ua*", "uart@1000"));
+        assert!(!glob_segment_matches("uart@2*", "uart@1000"));
+    }
+
+    #[cfg(feature = "alloc")]
+    #[test]
+    fn push_and_pop() {
+        let mut path = DtPathBuf::root();
+        path.push("a");
+        path.push("b//c");
+        assert_eq!(path.as_str(), "/a/b/c");
+        assert!(path.pop());
+        assert!(path.pop());
+        assert_eq!(path.as_str(), "/a");
+        assert!(path.pop());
+        assert_eq!(path.as_str(), "/");
+        assert!(!path.pop());
+
+        let mut path = DtPathBuf::from("a");
+        path.push("b");
+        assert_eq!(path.as_str(), "a/b");
+        assert!(path.pop());
+        assert!(path.pop());
+        assert_eq!(path.as_str(), "");
+        assert!(!path.pop());
+    }
+}
@@ -0,0 +1,598 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A small query language for selecting device tree nodes.
+//!
+//! A [`Query`] is compiled once from an XPath-like string, and can then be
+//! evaluated against a flattened device tree with [`Query::select`] or, with
+//! the `write` feature, against a [`DeviceTree`] with [`Query::select_tree`].
+//!
+//! # Syntax
+//!
+//! A query is a sequence of steps, each starting with `/` to select children
+//! of the nodes selected so far, or `//` to select descendants at any depth.
+//! Each step has a node name, in which `*` matches any sequence of characters,
+//! and as with [`Fdt::find_node`] a name without a `@` matches nodes with any
+//! unit address. The query `/` selects the root node.
+//!
+//! A step may be followed by any number of predicates in square brackets,
+//! which must all hold for a node to be selected. A predicate is made of:
+//!
+//! - `@name`, which holds if the node has a property called `name`.
+//! - `@name = 'string'`, which holds if the property is a string list
+//!   containing the string, so `@compatible = 'ns16550a'` selects nodes
+//!   compatible with `ns16550a` whatever their other compatible strings are.
+//! - `@name = 42` or `@name = 0x2a`, which holds if the property is a single
+//!   u32 or u64 with that value.
+//! - `@name != ...`, which holds if the `=` predicate doesn't, including if
+//!   the node has no such property.
+//! - `and`, `or`, `not(...)` and parentheses, with `and` binding tighter than
+//!   `or`.
+//!
+//! Strings may be quoted with `'` or `"`, and can't contain the quote.
+//!
+//! # Examples
+//!
+//! ```
+//! use dtoolkit::fdt::Fdt;
+//! use dtoolkit::query::Query;
+//!
+//! # let dtb = include_bytes!("../tests/dtb/test_props.dtb");
+//! let fdt = Fdt::new(dtb).unwrap();
+//! let query = Query::new("//*[@compatible = 'some,other' and @phandle != 0]").unwrap();
+//! let nodes = query.select(fdt).unwrap();
+//! assert_eq!(nodes.len(), 1);
+//! assert_eq!(nodes[0].name(), Ok("standard-props@1"));
+//! ```
+
+use alloc::boxed::Box;
+use alloc::string::{String, ToString};
+use alloc::vec;
+use alloc::vec::Vec;
+#[cfg(feature = "write")]
+use core::convert::Infallible;
+use core::str::FromStr;
+
+use thiserror::Error;
+
+use crate::error::{FdtErrorKind, FdtParseError};
+use crate::fdt::{Fdt, FdtNode, MAX_DESCENDANTS_DEPTH};
+#[cfg(feature = "write")]
+use crate::model::{DeviceTree, DeviceTreeNode, DeviceTreeProperty};
+use crate::path;
+
+/// The maximum number of steps in a query, as the steps matched so far are
+/// tracked as the bits of a u64.
+const MAX_STEPS: usize = 63;
+
+/// The maximum number of parentheses and `not`s a predicate may be nested in,
+/// so that parsing, evaluating and dropping it doesn't overflow the stack.
+const MAX_NESTING: usize = 32;
+
+/// An error that can occur when compiling a [`Query`].
+#[derive(Clone, Debug, Eq, Error, PartialEq)]
+#[error("{message} at offset {offset} of query")]
+#[non_exhaustive]
+pub struct QueryError {
+    /// The byte offset in the query at which the error was found.
+    pub offset: usize,
+    /// A description of the error.
+    pub message: &'static str,
+    code: u16,
+}
+
+impl QueryError {
+    /// Returns the stable numeric code of this error, in the 400s. See the
+    /// [`error`](crate::error) module for the ranges of codes.
+    #[must_use]
+    pub const fn code(&self) -> u16 {
+        self.code
+    }
+}
+
+/// The ways in which a query can be invalid, with their codes.
+#[derive(Clone, Copy, Debug)]
+enum Invalid {
+    ExpectedSlash = 400,
+    ExpectedNodeName = 401,
+    ExpectedCloseBracket = 402,
+    ExpectedOpenParen = 403,
+    ExpectedPredicate = 404,
+    ExpectedPropertyName = 405,
+    ExpectedCloseParen = 406,
+    UnterminatedString = 407,
+    ExpectedLiteral = 408,
+    TooManySteps = 409,
+    TooDeep = 410,
+}
+
+impl Invalid {
+    fn message(self) -> &'static str {
+        match self {
+            Self::ExpectedSlash => "expected `/`",
+            Self::ExpectedNodeName => "expected a node name",
+            Self::ExpectedCloseBracket => "expected `]`",
+            Self::ExpectedOpenParen => "expected `(`",
+            Self::ExpectedPredicate => "expected `@`, `(` or `not`",
+            Self::ExpectedPropertyName => "expected a property name",
+            Self::ExpectedCloseParen => "expected `)`",
+            Self::UnterminatedString => "unterminated string",
+            Self::ExpectedLiteral => "expected a string or number",
+            Self::TooManySteps => "too many steps",
+            Self::TooDeep => "predicate nested too deeply",
+        }
+    }
+}
+
+/// A compiled query selecting device tree nodes.
+///
+/// See the [module documentation](self) for the syntax.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Query {
+    steps: Vec<Step>,
+}
+
+/// A step of a query, selecting children or descendants of the nodes selected
+/// by the previous step.
+#[derive(Clone, Debug, Eq, PartialEq)]
+struct Step {
+    /// Whether the step selects descendants at any depth, for `//`.
+    descendants: bool,
+    /// The name of the nodes to select, which may contain `*` wildcards.
+    name: String,
+    predicates: Vec<Expr>,
+}
+
+/// A predicate on a node.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Expr {
+    /// All of the predicates hold, for a chain of `and`s.
+    And(Vec<Expr>),
+    /// Any of the predicates holds, for a chain of `or`s.
+    Or(Vec<Expr>),
+    Not(Box<Expr>),
+    /// The node has the property.
+    Has(String),
+    /// The node has the property with the value.
+    Equals(String, Literal),
+}
+
+/// A value to compare a property to.
+#[derive(Clone, Debug, Eq, PartialEq)]
+enum Literal {
+    String(String),
+    Number(u64),
+}
+
+impl Query {
+    /// Compiles a query.
+    ///
+    /// # Errors
+    ///
+    /// Returns a [`QueryError`] if the query isn't valid, has more than 63
+    /// steps, or has a predicate nested in more than 32 parentheses and
+    /// `not`s.
+    pub fn new(query: &str) -> Result<Self, QueryError> {
+        Parser {
+            query,
+            offset: 0,
+            nesting: 0,
+        }
+        .parse()
+    }
+
+    /// Returns the nodes of the device tree selected by this query, in the
+    /// order they appear in the device tree.
+    ///
+    /// # Performance
+    ///
+    /// This walks every subtree which may contain a selected node, which is
+    /// the whole device tree if the query starts with `//`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the device tree structure is malformed, or a node
+    /// or property name can't be read. As with [`FdtNode::descendants`], an
+    /// [`FdtErrorKind::TooDeep`] error is returned if a subtree to walk is
+    /// more than [`MAX_DESCENDANTS_DEPTH`] levels below the root node.
+    pub fn select<'a>(&self, fdt: Fdt<'a>) -> Result<Vec<FdtNode<'a>>, FdtParseError> {
+        self.select_from(fdt.root()?)
+    }
+
+    /// Returns the nodes of the device tree selected by this query, in the
+    /// order of their parents' children.
+    #[cfg(feature = "write")]
+    #[must_use]
+    pub fn select_tree<'t>(&self, tree: &'t DeviceTree) -> Vec<&'t DeviceTreeNode> {
+        let Ok(nodes) = self.select_from(&tree.root);
+        nodes
+    }
+
+    fn select_from<'a, N: QueryNode<'a>>(&self, root: N) -> Result<Vec<N>, N::Error> {
+        let mut nodes = Vec::new();
+        if self.steps.is_empty() {
+            nodes.push(root);
+        } else {
+            self.visit(root, &mut nodes)?;
+        }
+        Ok(nodes)
+    }
+
+    /// Adds the descendants of `root` selected by the query to `nodes`,
+    /// without recursing.
+    fn visit<'a, N: QueryNode<'a>>(&self, root: N, nodes: &mut Vec<N>) -> Result<(), N::Error> {
+        let selected = 1 << self.steps.len();
+        // The children being walked at each level, along with the states of
+        // their parent. Bit `i` of the states means that the parent was
+        // selected by the first `i` steps of the query.
+        let mut stack = vec![(root.children(), 1)];
+        while let Some((children, states)) = stack.last_mut() {
+            let Some(child) = children.next() else {
+                stack.pop();
+                continue;
+            };
+            let child = child?;
+            let next = self.step(*states, child)?;
+            if next & selected != 0 {
+                nodes.push(child);
+            }
+            if next & !selected != 0 {
+                child.check_depth(stack.len())?;
+                stack.push((child.children(), next));
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the states of `node`, given the states of its parent.
+    fn step<'a, N: QueryNode<'a>>(&self, states: u64, node: N) -> Result<u64, N::Error> {
+        let mut next = 0;
+        for (index, step) in self.steps.iter().enumerate() {
+            if states & (1 << index) == 0 {
+                continue;
+            }
+            if step.descendants {
+                next |= 1 << index;
+            }
+            if step.matches(node)? {
+                next |= 1 << (index + 1);
+            }
+        }
+        Ok(next)
+    }
+}
+
+impl FromStr for Query {
+    type Err = QueryError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::new(s)
+    }
+}
+
+impl Step {
+    fn matches<'a, N: QueryNode<'a>>(&self, node: N) -> Result<bool, N::Error> {
+        if !path::glob_segment_matches(&self.name, node.name()?) {
+            return Ok(false);
+        }
+        for predicate in &self.predicates {
+            if !predicate.eval(node)? {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+impl Expr {
+    fn eval<'a, N: QueryNode<'a>>(&self, node: N) -> Result<bool, N::Error> {
+        Ok(match self {
+            Self::And(exprs) => {
+                for expr in exprs {
+                    if !expr.eval(node)? {
+                        return Ok(false);
+                    }
+                }
+                true
+            }
+            Self::Or(exprs) => {
+                for expr in exprs {
+                    if expr.eval(node)? {
+                        return Ok(true);
+                    }
+                }
+                false
+            }
+            Self::Not(expr) => !expr.eval(node)?,
+            Self::Has(name) => node.property(name)?.is_some(),
+            Self::Equals(name, literal) => node
+                .property(name)?
+                .is_some_and(|value| literal.matches(value)),
+        })
+    }
+}
+
+impl Literal {
+    fn matches(&self, value: &[u8]) -> bool {
+        match self {
+            Self::String(string) => value.strip_suffix(&[0]).is_some_and(|strings| {
+                strings
+                    .split(|&byte| byte == 0)
+                    .any(|s| s == string.as_bytes())
+            }),
+            Self::Number(number) => {
+                if let Ok(value) = <[u8; 4]>::try_from(value) {
+                    u64::from(u32::from_be_bytes(value)) == *number
+                } else if let Ok(value) = <[u8; 8]>::try_from(value) {
+                    u64::from_be_bytes(value) == *number
+                } else {
+                    false
+                }
+            }
+        }
+    }
+}
+
+/// A node of either a flattened device tree or a [`DeviceTree`], which a query
+/// can be evaluated on.
+trait QueryNode<'a>: Copy {
+    type Error;
+
+    fn name(self) -> Result<&'a str, Self::Error>;
+
+    fn property(self, name: &str) -> Result<Option<&'a [u8]>, Self::Error>;
+
+    fn children(self) -> impl Iterator<Item = Result<Self, Self::Error>>;
+
+    /// Checks that the children of this node, `depth` levels below the node
+    /// the query is evaluated on, can be walked.
+    fn check_depth(self, depth: usize) -> Result<(), Self::Error>;
+}
+
+impl<'a> QueryNode<'a> for FdtNode<'a> {
+    type Error = FdtParseError;
+
+    fn name(self) -> Result<&'a str, Self::Error> {
+        FdtNode::name(&self)
+    }
+
+    fn property(self, name: &str) -> Result<Option<&'a [u8]>, Self::Error> {
+        Ok(FdtNode::property(&self, name)?.map(|property| property.value()))
+    }
+
+    fn children(self) -> impl Iterator<Item = Result<Self, Self::Error>> {
+        FdtNode::children(&self)
+    }
+
+    fn check_depth(self, depth: usize) -> Result<(), Self::Error> {
+        if depth >= MAX_DESCENDANTS_DEPTH && FdtNode::children(&self).next().is_some() {
+            return Err(FdtParseError::new(
+                FdtErrorKind::TooDeep(MAX_DESCENDANTS_DEPTH),
+                self.offset,
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "write")]
+impl<'t> QueryNode<'t> for &'t DeviceTreeNode {
+    type Error = Infallible;
+
+    fn name(self) -> Result<&'t str, Self::Error> {
+        Ok(DeviceTreeNode::name(self))
+    }
+
+    fn property(self, name: &str) -> Result<Option<&'t [u8]>, Self::Error> {
+        Ok(DeviceTreeNode::property(self, name).map(DeviceTreeProperty::value))
+    }
+
+    fn children(self) -> impl Iterator<Item = Result<Self, Self::Error>> {
+        DeviceTreeNode::children(self).map(Ok)
+    }
+
+    /// Nodes of a [`DeviceTree`] can be nested to any depth, as the stack of
+    /// nodes being walked is allocated.
+    fn check_depth(self, _depth: usize) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+/// A recursive descent parser for queries.
+struct Parser<'q> {
+    query: &'q str,
+    /// The offset of the next byte to parse.
+    offset: usize,
+    /// The number of parentheses the parser is in.
+    nesting: usize,
+}
+
+impl<'q> Parser<'q> {
+    fn parse(mut self) -> Result<Query, QueryError> {
+        if !self.query.starts_with('/') {
+            return Err(self.error(Invalid::ExpectedSlash));
+        }
+        let mut steps = Vec::new();
+        while self.eat("/") {
+            let descendants = self.eat("/");
+            if !descendants && steps.is_empty() && self.rest().is_empty() {
+                // The query is `/`, which selects the root node.
+                break;
+            }
+            if steps.len() == MAX_STEPS {
+                return Err(self.error(Invalid::TooManySteps));
+            }
+            let name = self.take_while(|c| is_name_char(c) || c == '@' || c == '*');
+            if name.is_empty() {
+                return Err(self.error(Invalid::ExpectedNodeName));
+            }
+            let mut predicates = Vec::new();
+            while self.eat("[") {
+                predicates.push(self.or()?);
+                self.skip_whitespace();
+                if !self.eat("]") {
+                    return Err(self.error(Invalid::ExpectedCloseBracket));
+                }
+            }
+            steps.push(Step {
+                descendants,
+                name: name.to_string(),
+                predicates,
+            });
+        }
+        if !self.rest().is_empty() {
+            return Err(self.error(Invalid::ExpectedSlash));
+        }
+        Ok(Query { steps })
+    }
+
+    fn or(&mut self) -> Result<Expr, QueryError> {
+        let mut exprs = vec![self.and()?];
+        while self.keyword("or") {
+            exprs.push(self.and()?);
+        }
+        Ok(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            Expr::Or(exprs)
+        })
+    }
+
+    fn and(&mut self) -> Result<Expr, QueryError> {
+        let mut exprs = vec![self.unary()?];
+        while self.keyword("and") {
+            exprs.push(self.unary()?);
+        }
+        Ok(if exprs.len() == 1 {
+            exprs.remove(0)
+        } else {
+            Expr::And(exprs)
+        })
+    }
+
+    fn unary(&mut self) -> Result<Expr, QueryError> {
+        if self.keyword("not") {
+            self.skip_whitespace();
+            if !self.eat("(") {
+                return Err(self.error(Invalid::ExpectedOpenParen));
+            }
+            return Ok(Expr::Not(Box::new(self.parenthesized()?)));
+        }
+        self.skip_whitespace();
+        if self.eat("(") {
+            return self.parenthesized();
+        }
+        if !self.eat("@") {
+            return Err(self.error(Invalid::ExpectedPredicate));
+        }
+        let name = self.take_while(|c| is_name_char(c) || c == '?' || c == '#');
+        if name.is_empty() {
+            return Err(self.error(Invalid::ExpectedPropertyName));
+        }
+        let name = name.to_string();
+        self.skip_whitespace();
+        if self.eat("!=") {
+            let literal = self.literal()?;
+            Ok(Expr::Not(Box::new(Expr::Equals(name, literal))))
+        } else if self.eat("=") {
+            Ok(Expr::Equals(name, self.literal()?))
+        } else {
+            Ok(Expr::Has(name))
+        }
+    }
+
+    /// Parses the rest of an expression in parentheses, after the `(`.
+    fn parenthesized(&mut self) -> Result<Expr, QueryError> {
+        if self.nesting == MAX_NESTING {
+            return Err(self.error(Invalid::TooDeep));
+        }
+        self.nesting += 1;
+        let expr = self.or()?;
+        self.nesting -= 1;
+        self.skip_whitespace();
+        if !self.eat(")") {
+            return Err(self.error(Invalid::ExpectedCloseParen));
+        }
+        Ok(expr)
+    }
+
+    fn literal(&mut self) -> Result<Literal, QueryError> {
+        self.skip_whitespace();
+        for quote in ["'", "\""] {
+            if self.eat(quote) {
+                let Some((string, _)) = self.rest().split_once(quote) else {
+                    return Err(self.error(Invalid::UnterminatedString));
+                };
+                self.offset += string.len() + quote.len();
+                return Ok(Literal::String(string.to_string()));
+            }
+        }
+        let start = self.offset;
+        let number = self.take_while(|c| c.is_ascii_alphanumeric());
+        let number = if let Some(hex) = number.strip_prefix("0x") {
+            u64::from_str_radix(hex, 16)
+        } else {
+            number.parse()
+        };
+        number.map(Literal::Number).map_err(|_| QueryError {
+            offset: start,
+            message: Invalid::ExpectedLiteral.message(),
+            code: Invalid::ExpectedLiteral as u16,
+        })
+    }
+
+    /// Skips whitespace and consumes the given keyword, if it isn't just the
+    /// start of a longer word.
+    fn keyword(&mut self, keyword: &str) -> bool {
+        self.skip_whitespace();
+        let matches = self.rest().strip_prefix(keyword).is_some_and(|rest| {
+            !rest.starts_with(|c: char| is_name_char(c) || c == '?' || c == '#')
+        });
+        if matches {
+            self.offset += keyword.len();
+        }
+        matches
+    }
+
+    /// Consumes the given string if the rest of the query starts with it.
+    fn eat(&mut self, s: &str) -> bool {
+        let matches = self.rest().starts_with(s);
+        if matches {
+            self.offset += s.len();
+        }
+        matches
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> &'q str {
+        let rest = self.rest();
+        let len = rest.find(|c| !predicate(c)).unwrap_or(rest.len());
+        self.offset += len;
+        &rest[..len]
+    }
+
+    fn skip_whitespace(&mut self) {
+        self.take_while(char::is_whitespace);
+    }
+
+    fn rest(&self) -> &'q str {
+        &self.query[self.offset..]
+    }
+
+    fn error(&self, invalid: Invalid) -> QueryError {
+        QueryError {
+            offset: self.offset,
+            message: invalid.message(),
+            code: invalid as u16,
+        }
+    }
+}
+
+/// Returns whether the character may be part of a node or property name.
+fn is_name_char(c: char) -> bool {
+    c.is_ascii_alphanumeric() || matches!(c, ',' | '.' | '_' | '+' | '-')
+}
@@ -8,19 +8,62 @@
 
 //! Standard nodes and properties.
 
+mod aliases;
+mod chosen;
+mod clocks;
 mod cpus;
+mod gpio;
+mod graph;
+mod interrupts;
+mod iommu;
 mod memory;
+mod msi;
+mod net;
+mod node;
+mod numa;
+mod nvmem;
+mod pci;
+mod phandle;
+mod pinctrl;
 mod ranges;
 mod reg;
+mod reserved_memory;
+mod specifier;
 mod status;
+mod symbols;
+mod translation;
+mod unit_address;
 
+pub use self::aliases::Aliases;
+#[cfg(feature = "write")]
+pub(crate) use self::chosen::ChosenNode;
+pub use self::chosen::{Chosen, UefiMemoryMap};
+#[cfg(feature = "write")]
+pub(crate) use self::cpus::CpuNodes;
 pub use self::cpus::{Cpu, Cpus};
+pub use self::gpio::{Gpio, GpioFlags, GpioRange};
+pub use self::interrupts::MappedInterrupt;
 pub use self::memory::{InitialMappedArea, Memory};
+pub use self::msi::{MappedMsi, MsiMapEntry};
+pub use self::net::{FixedLink, Phy, PhyMode};
+#[cfg(feature = "write")]
+pub(crate) use self::node::{StandardNode, StandardProperty};
+pub use self::numa::{DistanceMap, NumaDistance};
+pub use self::nvmem::{NvmemBits, NvmemCell};
+pub use self::pci::{PciAddress, PciSpace, swizzle_pci_pin};
+pub use self::phandle::Phandle;
+pub(crate) use self::phandle::{PhandleNode, max_phandle};
+pub use self::pinctrl::PinctrlState;
 pub use self::ranges::Range;
 pub use self::reg::Reg;
+pub use self::reserved_memory::{ReservedMemory, ReservedMemoryRegion};
+pub use self::specifier::PhandleSpecifier;
 pub use self::status::Status;
+pub use self::symbols::Symbols;
+pub use self::translation::{AddressMapEntry, MAX_ADDRESS_MAP_DEPTH};
+pub use self::unit_address::{UnitAddress, UnitAddressFinding, parse_unit_address};
 use crate::error::{FdtError, FdtParseError};
-use crate::fdt::FdtNode;
+use crate::fdt::{Fdt, FdtNode};
 
 pub(crate) const DEFAULT_ADDRESS_CELLS: u32 = 2;
 pub(crate) const DEFAULT_SIZE_CELLS: u32 = 1;
@@ -53,20 +96,69 @@ impl<'a> FdtNode<'a> {
         })
     }
 
+    /// Returns the entry of `table` which best matches the `compatible`
+    /// property of this node, along with its associated data.
+    ///
+    /// As with a kernel's driver match tables, the entry matching the earliest
+    /// string in the `compatible` property wins, regardless of the order of
+    /// the table. Returns `None` if no entry matches or the node has no
+    /// `compatible` property.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # let dtb = include_bytes!("../tests/dtb/test_props.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let node = fdt.find_node("/standard-props").unwrap().unwrap();
+    /// // The node is compatible with "abc,def" and then "some,other".
+    /// let table = [("some,other", 1), ("abc,def", 2), ("unrelated", 3)];
+    /// assert_eq!(
+    ///     node.match_compatible(&table).unwrap(),
+    ///     Some(&("abc,def", 2))
+    /// );
+    /// assert_eq!(node.match_compatible(&table[2..]).unwrap(), None);
+    /// ```
+    pub fn match_compatible<'t, T>(
+        &self,
+        table: &'t [(&str, T)],
+    ) -> Result<Option<&'t (&'t str, T)>, FdtParseError> {
+        let Some(mut compatible) = self.compatible()? else {
+            return Ok(None);
+        };
+        Ok(compatible.find_map(|compatible| table.iter().find(|(entry, _)| *entry == compatible)))
+    }
+
     /// Finds all child nodes with a `compatible` property containing the given
     /// string.
     pub fn find_compatible<'f>(
         &self,
         compatible_filter: &'f str,
     ) -> impl Iterator<Item = Result<FdtNode<'a>, FdtParseError>> + use<'a, 'f> {
-        self.children().filter_map(|child| match child {
-            Ok(child) => match child.is_compatible(compatible_filter) {
-                Ok(true) => Some(Ok(child)),
-                Ok(false) => None,
-                Err(e) => Some(Err(e)),
-            },
-            Err(e) => Some(Err(e)),
-        })
+        self.children()
+            .filter_map(move |child| filter_compatible(child, compatible_filter))
+    }
+
+    /// Finds all descendants of this node, not only its children, with a
+    /// `compatible` property containing the given string.
+    ///
+    /// The nodes are returned in the order of [`descendants`](Self::descendants).
+    ///
+    /// # Performance
+    ///
+    /// This walks the whole subtree. Use
+    /// [`FdtIndex::find_compatible`](crate::fdt::FdtIndex::find_compatible) to
+    /// look up many compatible strings in the same tree.
+    pub fn find_all_compatible<'f>(
+        &self,
+        compatible_filter: &'f str,
+    ) -> impl Iterator<Item = Result<FdtNode<'a>, FdtParseError>> + use<'a, 'f> {
+        self.descendants()
+            .filter_map(move |node| filter_compatible(node, compatible_filter))
     }
 
     /// Returns the value of the standard `model` property.
@@ -88,10 +180,10 @@ impl<'a> FdtNode<'a> {
     /// # Errors
     ///
     /// Returns an error if a property's name or value cannot be read, or the
-    /// value isn't a valid u32.
-    pub fn phandle(&self) -> Result<Option<u32>, FdtParseError> {
+    /// value isn't a valid phandle.
+    pub fn phandle(&self) -> Result<Option<Phandle>, FdtParseError> {
         Ok(if let Some(property) = self.property("phandle")? {
-            Some(property.as_u32()?)
+            Some(property.as_phandle()?)
         } else {
             None
         })
@@ -246,6 +338,59 @@ impl<'a> FdtNode<'a> {
     }
 }
 
+impl<'a> Fdt<'a> {
+    /// Finds all nodes in the device tree, including the root node, with a
+    /// `compatible` property containing the given string.
+    ///
+    /// The nodes are returned depth-first, in the order they appear in the
+    /// device tree.
+    ///
+    /// # Performance
+    ///
+    /// This walks the whole device tree. Use
+    /// [`FdtIndex::find_compatible`](crate::fdt::FdtIndex::find_compatible) to
+    /// look up many compatible strings in the same tree.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # let dtb = include_bytes!("../tests/dtb/test_props.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let mut nodes = fdt.find_all_compatible("some,other");
+    /// assert_eq!(nodes.next().unwrap().unwrap().name(), Ok("standard-props@1"));
+    /// assert!(nodes.next().is_none());
+    /// ```
+    pub fn find_all_compatible<'f>(
+        self,
+        compatible_filter: &'f str,
+    ) -> impl Iterator<Item = Result<FdtNode<'a>, FdtParseError>> + use<'a, 'f> {
+        let (root, descendants) = match self.root() {
+            Ok(root) => (Ok(root), Some(root.descendants())),
+            Err(e) => (Err(e), None),
+        };
+        core::iter::once(root)
+            .chain(descendants.into_iter().flatten())
+            .filter_map(move |node| filter_compatible(node, compatible_filter))
+    }
+}
+
+/// Returns the given node if it is compatible with `compatible_filter`, for
+/// use with [`Iterator::filter_map`].
+fn filter_compatible<'a>(
+    node: Result<FdtNode<'a>, FdtParseError>,
+    compatible_filter: &str,
+) -> Option<Result<FdtNode<'a>, FdtParseError>> {
+    match node {
+        Ok(node) => match node.is_compatible(compatible_filter) {
+            Ok(true) => Some(Ok(node)),
+            Ok(false) => None,
+            Err(e) => Some(Err(e)),
+        },
+        Err(e) => Some(Err(e)),
+    }
+}
+
 /// The `#address-cells` and `#size-cells` properties of a node.
 #[derive(Debug, Clone, Copy)]
 pub struct AddressSpaceProperties {
@@ -0,0 +1,91 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use core::fmt::{self, Display, Formatter};
+use core::ops::Deref;
+
+use crate::error::FdtParseError;
+use crate::fdt::{Fdt, FdtNode};
+
+impl<'a> Fdt<'a> {
+    /// Returns the `/aliases` node, if there is one.
+    ///
+    /// # Errors
+    ///
+    /// Returns a parse error if there was a problem reading the FDT structure
+    /// to find the node.
+    pub fn aliases(self) -> Result<Option<Aliases<'a>>, FdtParseError> {
+        Ok(self.find_node("/aliases")?.map(|node| Aliases { node }))
+    }
+
+    /// Finds the node with the given alias, as listed in the `/aliases` node.
+    ///
+    /// Returns `None` if there is no `/aliases` node, the alias isn't in it,
+    /// or its path isn't absolute or doesn't lead to a node.
+    ///
+    /// # Errors
+    ///
+    /// Returns a parse error if there was a problem reading the FDT structure,
+    /// or the path of the alias isn't a valid string.
+    pub fn resolve_alias(self, alias: &str) -> Result<Option<FdtNode<'a>>, FdtParseError> {
+        let Some(aliases) = self.aliases()? else {
+            return Ok(None);
+        };
+        match aliases.path(alias)? {
+            Some(path) if path.starts_with('/') => self.find_node(path),
+            _ => Ok(None),
+        }
+    }
+}
+
+/// Typed wrapper for an `/aliases` node.
+#[derive(Clone, Copy, Debug)]
+pub struct Aliases<'a> {
+    node: FdtNode<'a>,
+}
+
+impl<'a> Deref for Aliases<'a> {
+    type Target = FdtNode<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.node
+    }
+}
+
+impl Display for Aliases<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.node.fmt(f)
+    }
+}
+
+impl<'a> Aliases<'a> {
+    /// Returns an iterator over the aliases and the paths of the nodes they
+    /// refer to.
+    pub fn aliases(
+        &self,
+    ) -> impl Iterator<Item = Result<(&'a str, &'a str), FdtParseError>> + use<'a> {
+        self.node.properties().map(|property| {
+            let property = property?;
+            Ok((property.name(), property.as_str()?))
+        })
+    }
+
+    /// Returns the path of the node with the given alias.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read, or the
+    /// value isn't a valid string.
+    pub fn path(&self, alias: &str) -> Result<Option<&'a str>, FdtParseError> {
+        Ok(if let Some(property) = self.node.property(alias)? {
+            Some(property.as_str()?)
+        } else {
+            None
+        })
+    }
+}
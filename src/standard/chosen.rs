@@ -0,0 +1,126 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use core::fmt::{self, Display, Formatter};
+use core::ops::Deref;
+
+use super::node::{StandardNode, StandardProperty};
+use crate::error::{FdtError, FdtParseError};
+use crate::fdt::{Fdt, FdtNode};
+
+impl<'a> Fdt<'a> {
+    /// Returns the `/chosen` node, if there is one.
+    ///
+    /// # Errors
+    ///
+    /// Returns a parse error if there was a problem reading the FDT structure
+    /// to find the node.
+    pub fn chosen(self) -> Result<Option<Chosen<'a>>, FdtParseError> {
+        Ok(self.find_node("/chosen")?.map(|node| Chosen { node }))
+    }
+}
+
+/// Typed wrapper for a `/chosen` node.
+#[derive(Clone, Copy, Debug)]
+pub struct Chosen<'a> {
+    node: FdtNode<'a>,
+}
+
+impl<'a> Deref for Chosen<'a> {
+    type Target = FdtNode<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.node
+    }
+}
+
+impl Display for Chosen<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.node.fmt(f)
+    }
+}
+
+impl Chosen<'_> {
+    /// Returns the physical address of the UEFI system table, from the
+    /// `linux,uefi-system-table` property.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read, or the
+    /// value isn't a valid u64.
+    pub fn uefi_system_table(&self) -> Result<Option<u64>, FdtParseError> {
+        ChosenNode(self.node).uefi_system_table()
+    }
+
+    /// Returns the UEFI memory map passed by the EFI stub, from the
+    /// `linux,uefi-mmap-*` properties.
+    ///
+    /// Returns `None` if there is no `linux,uefi-mmap-start` property.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read, or a
+    /// value has the wrong size, or [`FdtError::MissingProperty`] if only
+    /// some of the properties are present.
+    pub fn uefi_memory_map(&self) -> Result<Option<UefiMemoryMap>, FdtError> {
+        ChosenNode(self.node).uefi_memory_map()
+    }
+}
+
+/// A `/chosen` node of either a flattened device tree or a
+/// [`DeviceTree`](crate::model::DeviceTree), which implements the accessors of
+/// both kinds of [`Chosen`] wrapper.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ChosenNode<N>(pub(crate) N);
+
+impl<'a, N: StandardNode<'a>> ChosenNode<N> {
+    pub(crate) fn uefi_system_table(self) -> Result<Option<u64>, N::Error> {
+        Ok(
+            if let Some(property) = self.0.node_property("linux,uefi-system-table")? {
+                Some(property.property_u64()?)
+            } else {
+                None
+            },
+        )
+    }
+
+    pub(crate) fn uefi_memory_map(self) -> Result<Option<UefiMemoryMap>, N::ValueError> {
+        let Some(start) = self.0.node_property("linux,uefi-mmap-start")? else {
+            return Ok(None);
+        };
+        let u32_property = |name| -> Result<u32, N::ValueError> {
+            Ok(self
+                .0
+                .node_property(name)?
+                .ok_or_else(|| N::missing_property(name))?
+                .property_u32()?)
+        };
+        Ok(Some(UefiMemoryMap {
+            start: start.property_u64()?,
+            size: u32_property("linux,uefi-mmap-size")?,
+            descriptor_size: u32_property("linux,uefi-mmap-desc-size")?,
+            descriptor_version: u32_property("linux,uefi-mmap-desc-ver")?,
+        }))
+    }
+}
+
+/// The location and layout of the UEFI memory map, as passed from the EFI stub
+/// to the kernel.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct UefiMemoryMap {
+    /// The physical address of the memory map (`linux,uefi-mmap-start`).
+    pub start: u64,
+    /// The size of the memory map in bytes (`linux,uefi-mmap-size`).
+    pub size: u32,
+    /// The size of a single memory descriptor in bytes
+    /// (`linux,uefi-mmap-desc-size`).
+    pub descriptor_size: u32,
+    /// The version of the memory descriptor format
+    /// (`linux,uefi-mmap-desc-ver`).
+    pub descriptor_version: u32,
+}
@@ -0,0 +1,56 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::PhandleSpecifier;
+use super::node::u32_or_u64;
+use crate::error::{FdtError, FdtParseError};
+use crate::fdt::FdtNode;
+
+impl<'a> FdtNode<'a> {
+    /// Returns the clocks of the standard `clocks` property, named by the
+    /// `clock-names` property if present.
+    ///
+    /// Each entry is made of the phandle of a clock provider followed by the
+    /// number of cells given by its `#clock-cells` property. Entries with a
+    /// phandle of 0 are placeholders for unused clocks, which are skipped but
+    /// still take up a name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read. The
+    /// iterator returns an error and then ends if a clock provider can't be
+    /// found, it has no `#clock-cells` property, or the entry is truncated.
+    pub fn clocks(
+        &self,
+    ) -> Result<
+        Option<impl Iterator<Item = Result<PhandleSpecifier<'a>, FdtError>> + use<'a>>,
+        FdtParseError,
+    > {
+        self.phandle_specifiers("clocks", "clock-names", "#clock-cells")
+    }
+
+    /// Returns the clock with the given name in the `clock-names` property.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`clocks`](Self::clocks) for the clock or any
+    /// clock before it.
+    pub fn clock(&self, name: &str) -> Result<Option<PhandleSpecifier<'a>>, FdtError> {
+        self.phandle_specifier("clocks", "clock-names", "#clock-cells", name)
+    }
+
+    /// Returns the value of the standard `clock-frequency` property, in Hz.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read, or the
+    /// value isn't a valid u32 or u64.
+    pub fn clock_frequency(&self) -> Result<Option<u64>, FdtParseError> {
+        u32_or_u64(*self, "clock-frequency")
+    }
+}
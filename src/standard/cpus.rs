@@ -9,8 +9,12 @@
 use core::fmt::{self, Display, Formatter};
 use core::ops::Deref;
 
+use zerocopy::{FromBytes, big_endian};
+
+use super::node::{StandardNode, StandardProperty, u32_or_u64};
+use super::{DEFAULT_ADDRESS_CELLS, DEFAULT_SIZE_CELLS};
 use crate::error::{FdtError, FdtParseError};
-use crate::fdt::{Cells, Fdt, FdtNode};
+use crate::fdt::{Cells, Fdt, FdtNode, str_list};
 
 impl<'a> Fdt<'a> {
     /// Returns the `/cpus` node.
@@ -51,41 +55,31 @@ impl Display for Cpus<'_> {
 impl<'a> Cpus<'a> {
     /// Returns an iterator over the `/cpus/cpu@*` nodes.
     pub fn cpus(&self) -> impl Iterator<Item = Result<Cpu<'a>, FdtParseError>> + use<'a> {
-        self.node.children().filter_map(|child| {
-            let child = match child {
-                Ok(child) => child,
-                Err(e) => return Some(Err(e)),
-            };
-            let name = match child.name_without_address() {
-                Ok(name) => name,
-                Err(e) => return Some(Err(e)),
-            };
-            if name == "cpu" {
-                Some(Ok(Cpu { node: child }))
-            } else {
-                None
-            }
-        })
+        CpuNodes::children_of(self.node).map(|nodes| nodes.map(|nodes| Cpu { nodes }))
     }
 }
 
 /// Typed wrapper for a `/cpus/cpu` node.
+///
+/// The standard `clock-frequency`, `compatible` and `status` properties can be
+/// read with [`FdtNode::clock_frequency`], [`FdtNode::compatible`] and
+/// [`FdtNode::status`].
 #[derive(Clone, Copy, Debug)]
 pub struct Cpu<'a> {
-    node: FdtNode<'a>,
+    nodes: CpuNodes<FdtNode<'a>>,
 }
 
 impl<'a> Deref for Cpu<'a> {
     type Target = FdtNode<'a>;
 
     fn deref(&self) -> &Self::Target {
-        &self.node
+        &self.nodes.node
     }
 }
 
 impl Display for Cpu<'_> {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
-        self.node.fmt(f)
+        self.nodes.node.fmt(f)
     }
 }
 
@@ -99,9 +93,230 @@ impl<'a> Cpu<'a> {
     /// `reg` property is missing, or the size of the value isn't a multiple of
     /// the expected number of address and size cells.
     pub fn ids(&self) -> Result<impl Iterator<Item = Cells<'a>> + use<'a>, FdtError> {
+        self.nodes.ids()?.ok_or(FdtError::CpuMissingReg)
+    }
+
+    /// Returns the value of the standard `timebase-frequency` property, in Hz,
+    /// from the CPU node or else the `/cpus` node.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read, or the
+    /// value isn't a valid u32 or u64.
+    pub fn timebase_frequency(&self) -> Result<Option<u64>, FdtParseError> {
+        self.nodes.timebase_frequency()
+    }
+
+    /// Returns the value of the standard `enable-method` property, which
+    /// describes how to start the CPU if it isn't already running, such as
+    /// `spin-table` or `psci`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read.
+    pub fn enable_method(
+        &self,
+    ) -> Result<Option<impl Iterator<Item = &'a str> + use<'a>>, FdtParseError> {
+        self.nodes.enable_method()
+    }
+
+    /// Returns the value of the standard `cpu-release-addr` property, which is
+    /// the address a secondary CPU spins on with the `spin-table` enable
+    /// method.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read, or the
+    /// value isn't a valid u64.
+    pub fn cpu_release_addr(&self) -> Result<Option<u64>, FdtParseError> {
+        self.nodes.cpu_release_addr()
+    }
+
+    /// Returns the value of the RISC-V `riscv,isa` property, the ISA string of
+    /// the hart such as `rv64imafdc_zicsr`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read, or the
+    /// value isn't a valid string.
+    pub fn riscv_isa(&self) -> Result<Option<&'a str>, FdtParseError> {
+        self.nodes.riscv_isa()
+    }
+
+    /// Returns the value of the RISC-V `riscv,isa-extensions` property, which
+    /// lists the extensions supported by the hart, such as `i`, `m` and
+    /// `zicsr`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read.
+    pub fn riscv_isa_extensions(
+        &self,
+    ) -> Result<Option<impl Iterator<Item = &'a str> + use<'a>>, FdtParseError> {
+        self.nodes.riscv_isa_extensions()
+    }
+
+    /// Returns whether the RISC-V `riscv,isa-extensions` property lists the
+    /// given extension.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read.
+    pub fn has_riscv_isa_extension(&self, extension: &str) -> Result<bool, FdtParseError> {
+        self.nodes.has_riscv_isa_extension(extension)
+    }
+
+    /// Returns the value of the standard `mmu-type` property, such as
+    /// `riscv,sv39` or `riscv,none`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read, or the
+    /// value isn't a valid string.
+    pub fn mmu_type(&self) -> Result<Option<&'a str>, FdtParseError> {
+        self.nodes.mmu_type()
+    }
+
+    /// Returns the `interrupt-controller` child of a RISC-V hart, which is its
+    /// local interrupt controller and the interrupt parent of platform-level
+    /// interrupt controllers such as the PLIC or the ACLINT.
+    ///
+    /// Unlike [`FdtNode::interrupt_controller`], this doesn't follow
+    /// `interrupt-parent`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a child can't be read.
+    pub fn hart_interrupt_controller(&self) -> Result<Option<FdtNode<'a>>, FdtParseError> {
+        self.nodes.hart_interrupt_controller()
+    }
+}
+
+/// A `/cpus/cpu` node and the `/cpus` node, of either a flattened device tree
+/// or a [`DeviceTree`](crate::model::DeviceTree), which implement the
+/// accessors of both kinds of [`Cpu`] wrapper.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct CpuNodes<N> {
+    pub(crate) node: N,
+    /// The `/cpus` node, which may hold properties common to all CPUs.
+    pub(crate) cpus: N,
+}
+
+impl<'a, N: StandardNode<'a>> CpuNodes<N> {
+    /// Returns the `cpu` children of the given `/cpus` node.
+    pub(crate) fn children_of(
+        cpus: N,
+    ) -> impl Iterator<Item = Result<Self, N::Error>> + use<'a, N> {
+        cpus.child_nodes().filter_map(move |child| {
+            let child = match child {
+                Ok(child) => child,
+                Err(e) => return Some(Err(e)),
+            };
+            let name = match child.node_name_without_address() {
+                Ok(name) => name,
+                Err(e) => return Some(Err(e)),
+            };
+            if name == "cpu" {
+                Some(Ok(Self { node: child, cpus }))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Returns the addresses of the `reg` property, or `None` if it is
+    /// missing.
+    pub(crate) fn ids(
+        self,
+    ) -> Result<Option<impl Iterator<Item = Cells<'a>> + use<'a, N>>, N::ValueError> {
+        let Some(reg) = self.node.node_property("reg")? else {
+            return Ok(None);
+        };
+        let cells = |name, default: u32| -> Result<usize, N::Error> {
+            Ok(if let Some(property) = self.cpus.node_property(name)? {
+                property.property_u32()? as usize
+            } else {
+                default as usize
+            })
+        };
+        let address_cells = cells("#address-cells", DEFAULT_ADDRESS_CELLS)?;
+        let entry_cells = address_cells + cells("#size-cells", DEFAULT_SIZE_CELLS)?;
+        let value = reg.property_value();
+        let entries = <[big_endian::U32]>::ref_from_bytes(value)
+            .ok()
+            .filter(|cells| entry_cells != 0 && cells.len().is_multiple_of(entry_cells))
+            .ok_or_else(|| N::size_mismatch(value.len(), entry_cells))?;
+        Ok(Some(
+            entries
+                .chunks_exact(entry_cells)
+                .map(move |entry| Cells(&entry[..address_cells])),
+        ))
+    }
+
+    pub(crate) fn timebase_frequency(self) -> Result<Option<u64>, N::Error> {
+        match u32_or_u64(self.node, "timebase-frequency")? {
+            Some(frequency) => Ok(Some(frequency)),
+            None => u32_or_u64(self.cpus, "timebase-frequency"),
+        }
+    }
+
+    pub(crate) fn enable_method(
+        self,
+    ) -> Result<Option<impl Iterator<Item = &'a str> + use<'a, N>>, N::Error> {
+        self.str_list("enable-method")
+    }
+
+    pub(crate) fn cpu_release_addr(self) -> Result<Option<u64>, N::Error> {
+        Ok(
+            if let Some(property) = self.node.node_property("cpu-release-addr")? {
+                Some(property.property_u64()?)
+            } else {
+                None
+            },
+        )
+    }
+
+    pub(crate) fn riscv_isa(self) -> Result<Option<&'a str>, N::Error> {
+        self.str("riscv,isa")
+    }
+
+    pub(crate) fn riscv_isa_extensions(
+        self,
+    ) -> Result<Option<impl Iterator<Item = &'a str> + use<'a, N>>, N::Error> {
+        self.str_list("riscv,isa-extensions")
+    }
+
+    pub(crate) fn has_riscv_isa_extension(self, extension: &str) -> Result<bool, N::Error> {
+        Ok(if let Some(mut extensions) = self.riscv_isa_extensions()? {
+            extensions.any(|e| e == extension)
+        } else {
+            false
+        })
+    }
+
+    pub(crate) fn mmu_type(self) -> Result<Option<&'a str>, N::Error> {
+        self.str("mmu-type")
+    }
+
+    pub(crate) fn hart_interrupt_controller(self) -> Result<Option<N>, N::Error> {
+        self.node.named_child("interrupt-controller")
+    }
+
+    fn str(self, name: &str) -> Result<Option<&'a str>, N::Error> {
+        Ok(if let Some(property) = self.node.node_property(name)? {
+            Some(property.property_str()?)
+        } else {
+            None
+        })
+    }
+
+    fn str_list(
+        self,
+        name: &str,
+    ) -> Result<Option<impl Iterator<Item = &'a str> + use<'a, N>>, N::Error> {
         Ok(self
-            .reg()?
-            .ok_or(FdtError::CpuMissingReg)?
-            .map(|reg| reg.address))
+            .node
+            .node_property(name)?
+            .map(|property| str_list(property.property_value())))
     }
 }
@@ -0,0 +1,218 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::specifier::SpecifierCells;
+use crate::error::{FdtError, FdtParseError};
+use crate::fdt::{FdtNode, FdtProperty, U32Array};
+
+impl<'a> FdtNode<'a> {
+    /// Returns the GPIOs of the `<prefix>-gpios` property, or of the `gpios`
+    /// property if `prefix` is `None`.
+    ///
+    /// The deprecated `<prefix>-gpio` and `gpio` forms are used if the usual
+    /// property isn't present.
+    ///
+    /// Each entry is made of the phandle of a GPIO controller followed by the
+    /// number of cells given by its `#gpio-cells` property. Entries with a
+    /// phandle of 0 are placeholders for unused GPIOs, which are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read. The
+    /// iterator returns an error and then ends if a GPIO controller can't be
+    /// found, it has no `#gpio-cells` property, or the entry is truncated.
+    pub fn gpios(
+        &self,
+        prefix: Option<&str>,
+    ) -> Result<Option<impl Iterator<Item = Result<Gpio<'a>, FdtError>> + use<'a>>, FdtParseError>
+    {
+        let Some(property) = self.gpios_property(prefix)? else {
+            return Ok(None);
+        };
+        Ok(Some(
+            self.phandle_array(property, None, SpecifierCells::Property("#gpio-cells"))
+                .map(|entry| {
+                    entry.map(|entry| Gpio {
+                        controller: entry.provider,
+                        specifier: entry.specifier,
+                    })
+                }),
+        ))
+    }
+
+    /// Returns the property holding the GPIOs with the given prefix, preferring
+    /// the `-gpios` suffix over the deprecated `-gpio` one.
+    fn gpios_property(
+        &self,
+        prefix: Option<&str>,
+    ) -> Result<Option<FdtProperty<'a>>, FdtParseError> {
+        let mut deprecated = None;
+        for property in self.properties() {
+            let property = property?;
+            let suffix = match prefix {
+                Some(prefix) => property
+                    .name()
+                    .strip_prefix(prefix)
+                    .and_then(|name| name.strip_prefix('-')),
+                None => Some(property.name()),
+            };
+            match suffix {
+                Some("gpios") => return Ok(Some(property)),
+                Some("gpio") => deprecated = Some(property),
+                _ => {}
+            }
+        }
+        Ok(deprecated)
+    }
+
+    /// Returns whether the standard `gpio-controller` property is present.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property can't be read.
+    pub fn is_gpio_controller(&self) -> Result<bool, FdtParseError> {
+        Ok(self.property("gpio-controller")?.is_some())
+    }
+
+    /// Returns the ranges of the `gpio-ranges` property of a GPIO controller,
+    /// which map its GPIOs to pins of pin controllers.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read. The
+    /// iterator returns an error and then ends if a pin controller can't be
+    /// found or the entry is truncated.
+    pub fn gpio_ranges(
+        &self,
+    ) -> Result<
+        Option<impl Iterator<Item = Result<GpioRange<'a>, FdtError>> + use<'a>>,
+        FdtParseError,
+    > {
+        Ok(self
+            .phandle_array_by_name(
+                "gpio-ranges",
+                "gpio-ranges-group-names",
+                SpecifierCells::Fixed(3),
+            )?
+            .map(|entries| {
+                entries.map(|entry| {
+                    entry.map(|entry| {
+                        // The specifier always has 3 cells.
+                        let cell = |index| entry.specifier.get(index).unwrap_or_default();
+                        GpioRange {
+                            pin_controller: entry.provider,
+                            gpio_offset: cell(0),
+                            pin_offset: cell(1),
+                            count: cell(2),
+                            group: entry.name.filter(|group| !group.is_empty()),
+                        }
+                    })
+                })
+            }))
+    }
+}
+
+/// A GPIO referred to by a `gpios` property, as returned by
+/// [`FdtNode::gpios`].
+#[derive(Clone, Copy, Debug)]
+pub struct Gpio<'a> {
+    /// The GPIO controller.
+    pub controller: FdtNode<'a>,
+    /// The specifier, with the number of cells given by the `#gpio-cells`
+    /// property of the controller.
+    pub specifier: U32Array<'a>,
+}
+
+impl Gpio<'_> {
+    /// Returns the pin number, which is the first cell of the specifier for
+    /// the usual `<&controller pin flags>` form.
+    #[must_use]
+    pub fn pin(&self) -> Option<u32> {
+        self.specifier.get(0)
+    }
+
+    /// Returns the flags, which are the second cell of the specifier for the
+    /// usual `<&controller pin flags>` form.
+    #[must_use]
+    pub fn flags(&self) -> Option<GpioFlags> {
+        self.specifier.get(1).map(GpioFlags)
+    }
+}
+
+/// The flags of a GPIO specifier, as defined in
+/// `include/dt-bindings/gpio/gpio.h` of Linux.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct GpioFlags(pub u32);
+
+impl GpioFlags {
+    /// The GPIO is active when its line is low.
+    pub const ACTIVE_LOW: u32 = 1 << 0;
+    /// The line is only driven in one direction, and floats otherwise.
+    pub const SINGLE_ENDED: u32 = 1 << 1;
+    /// For a single-ended line, the line is only driven low.
+    pub const LINE_OPEN_DRAIN: u32 = 1 << 2;
+    /// The state of the GPIO may be lost when suspending or resetting.
+    pub const TRANSITORY: u32 = 1 << 3;
+    /// The line has a pull-up bias.
+    pub const PULL_UP: u32 = 1 << 4;
+    /// The line has a pull-down bias.
+    pub const PULL_DOWN: u32 = 1 << 5;
+    /// The line has no bias.
+    pub const PULL_DISABLE: u32 = 1 << 6;
+
+    /// Returns whether the GPIO is active low.
+    #[must_use]
+    pub fn is_active_low(self) -> bool {
+        self.0 & Self::ACTIVE_LOW != 0
+    }
+
+    /// Returns whether the line is open drain, i.e. single-ended and only
+    /// driven low.
+    #[must_use]
+    pub fn is_open_drain(self) -> bool {
+        self.0 & (Self::SINGLE_ENDED | Self::LINE_OPEN_DRAIN)
+            == Self::SINGLE_ENDED | Self::LINE_OPEN_DRAIN
+    }
+
+    /// Returns whether the line is open source, i.e. single-ended and only
+    /// driven high.
+    #[must_use]
+    pub fn is_open_source(self) -> bool {
+        self.0 & (Self::SINGLE_ENDED | Self::LINE_OPEN_DRAIN) == Self::SINGLE_ENDED
+    }
+
+    /// Returns whether the line has a pull-up bias.
+    #[must_use]
+    pub fn pull_up(self) -> bool {
+        self.0 & Self::PULL_UP != 0
+    }
+
+    /// Returns whether the line has a pull-down bias.
+    #[must_use]
+    pub fn pull_down(self) -> bool {
+        self.0 & Self::PULL_DOWN != 0
+    }
+}
+
+/// A range of GPIOs mapped to pins of a pin controller, from the `gpio-ranges`
+/// property of a GPIO controller.
+#[derive(Clone, Copy, Debug)]
+pub struct GpioRange<'a> {
+    /// The pin controller the GPIOs are mapped to.
+    pub pin_controller: FdtNode<'a>,
+    /// The first GPIO of the range.
+    pub gpio_offset: u32,
+    /// The first pin of the range.
+    pub pin_offset: u32,
+    /// The number of GPIOs in the range, or 0 if they are given by a pin group
+    /// instead.
+    pub count: u32,
+    /// The name of the pin group the GPIOs are mapped to, from
+    /// `gpio-ranges-group-names`, if present and not empty.
+    pub group: Option<&'a str>,
+}
@@ -0,0 +1,143 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::error::{FdtError, FdtParseError};
+use crate::fdt::FdtNode;
+
+impl<'a> FdtNode<'a> {
+    /// Returns the `port` or `port@<id>` nodes of a device in a graph, as
+    /// used to link devices such as a display controller and a panel. They
+    /// are either children of its `ports` child, if it has one, or of the
+    /// device itself.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `ports` child can't be read. The iterator
+    /// returns an error for a child which can't be read.
+    pub fn graph_ports(
+        &self,
+    ) -> Result<impl Iterator<Item = Result<FdtNode<'a>, FdtParseError>> + use<'a>, FdtParseError>
+    {
+        let ports = self.child("ports")?.unwrap_or(*self);
+        Ok(children_named(ports, "port"))
+    }
+
+    /// Returns the port of a device in a graph with the given ID, which is the
+    /// value of its `reg` property, or 0 if it has none.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a port can't be read or has an invalid `reg`
+    /// property.
+    pub fn graph_port(&self, id: u32) -> Result<Option<FdtNode<'a>>, FdtParseError> {
+        for port in self.graph_ports()? {
+            let port = port?;
+            if port.graph_id()? == id {
+                return Ok(Some(port));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the `endpoint` or `endpoint@<id>` children of a port in a
+    /// graph.
+    pub fn graph_endpoints(
+        &self,
+    ) -> impl Iterator<Item = Result<FdtNode<'a>, FdtParseError>> + use<'a> {
+        children_named(*self, "endpoint")
+    }
+
+    /// Returns the ID of a port or endpoint in a graph, which is the value of
+    /// its `reg` property, or 0 if it has none.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read, or the
+    /// value isn't a valid u32.
+    pub fn graph_id(&self) -> Result<u32, FdtParseError> {
+        Ok(if let Some(property) = self.property("reg")? {
+            property.as_u32()?
+        } else {
+            0
+        })
+    }
+
+    /// Returns the endpoint which this endpoint is linked to, from its
+    /// `remote-endpoint` property.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read, the
+    /// value isn't a valid phandle, or [`FdtError::PhandleNotFound`] if it
+    /// doesn't refer to a node.
+    pub fn remote_endpoint(&self) -> Result<Option<FdtNode<'a>>, FdtError> {
+        let Some(property) = self.property("remote-endpoint")? else {
+            return Ok(None);
+        };
+        let phandle = property.as_phandle()?;
+        Ok(Some(
+            self.fdt
+                .find_node_by_phandle(phandle)?
+                .ok_or(FdtError::PhandleNotFound(phandle.get()))?,
+        ))
+    }
+
+    /// Returns the device which the endpoint linked to this endpoint belongs
+    /// to.
+    ///
+    /// # Performance
+    ///
+    /// Finding the parent nodes walks the device tree, so this is linear in
+    /// the number of nodes.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`remote_endpoint`](Self::remote_endpoint),
+    /// or an error if the device tree structure is malformed.
+    pub fn remote_port_parent(&self) -> Result<Option<FdtNode<'a>>, FdtError> {
+        let Some(endpoint) = self.remote_endpoint()? else {
+            return Ok(None);
+        };
+        Ok(endpoint.port_parent()?)
+    }
+
+    /// Returns the device which this endpoint belongs to, skipping its port
+    /// and the `ports` node if there is one.
+    fn port_parent(&self) -> Result<Option<FdtNode<'a>>, FdtParseError> {
+        let Some(port) = self.parent()? else {
+            return Ok(None);
+        };
+        let Some(parent) = port.parent()? else {
+            return Ok(None);
+        };
+        if parent.name()? == "ports" {
+            parent.parent()
+        } else {
+            Ok(Some(parent))
+        }
+    }
+}
+
+/// Returns the children of `node` with the given name, ignoring their unit
+/// addresses.
+fn children_named<'a>(
+    node: FdtNode<'a>,
+    name: &'static str,
+) -> impl Iterator<Item = Result<FdtNode<'a>, FdtParseError>> + use<'a> {
+    node.children().filter_map(move |child| {
+        let child = match child {
+            Ok(child) => child,
+            Err(error) => return Some(Err(error)),
+        };
+        match child.name_without_address() {
+            Ok(child_name) if child_name == name => Some(Ok(child)),
+            Ok(_) => None,
+            Err(error) => Some(Err(error)),
+        }
+    })
+}
@@ -0,0 +1,275 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::Phandle;
+use crate::error::{FdtError, FdtErrorKind, FdtParseError};
+use crate::fdt::{FdtNode, U32Array};
+
+/// The maximum number of nodes visited by [`FdtNode::interrupt_parent`] and
+/// [`FdtNode::interrupt_controller`], so that `interrupt-parent` properties
+/// referring to each other don't loop forever.
+pub(super) const MAX_INTERRUPT_PARENT_HOPS: usize = 64;
+
+impl<'a> FdtNode<'a> {
+    /// Returns the value of the standard `#interrupt-cells` property.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read, or the
+    /// value isn't a valid u32.
+    pub fn interrupt_cells(&self) -> Result<Option<u32>, FdtParseError> {
+        Ok(if let Some(property) = self.property("#interrupt-cells")? {
+            Some(property.as_u32()?)
+        } else {
+            None
+        })
+    }
+
+    /// Returns whether the standard `interrupt-controller` property is
+    /// present.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property can't be read.
+    pub fn is_interrupt_controller(&self) -> Result<bool, FdtParseError> {
+        Ok(self.property("interrupt-controller")?.is_some())
+    }
+
+    /// Returns the interrupt parent of this node, which its `interrupts` are
+    /// routed to.
+    ///
+    /// Like Linux's `of_irq_find_parent`, this follows the `interrupt-parent`
+    /// phandle of the node, or goes to its parent node if it has none, until it
+    /// finds a node with an `#interrupt-cells` property. This may be an
+    /// interrupt controller or an interrupt nexus with an `interrupt-map`.
+    ///
+    /// Returns `None` if there is no such node, an `interrupt-parent` phandle
+    /// doesn't refer to a node, or it leads back to this node, as for a root
+    /// interrupt controller inheriting the `interrupt-parent` of the root node.
+    ///
+    /// # Performance
+    ///
+    /// Finding the parent node or the node with a phandle walks the device
+    /// tree, so this is linear in the number of nodes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the device tree structure is malformed, an
+    /// `interrupt-parent` property isn't a valid phandle, or
+    /// [`FdtErrorKind::TooDeep`] if no interrupt parent was found after
+    /// following many `interrupt-parent` properties.
+    pub fn interrupt_parent(&self) -> Result<Option<FdtNode<'a>>, FdtParseError> {
+        self.find_interrupt_ancestor(|node| Ok(node.interrupt_cells()?.is_some()))
+    }
+
+    /// Returns the interrupt controller which services the interrupts of this
+    /// node.
+    ///
+    /// Like [`interrupt_parent`](Self::interrupt_parent), this follows the
+    /// `interrupt-parent` phandles of the node and its ancestors, but until it
+    /// finds a node with an `interrupt-controller` property. Interrupt nexus
+    /// nodes are passed through without translating through their
+    /// `interrupt-map`; use [`map_interrupt`](Self::map_interrupt) for that.
+    ///
+    /// Returns `None` if there is no such node, an `interrupt-parent` phandle
+    /// doesn't refer to a node, or it leads back to this node.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`interrupt_parent`](Self::interrupt_parent).
+    pub fn interrupt_controller(&self) -> Result<Option<FdtNode<'a>>, FdtParseError> {
+        self.find_interrupt_ancestor(FdtNode::is_interrupt_controller)
+    }
+
+    /// Follows the `interrupt-parent` phandle of the node, or goes to its
+    /// parent node if it has none, until `found` returns true for a node.
+    fn find_interrupt_ancestor(
+        &self,
+        found: impl Fn(&FdtNode<'a>) -> Result<bool, FdtParseError>,
+    ) -> Result<Option<FdtNode<'a>>, FdtParseError> {
+        let mut node = *self;
+        for _ in 0..MAX_INTERRUPT_PARENT_HOPS {
+            let next = if let Some(property) = node.property("interrupt-parent")? {
+                self.fdt.find_node_by_phandle(property.as_phandle()?)?
+            } else {
+                node.parent()?
+            };
+            let Some(next) = next.filter(|next| next.offset != self.offset) else {
+                return Ok(None);
+            };
+            if found(&next)? {
+                return Ok(Some(next));
+            }
+            node = next;
+        }
+        Err(FdtParseError::new(
+            FdtErrorKind::TooDeep(MAX_INTERRUPT_PARENT_HOPS),
+            self.offset,
+        ))
+    }
+
+    /// Returns the interrupt specifiers of the standard `interrupts` property,
+    /// each made of the number of cells given by the `#interrupt-cells`
+    /// property of the [interrupt parent](Self::interrupt_parent).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read, the
+    /// interrupt parent can't be found, or the size of the value isn't a
+    /// multiple of its `#interrupt-cells`.
+    pub fn interrupts(
+        &self,
+    ) -> Result<Option<impl Iterator<Item = U32Array<'a>> + use<'a>>, FdtError> {
+        let Some(property) = self.property("interrupts")? else {
+            return Ok(None);
+        };
+        let interrupt_parent = self.interrupt_parent()?.ok_or(FdtError::MissingProperty {
+            name: "interrupt-parent",
+        })?;
+        let interrupt_cells =
+            interrupt_parent
+                .interrupt_cells()?
+                .ok_or(FdtError::MissingProperty {
+                    name: "#interrupt-cells",
+                })? as usize;
+        let chunk = interrupt_cells * size_of::<u32>();
+        let value = property.value();
+        if chunk == 0 || !value.len().is_multiple_of(chunk) {
+            return Err(FdtError::PropEncodedArraySizeMismatch {
+                size: value.len(),
+                chunk: interrupt_cells,
+            });
+        }
+        Ok(Some(value.chunks_exact(chunk).filter_map(U32Array::new)))
+    }
+
+    /// Translates an interrupt of a child of this interrupt nexus node to its
+    /// interrupt parent, using the `interrupt-map` and `interrupt-map-mask`
+    /// properties.
+    ///
+    /// The unit address of the child must have `#address-cells` cells and its
+    /// specifier `#interrupt-cells` cells, as given by this node. Both are
+    /// masked with `interrupt-map-mask`, if present, and compared with the
+    /// masked child unit address and specifier of each entry of
+    /// `interrupt-map`. The first matching entry gives the interrupt parent and
+    /// the translated unit address and specifier, which may need to be
+    /// translated again if the parent is itself a nexus.
+    ///
+    /// Returns `None` if no entry matches, or the unit address or specifier
+    /// don't have the expected number of cells.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read,
+    /// [`FdtError::MissingProperty`] if this node has no `interrupt-map` or
+    /// `#interrupt-cells` property, or [`FdtError::InvalidInterruptMap`] if an
+    /// entry of `interrupt-map` before the matching one can't be parsed.
+    pub fn map_interrupt(
+        &self,
+        unit_address: &[u32],
+        specifier: &[u32],
+    ) -> Result<Option<MappedInterrupt<'a>>, FdtError> {
+        let map = self
+            .property("interrupt-map")?
+            .ok_or(FdtError::MissingProperty {
+                name: "interrupt-map",
+            })?;
+        let Some(interrupt_cells) = self.interrupt_cells()? else {
+            return Err(FdtError::MissingProperty {
+                name: "#interrupt-cells",
+            });
+        };
+        if unit_address.len() != self.address_cells()? as usize
+            || specifier.len() != interrupt_cells as usize
+        {
+            return Ok(None);
+        }
+        let child_cells = unit_address.len() + specifier.len();
+        let mask = match self.property("interrupt-map-mask")? {
+            Some(mask) => Some(
+                U32Array::new(mask.value())
+                    .filter(|mask| mask.len() == child_cells)
+                    .ok_or(FdtError::InvalidInterruptMap(
+                        "interrupt-map-mask has the wrong size",
+                    ))?,
+            ),
+            None => None,
+        };
+
+        let truncated = FdtError::InvalidInterruptMap("entry is truncated");
+        let bytes = map.value();
+        let cells = U32Array::new(bytes).ok_or(truncated.clone())?;
+        let array = |start: usize, len: usize| {
+            bytes
+                .get(start * 4..(start + len) * 4)
+                .and_then(U32Array::new)
+                .ok_or(truncated.clone())
+        };
+        let mut entry = 0;
+        while entry < cells.len() {
+            let child = array(entry, child_cells)?;
+            let matches = unit_address
+                .iter()
+                .chain(specifier)
+                .zip(child.iter())
+                .enumerate()
+                .all(|(index, (&value, entry_value))| {
+                    let mask = mask.map_or(u32::MAX, |mask| mask.get(index).unwrap_or(0));
+                    (value ^ entry_value) & mask == 0
+                });
+
+            let phandle = cells.get(entry + child_cells).ok_or(truncated.clone())?;
+            let phandle = Phandle::new(phandle).ok_or(FdtError::InvalidInterruptMap(
+                "entry has an invalid phandle",
+            ))?;
+            let parent = self
+                .fdt
+                .find_node_by_phandle(phandle)?
+                .ok_or(FdtError::InvalidInterruptMap("interrupt parent not found"))?;
+            let parent_address_cells = match parent.property("#address-cells")? {
+                Some(property) => property.as_u32()? as usize,
+                None => 0,
+            };
+            let Some(parent_interrupt_cells) = parent.interrupt_cells()? else {
+                return Err(FdtError::InvalidInterruptMap(
+                    "interrupt parent has no #interrupt-cells",
+                ));
+            };
+
+            let parent_start = entry + child_cells + 1;
+            let parent_unit_address = array(parent_start, parent_address_cells)?;
+            let parent_specifier = array(
+                parent_start + parent_address_cells,
+                parent_interrupt_cells as usize,
+            )?;
+            if matches {
+                return Ok(Some(MappedInterrupt {
+                    parent,
+                    unit_address: parent_unit_address,
+                    specifier: parent_specifier,
+                }));
+            }
+            entry = parent_start + parent_unit_address.len() + parent_specifier.len();
+        }
+        Ok(None)
+    }
+}
+
+/// An interrupt translated by an interrupt nexus with
+/// [`FdtNode::map_interrupt`].
+#[derive(Clone, Copy, Debug)]
+pub struct MappedInterrupt<'a> {
+    /// The interrupt parent the interrupt is routed to.
+    pub parent: FdtNode<'a>,
+    /// The unit address in the address space of the interrupt parent, with its
+    /// `#address-cells` cells.
+    pub unit_address: U32Array<'a>,
+    /// The interrupt specifier for the interrupt parent, with its
+    /// `#interrupt-cells` cells.
+    pub specifier: U32Array<'a>,
+}
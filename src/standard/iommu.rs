@@ -0,0 +1,37 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::PhandleSpecifier;
+use super::specifier::SpecifierCells;
+use crate::error::{FdtError, FdtParseError};
+use crate::fdt::FdtNode;
+
+impl<'a> FdtNode<'a> {
+    /// Returns the IOMMUs the device is behind, from the standard `iommus`
+    /// property.
+    ///
+    /// Each entry is made of the phandle of an IOMMU followed by the number of
+    /// cells given by its `#iommu-cells` property, which usually identify the
+    /// stream of the device. The entries have no names.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read. The
+    /// iterator returns an error and then ends if an IOMMU can't be found, it
+    /// has no `#iommu-cells` property, or the entry is truncated.
+    pub fn iommus(
+        &self,
+    ) -> Result<
+        Option<impl Iterator<Item = Result<PhandleSpecifier<'a>, FdtError>> + use<'a>>,
+        FdtParseError,
+    > {
+        Ok(self.property("iommus")?.map(|property| {
+            self.phandle_array(property, None, SpecifierCells::Property("#iommu-cells"))
+        }))
+    }
+}
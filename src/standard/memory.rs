@@ -6,11 +6,17 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
 use core::fmt::{self, Display, Formatter};
 use core::ops::Deref;
 
+#[cfg(feature = "alloc")]
+use super::Status;
 use crate::error::FdtError;
 use crate::fdt::{Cells, Fdt, FdtNode};
+#[cfg(feature = "alloc")]
+use crate::memreserve::{MemoryReservation, coalesce_reservations};
 
 impl<'a> Fdt<'a> {
     /// Returns the `/memory` node.
@@ -26,6 +32,90 @@ impl<'a> Fdt<'a> {
         let node = self.find_node("/memory")?.ok_or(FdtError::MemoryMissing)?;
         Ok(Memory { node })
     }
+
+    /// Returns the RAM which is free for use, as a sorted list of
+    /// non-overlapping `(start, size)` regions.
+    ///
+    /// This is the union of the regions of every available node with a
+    /// `device_type` of `memory`, minus the entries of the memory reservation
+    /// block and the static regions of the `/reserved-memory` node. Regions
+    /// with the `no-map` property must not even be mapped, and the others are
+    /// in use by firmware or set aside for devices, including `reusable` ones,
+    /// which can only be used by an operating system which knows how to
+    /// reclaim them. Dynamic regions aren't allocated yet, so they don't take
+    /// anything away.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FdtError::MemoryMissing`] if there is no memory node, or an
+    /// error if the device tree structure is malformed, a `reg` property can't
+    /// be parsed or one of its regions doesn't fit in a u64.
+    #[cfg(feature = "alloc")]
+    pub fn usable_memory(self) -> Result<Vec<(u64, u64)>, FdtError> {
+        let mut memory = Vec::new();
+        for child in self.root()?.children() {
+            let node = child?;
+            let is_memory = node
+                .property("device_type")?
+                .is_some_and(|device_type| device_type.as_str() == Ok("memory"));
+            if is_memory && node.status()? == Status::Okay {
+                for region in (Memory { node }).regions()? {
+                    let (start, size) = region?;
+                    memory.push(MemoryReservation::new(start, size));
+                }
+            }
+        }
+        if memory.is_empty() {
+            return Err(FdtError::MemoryMissing);
+        }
+
+        let mut reserved = Vec::new();
+        for reservation in self.memory_reservations() {
+            let reservation = reservation?;
+            reserved.push(reservation);
+        }
+        if let Some(reserved_memory) = self.reserved_memory()? {
+            for region in reserved_memory.regions() {
+                if let Some(reg) = region?.reg()? {
+                    for reg in reg {
+                        reserved.push(MemoryReservation::new(reg.address()?, reg.size()?));
+                    }
+                }
+            }
+        }
+
+        let reserved = merge_regions(reserved);
+        let mut usable = Vec::new();
+        for region in merge_regions(memory) {
+            let (mut start, end) = (region.address(), region.end());
+            for reserved in &reserved {
+                let (reserved_start, reserved_end) = (reserved.address(), reserved.end());
+                if reserved_end <= start || reserved_start >= end {
+                    continue;
+                }
+                if reserved_start > start {
+                    usable.push((start, reserved_start - start));
+                }
+                start = reserved_end;
+                if start >= end {
+                    break;
+                }
+            }
+            if start < end {
+                usable.push((start, end - start));
+            }
+        }
+        Ok(usable)
+    }
+}
+
+/// Sorts regions and merges those which overlap or are adjacent, dropping
+/// empty ones.
+#[cfg(feature = "alloc")]
+fn merge_regions(mut regions: Vec<MemoryReservation>) -> Vec<MemoryReservation> {
+    let len = coalesce_reservations(&mut regions);
+    regions.truncate(len);
+    regions
 }
 
 /// Typed wrapper for a `/memory` node.
@@ -49,6 +139,26 @@ impl Display for Memory<'_> {
 }
 
 impl<'a> Memory<'a> {
+    /// Returns the regions of memory described by the `reg` property of the
+    /// memory node, as `(start, size)` pairs.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FdtError::MissingProperty`] if the memory node has no `reg`
+    /// property, or an error if its value cannot be read or its size isn't a
+    /// multiple of the expected number of address and size cells. The iterator
+    /// returns [`FdtError::TooManyCells`] for a region whose start or size
+    /// doesn't fit in a u64.
+    pub fn regions(
+        &self,
+    ) -> Result<impl Iterator<Item = Result<(u64, u64), FdtError>> + use<'a>, FdtError> {
+        Ok(self
+            .node
+            .reg()?
+            .ok_or(FdtError::MissingProperty { name: "reg" })?
+            .map(|reg| Ok((reg.address()?, reg.size()?))))
+    }
+
     /// Returns the value of the standard `initial-mapped-area` property of the
     /// memory node.
     ///
@@ -0,0 +1,158 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::specifier::SpecifierCells;
+use super::{Phandle, PhandleSpecifier};
+use crate::error::{FdtError, FdtParseError};
+use crate::fdt::{FdtNode, U32Array};
+
+/// The number of cells of each entry of an `msi-map` property.
+const MSI_MAP_ENTRY_CELLS: usize = 4;
+
+impl<'a> FdtNode<'a> {
+    /// Returns the MSI controllers the device sends its MSIs to, from the
+    /// standard `msi-parent` property.
+    ///
+    /// Each entry is made of the phandle of an MSI controller followed by the
+    /// number of cells given by its `#msi-cells` property, or no cells if it
+    /// has none. The entries have no names.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read. The
+    /// iterator returns an error and then ends if an MSI controller can't be
+    /// found or the entry is truncated.
+    pub fn msi_parents(
+        &self,
+    ) -> Result<
+        Option<impl Iterator<Item = Result<PhandleSpecifier<'a>, FdtError>> + use<'a>>,
+        FdtParseError,
+    > {
+        Ok(self.property("msi-parent")?.map(|property| {
+            self.phandle_array(
+                property,
+                None,
+                SpecifierCells::OptionalProperty("#msi-cells"),
+            )
+        }))
+    }
+
+    /// Returns the entries of the `msi-map` property of a PCI host bridge,
+    /// which map ranges of requester IDs to MSI controllers.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read, or the
+    /// size of the value isn't a multiple of 4 cells. The iterator returns an
+    /// error for an entry whose MSI controller can't be found.
+    pub fn msi_map(
+        &self,
+    ) -> Result<Option<impl Iterator<Item = Result<MsiMapEntry<'a>, FdtError>> + use<'a>>, FdtError>
+    {
+        let Some(property) = self.property("msi-map")? else {
+            return Ok(None);
+        };
+        let value = property.value();
+        let chunk = MSI_MAP_ENTRY_CELLS * size_of::<u32>();
+        if !value.len().is_multiple_of(chunk) {
+            return Err(FdtError::PropEncodedArraySizeMismatch {
+                size: value.len(),
+                chunk: MSI_MAP_ENTRY_CELLS,
+            });
+        }
+        let fdt = self.fdt;
+        Ok(Some(
+            value
+                .chunks_exact(chunk)
+                .filter_map(U32Array::new)
+                .map(move |entry| {
+                    let cell = |index| entry.get(index).unwrap_or_default();
+                    let phandle = cell(1);
+                    let controller = match Phandle::new(phandle) {
+                        Some(phandle) => fdt.find_node_by_phandle(phandle)?,
+                        None => None,
+                    };
+                    Ok(MsiMapEntry {
+                        rid_base: cell(0),
+                        controller: controller.ok_or(FdtError::PhandleNotFound(phandle))?,
+                        msi_base: cell(2),
+                        length: cell(3),
+                    })
+                }),
+        ))
+    }
+
+    /// Returns the value of the `msi-map-mask` property of a PCI host bridge,
+    /// which is applied to requester IDs before looking them up in `msi-map`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read, or the
+    /// value isn't a valid u32.
+    pub fn msi_map_mask(&self) -> Result<Option<u32>, FdtParseError> {
+        Ok(if let Some(property) = self.property("msi-map-mask")? {
+            Some(property.as_u32()?)
+        } else {
+            None
+        })
+    }
+
+    /// Maps the requester ID of a PCI device behind this host bridge to an MSI
+    /// controller and the ID of the device for it, using the `msi-map` and
+    /// `msi-map-mask` properties.
+    ///
+    /// Returns `None` if there is no `msi-map` property or no entry of it
+    /// contains the masked requester ID, in which case the MSI controller may
+    /// be given by [`msi_parents`](Self::msi_parents) instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`msi_map`](Self::msi_map) and
+    /// [`msi_map_mask`](Self::msi_map_mask) for the entry or any entry before
+    /// it.
+    pub fn map_msi(&self, rid: u32) -> Result<Option<MappedMsi<'a>>, FdtError> {
+        let Some(entries) = self.msi_map()? else {
+            return Ok(None);
+        };
+        let rid = rid & self.msi_map_mask()?.unwrap_or(u32::MAX);
+        for entry in entries {
+            let entry = entry?;
+            if let Some(offset) = rid.checked_sub(entry.rid_base)
+                && offset < entry.length
+            {
+                return Ok(Some(MappedMsi {
+                    controller: entry.controller,
+                    msi_id: entry.msi_base.wrapping_add(offset),
+                }));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// An entry of an `msi-map` property, as returned by [`FdtNode::msi_map`].
+#[derive(Clone, Copy, Debug)]
+pub struct MsiMapEntry<'a> {
+    /// The first requester ID of the range.
+    pub rid_base: u32,
+    /// The MSI controller the range is mapped to.
+    pub controller: FdtNode<'a>,
+    /// The ID for the MSI controller of the first requester ID of the range.
+    pub msi_base: u32,
+    /// The number of requester IDs in the range.
+    pub length: u32,
+}
+
+/// A requester ID mapped to an MSI controller with [`FdtNode::map_msi`].
+#[derive(Clone, Copy, Debug)]
+pub struct MappedMsi<'a> {
+    /// The MSI controller the device sends its MSIs to.
+    pub controller: FdtNode<'a>,
+    /// The ID of the device for the MSI controller, such as an ITS device ID.
+    pub msi_id: u32,
+}
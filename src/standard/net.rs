@@ -0,0 +1,340 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use core::fmt::{self, Display, Formatter};
+use core::str::FromStr;
+
+use crate::error::{FdtError, FdtParseError};
+use crate::fdt::{FdtNode, U32Array};
+
+/// The properties which may hold the MAC address of a network device, in order
+/// of preference.
+const MAC_ADDRESS_PROPERTIES: [&str; 3] = ["mac-address", "local-mac-address", "address"];
+
+/// The properties which may refer to the PHY of a network device, in order of
+/// preference.
+const PHY_PROPERTIES: [&str; 3] = ["phy-handle", "phy", "phy-device"];
+
+/// The number of cells of a legacy `fixed-link` property.
+const LEGACY_FIXED_LINK_CELLS: usize = 5;
+
+impl<'a> FdtNode<'a> {
+    /// Returns the MAC address of a network device, from its `mac-address`,
+    /// `local-mac-address` or `address` property, whichever comes first.
+    ///
+    /// `mac-address` is the address most recently used, such as one set by a
+    /// bootloader, while `local-mac-address` is the address assigned to the
+    /// device. An all-zero address is treated as a placeholder which hasn't
+    /// been filled in, so the next property is tried instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read, or the
+    /// value isn't 6 bytes long.
+    pub fn mac_address(&self) -> Result<Option<[u8; 6]>, FdtParseError> {
+        for name in MAC_ADDRESS_PROPERTIES {
+            if let Some(property) = self.property(name)? {
+                let mac = property.as_byte_array()?;
+                if mac != [0; 6] {
+                    return Ok(Some(mac));
+                }
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the PHY of a network device, from its `phy-handle` property or
+    /// the deprecated `phy` or `phy-device` properties.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read, the
+    /// value isn't a valid phandle, [`FdtError::PhandleNotFound`] if it doesn't
+    /// refer to a node, or an error if the `reg` property of the PHY isn't a
+    /// valid u32.
+    pub fn phy(&self) -> Result<Option<Phy<'a>>, FdtError> {
+        for name in PHY_PROPERTIES {
+            if let Some(property) = self.property(name)? {
+                let phandle = property.as_phandle()?;
+                let node = self
+                    .fdt
+                    .find_node_by_phandle(phandle)?
+                    .ok_or(FdtError::PhandleNotFound(phandle.get()))?;
+                let address = if let Some(reg) = node.property("reg")? {
+                    Some(reg.as_u32()?)
+                } else {
+                    None
+                };
+                return Ok(Some(Phy { node, address }));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Returns the interface between a network device and its PHY, from its
+    /// `phy-mode` property or the `phy-connection-type` property.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read, the
+    /// value isn't a valid string, or [`FdtError::InvalidPhyMode`] if it isn't
+    /// a known mode.
+    pub fn phy_mode(&self) -> Result<Option<PhyMode>, FdtError> {
+        let property = match self.property("phy-mode")? {
+            Some(property) => Some(property),
+            None => self.property("phy-connection-type")?,
+        };
+        Ok(if let Some(property) = property {
+            Some(property.as_str()?.parse()?)
+        } else {
+            None
+        })
+    }
+
+    /// Returns the fixed link of a network device which is connected to
+    /// something other than a PHY, such as a switch, from its `fixed-link`
+    /// child or the deprecated 5-cell `fixed-link` property.
+    ///
+    /// The GPIO giving the state of the link, if any, can be read from the
+    /// `link-gpios` property of the child with
+    /// [`gpios(Some("link"))`](Self::gpios).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read,
+    /// [`FdtError::MissingProperty`] if the child has no `speed` property, or
+    /// [`FdtError::PropEncodedArraySizeMismatch`] if the `fixed-link` property
+    /// isn't 5 cells long.
+    pub fn fixed_link(&self) -> Result<Option<FixedLink>, FdtError> {
+        if let Some(node) = self.child("fixed-link")? {
+            return Ok(Some(FixedLink {
+                speed: node
+                    .property("speed")?
+                    .ok_or(FdtError::MissingProperty { name: "speed" })?
+                    .as_u32()?,
+                full_duplex: node.property("full-duplex")?.is_some(),
+                pause: node.property("pause")?.is_some(),
+                asym_pause: node.property("asym-pause")?.is_some(),
+            }));
+        }
+        let Some(property) = self.property("fixed-link")? else {
+            return Ok(None);
+        };
+        let value = property.value();
+        let cells = U32Array::new(value)
+            .filter(|cells| cells.len() == LEGACY_FIXED_LINK_CELLS)
+            .ok_or(FdtError::PropEncodedArraySizeMismatch {
+                size: value.len(),
+                chunk: LEGACY_FIXED_LINK_CELLS,
+            })?;
+        // The first cell is an emulated PHY ID, which is ignored.
+        let cell = |index| cells.get(index).unwrap_or_default();
+        Ok(Some(FixedLink {
+            speed: cell(2),
+            full_duplex: cell(1) != 0,
+            pause: cell(3) != 0,
+            asym_pause: cell(4) != 0,
+        }))
+    }
+}
+
+/// The PHY of a network device, as returned by [`FdtNode::phy`].
+#[derive(Clone, Copy, Debug)]
+pub struct Phy<'a> {
+    /// The PHY node.
+    pub node: FdtNode<'a>,
+    /// The address of the PHY on its MDIO bus, from its `reg` property.
+    pub address: Option<u32>,
+}
+
+/// The settings of a fixed link, as returned by [`FdtNode::fixed_link`].
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct FixedLink {
+    /// The speed of the link in Mb/s.
+    pub speed: u32,
+    /// Whether the link is full duplex.
+    pub full_duplex: bool,
+    /// Whether pause frames are supported.
+    pub pause: bool,
+    /// Whether asymmetric pause frames are supported.
+    pub asym_pause: bool,
+}
+
+/// The value of a `phy-mode` or `phy-connection-type` property, which is the
+/// interface between a network device and its PHY.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum PhyMode {
+    /// The PHY is internal to the device.
+    Internal,
+    /// MII.
+    Mii,
+    /// GMII.
+    Gmii,
+    /// SGMII.
+    Sgmii,
+    /// TBI.
+    Tbi,
+    /// Reverse MII.
+    RevMii,
+    /// RMII.
+    Rmii,
+    /// Reverse RMII.
+    RevRmii,
+    /// RGMII, with the delays added by the PCB.
+    Rgmii,
+    /// RGMII, with the RX and TX delays added by the PHY.
+    RgmiiId,
+    /// RGMII, with the RX delay added by the PHY.
+    RgmiiRxid,
+    /// RGMII, with the TX delay added by the PHY.
+    RgmiiTxid,
+    /// RTBI.
+    Rtbi,
+    /// SMII.
+    Smii,
+    /// XGMII.
+    Xgmii,
+    /// XLGMII.
+    Xlgmii,
+    /// Multimedia over Coax (`MoCA`).
+    Moca,
+    /// PSGMII.
+    Psgmii,
+    /// QSGMII.
+    Qsgmii,
+    /// TRGMII.
+    Trgmii,
+    /// 100BASE-X.
+    Base100X,
+    /// 1000BASE-X.
+    Base1000X,
+    /// 1000BASE-KX.
+    Base1000Kx,
+    /// 2500BASE-X.
+    Base2500X,
+    /// 5GBASE-R.
+    Base5GR,
+    /// RXAUI.
+    Rxaui,
+    /// XAUI.
+    Xaui,
+    /// 10GBASE-R.
+    Base10GR,
+    /// 25GBASE-R.
+    Base25GR,
+    /// USXGMII.
+    Usxgmii,
+    /// 10GBASE-KR.
+    Base10GKr,
+    /// QUSGMII.
+    Qusgmii,
+    /// 10G-QXGMII.
+    Qxgmii10G,
+}
+
+impl PhyMode {
+    /// Returns the value of the property for this mode.
+    #[must_use]
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Internal => "internal",
+            Self::Mii => "mii",
+            Self::Gmii => "gmii",
+            Self::Sgmii => "sgmii",
+            Self::Tbi => "tbi",
+            Self::RevMii => "rev-mii",
+            Self::Rmii => "rmii",
+            Self::RevRmii => "rev-rmii",
+            Self::Rgmii => "rgmii",
+            Self::RgmiiId => "rgmii-id",
+            Self::RgmiiRxid => "rgmii-rxid",
+            Self::RgmiiTxid => "rgmii-txid",
+            Self::Rtbi => "rtbi",
+            Self::Smii => "smii",
+            Self::Xgmii => "xgmii",
+            Self::Xlgmii => "xlgmii",
+            Self::Moca => "moca",
+            Self::Psgmii => "psgmii",
+            Self::Qsgmii => "qsgmii",
+            Self::Trgmii => "trgmii",
+            Self::Base100X => "100base-x",
+            Self::Base1000X => "1000base-x",
+            Self::Base1000Kx => "1000base-kx",
+            Self::Base2500X => "2500base-x",
+            Self::Base5GR => "5gbase-r",
+            Self::Rxaui => "rxaui",
+            Self::Xaui => "xaui",
+            Self::Base10GR => "10gbase-r",
+            Self::Base25GR => "25gbase-r",
+            Self::Usxgmii => "usxgmii",
+            Self::Base10GKr => "10gbase-kr",
+            Self::Qusgmii => "qusgmii",
+            Self::Qxgmii10G => "10g-qxgmii",
+        }
+    }
+
+    /// Returns whether this is one of the RGMII modes, with or without
+    /// internal delays.
+    #[must_use]
+    pub fn is_rgmii(self) -> bool {
+        matches!(
+            self,
+            Self::Rgmii | Self::RgmiiId | Self::RgmiiRxid | Self::RgmiiTxid
+        )
+    }
+}
+
+impl Display for PhyMode {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PhyMode {
+    type Err = FdtError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "internal" => Self::Internal,
+            "mii" => Self::Mii,
+            "gmii" => Self::Gmii,
+            "sgmii" => Self::Sgmii,
+            "tbi" => Self::Tbi,
+            "rev-mii" => Self::RevMii,
+            "rmii" => Self::Rmii,
+            "rev-rmii" => Self::RevRmii,
+            "rgmii" => Self::Rgmii,
+            "rgmii-id" => Self::RgmiiId,
+            "rgmii-rxid" => Self::RgmiiRxid,
+            "rgmii-txid" => Self::RgmiiTxid,
+            "rtbi" => Self::Rtbi,
+            "smii" => Self::Smii,
+            "xgmii" => Self::Xgmii,
+            "xlgmii" => Self::Xlgmii,
+            "moca" => Self::Moca,
+            "psgmii" => Self::Psgmii,
+            "qsgmii" => Self::Qsgmii,
+            "trgmii" => Self::Trgmii,
+            "100base-x" => Self::Base100X,
+            "1000base-x" => Self::Base1000X,
+            "1000base-kx" => Self::Base1000Kx,
+            "2500base-x" => Self::Base2500X,
+            "5gbase-r" => Self::Base5GR,
+            "rxaui" => Self::Rxaui,
+            "xaui" => Self::Xaui,
+            "10gbase-r" => Self::Base10GR,
+            "25gbase-r" => Self::Base25GR,
+            "usxgmii" => Self::Usxgmii,
+            "10gbase-kr" => Self::Base10GKr,
+            "qusgmii" => Self::Qusgmii,
+            "10g-qxgmii" => Self::Qxgmii10G,
+            _ => return Err(FdtError::InvalidPhyMode),
+        })
+    }
+}
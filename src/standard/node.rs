@@ -0,0 +1,132 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use crate::error::{FdtError, FdtParseError};
+use crate::fdt::{FdtNode, FdtProperty};
+
+/// A node of either a flattened device tree or a
+/// [`DeviceTree`](crate::model::DeviceTree), whose standard properties can be
+/// read.
+///
+/// The typed wrappers for standard nodes of both kinds of tree are
+/// implemented once in terms of this.
+pub(crate) trait StandardNode<'a>: Copy {
+    /// The type of the node's properties.
+    type Property: StandardProperty<'a, Error = Self::Error>;
+
+    /// The error returned if the node or a property value can't be read.
+    type Error;
+
+    /// The error returned if a property value is missing or doesn't have the
+    /// expected layout.
+    type ValueError: From<Self::Error>;
+
+    /// Returns the name of this node without its unit address.
+    fn node_name_without_address(self) -> Result<&'a str, Self::Error>;
+
+    /// Returns the children of this node.
+    fn child_nodes(self) -> impl Iterator<Item = Result<Self, Self::Error>> + use<'a, Self>;
+
+    /// Returns the child of this node with the given name.
+    fn named_child(self, name: &str) -> Result<Option<Self>, Self::Error>;
+
+    /// Returns the property of this node with the given name.
+    fn node_property(self, name: &str) -> Result<Option<Self::Property>, Self::Error>;
+
+    /// Returns the error for a required property which is missing.
+    fn missing_property(name: &'static str) -> Self::ValueError;
+
+    /// Returns the error for a prop-encoded-array whose size isn't a multiple
+    /// of the size of an element.
+    fn size_mismatch(size: usize, chunk: usize) -> Self::ValueError;
+}
+
+/// A property of a [`StandardNode`].
+pub(crate) trait StandardProperty<'a>: Copy {
+    /// The error returned if the value doesn't have the requested type.
+    type Error;
+
+    /// Returns the raw value of this property.
+    fn property_value(self) -> &'a [u8];
+
+    /// Returns the value of this property as a `u32`.
+    fn property_u32(self) -> Result<u32, Self::Error>;
+
+    /// Returns the value of this property as a `u64`.
+    fn property_u64(self) -> Result<u64, Self::Error>;
+
+    /// Returns the value of this property as a string.
+    fn property_str(self) -> Result<&'a str, Self::Error>;
+}
+
+impl<'a> StandardNode<'a> for FdtNode<'a> {
+    type Property = FdtProperty<'a>;
+    type Error = FdtParseError;
+    type ValueError = FdtError;
+
+    fn node_name_without_address(self) -> Result<&'a str, FdtParseError> {
+        self.name_without_address()
+    }
+
+    fn child_nodes(self) -> impl Iterator<Item = Result<Self, FdtParseError>> + use<'a> {
+        self.children()
+    }
+
+    fn named_child(self, name: &str) -> Result<Option<Self>, FdtParseError> {
+        self.child(name)
+    }
+
+    fn node_property(self, name: &str) -> Result<Option<FdtProperty<'a>>, FdtParseError> {
+        self.property(name)
+    }
+
+    fn missing_property(name: &'static str) -> FdtError {
+        FdtError::MissingProperty { name }
+    }
+
+    fn size_mismatch(size: usize, chunk: usize) -> FdtError {
+        FdtError::PropEncodedArraySizeMismatch { size, chunk }
+    }
+}
+
+impl<'a> StandardProperty<'a> for FdtProperty<'a> {
+    type Error = FdtParseError;
+
+    fn property_value(self) -> &'a [u8] {
+        self.value()
+    }
+
+    fn property_u32(self) -> Result<u32, FdtParseError> {
+        self.as_u32()
+    }
+
+    fn property_u64(self) -> Result<u64, FdtParseError> {
+        self.as_u64()
+    }
+
+    fn property_str(self) -> Result<&'a str, FdtParseError> {
+        self.as_str()
+    }
+}
+
+/// Returns the value of the given property, which may be either a u32 or a
+/// u64 as for frequencies.
+pub(crate) fn u32_or_u64<'a, N: StandardNode<'a>>(
+    node: N,
+    name: &str,
+) -> Result<Option<u64>, N::Error> {
+    Ok(if let Some(property) = node.node_property(name)? {
+        if property.property_value().len() == size_of::<u32>() {
+            Some(property.property_u32()?.into())
+        } else {
+            Some(property.property_u64()?)
+        }
+    } else {
+        None
+    })
+}
@@ -0,0 +1,149 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use core::fmt::{self, Display, Formatter};
+use core::ops::Deref;
+
+use crate::error::{FdtError, FdtParseError};
+use crate::fdt::{Cells, Fdt, FdtNode};
+
+/// The distance from a NUMA node to itself, if the distance map doesn't give
+/// it.
+const LOCAL_DISTANCE: u32 = 10;
+
+impl<'a> Fdt<'a> {
+    /// Returns the `/distance-map` node, if it is compatible with
+    /// `numa,distance-map-v1`.
+    ///
+    /// # Errors
+    ///
+    /// Returns a parse error if there was a problem reading the FDT structure
+    /// to find the node or its `compatible` property.
+    pub fn distance_map(self) -> Result<Option<DistanceMap<'a>>, FdtParseError> {
+        let Some(node) = self.find_node("/distance-map")? else {
+            return Ok(None);
+        };
+        Ok(if node.is_compatible("numa,distance-map-v1")? {
+            Some(DistanceMap { node })
+        } else {
+            None
+        })
+    }
+}
+
+impl FdtNode<'_> {
+    /// Returns the value of the `numa-node-id` property, which is the NUMA
+    /// node that a CPU, memory node or device belongs to.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read, or the
+    /// value isn't a valid u32.
+    pub fn numa_node_id(&self) -> Result<Option<u32>, FdtParseError> {
+        Ok(if let Some(property) = self.property("numa-node-id")? {
+            Some(property.as_u32()?)
+        } else {
+            None
+        })
+    }
+}
+
+/// Typed wrapper for a `/distance-map` node, which gives the relative
+/// distances between NUMA nodes.
+#[derive(Clone, Copy, Debug)]
+pub struct DistanceMap<'a> {
+    node: FdtNode<'a>,
+}
+
+impl<'a> Deref for DistanceMap<'a> {
+    type Target = FdtNode<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.node
+    }
+}
+
+impl Display for DistanceMap<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.node.fmt(f)
+    }
+}
+
+impl<'a> DistanceMap<'a> {
+    /// Returns the entries of the `distance-matrix` property.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FdtError::MissingProperty`] if there is no `distance-matrix`
+    /// property, or an error if its value cannot be read or its size isn't a
+    /// multiple of 3 cells.
+    pub fn entries(&self) -> Result<impl Iterator<Item = NumaDistance> + use<'a>, FdtError> {
+        let property = self
+            .node
+            .property("distance-matrix")?
+            .ok_or(FdtError::MissingProperty {
+                name: "distance-matrix",
+            })?;
+        Ok(property
+            .as_prop_encoded_array([1, 1, 1])?
+            .map(NumaDistance::from_cells))
+    }
+
+    /// Returns the distance from one NUMA node to another, which makes up an
+    /// entry of the distance matrix.
+    ///
+    /// Distances are assumed to be symmetric if the map only gives one
+    /// direction, and the distance from a node to itself defaults to 10.
+    /// Returns `None` if the map doesn't give the distance.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`entries`](Self::entries).
+    pub fn distance(&self, from: u32, to: u32) -> Result<Option<u32>, FdtError> {
+        let mut reverse = None;
+        for entry in self.entries()? {
+            if entry.from == from && entry.to == to {
+                return Ok(Some(entry.distance));
+            }
+            if entry.from == to && entry.to == from {
+                reverse = Some(entry.distance);
+            }
+        }
+        Ok(reverse.or((from == to).then_some(LOCAL_DISTANCE)))
+    }
+}
+
+/// An entry of a `distance-matrix` property, giving the distance from one NUMA
+/// node to another.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct NumaDistance {
+    /// The NUMA node the distance is from.
+    pub from: u32,
+    /// The NUMA node the distance is to.
+    pub to: u32,
+    /// The relative distance, where 10 is the distance from a node to itself.
+    pub distance: u32,
+}
+
+impl NumaDistance {
+    /// Creates a `NumaDistance` from an array of three `Cells` containing the
+    /// two nodes and the distance.
+    ///
+    /// Each of the `Cells` must contain 1 cell, or the method will panic.
+    #[expect(
+        clippy::unwrap_used,
+        reason = "The Cells passed are always the correct size"
+    )]
+    fn from_cells([from, to, distance]: [Cells; 3]) -> Self {
+        Self {
+            from: from.to_int().unwrap(),
+            to: to.to_int().unwrap(),
+            distance: distance.to_int().unwrap(),
+        }
+    }
+}
@@ -0,0 +1,137 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::PhandleSpecifier;
+use super::specifier::{SpecifierCells, find_named};
+use crate::error::{FdtError, FdtParseError};
+use crate::fdt::{FdtNode, U32Array};
+
+impl<'a> FdtNode<'a> {
+    /// Returns the NVMEM cells the device reads data from, from the
+    /// `nvmem-cells` property, named by the `nvmem-cell-names` property if
+    /// present.
+    ///
+    /// Each entry is made of the phandle of an NVMEM cell node followed by the
+    /// number of cells given by its `#nvmem-cell-cells` property, or no cells
+    /// if it has none. The cell node can be read with
+    /// [`as_nvmem_cell`](Self::as_nvmem_cell).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read. The
+    /// iterator returns an error and then ends if an NVMEM cell can't be found
+    /// or the entry is truncated.
+    pub fn nvmem_cells(
+        &self,
+    ) -> Result<
+        Option<impl Iterator<Item = Result<PhandleSpecifier<'a>, FdtError>> + use<'a>>,
+        FdtParseError,
+    > {
+        self.phandle_array_by_name(
+            "nvmem-cells",
+            "nvmem-cell-names",
+            SpecifierCells::OptionalProperty("#nvmem-cell-cells"),
+        )
+    }
+
+    /// Returns the NVMEM cell with the given name in the `nvmem-cell-names`
+    /// property, such as `mac-address` or `calibration`.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`nvmem_cells`](Self::nvmem_cells) for the
+    /// cell or any cell before it.
+    pub fn nvmem_cell(&self, name: &str) -> Result<Option<PhandleSpecifier<'a>>, FdtError> {
+        let Some(cells) = self.nvmem_cells()? else {
+            return Ok(None);
+        };
+        find_named(cells, name)
+    }
+
+    /// Returns the cells of an NVMEM provider, which are its children with a
+    /// `reg` property, or those of its `nvmem-layout` child if it has one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the `nvmem-layout` child can't be read. The iterator
+    /// returns an error for a child which can't be read or has an invalid
+    /// `reg` or `bits` property.
+    pub fn nvmem_provider_cells(
+        &self,
+    ) -> Result<impl Iterator<Item = Result<NvmemCell<'a>, FdtError>> + use<'a>, FdtParseError>
+    {
+        let layout = self.child("nvmem-layout")?.unwrap_or(*self);
+        Ok(layout.children().filter_map(|child| {
+            child
+                .map_err(FdtError::from)
+                .and_then(|child| child.as_nvmem_cell())
+                .transpose()
+        }))
+    }
+
+    /// Returns the location of the data of this NVMEM cell node in its
+    /// provider, or `None` if it has no `reg` property.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read, the
+    /// offset or length don't fit in a u64, or the `bits` property isn't 2
+    /// cells long.
+    pub fn as_nvmem_cell(&self) -> Result<Option<NvmemCell<'a>>, FdtError> {
+        let Some(reg) = self.reg()?.and_then(|mut reg| reg.next()) else {
+            return Ok(None);
+        };
+        let bits = match self.property("bits")? {
+            Some(property) => {
+                let value = property.value();
+                let cells = U32Array::new(value)
+                    .filter(|cells| cells.len() == 2)
+                    .ok_or(FdtError::PropEncodedArraySizeMismatch {
+                        size: value.len(),
+                        chunk: 2,
+                    })?;
+                Some(NvmemBits {
+                    offset: cells.get(0).unwrap_or_default(),
+                    count: cells.get(1).unwrap_or_default(),
+                })
+            }
+            None => None,
+        };
+        Ok(Some(NvmemCell {
+            node: *self,
+            offset: reg.address()?,
+            length: reg.size()?,
+            bits,
+        }))
+    }
+}
+
+/// A cell of an NVMEM provider, such as an EEPROM or eFuse block, which holds
+/// data like a MAC address or calibration values.
+#[derive(Clone, Copy, Debug)]
+pub struct NvmemCell<'a> {
+    /// The node of the cell.
+    pub node: FdtNode<'a>,
+    /// The offset in bytes of the data in the provider, from `reg`.
+    pub offset: u64,
+    /// The length in bytes of the data, from `reg`.
+    pub length: u64,
+    /// The bits of the data which make up the value, from `bits`, if only part
+    /// of it is used.
+    pub bits: Option<NvmemBits>,
+}
+
+/// The bits of an NVMEM cell which make up its value, from its `bits`
+/// property.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct NvmemBits {
+    /// The offset of the first bit within the first byte of the cell.
+    pub offset: u32,
+    /// The number of bits.
+    pub count: u32,
+}
@@ -0,0 +1,346 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use core::fmt::{self, Display, Formatter};
+
+use super::interrupts::MAX_INTERRUPT_PARENT_HOPS;
+use super::{MappedInterrupt, Range, Reg};
+use crate::error::{FdtError, FdtErrorKind, FdtParseError};
+use crate::fdt::{Cells, FdtNode, U32Array};
+
+/// The maximum number of cells of a unit address or interrupt specifier which
+/// [`FdtNode::map_pci_interrupt`] can translate through an interrupt nexus.
+const MAX_INTERRUPT_CELLS: usize = 8;
+
+impl<'a> FdtNode<'a> {
+    /// Returns the value of the `assigned-addresses` property of a PCI device,
+    /// which lists the addresses assigned to its base address registers.
+    ///
+    /// The addresses are encoded like the `reg` property, and can be decoded
+    /// with [`Reg::pci_address`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read, or the
+    /// size of the value isn't a multiple of the expected number of address and
+    /// size cells.
+    pub fn assigned_addresses(
+        &self,
+    ) -> Result<Option<impl Iterator<Item = Reg<'a>> + use<'a>>, FdtError> {
+        let address_cells = self.parent_address_space.address_cells as usize;
+        let size_cells = self.parent_address_space.size_cells as usize;
+        Ok(
+            if let Some(property) = self.property("assigned-addresses")? {
+                Some(
+                    property
+                        .as_prop_encoded_array([address_cells, size_cells])?
+                        .map(Reg::from_cells),
+                )
+            } else {
+                None
+            },
+        )
+    }
+
+    /// Translates a legacy `INTx` interrupt of a PCI device behind this host
+    /// bridge to the interrupt controller which services it.
+    ///
+    /// `path` lists the addresses of the PCI-to-PCI bridges between the root
+    /// bus and the device, starting with the one on the root bus, followed by
+    /// the device itself. `pin` is the interrupt pin of the device, from 1 for
+    /// INTA to 4 for INTD. The pin is swizzled through each bridge with
+    /// [`swizzle_pci_pin`], then looked up with the address of the first device
+    /// of the path in the `interrupt-map` of this node, and of any further
+    /// interrupt nexus it leads to.
+    ///
+    /// Returns `None` if `path` is empty, `pin` isn't valid, or an
+    /// `interrupt-map` has no entry for the interrupt.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`map_interrupt`](Self::map_interrupt),
+    /// [`FdtError::CapacityExceeded`] if a translated unit address or
+    /// specifier is too long, or [`FdtErrorKind::TooDeep`] if it goes through
+    /// too many interrupt nexus nodes.
+    pub fn map_pci_interrupt(
+        &self,
+        path: &[PciAddress],
+        pin: u8,
+    ) -> Result<Option<MappedInterrupt<'a>>, FdtError> {
+        let Some((root, rest)) = path.split_first() else {
+            return Ok(None);
+        };
+        if !(1..=4).contains(&pin) {
+            return Ok(None);
+        }
+        let pin = rest
+            .iter()
+            .fold(pin, |pin, address| swizzle_pci_pin(pin, address.device));
+        // Only the bus, device and function identify the device.
+        let unit_address = root.phys_hi() & 0x00ff_ff00;
+        let mut mapped = self.map_interrupt(&[unit_address, 0, 0], &[pin.into()])?;
+        for _ in 0..MAX_INTERRUPT_PARENT_HOPS {
+            let Some(interrupt) = mapped else {
+                return Ok(None);
+            };
+            let parent = interrupt.parent;
+            if parent.is_interrupt_controller()? || parent.property("interrupt-map")?.is_none() {
+                return Ok(Some(interrupt));
+            }
+            let mut unit_address = [0; MAX_INTERRUPT_CELLS];
+            let mut specifier = [0; MAX_INTERRUPT_CELLS];
+            let unit_address = copy_cells(interrupt.unit_address, &mut unit_address)?;
+            let specifier = copy_cells(interrupt.specifier, &mut specifier)?;
+            mapped = parent.map_interrupt(unit_address, specifier)?;
+        }
+        Err(FdtParseError::new(
+            FdtErrorKind::TooDeep(MAX_INTERRUPT_PARENT_HOPS),
+            self.offset,
+        )
+        .into())
+    }
+}
+
+/// Copies the given cells into the start of `buffer`, returning the part of it
+/// which holds them.
+fn copy_cells<'b>(cells: U32Array<'_>, buffer: &'b mut [u32]) -> Result<&'b [u32], FdtError> {
+    let buffer = buffer
+        .get_mut(..cells.len())
+        .ok_or(FdtError::CapacityExceeded("interrupt cells"))?;
+    for (cell, value) in buffer.iter_mut().zip(cells.iter()) {
+        *cell = value;
+    }
+    Ok(buffer)
+}
+
+/// Returns the interrupt pin which an `INTx` interrupt of a device behind a
+/// PCI-to-PCI bridge is routed to on the bus of the bridge, following the
+/// standard swizzle of the PCI-to-PCI bridge specification.
+///
+/// Pins are numbered from 1 for INTA to 4 for INTD.
+///
+/// # Examples
+///
+/// ```
+/// # use dtoolkit::standard::swizzle_pci_pin;
+/// assert_eq!(swizzle_pci_pin(1, 0), 1);
+/// assert_eq!(swizzle_pci_pin(1, 1), 2);
+/// assert_eq!(swizzle_pci_pin(4, 2), 2);
+/// ```
+#[must_use]
+pub fn swizzle_pci_pin(pin: u8, device: u8) -> u8 {
+    (pin.wrapping_sub(1).wrapping_add(device) & 0x3) + 1
+}
+
+impl Reg<'_> {
+    /// Decodes the address as a PCI address, for the `reg` or
+    /// `assigned-addresses` property of a PCI device.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FdtError::InvalidPciAddress`] if the address isn't 3 cells
+    /// long.
+    pub fn pci_address(&self) -> Result<PciAddress, FdtError> {
+        self.address.try_into()
+    }
+}
+
+impl Range<'_> {
+    /// Decodes the child bus address as a PCI address, for the `ranges`
+    /// property of a PCI host bridge.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FdtError::InvalidPciAddress`] if the address isn't 3 cells
+    /// long.
+    pub fn pci_child_bus_address(&self) -> Result<PciAddress, FdtError> {
+        self.child_bus_address.try_into()
+    }
+}
+
+/// An address on a PCI bus, as encoded in 3 cells by the PCI bus binding.
+///
+/// The first cell, `phys.hi`, has the bits `npt000ss bbbbbbbb dddddfff
+/// rrrrrrrr`, and the other two hold the 64-bit address within the space.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct PciAddress {
+    /// The address space (`ss`).
+    pub space: PciSpace,
+    /// The bus number (`bbbbbbbb`).
+    pub bus: u8,
+    /// The device number (`ddddd`).
+    pub device: u8,
+    /// The function number (`fff`).
+    pub function: u8,
+    /// The offset of the base address register in configuration space
+    /// (`rrrrrrrr`).
+    pub register: u8,
+    /// Whether the region is prefetchable (`p`).
+    pub prefetchable: bool,
+    /// Whether the address is relocatable, i.e. the `n` bit is clear.
+    pub relocatable: bool,
+    /// Whether the address is aliased, for non-relocatable I/O or memory below
+    /// 1 MiB (`t`).
+    pub aliased: bool,
+    /// The address within the space, from the `phys.mid` and `phys.lo` cells.
+    pub address: u64,
+}
+
+impl PciAddress {
+    const NON_RELOCATABLE: u32 = 1 << 31;
+    const PREFETCHABLE: u32 = 1 << 30;
+    const ALIASED: u32 = 1 << 29;
+
+    /// Decodes an address from the `phys.hi`, `phys.mid` and `phys.lo` cells.
+    #[must_use]
+    #[expect(
+        clippy::cast_possible_truncation,
+        reason = "The fields are masked to fit"
+    )]
+    pub fn from_cells(hi: u32, mid: u32, lo: u32) -> Self {
+        Self {
+            space: PciSpace::from_bits(hi >> 24),
+            bus: (hi >> 16) as u8,
+            device: ((hi >> 11) & 0x1f) as u8,
+            function: ((hi >> 8) & 0x7) as u8,
+            register: hi as u8,
+            prefetchable: hi & Self::PREFETCHABLE != 0,
+            relocatable: hi & Self::NON_RELOCATABLE == 0,
+            aliased: hi & Self::ALIASED != 0,
+            address: u64::from(mid) << 32 | u64::from(lo),
+        }
+    }
+
+    /// Returns the address of the configuration space of the given device
+    /// function.
+    #[must_use]
+    pub fn config(bus: u8, device: u8, function: u8) -> Self {
+        Self {
+            space: PciSpace::Configuration,
+            bus,
+            device,
+            function,
+            register: 0,
+            prefetchable: false,
+            relocatable: true,
+            aliased: false,
+            address: 0,
+        }
+    }
+
+    /// Returns the `phys.hi` cell of the address.
+    #[must_use]
+    pub fn phys_hi(&self) -> u32 {
+        let mut hi = (self.space as u32) << 24
+            | u32::from(self.bus) << 16
+            | u32::from(self.device & 0x1f) << 11
+            | u32::from(self.function & 0x7) << 8
+            | u32::from(self.register);
+        if !self.relocatable {
+            hi |= Self::NON_RELOCATABLE;
+        }
+        if self.prefetchable {
+            hi |= Self::PREFETCHABLE;
+        }
+        if self.aliased {
+            hi |= Self::ALIASED;
+        }
+        hi
+    }
+}
+
+impl TryFrom<Cells<'_>> for PciAddress {
+    type Error = FdtError;
+
+    fn try_from(cells: Cells<'_>) -> Result<Self, Self::Error> {
+        match cells.0 {
+            [hi, mid, lo] => Ok(Self::from_cells(hi.get(), mid.get(), lo.get())),
+            _ => Err(FdtError::InvalidPciAddress {
+                cells: cells.0.len(),
+            }),
+        }
+    }
+}
+
+/// Formats the address as its space, whether it is prefetchable, the
+/// bus:device.function of the device and the address, e.g.
+/// `mem64 pref 00:01.0 0x0000004000000000`.
+impl Display for PciAddress {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{}", self.space)?;
+        if self.prefetchable {
+            f.write_str(" pref")?;
+        }
+        write!(
+            f,
+            " {:02x}:{:02x}.{} {:#018x}",
+            self.bus, self.device, self.function, self.address
+        )
+    }
+}
+
+/// The address space of a [`PciAddress`].
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum PciSpace {
+    /// Configuration space.
+    Configuration = 0,
+    /// I/O space.
+    Io = 1,
+    /// 32-bit memory space.
+    Memory32 = 2,
+    /// 64-bit memory space.
+    Memory64 = 3,
+}
+
+impl PciSpace {
+    fn from_bits(bits: u32) -> Self {
+        match bits & 0x3 {
+            0 => Self::Configuration,
+            1 => Self::Io,
+            2 => Self::Memory32,
+            _ => Self::Memory64,
+        }
+    }
+}
+
+impl Display for PciSpace {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Self::Configuration => "config",
+            Self::Io => "io",
+            Self::Memory32 => "mem",
+            Self::Memory64 => "mem64",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip() {
+        let address = PciAddress::from_cells(0xc300_0810, 0x40, 0x1000);
+        assert_eq!(
+            address,
+            PciAddress {
+                space: PciSpace::Memory64,
+                bus: 0,
+                device: 1,
+                function: 0,
+                register: 0x10,
+                prefetchable: true,
+                relocatable: false,
+                aliased: false,
+                address: 0x40_0000_1000,
+            }
+        );
+        assert_eq!(address.phys_hi(), 0xc300_0810);
+        assert_eq!(address.to_string(), "mem64 pref 00:01.0 0x0000004000001000");
+    }
+}
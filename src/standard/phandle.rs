@@ -0,0 +1,101 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use core::fmt::{self, Display, Formatter};
+
+use crate::error::FdtParseError;
+use crate::fdt::FdtNode;
+
+/// The value of a `phandle` property, which uniquely identifies a node so
+/// that other properties can refer to it.
+///
+/// A phandle can't be 0 or `0xffffffff`, as the Devicetree Specification
+/// reserves these values.
+///
+/// # Examples
+///
+/// ```
+/// # use dtoolkit::standard::Phandle;
+/// let phandle = Phandle::new(0x1234).unwrap();
+/// assert_eq!(phandle.get(), 0x1234);
+/// assert!(Phandle::new(0).is_none());
+/// assert!(Phandle::new(0xffff_ffff).is_none());
+/// ```
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct Phandle(u32);
+
+impl Phandle {
+    /// Returns the phandle with the given value, or `None` if it is one of the
+    /// reserved values.
+    #[must_use]
+    pub const fn new(value: u32) -> Option<Self> {
+        match value {
+            0 | u32::MAX => None,
+            _ => Some(Self(value)),
+        }
+    }
+
+    /// Returns the value of this phandle.
+    #[must_use]
+    pub const fn get(self) -> u32 {
+        self.0
+    }
+}
+
+impl From<Phandle> for u32 {
+    fn from(phandle: Phandle) -> Self {
+        phandle.0
+    }
+}
+
+impl Display for Phandle {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "{:#x}", self.0)
+    }
+}
+
+/// A node of either a flattened device tree or a
+/// [`DeviceTree`](crate::model::DeviceTree), whose phandle can be read.
+pub(crate) trait PhandleNode: Copy {
+    /// The error returned if the node can't be read.
+    type Error;
+
+    /// Returns the phandle of this node, from its `phandle` property or else
+    /// its legacy `linux,phandle` property.
+    fn node_phandle(self) -> Result<Option<Phandle>, Self::Error>;
+
+    /// Returns the descendants of this node in depth-first order, without
+    /// recursing.
+    fn descendant_nodes(self) -> impl Iterator<Item = Result<Self, Self::Error>>;
+}
+
+impl PhandleNode for FdtNode<'_> {
+    type Error = FdtParseError;
+
+    fn node_phandle(self) -> Result<Option<Phandle>, FdtParseError> {
+        let property = match self.property("phandle")? {
+            Some(property) => Some(property),
+            None => self.property("linux,phandle")?,
+        };
+        property.map(|property| property.as_phandle()).transpose()
+    }
+
+    fn descendant_nodes(self) -> impl Iterator<Item = Result<Self, FdtParseError>> {
+        self.descendants()
+    }
+}
+
+/// Returns the largest phandle of `node` and its descendants, or `None` if
+/// none of them have one.
+pub(crate) fn max_phandle<N: PhandleNode>(node: N) -> Result<Option<Phandle>, N::Error> {
+    let mut max = node.node_phandle()?;
+    for descendant in node.descendant_nodes() {
+        max = max.max(descendant?.node_phandle()?);
+    }
+    Ok(max)
+}
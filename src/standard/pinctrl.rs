@@ -0,0 +1,111 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use super::specifier::SpecifierCells;
+use crate::error::{FdtError, FdtParseError};
+use crate::fdt::{FdtNode, FdtProperty};
+
+impl<'a> FdtNode<'a> {
+    /// Returns the pin control states of the node, from its `pinctrl-0`,
+    /// `pinctrl-1`, ... properties, named by the `pinctrl-names` property if
+    /// present.
+    ///
+    /// Like Linux, this stops at the first missing `pinctrl-<n>` property.
+    ///
+    /// # Performance
+    ///
+    /// Finding each state walks the properties of the node, so this is
+    /// quadratic in the number of properties.
+    pub fn pinctrl_states(
+        &self,
+    ) -> impl Iterator<Item = Result<PinctrlState<'a>, FdtParseError>> + use<'a> {
+        let node = *self;
+        let mut done = false;
+        (0..).map_while(move |index| {
+            if done {
+                return None;
+            }
+            let state = node.pinctrl_state_at(index).transpose();
+            done = !matches!(state, Some(Ok(_)));
+            state
+        })
+    }
+
+    /// Returns the pin control state with the given name in the
+    /// `pinctrl-names` property, such as `default` or `sleep`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read.
+    pub fn pinctrl_state(&self, name: &str) -> Result<Option<PinctrlState<'a>>, FdtParseError> {
+        let Some(names) = self.property("pinctrl-names")? else {
+            return Ok(None);
+        };
+        let Some(index) = names.as_str_list().position(|state| state == name) else {
+            return Ok(None);
+        };
+        self.pinctrl_state_at(u32::try_from(index).unwrap_or(u32::MAX))
+    }
+
+    /// Returns the pin control state from the `pinctrl-<index>` property.
+    fn pinctrl_state_at(&self, index: u32) -> Result<Option<PinctrlState<'a>>, FdtParseError> {
+        for property in self.properties() {
+            let property = property?;
+            if pinctrl_index(property.name()) == Some(index) {
+                let name = match self.property("pinctrl-names")? {
+                    Some(names) => names.as_str_list().nth(index as usize),
+                    None => None,
+                };
+                return Ok(Some(PinctrlState {
+                    node: *self,
+                    property,
+                    index,
+                    name,
+                }));
+            }
+        }
+        Ok(None)
+    }
+}
+
+/// Returns the index of a `pinctrl-<index>` property, or `None` for any other
+/// property.
+fn pinctrl_index(name: &str) -> Option<u32> {
+    let index = name.strip_prefix("pinctrl-")?;
+    if index.is_empty() || !index.bytes().all(|ch| ch.is_ascii_digit()) {
+        return None;
+    }
+    index.parse().ok()
+}
+
+/// A pin control state of a device, from one of its `pinctrl-<index>`
+/// properties.
+#[derive(Clone, Copy, Debug)]
+pub struct PinctrlState<'a> {
+    node: FdtNode<'a>,
+    property: FdtProperty<'a>,
+    /// The index of the state, as in `pinctrl-<index>`.
+    pub index: u32,
+    /// The name of the state from `pinctrl-names`, if any.
+    pub name: Option<&'a str>,
+}
+
+impl<'a> PinctrlState<'a> {
+    /// Returns the pin configuration nodes of the state, which are children of
+    /// pin controllers.
+    ///
+    /// Phandles of 0 are skipped.
+    ///
+    /// The iterator returns an error and then ends if a phandle doesn't refer
+    /// to a node, or the value isn't a list of phandles.
+    pub fn configs(&self) -> impl Iterator<Item = Result<FdtNode<'a>, FdtError>> + use<'a> {
+        self.node
+            .phandle_array(self.property, None, SpecifierCells::Fixed(0))
+            .map(|entry| entry.map(|entry| entry.provider))
+    }
+}
@@ -9,6 +9,7 @@
 use core::fmt::{self, Debug, Display, Formatter};
 use core::ops::{BitOr, Shl};
 
+use super::UnitAddress;
 use crate::error::FdtError;
 use crate::fdt::Cells;
 
@@ -37,6 +38,27 @@ impl<'a> Reg<'a> {
         self.address.to_int()
     }
 
+    /// Returns the canonical unit address for a node with this `reg`, for
+    /// example to name a generated node.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use dtoolkit::fdt::Fdt;
+    /// # let dtb = include_bytes!("../../tests/dtb/test_props.dtb");
+    /// let fdt = Fdt::new(dtb).unwrap();
+    /// let node = fdt.find_node("/standard-props").unwrap().unwrap();
+    /// let reg = node.reg().unwrap().unwrap().next().unwrap();
+    /// assert_eq!(
+    ///     format!("serial@{}", reg.unit_address()),
+    ///     "serial@1234567800003000"
+    /// );
+    /// ```
+    #[must_use]
+    pub fn unit_address(self) -> UnitAddress<'a> {
+        UnitAddress(self.address)
+    }
+
     /// Attempts to return the size as the given type, if it will fit.
     ///
     /// # Errors
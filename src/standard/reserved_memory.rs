@@ -0,0 +1,192 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use core::fmt::{self, Display, Formatter};
+use core::ops::Deref;
+
+use super::Reg;
+use crate::error::{FdtError, FdtParseError};
+use crate::fdt::{Cells, Fdt, FdtNode};
+
+impl<'a> Fdt<'a> {
+    /// Returns the `/reserved-memory` node, if there is one.
+    ///
+    /// # Errors
+    ///
+    /// Returns a parse error if there was a problem reading the FDT structure
+    /// to find the node.
+    pub fn reserved_memory(self) -> Result<Option<ReservedMemory<'a>>, FdtParseError> {
+        Ok(self
+            .find_node("/reserved-memory")?
+            .map(|node| ReservedMemory { node }))
+    }
+}
+
+/// Typed wrapper for a `/reserved-memory` node.
+///
+/// Unlike the memory reservation block of the blob, which only lists static
+/// regions, the children of this node can also describe regions which are
+/// allocated dynamically by the operating system.
+#[derive(Clone, Copy, Debug)]
+pub struct ReservedMemory<'a> {
+    node: FdtNode<'a>,
+}
+
+impl<'a> Deref for ReservedMemory<'a> {
+    type Target = FdtNode<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.node
+    }
+}
+
+impl Display for ReservedMemory<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.node.fmt(f)
+    }
+}
+
+impl<'a> ReservedMemory<'a> {
+    /// Returns an iterator over the reserved memory regions, one for each
+    /// child node.
+    pub fn regions(
+        &self,
+    ) -> impl Iterator<Item = Result<ReservedMemoryRegion<'a>, FdtParseError>> + use<'a> {
+        self.node
+            .children()
+            .map(|child| child.map(|node| ReservedMemoryRegion { node }))
+    }
+}
+
+/// Typed wrapper for a child of the `/reserved-memory` node.
+///
+/// A static region has a `reg` property, available through
+/// [`FdtNode::reg`]. A dynamic region instead has a `size` and optionally an
+/// `alignment` and `alloc-ranges`, to be allocated by the operating system.
+#[derive(Clone, Copy, Debug)]
+pub struct ReservedMemoryRegion<'a> {
+    node: FdtNode<'a>,
+}
+
+impl<'a> Deref for ReservedMemoryRegion<'a> {
+    type Target = FdtNode<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.node
+    }
+}
+
+impl Display for ReservedMemoryRegion<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.node.fmt(f)
+    }
+}
+
+impl<'a> ReservedMemoryRegion<'a> {
+    /// Returns the value of the `size` property of a dynamic region.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read, or the
+    /// value isn't `#size-cells` cells long.
+    pub fn size(&self) -> Result<Option<Cells<'a>>, FdtError> {
+        self.size_cells_property("size")
+    }
+
+    /// Returns the value of the `alignment` property of a dynamic region.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read, or the
+    /// value isn't `#size-cells` cells long.
+    pub fn alignment(&self) -> Result<Option<Cells<'a>>, FdtError> {
+        self.size_cells_property("alignment")
+    }
+
+    /// Returns the value of the `alloc-ranges` property of a dynamic region,
+    /// which lists the ranges it may be allocated from.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read, or the
+    /// size of the value isn't a multiple of the expected number of address and
+    /// size cells.
+    pub fn alloc_ranges(
+        &self,
+    ) -> Result<Option<impl Iterator<Item = Reg<'a>> + use<'a>>, FdtError> {
+        let address_cells = self.node.parent_address_space.address_cells as usize;
+        let size_cells = self.node.parent_address_space.size_cells as usize;
+        Ok(
+            if let Some(property) = self.node.property("alloc-ranges")? {
+                Some(
+                    property
+                        .as_prop_encoded_array([address_cells, size_cells])?
+                        .map(Reg::from_cells),
+                )
+            } else {
+                None
+            },
+        )
+    }
+
+    /// Returns whether the region is allocated dynamically, i.e. it has a
+    /// `size` property but no `reg` property.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property can't be read.
+    pub fn is_dynamic(&self) -> Result<bool, FdtParseError> {
+        Ok(self.node.property("reg")?.is_none() && self.node.property("size")?.is_some())
+    }
+
+    /// Returns whether the `no-map` property is present, meaning that the
+    /// operating system must not create a mapping of the region.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property can't be read.
+    pub fn no_map(&self) -> Result<bool, FdtParseError> {
+        Ok(self.node.property("no-map")?.is_some())
+    }
+
+    /// Returns whether the `reusable` property is present, meaning that the
+    /// operating system may use the region as long as it can reclaim it.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property can't be read.
+    pub fn reusable(&self) -> Result<bool, FdtParseError> {
+        Ok(self.node.property("reusable")?.is_some())
+    }
+
+    /// Returns whether the region is compatible with `shared-dma-pool`,
+    /// meaning that it is a pool of memory for DMA buffers shared by devices.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property can't be read.
+    pub fn is_shared_dma_pool(&self) -> Result<bool, FdtParseError> {
+        self.node.is_compatible("shared-dma-pool")
+    }
+
+    /// Returns the value of a property which must be a single size.
+    fn size_cells_property(&self, name: &str) -> Result<Option<Cells<'a>>, FdtError> {
+        let Some(property) = self.node.property(name)? else {
+            return Ok(None);
+        };
+        let size_cells = self.node.parent_address_space.size_cells as usize;
+        let mut sizes = property.as_prop_encoded_array([size_cells])?;
+        match (sizes.next(), sizes.next()) {
+            (Some([size]), None) => Ok(Some(size)),
+            _ => Err(FdtError::PropEncodedArraySizeMismatch {
+                size: property.value().len(),
+                chunk: size_cells,
+            }),
+        }
+    }
+}
@@ -0,0 +1,218 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use core::iter;
+
+use super::Phandle;
+use crate::error::{FdtError, FdtParseError};
+use crate::fdt::{Fdt, FdtNode, FdtProperty, U32Array};
+
+/// An entry of a phandle-array property such as `clocks` or `resets`, which
+/// refers to a provider node along with a specifier for it.
+#[derive(Clone, Copy, Debug)]
+pub struct PhandleSpecifier<'a> {
+    /// The name of the entry, from the matching `-names` property, if any.
+    pub name: Option<&'a str>,
+    /// The node the entry refers to.
+    pub provider: FdtNode<'a>,
+    /// The specifier, with the number of cells given by the `#…-cells`
+    /// property of the provider.
+    pub specifier: U32Array<'a>,
+}
+
+impl<'a> FdtNode<'a> {
+    /// Returns the entries of the phandle-array property `property`, paired
+    /// with the strings of the `names` property in order.
+    ///
+    /// This is the common form of bindings such as `resets` and `reset-names`,
+    /// `power-domains` and `power-domain-names`, `mboxes` and `mbox-names`,
+    /// `dmas` and `dma-names`, or `interconnects` and `interconnect-names`.
+    /// Each entry is made of the phandle of a provider followed by the number
+    /// of cells given by its `cells_name` property, such as `#reset-cells`.
+    /// Entries with a phandle of 0 are placeholders, which are skipped but
+    /// still take up a name.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read. The
+    /// iterator returns an error and then ends if a provider can't be found,
+    /// it has no `cells_name` property, or the entry is truncated.
+    pub fn phandle_specifiers(
+        &self,
+        property: &str,
+        names: &str,
+        cells_name: &'static str,
+    ) -> Result<
+        Option<impl Iterator<Item = Result<PhandleSpecifier<'a>, FdtError>> + use<'a>>,
+        FdtParseError,
+    > {
+        self.phandle_array_by_name(property, names, SpecifierCells::Property(cells_name))
+    }
+
+    /// Returns the entry of the phandle-array property `property` with the
+    /// given name in the `names` property.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as
+    /// [`phandle_specifiers`](Self::phandle_specifiers) for the entry or any
+    /// entry before it.
+    pub fn phandle_specifier(
+        &self,
+        property: &str,
+        names: &str,
+        cells_name: &'static str,
+        name: &str,
+    ) -> Result<Option<PhandleSpecifier<'a>>, FdtError> {
+        let Some(specifiers) = self.phandle_specifiers(property, names, cells_name)? else {
+            return Ok(None);
+        };
+        find_named(specifiers, name)
+    }
+
+    /// Returns the entries of the phandle-array property `property`, paired
+    /// with the strings of the `names` property in order.
+    pub(crate) fn phandle_array_by_name(
+        &self,
+        property: &str,
+        names: &str,
+        cells: SpecifierCells,
+    ) -> Result<
+        Option<impl Iterator<Item = Result<PhandleSpecifier<'a>, FdtError>> + use<'a>>,
+        FdtParseError,
+    > {
+        let Some(property) = self.property(property)? else {
+            return Ok(None);
+        };
+        let names = self.property(names)?;
+        Ok(Some(self.phandle_array(property, names, cells)))
+    }
+
+    /// Returns the entries of the given phandle-array property, paired with
+    /// the strings of the `names` property in order.
+    ///
+    /// Entries with a phandle of 0 are placeholders, which are skipped but
+    /// still take up a name.
+    pub(crate) fn phandle_array(
+        &self,
+        property: FdtProperty<'a>,
+        names: Option<FdtProperty<'a>>,
+        cells: SpecifierCells,
+    ) -> impl Iterator<Item = Result<PhandleSpecifier<'a>, FdtError>> + use<'a> {
+        let entries = PhandleArray {
+            fdt: self.fdt,
+            value: property.value(),
+            cells,
+        };
+        let names = names
+            .map(|names| names.as_str_list())
+            .into_iter()
+            .flatten()
+            .map(Some)
+            .chain(iter::repeat(None));
+        entries.zip(names).filter_map(|(entry, name)| match entry {
+            Ok(Some((provider, specifier))) => Some(Ok(PhandleSpecifier {
+                name,
+                provider,
+                specifier,
+            })),
+            Ok(None) => None,
+            Err(error) => Some(Err(error)),
+        })
+    }
+}
+
+/// Returns the entry with the given name, or the first error before it.
+pub(crate) fn find_named<'a>(
+    mut specifiers: impl Iterator<Item = Result<PhandleSpecifier<'a>, FdtError>>,
+    name: &str,
+) -> Result<Option<PhandleSpecifier<'a>>, FdtError> {
+    specifiers
+        .find(|specifier| {
+            specifier
+                .as_ref()
+                .map_or(true, |specifier| specifier.name == Some(name))
+        })
+        .transpose()
+}
+
+/// The number of cells of the specifiers in a phandle-array property.
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum SpecifierCells {
+    /// Given by the property with this name on each provider, such as
+    /// `#clock-cells`.
+    Property(&'static str),
+    /// Like `Property`, but 0 if a provider doesn't have the property, as for
+    /// `#msi-cells`.
+    OptionalProperty(&'static str),
+    /// The same for all entries, as for `gpio-ranges`.
+    Fixed(usize),
+}
+
+/// An iterator over the entries of a phandle-array property, which yields
+/// `None` for placeholder entries with a phandle of 0.
+struct PhandleArray<'a> {
+    fdt: Fdt<'a>,
+    value: &'a [u8],
+    cells: SpecifierCells,
+}
+
+impl<'a> PhandleArray<'a> {
+    fn read_entry(&mut self) -> Result<Option<(FdtNode<'a>, U32Array<'a>)>, FdtError> {
+        let truncated = |cells| FdtError::PropEncodedArraySizeMismatch {
+            size: self.value.len(),
+            chunk: cells,
+        };
+        let Some((phandle, rest)) = self.value.split_first_chunk() else {
+            return Err(truncated(1));
+        };
+        let value = u32::from_be_bytes(*phandle);
+        let Some(phandle) = Phandle::new(value) else {
+            self.value = rest;
+            return Ok(None);
+        };
+        let provider = self
+            .fdt
+            .find_node_by_phandle(phandle)?
+            .ok_or(FdtError::PhandleNotFound(value))?;
+        let cells = match self.cells {
+            SpecifierCells::Property(name) => provider
+                .property(name)?
+                .ok_or(FdtError::MissingProperty { name })?
+                .as_u32()? as usize,
+            SpecifierCells::OptionalProperty(name) => match provider.property(name)? {
+                Some(property) => property.as_u32()? as usize,
+                None => 0,
+            },
+            SpecifierCells::Fixed(cells) => cells,
+        };
+        let Some((specifier, rest)) = rest
+            .split_at_checked(cells * size_of::<u32>())
+            .and_then(|(specifier, rest)| Some((U32Array::new(specifier)?, rest)))
+        else {
+            return Err(truncated(cells + 1));
+        };
+        self.value = rest;
+        Ok(Some((provider, specifier)))
+    }
+}
+
+impl<'a> Iterator for PhandleArray<'a> {
+    type Item = Result<Option<(FdtNode<'a>, U32Array<'a>)>, FdtError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.value.is_empty() {
+            return None;
+        }
+        let entry = self.read_entry();
+        if entry.is_err() {
+            self.value = &[];
+        }
+        Some(entry)
+    }
+}
@@ -29,10 +29,10 @@ pub enum Status {
 }
 
 impl Status {
-    fn as_str(self) -> &'static str {
+    pub(crate) fn as_str(self) -> &'static str {
         match self {
             Status::Okay => "okay",
-            Status::Disabled => "disadbled",
+            Status::Disabled => "disabled",
             Status::Reserved => "reserved",
             Status::Fail => "fail",
             Status::FailSss => "fail-sss",
@@ -52,7 +52,7 @@ impl FromStr for Status {
     fn from_str(s: &str) -> Result<Self, Self::Err> {
         match s {
             "okay" => Ok(Self::Okay),
-            "disadbled" => Ok(Self::Disabled),
+            "disabled" => Ok(Self::Disabled),
             "reserved" => Ok(Self::Reserved),
             "fail" => Ok(Self::Fail),
             "fail-sss" => Ok(Self::FailSss),
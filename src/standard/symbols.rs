@@ -0,0 +1,95 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use core::fmt::{self, Display, Formatter};
+use core::ops::Deref;
+
+use crate::error::FdtParseError;
+use crate::fdt::{Fdt, FdtNode};
+
+impl<'a> Fdt<'a> {
+    /// Returns the `/__symbols__` node, if there is one.
+    ///
+    /// This node is added by `dtc -@` to list the labels of the device tree,
+    /// so that overlays can refer to them.
+    ///
+    /// # Errors
+    ///
+    /// Returns a parse error if there was a problem reading the FDT structure
+    /// to find the node.
+    pub fn symbols(self) -> Result<Option<Symbols<'a>>, FdtParseError> {
+        Ok(self.find_node("/__symbols__")?.map(|node| Symbols { node }))
+    }
+
+    /// Finds the node with the given label, as listed in the `/__symbols__`
+    /// node.
+    ///
+    /// Returns `None` if there is no `/__symbols__` node, the label isn't in
+    /// it, or its path doesn't lead to a node.
+    ///
+    /// # Errors
+    ///
+    /// Returns a parse error if there was a problem reading the FDT structure,
+    /// or the path of the label isn't a valid string.
+    pub fn resolve_label(self, label: &str) -> Result<Option<FdtNode<'a>>, FdtParseError> {
+        let Some(symbols) = self.symbols()? else {
+            return Ok(None);
+        };
+        match symbols.path(label)? {
+            Some(path) => self.find_node(path),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Typed wrapper for a `/__symbols__` node.
+#[derive(Clone, Copy, Debug)]
+pub struct Symbols<'a> {
+    node: FdtNode<'a>,
+}
+
+impl<'a> Deref for Symbols<'a> {
+    type Target = FdtNode<'a>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.node
+    }
+}
+
+impl Display for Symbols<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        self.node.fmt(f)
+    }
+}
+
+impl<'a> Symbols<'a> {
+    /// Returns an iterator over the labels and the paths of the nodes they
+    /// refer to.
+    pub fn labels(
+        &self,
+    ) -> impl Iterator<Item = Result<(&'a str, &'a str), FdtParseError>> + use<'a> {
+        self.node.properties().map(|property| {
+            let property = property?;
+            Ok((property.name(), property.as_str()?))
+        })
+    }
+
+    /// Returns the path of the node with the given label.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read, or the
+    /// value isn't a valid string.
+    pub fn path(&self, label: &str) -> Result<Option<&'a str>, FdtParseError> {
+        Ok(if let Some(property) = self.node.property(label)? {
+            Some(property.as_str()?)
+        } else {
+            None
+        })
+    }
+}
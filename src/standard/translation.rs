@@ -0,0 +1,336 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use zerocopy::FromBytes;
+use zerocopy::byteorder::big_endian;
+
+use super::Reg;
+use crate::error::{FdtError, FdtErrorKind, FdtParseError};
+use crate::fdt::{Cells, Fdt, FdtChildIter, FdtNode};
+
+/// The maximum depth of nodes which [`Fdt::address_map`] can descend into, as
+/// it keeps the buses being walked at each level on the stack rather than
+/// allocating.
+pub const MAX_ADDRESS_MAP_DEPTH: usize = 32;
+
+/// The maximum number of cells of an address or size which can be translated,
+/// so that it fits in a u64.
+const MAX_TRANSLATABLE_CELLS: u32 = 2;
+
+impl<'a> Fdt<'a> {
+    /// Returns the deepest node with a `reg` region containing the given CPU
+    /// physical address, once translated through the `ranges` of its
+    /// ancestors.
+    ///
+    /// Nodes under a bus which isn't [translatable](FdtNode::is_translatable),
+    /// such as an I2C controller or a PCI host bridge, are skipped. If several
+    /// nodes at the same depth contain the address, the first one is
+    /// returned.
+    ///
+    /// # Performance
+    ///
+    /// This walks the whole device tree once, as [`Fdt::address_map`] does,
+    /// without recursing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the device tree structure is malformed, or a `reg`
+    /// or `ranges` property can't be parsed. As with [`Fdt::address_map`], an
+    /// [`FdtErrorKind::TooDeep`] error is returned if buses are nested more
+    /// than [`MAX_ADDRESS_MAP_DEPTH`] levels below the root.
+    pub fn find_node_by_address(self, address: u64) -> Result<Option<FdtNode<'a>>, FdtError> {
+        let mut regions = AddressMap::new(self.root()?);
+        let mut found: Option<(usize, FdtNode<'a>)> = None;
+        while let Some((node, start, size)) = regions.next_region()? {
+            // The depth of the node is the number of buses above it.
+            let depth = regions.depth;
+            if found.is_none_or(|(found_depth, _)| depth > found_depth)
+                && address
+                    .checked_sub(start)
+                    .is_some_and(|offset| offset < size)
+            {
+                found = Some((depth, node));
+            }
+        }
+        Ok(found.map(|(_, node)| node))
+    }
+
+    /// Returns an iterator over the memory-mapped regions of the device tree,
+    /// from the `reg` property of every node, translated to CPU physical
+    /// addresses through the `ranges` of its ancestors.
+    ///
+    /// This can be used to build a map of the MMIO regions which a guest or
+    /// non-secure world may access in one pass. Each entry has the node it
+    /// belongs to, whose path can be found with [`FdtNode::path`] with the
+    /// `alloc` feature. Nodes under a bus which isn't
+    /// [translatable](FdtNode::is_translatable) are skipped, as are `reg`
+    /// entries outside of the ranges of a bus.
+    ///
+    /// The regions are returned in the order of the nodes in the device tree.
+    /// Buses nested more than [`MAX_ADDRESS_MAP_DEPTH`] levels below the root
+    /// aren't walked, and an [`FdtErrorKind::TooDeep`] error is returned
+    /// instead. The iterator ends after returning an error.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the root node can't be read. The iterator returns
+    /// an error if the device tree structure is malformed, or a `reg`,
+    /// `ranges` or `compatible` property can't be parsed.
+    pub fn address_map(
+        self,
+    ) -> Result<impl Iterator<Item = Result<AddressMapEntry<'a>, FdtError>> + use<'a>, FdtParseError>
+    {
+        Ok(AddressMap::new(self.root()?))
+    }
+}
+
+/// A memory-mapped region of a node, as returned by [`Fdt::address_map`].
+#[derive(Clone, Copy, Debug)]
+pub struct AddressMapEntry<'a> {
+    /// The node the region belongs to.
+    pub node: FdtNode<'a>,
+    /// The first, most specific, string of the `compatible` property of the
+    /// node, if it has one.
+    pub compatible: Option<&'a str>,
+    /// The CPU physical address of the start of the region.
+    pub start: u64,
+    /// The size of the region in bytes.
+    pub size: u64,
+}
+
+/// The iterator returned by [`Fdt::address_map`].
+struct AddressMap<'a> {
+    /// The buses being walked at each level, from the root down.
+    stack: [Option<Level<'a>>; MAX_ADDRESS_MAP_DEPTH],
+    /// The number of levels in `stack`.
+    depth: usize,
+    /// The node whose `reg` entries are being returned, with the cells of the
+    /// entries left.
+    current: Option<(FdtNode<'a>, &'a [big_endian::U32])>,
+    done: bool,
+}
+
+/// A bus being walked, with its children left to look at.
+struct Level<'a> {
+    node: FdtNode<'a>,
+    children: FdtChildIter<'a>,
+}
+
+impl<'a> AddressMap<'a> {
+    fn new(root: FdtNode<'a>) -> Self {
+        let mut stack = [const { None }; MAX_ADDRESS_MAP_DEPTH];
+        stack[0] = Some(Level {
+            node: root,
+            children: FdtChildIter::Start { node: root },
+        });
+        Self {
+            stack,
+            depth: 1,
+            current: None,
+            done: false,
+        }
+    }
+
+    fn next_entry(&mut self) -> Result<Option<AddressMapEntry<'a>>, FdtError> {
+        let Some((node, start, size)) = self.next_region()? else {
+            return Ok(None);
+        };
+        Ok(Some(AddressMapEntry {
+            node,
+            compatible: node
+                .compatible()?
+                .and_then(|mut compatible| compatible.next()),
+            start,
+            size,
+        }))
+    }
+
+    /// Returns the next translated region, with the node it belongs to.
+    fn next_region(&mut self) -> Result<Option<(FdtNode<'a>, u64, u64)>, FdtError> {
+        loop {
+            if let Some((node, cells)) = self.current {
+                let address_cells = node.parent_address_space.address_cells as usize;
+                let size_cells = node.parent_address_space.size_cells as usize;
+                let entry = cells
+                    .split_at_checked(address_cells + size_cells)
+                    .filter(|(entry, _)| !entry.is_empty());
+                let Some((entry, rest)) = entry else {
+                    self.current = None;
+                    if node.is_translatable()? {
+                        self.push(node)?;
+                    }
+                    continue;
+                };
+                self.current = Some((node, rest));
+                let (address, size) = entry.split_at(address_cells);
+                let reg = Reg::from_cells([Cells(address), Cells(size)]);
+                let (Ok(address), Ok(size)) = (reg.address::<u64>(), reg.size::<u64>()) else {
+                    continue;
+                };
+                if let Some(start) = self.translate(address)? {
+                    return Ok(Some((node, start, size)));
+                }
+                continue;
+            }
+            let Some(index) = self.depth.checked_sub(1) else {
+                return Ok(None);
+            };
+            let Some(level) = &mut self.stack[index] else {
+                unreachable!("levels below the depth are set");
+            };
+            if let Some(child) = level.children.next().transpose()? {
+                self.current = Some((child, reg_cells(child)?));
+            } else {
+                self.depth = index;
+            }
+        }
+    }
+
+    fn push(&mut self, node: FdtNode<'a>) -> Result<(), FdtParseError> {
+        let level = self.stack.get_mut(self.depth).ok_or(FdtParseError::new(
+            FdtErrorKind::TooDeep(MAX_ADDRESS_MAP_DEPTH),
+            node.offset,
+        ))?;
+        *level = Some(Level {
+            node,
+            children: FdtChildIter::Start { node },
+        });
+        self.depth += 1;
+        Ok(())
+    }
+
+    /// Translates an address in the address space of the innermost bus being
+    /// walked to a CPU physical address, through the `ranges` of every bus
+    /// but the root.
+    fn translate(&self, address: u64) -> Result<Option<u64>, FdtError> {
+        let mut address = address;
+        for bus in self.stack[1..self.depth].iter().rev().flatten() {
+            let Some(translated) = bus.node.translate_child_address(address)? else {
+                return Ok(None);
+            };
+            address = translated;
+        }
+        Ok(Some(address))
+    }
+}
+
+/// Returns the cells of the `reg` property of `node`, or none if it doesn't
+/// have one.
+fn reg_cells(node: FdtNode<'_>) -> Result<&[big_endian::U32], FdtError> {
+    let Some(reg) = node.property("reg")? else {
+        return Ok(&[]);
+    };
+    // Check that the value is a whole number of entries.
+    node.reg()?;
+    Ok(<[big_endian::U32]>::ref_from_bytes(reg.value()).unwrap_or_default())
+}
+
+impl<'a> Iterator for AddressMap<'a> {
+    type Item = Result<AddressMapEntry<'a>, FdtError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        let entry = self.next_entry().transpose();
+        if !matches!(entry, Some(Ok(_))) {
+            self.done = true;
+        }
+        entry
+    }
+}
+
+impl FdtNode<'_> {
+    /// Returns whether the addresses of the children of this bus can be
+    /// translated to the address space of its parent with its `ranges`
+    /// property, as for a `simple-bus` or a node with an empty `ranges`
+    /// property.
+    ///
+    /// Buses without a `ranges` property, such as I2C and SPI controllers,
+    /// aren't memory-mapped, and buses whose addresses or sizes don't fit in a
+    /// u64, such as PCI host bridges, need bus-specific translation, so they
+    /// aren't translatable. The root node is always translatable, as the
+    /// addresses of its children are CPU physical addresses.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read, or the
+    /// value of `#address-cells` or `#size-cells` isn't a valid u32.
+    pub fn is_translatable(&self) -> Result<bool, FdtParseError> {
+        if self.offset == self.fdt.root()?.offset {
+            return Ok(true);
+        }
+        Ok(self.property("ranges")?.is_some()
+            && self.address_cells()? <= MAX_TRANSLATABLE_CELLS
+            && self.size_cells()? <= MAX_TRANSLATABLE_CELLS
+            && self.parent_address_space.address_cells <= MAX_TRANSLATABLE_CELLS)
+    }
+
+    /// Translates an address in the address space of the parent of this
+    /// node, such as the address of a `reg` entry, to a CPU physical address.
+    ///
+    /// The address is translated through the `ranges` property of each
+    /// ancestor bus up to the root. Returns `None` if none of the ranges of a
+    /// bus contains the address.
+    ///
+    /// # Performance
+    ///
+    /// Finding the parent nodes walks the device tree, so this is linear in
+    /// the number of nodes times the depth of this node.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FdtError::NotTranslatable`] if an ancestor bus isn't
+    /// [translatable](Self::is_translatable), or an error if the device tree
+    /// structure is malformed or a `ranges` property can't be parsed.
+    pub fn translate_address(&self, address: u64) -> Result<Option<u64>, FdtError> {
+        let mut address = address;
+        let Some(mut bus) = self.parent()? else {
+            return Ok(Some(address));
+        };
+        while let Some(parent) = bus.parent()? {
+            if !bus.is_translatable()? {
+                return Err(FdtError::NotTranslatable {
+                    node_offset: bus.offset,
+                });
+            }
+            let Some(translated) = bus.translate_child_address(address)? else {
+                return Ok(None);
+            };
+            address = translated;
+            bus = parent;
+        }
+        Ok(Some(address))
+    }
+
+    /// Translates an address in the address space of the children of this
+    /// bus to the address space of its parent, using its `ranges` property.
+    ///
+    /// The bus must be [translatable](Self::is_translatable).
+    fn translate_child_address(&self, address: u64) -> Result<Option<u64>, FdtError> {
+        let Some(ranges) = self.ranges()? else {
+            return Ok(None);
+        };
+        let mut ranges = ranges.peekable();
+        if ranges.peek().is_none() {
+            // An empty `ranges` property means an identity mapping.
+            return Ok(Some(address));
+        }
+        for range in ranges {
+            let child: u64 = range.child_bus_address()?;
+            let length: u64 = range.length()?;
+            if let Some(offset) = address.checked_sub(child)
+                && offset < length
+            {
+                let parent: u64 = range.parent_bus_address()?;
+                return Ok(parent.checked_add(offset));
+            }
+        }
+        Ok(None)
+    }
+}
@@ -0,0 +1,204 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use core::fmt::{self, Display, Formatter, Write};
+use core::num::ParseIntError;
+
+use crate::error::FdtError;
+use crate::fdt::{Cells, FdtNode};
+
+/// Parses a unit address as one or more comma-separated hexadecimal values.
+///
+/// Most buses use a single number as the unit address, but some, such as PCI,
+/// use several.
+///
+/// # Examples
+///
+/// ```
+/// # use dtoolkit::standard::parse_unit_address;
+/// assert!(parse_unit_address("4000").eq([Ok(0x4000)]));
+/// assert!(parse_unit_address("1,2").eq([Ok(1), Ok(2)]));
+/// assert!(parse_unit_address("xyz").next().unwrap().is_err());
+/// ```
+pub fn parse_unit_address(
+    unit_address: &str,
+) -> impl Iterator<Item = Result<u64, ParseIntError>> + use<'_> {
+    unit_address
+        .split(',')
+        .map(|part| u64::from_str_radix(part, 16))
+}
+
+/// The canonical unit address of a node for a given address, as lowercase
+/// hexadecimal without leading zeros.
+///
+/// This is returned by [`Reg::unit_address`](super::Reg::unit_address).
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct UnitAddress<'a>(pub(crate) Cells<'a>);
+
+impl Display for UnitAddress<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let mut cells = self
+            .0
+            .0
+            .iter()
+            .map(|cell| cell.get())
+            .skip_while(|&cell| cell == 0);
+        let Some(first) = cells.next() else {
+            return f.write_str("0");
+        };
+        write!(f, "{first:x}")?;
+        for cell in cells {
+            write!(f, "{cell:08x}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Compares the unit address with a string without allocating, so that
+/// `"0x1000"` or `"01000"` don't match the canonical `1000`.
+impl PartialEq<str> for UnitAddress<'_> {
+    fn eq(&self, other: &str) -> bool {
+        let mut compare = Compare {
+            rest: other,
+            equal: true,
+        };
+        write!(compare, "{self}").is_ok() && compare.equal && compare.rest.is_empty()
+    }
+}
+
+/// Compares formatted output with the start of a string.
+struct Compare<'s> {
+    rest: &'s str,
+    equal: bool,
+}
+
+impl Write for Compare<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        match self.rest.strip_prefix(s) {
+            Some(rest) => self.rest = rest,
+            None => self.equal = false,
+        }
+        Ok(())
+    }
+}
+
+impl<'a> FdtNode<'a> {
+    /// Checks that the unit address of this node is consistent with its `reg`
+    /// property, like the `unit_address_vs_reg` and `unit_address_format`
+    /// warnings of dtc.
+    ///
+    /// A node with a `reg` property or a non-empty `ranges` property must have
+    /// a unit address, a node with a unit address must have one of them, and
+    /// the unit address must be the canonical form of the address of the
+    /// first `reg` entry, in lowercase hexadecimal without leading zeros.
+    /// Unit addresses made of several comma-separated values, as used by PCI
+    /// and some other buses, have a bus-specific format, so they aren't
+    /// compared with `reg`.
+    ///
+    /// Returns `None` if the node is consistent. This can be called for each
+    /// node of a device tree as part of a validation pass.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a property's name or value cannot be read, or the
+    /// size of the `reg` property isn't a multiple of the expected number of
+    /// address and size cells.
+    pub fn check_unit_address(&self) -> Result<Option<UnitAddressFinding<'a>>, FdtError> {
+        let node = *self;
+        let unit_address = self.unit_address()?;
+        let reg = self.reg()?.and_then(|mut reg| reg.next());
+        let has_ranges = self
+            .property("ranges")?
+            .is_some_and(|ranges| !ranges.value().is_empty());
+        Ok(match (unit_address, reg) {
+            (None, Some(_)) => Some(UnitAddressFinding::MissingUnitAddress { node }),
+            (None, None) if has_ranges => Some(UnitAddressFinding::MissingUnitAddress { node }),
+            (Some(_), None) if !has_ranges => Some(UnitAddressFinding::MissingReg { node }),
+            (Some(unit_address), Some(reg))
+                if !unit_address.contains(',') && reg.unit_address() != *unit_address =>
+            {
+                Some(UnitAddressFinding::Mismatch {
+                    node,
+                    expected: reg.unit_address(),
+                })
+            }
+            _ => None,
+        })
+    }
+}
+
+/// An inconsistency between the unit address of a node and its `reg`
+/// property, as found by [`FdtNode::check_unit_address`].
+#[derive(Clone, Copy, Debug)]
+pub enum UnitAddressFinding<'a> {
+    /// The node has a `reg` or `ranges` property, but no unit address.
+    MissingUnitAddress {
+        /// The node.
+        node: FdtNode<'a>,
+    },
+    /// The node has a unit address, but no `reg` or `ranges` property.
+    MissingReg {
+        /// The node.
+        node: FdtNode<'a>,
+    },
+    /// The unit address of the node isn't the canonical unit address of the
+    /// first entry of its `reg` property.
+    Mismatch {
+        /// The node.
+        node: FdtNode<'a>,
+        /// The canonical unit address of the first `reg` entry.
+        expected: UnitAddress<'a>,
+    },
+}
+
+impl<'a> UnitAddressFinding<'a> {
+    /// Returns the node the finding is about.
+    #[must_use]
+    pub fn node(&self) -> FdtNode<'a> {
+        match *self {
+            Self::MissingUnitAddress { node }
+            | Self::MissingReg { node }
+            | Self::Mismatch { node, .. } => node,
+        }
+    }
+}
+
+/// Formats the finding like the corresponding dtc warning, without the path of
+/// the node.
+impl Display for UnitAddressFinding<'_> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self {
+            Self::MissingUnitAddress { .. } => {
+                f.write_str("node has a reg or ranges property, but no unit name")
+            }
+            Self::MissingReg { .. } => {
+                f.write_str("node has a unit name, but no reg or ranges property")
+            }
+            Self::Mismatch { expected, .. } => {
+                write!(f, "unit name doesn't match reg, expected \"{expected}\"")
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn format_unit_address() {
+        let address = [0.into(), 0x1.into(), 0xabcd_0000.into()];
+        assert_eq!(UnitAddress(Cells(&address)).to_string(), "1abcd0000");
+        assert_eq!(UnitAddress(Cells(&address[..1])).to_string(), "0");
+        assert_eq!(UnitAddress(Cells(&[])).to_string(), "0");
+        assert!(UnitAddress(Cells(&address)) == *"1abcd0000");
+        assert!(UnitAddress(Cells(&address)) != *"1abcd");
+        assert!(UnitAddress(Cells(&address)) != *"01abcd0000");
+        assert!(UnitAddress(Cells(&address)) != *"1abcd00000");
+    }
+}
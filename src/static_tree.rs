@@ -0,0 +1,489 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! A fixed-capacity device tree that can be built without memory allocation.
+//!
+//! [`StaticDeviceTree`] stores its nodes, properties and all names and values
+//! in arrays whose sizes are given as const generic parameters, so it can be
+//! used on targets without a heap. It only supports appending nodes and
+//! properties and serializing the result to a caller-provided buffer, which is
+//! enough for firmware that needs to hand a small, generated device tree to the
+//! next stage. For anything more involved, use
+//! [`DeviceTree`](crate::model::DeviceTree) from the `write` feature instead.
+//!
+//! # Examples
+//!
+//! ```
+//! use dtoolkit::fdt::Fdt;
+//! use dtoolkit::static_tree::StaticDeviceTree;
+//!
+//! let mut tree = StaticDeviceTree::<4, 8, 256>::new();
+//! let root = tree.root();
+//! tree.add_property_u32(root, "#address-cells", 2).unwrap();
+//! tree.add_property_u32(root, "#size-cells", 2).unwrap();
+//! let chosen = tree.add_node(root, "chosen").unwrap();
+//! tree.add_property_str(chosen, "bootargs", "console=ttyS0")
+//!     .unwrap();
+//! let memory = tree.add_node(root, "memory@80000000").unwrap();
+//! tree.add_property_str(memory, "device_type", "memory")
+//!     .unwrap();
+//! tree.add_property(
+//!     memory,
+//!     "reg",
+//!     &[0, 0, 0, 0, 0x80, 0, 0, 0, 0, 0, 0, 0, 0x40, 0, 0, 0],
+//! )
+//! .unwrap();
+//!
+//! let mut dtb = [0; 512];
+//! let size = tree.to_dtb(&mut dtb).unwrap();
+//! let fdt = Fdt::new(&dtb[..size]).unwrap();
+//! let chosen = fdt.find_node("/chosen").unwrap().unwrap();
+//! let bootargs = chosen.property("bootargs").unwrap().unwrap();
+//! assert_eq!(bootargs.as_str(), Ok("console=ttyS0"));
+//! ```
+
+use zerocopy::IntoBytes;
+
+use crate::error::FdtError;
+use crate::fdt::{
+    FDT_BEGIN_NODE, FDT_END, FDT_END_NODE, FDT_MAGIC, FDT_PROP, FDT_TAGSIZE, Fdt, FdtHeader,
+    LAST_COMP_VERSION, LAST_VERSION,
+};
+use crate::memreserve::MemoryReservation;
+
+/// A handle to a node of a [`StaticDeviceTree`].
+///
+/// A handle is only meaningful for the tree which returned it. Handles which
+/// don't refer to a node of the tree they are used with are rejected with
+/// [`FdtError::InvalidNodeId`], but a handle from another tree may happen to
+/// refer to a node of this one as well.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct StaticNodeId(usize);
+
+#[derive(Clone, Copy, Debug)]
+struct StaticNode {
+    parent: usize,
+    name: (usize, usize),
+}
+
+#[derive(Clone, Copy, Debug)]
+struct StaticProperty {
+    node: usize,
+    name: (usize, usize),
+    value: (usize, usize),
+    /// The offset of the name in the strings block.
+    name_offset: usize,
+    /// Whether this is the first property with this name, which is therefore
+    /// responsible for writing the name to the strings block.
+    first_with_name: bool,
+}
+
+/// A device tree with room for `NODES` nodes (including the root node),
+/// `PROPS` properties and `BUF` bytes of names and property values.
+///
+/// See the [module documentation](self) for details.
+#[derive(Clone, Debug)]
+pub struct StaticDeviceTree<const NODES: usize, const PROPS: usize, const BUF: usize> {
+    nodes: [StaticNode; NODES],
+    node_count: usize,
+    props: [StaticProperty; PROPS],
+    prop_count: usize,
+    buf: [u8; BUF],
+    buf_len: usize,
+    strings_size: usize,
+}
+
+impl<const NODES: usize, const PROPS: usize, const BUF: usize> Default
+    for StaticDeviceTree<NODES, PROPS, BUF>
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const NODES: usize, const PROPS: usize, const BUF: usize> StaticDeviceTree<NODES, PROPS, BUF> {
+    /// Creates a new device tree containing only an empty root node.
+    ///
+    /// `NODES` must be at least 1 to make room for the root node, which is
+    /// checked at compile time.
+    #[must_use]
+    pub const fn new() -> Self {
+        const {
+            assert!(
+                NODES > 0,
+                "a device tree needs room for at least the root node"
+            );
+        }
+        Self {
+            nodes: [StaticNode {
+                parent: 0,
+                name: (0, 0),
+            }; NODES],
+            node_count: 1,
+            props: [StaticProperty {
+                node: 0,
+                name: (0, 0),
+                value: (0, 0),
+                name_offset: 0,
+                first_with_name: false,
+            }; PROPS],
+            prop_count: 0,
+            buf: [0; BUF],
+            buf_len: 0,
+            strings_size: 0,
+        }
+    }
+
+    /// Returns the root node of the device tree.
+    #[must_use]
+    pub const fn root(&self) -> StaticNodeId {
+        StaticNodeId(0)
+    }
+
+    /// Adds a new child node with the given name to the given node.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FdtError::InvalidNodeId`] if `parent` isn't a node of this
+    /// tree, or [`FdtError::CapacityExceeded`] if there is no room left for
+    /// the node or its name.
+    pub fn add_node(&mut self, parent: StaticNodeId, name: &str) -> Result<StaticNodeId, FdtError> {
+        self.check_id(parent)?;
+        if self.node_count == NODES {
+            return Err(FdtError::CapacityExceeded("nodes"));
+        }
+        let name = self.store(name.as_bytes())?;
+        let id = self.node_count;
+        self.nodes[id] = StaticNode {
+            parent: parent.0,
+            name,
+        };
+        self.node_count += 1;
+        Ok(StaticNodeId(id))
+    }
+
+    /// Adds a property with the given name and raw value to the given node.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FdtError::InvalidNodeId`] if `node` isn't a node of this
+    /// tree, or [`FdtError::CapacityExceeded`] if there is no room left for
+    /// the property, its name or its value.
+    pub fn add_property(
+        &mut self,
+        node: StaticNodeId,
+        name: &str,
+        value: &[u8],
+    ) -> Result<(), FdtError> {
+        self.add_property_parts(node, name, &[value])
+    }
+
+    /// Adds a property with a single `u32` value to the given node.
+    ///
+    /// # Errors
+    ///
+    /// See [`StaticDeviceTree::add_property`].
+    pub fn add_property_u32(
+        &mut self,
+        node: StaticNodeId,
+        name: &str,
+        value: u32,
+    ) -> Result<(), FdtError> {
+        self.add_property(node, name, &value.to_be_bytes())
+    }
+
+    /// Adds a property with a single `u64` value to the given node.
+    ///
+    /// # Errors
+    ///
+    /// See [`StaticDeviceTree::add_property`].
+    pub fn add_property_u64(
+        &mut self,
+        node: StaticNodeId,
+        name: &str,
+        value: u64,
+    ) -> Result<(), FdtError> {
+        self.add_property(node, name, &value.to_be_bytes())
+    }
+
+    /// Adds a property with a string value to the given node. The null
+    /// terminator is added automatically.
+    ///
+    /// # Errors
+    ///
+    /// See [`StaticDeviceTree::add_property`].
+    pub fn add_property_str(
+        &mut self,
+        node: StaticNodeId,
+        name: &str,
+        value: &str,
+    ) -> Result<(), FdtError> {
+        self.add_property_parts(node, name, &[value.as_bytes(), &[0]])
+    }
+
+    /// Adds a property whose value is the concatenation of the given parts.
+    fn add_property_parts(
+        &mut self,
+        node: StaticNodeId,
+        name: &str,
+        parts: &[&[u8]],
+    ) -> Result<(), FdtError> {
+        self.check_id(node)?;
+        if self.prop_count == PROPS {
+            return Err(FdtError::CapacityExceeded("properties"));
+        }
+        let existing = self
+            .props()
+            .find(|prop| self.slice(prop.name) == name.as_bytes())
+            .copied();
+        let value_len: usize = parts.iter().map(|part| part.len()).sum();
+        let name_len = if existing.is_some() { 0 } else { name.len() };
+        if self.buf_len + name_len + value_len > BUF {
+            return Err(FdtError::CapacityExceeded("buffer"));
+        }
+
+        let (name_range, name_offset, first_with_name) = if let Some(prop) = existing {
+            (prop.name, prop.name_offset, false)
+        } else {
+            let offset = self.strings_size;
+            self.strings_size += name.len() + 1;
+            (self.store(name.as_bytes())?, offset, true)
+        };
+        let value_start = self.buf_len;
+        for part in parts {
+            self.store(part)?;
+        }
+        self.props[self.prop_count] = StaticProperty {
+            node: node.0,
+            name: name_range,
+            value: (value_start, value_len),
+            name_offset,
+            first_with_name,
+        };
+        self.prop_count += 1;
+        Ok(())
+    }
+
+    /// Returns the size of the DTB produced by [`StaticDeviceTree::to_dtb`].
+    #[must_use]
+    pub fn dtb_size(&self) -> usize {
+        size_of::<FdtHeader>()
+            + size_of::<MemoryReservation>()
+            + self.struct_size()
+            + self.strings_size
+    }
+
+    /// Serializes the device tree to the given buffer, returning the number of
+    /// bytes written.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`FdtError::BufferTooSmall`] if the DTB doesn't fit in `out`.
+    pub fn to_dtb(&self, out: &mut [u8]) -> Result<usize, FdtError> {
+        let totalsize = self.dtb_size();
+        if out.len() < totalsize {
+            return Err(FdtError::BufferTooSmall { needed: totalsize });
+        }
+        let out = &mut out[..totalsize];
+        out.fill(0);
+
+        let off_mem_rsvmap = size_of::<FdtHeader>();
+        let off_dt_struct = off_mem_rsvmap + size_of::<MemoryReservation>();
+        let size_dt_struct = self.struct_size();
+        let off_dt_strings = off_dt_struct + size_dt_struct;
+        let to_u32 =
+            |value: usize| u32::try_from(value).map_err(|_| FdtError::CapacityExceeded("DTB size"));
+        let header = FdtHeader {
+            magic: FDT_MAGIC.into(),
+            totalsize: to_u32(totalsize)?.into(),
+            off_dt_struct: to_u32(off_dt_struct)?.into(),
+            off_dt_strings: to_u32(off_dt_strings)?.into(),
+            off_mem_rsvmap: to_u32(off_mem_rsvmap)?.into(),
+            version: LAST_VERSION.into(),
+            last_comp_version: LAST_COMP_VERSION.into(),
+            boot_cpuid_phys: 0u32.into(),
+            size_dt_strings: to_u32(self.strings_size)?.into(),
+            size_dt_struct: to_u32(size_dt_struct)?.into(),
+        };
+
+        let mut writer = SliceWriter { out, position: 0 };
+        writer.put(header.as_bytes());
+        writer.put(MemoryReservation::TERMINATOR.as_bytes());
+        self.write_node(&mut writer, 0)?;
+        writer.put(&FDT_END.to_be_bytes());
+        debug_assert_eq!(writer.position, off_dt_strings);
+
+        for prop in self.props().filter(|prop| prop.first_with_name) {
+            writer.position = off_dt_strings + prop.name_offset;
+            writer.put(self.slice(prop.name));
+        }
+        Ok(totalsize)
+    }
+
+    fn write_node(&self, writer: &mut SliceWriter, node: usize) -> Result<(), FdtError> {
+        writer.put(&FDT_BEGIN_NODE.to_be_bytes());
+        if node != 0 {
+            writer.put(self.slice(self.nodes[node].name));
+        }
+        // The buffer is zeroed, so skipping bytes writes the null terminator
+        // and padding.
+        writer.position = Fdt::align_tag_offset(writer.position + 1);
+
+        for prop in self.props().filter(|prop| prop.node == node) {
+            let value = self.slice(prop.value);
+            writer.put(&FDT_PROP.to_be_bytes());
+            writer.put(
+                &u32::try_from(value.len())
+                    .map_err(|_| FdtError::CapacityExceeded("property value length"))?
+                    .to_be_bytes(),
+            );
+            writer.put(
+                &u32::try_from(prop.name_offset)
+                    .map_err(|_| FdtError::CapacityExceeded("strings block"))?
+                    .to_be_bytes(),
+            );
+            writer.put(value);
+            writer.position = Fdt::align_tag_offset(writer.position);
+        }
+        for child in self.children(node) {
+            self.write_node(writer, child)?;
+        }
+
+        writer.put(&FDT_END_NODE.to_be_bytes());
+        Ok(())
+    }
+
+    fn struct_size(&self) -> usize {
+        let nodes: usize = self.nodes[..self.node_count]
+            .iter()
+            .enumerate()
+            .map(|(id, node)| {
+                let name_len = if id == 0 { 0 } else { node.name.1 };
+                // FDT_BEGIN_NODE + name + null terminator + padding + FDT_END_NODE
+                FDT_TAGSIZE + Fdt::align_tag_offset(name_len + 1) + FDT_TAGSIZE
+            })
+            .sum();
+        let props: usize = self
+            .props()
+            .map(|prop| 3 * FDT_TAGSIZE + Fdt::align_tag_offset(prop.value.1))
+            .sum();
+        // + FDT_END
+        nodes + props + FDT_TAGSIZE
+    }
+
+    /// Checks that the given handle refers to a node which has been added.
+    fn check_id(&self, id: StaticNodeId) -> Result<(), FdtError> {
+        if id.0 < self.node_count {
+            Ok(())
+        } else {
+            Err(FdtError::InvalidNodeId(id.0))
+        }
+    }
+
+    fn props(&self) -> impl Iterator<Item = &StaticProperty> {
+        self.props[..self.prop_count].iter()
+    }
+
+    fn children(&self, node: usize) -> impl Iterator<Item = usize> {
+        (1..self.node_count).filter(move |&child| self.nodes[child].parent == node)
+    }
+
+    fn slice(&self, (start, len): (usize, usize)) -> &[u8] {
+        &self.buf[start..start + len]
+    }
+
+    /// Copies the given bytes into the buffer, returning their `(start, len)`.
+    fn store(&mut self, bytes: &[u8]) -> Result<(usize, usize), FdtError> {
+        let start = self.buf_len;
+        self.buf
+            .get_mut(start..start + bytes.len())
+            .ok_or(FdtError::CapacityExceeded("buffer"))?
+            .copy_from_slice(bytes);
+        self.buf_len += bytes.len();
+        Ok((start, bytes.len()))
+    }
+}
+
+/// Writes to a slice which is known to be big enough.
+struct SliceWriter<'a> {
+    out: &'a mut [u8],
+    position: usize,
+}
+
+impl SliceWriter<'_> {
+    fn put(&mut self, bytes: &[u8]) {
+        self.out[self.position..self.position + bytes.len()].copy_from_slice(bytes);
+        self.position += bytes.len();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn capacity() {
+        let mut tree = StaticDeviceTree::<2, 1, 10>::new();
+        let root = tree.root();
+        let child = tree.add_node(root, "child").unwrap();
+        assert_eq!(
+            tree.add_node(root, "other"),
+            Err(FdtError::CapacityExceeded("nodes"))
+        );
+        assert_eq!(
+            tree.add_property_str(child, "a", "toolong"),
+            Err(FdtError::CapacityExceeded("buffer"))
+        );
+        tree.add_property_u32(child, "a", 1).unwrap();
+        assert_eq!(
+            tree.add_property(child, "b", &[]),
+            Err(FdtError::CapacityExceeded("properties"))
+        );
+
+        let mut other = StaticDeviceTree::<3, 1, 10>::new();
+        other.add_node(other.root(), "x").unwrap();
+        let foreign = other.add_node(other.root(), "y").unwrap();
+        assert_eq!(tree.add_node(foreign, "z"), Err(FdtError::InvalidNodeId(2)));
+        assert_eq!(
+            StaticDeviceTree::<3, 1, 10>::new().add_property_u32(foreign, "a", 1),
+            Err(FdtError::InvalidNodeId(2))
+        );
+
+        let mut small = [0; 8];
+        assert_eq!(
+            tree.to_dtb(&mut small),
+            Err(FdtError::BufferTooSmall {
+                needed: tree.dtb_size()
+            })
+        );
+    }
+
+    #[test]
+    fn shared_property_names() {
+        let mut tree = StaticDeviceTree::<4, 3, 64>::new();
+        let root = tree.root();
+        let a = tree.add_node(root, "a").unwrap();
+        let b = tree.add_node(root, "b").unwrap();
+        let c = tree.add_node(a, "c").unwrap();
+        tree.add_property_str(a, "compatible", "x").unwrap();
+        tree.add_property_str(b, "compatible", "y").unwrap();
+        tree.add_property_u64(c, "reg", 5).unwrap();
+
+        let mut dtb = [0; 256];
+        let size = tree.to_dtb(&mut dtb).unwrap();
+        let fdt = Fdt::new(&dtb[..size]).unwrap();
+        assert_eq!(
+            fdt.header().size_dt_strings() as usize,
+            "compatible\0reg\0".len()
+        );
+        let c = fdt.find_node("/a/c").unwrap().unwrap();
+        assert_eq!(c.property("reg").unwrap().unwrap().as_u64(), Ok(5));
+        let b = fdt.find_node("/b").unwrap().unwrap();
+        assert_eq!(b.property("compatible").unwrap().unwrap().as_str(), Ok("y"));
+    }
+}
@@ -0,0 +1,354 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Utilities for testing code which consumes device trees.
+//!
+//! Instead of checking in binary `.dtb` fixtures, tests can describe the
+//! blob they need with [`TestDtb`], or use one of the canned blobs such as
+//! [`minimal_system_dtb`]. The [`assert_tree_eq!`](crate::assert_tree_eq)
+//! macro compares two trees and lists every difference along with the path of
+//! the node it is in.
+//!
+//! # Examples
+//!
+//! ```
+//! # use dtoolkit::fdt::Fdt;
+//! # use dtoolkit::test_utils::TestDtb;
+//! let dtb = TestDtb::new()
+//!     .memory(0x8000_0000, 0x4000_0000)
+//!     .cpus(2)
+//!     .property_str("/chosen", "bootargs", "console=ttyS0")
+//!     .to_dtb();
+//!
+//! let fdt = Fdt::new(&dtb).unwrap();
+//! assert_eq!(fdt.cpus().unwrap().cpus().count(), 2);
+//! let chosen = fdt.find_node("/chosen").unwrap().unwrap();
+//! assert_eq!(chosen.required_str("bootargs").unwrap(), "console=ttyS0");
+//! ```
+
+use alloc::format;
+use alloc::string::String;
+use alloc::vec::Vec;
+
+use crate::memreserve::MemoryReservation;
+use crate::model::{DeviceTree, DeviceTreeNode, DeviceTreeProperty};
+use crate::path::{DtPath, DtPathBuf};
+
+/// A fluent builder for device tree blobs used in tests.
+///
+/// The root node has `#address-cells` and `#size-cells` of 2, so addresses
+/// and sizes are always encoded as 64-bit values. Nodes are created as needed
+/// when properties are added to them.
+#[derive(Clone, Debug)]
+pub struct TestDtb {
+    tree: DeviceTree,
+}
+
+impl Default for TestDtb {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TestDtb {
+    /// Creates a builder for a tree with only a root node.
+    #[must_use]
+    pub fn new() -> Self {
+        let mut tree = DeviceTree::new();
+        tree.root.add_property(DeviceTreeProperty::new(
+            "#address-cells",
+            2u32.to_be_bytes(),
+        ));
+        tree.root
+            .add_property(DeviceTreeProperty::new("#size-cells", 2u32.to_be_bytes()));
+        Self { tree }
+    }
+
+    /// Adds the node with the given absolute path, along with any missing
+    /// ancestors.
+    #[must_use]
+    pub fn node(mut self, path: &str) -> Self {
+        self.node_mut(path);
+        self
+    }
+
+    /// Sets a property of the node with the given absolute path, creating the
+    /// node if needed.
+    #[must_use]
+    pub fn property(mut self, path: &str, name: &str, value: impl AsRef<[u8]>) -> Self {
+        self.node_mut(path)
+            .add_property(DeviceTreeProperty::new(name, value));
+        self
+    }
+
+    /// Sets a property to a single `u32` value.
+    #[must_use]
+    pub fn property_u32(self, path: &str, name: &str, value: u32) -> Self {
+        self.property(path, name, value.to_be_bytes())
+    }
+
+    /// Sets a property to a single string value.
+    #[must_use]
+    pub fn property_str(self, path: &str, name: &str, value: &str) -> Self {
+        self.property(path, name, format!("{value}\0"))
+    }
+
+    /// Adds a `/memory@<address>` node describing a bank of memory.
+    #[must_use]
+    pub fn memory(self, address: u64, size: u64) -> Self {
+        let path = format!("/memory@{address:x}");
+        let mut reg = Vec::with_capacity(16);
+        reg.extend_from_slice(&address.to_be_bytes());
+        reg.extend_from_slice(&size.to_be_bytes());
+        self.property_str(&path, "device_type", "memory")
+            .property(&path, "reg", reg)
+    }
+
+    /// Adds a `/cpus` node with `count` CPU nodes, numbered from 0.
+    #[must_use]
+    pub fn cpus(self, count: u32) -> Self {
+        let mut builder = self
+            .property_u32("/cpus", "#address-cells", 1)
+            .property_u32("/cpus", "#size-cells", 0);
+        for cpu in 0..count {
+            let path = format!("/cpus/cpu@{cpu:x}");
+            builder = builder
+                .property_str(&path, "device_type", "cpu")
+                .property_u32(&path, "reg", cpu);
+        }
+        builder
+    }
+
+    /// Adds an entry to the memory reservation block.
+    #[must_use]
+    pub fn reserve(mut self, address: u64, size: u64) -> Self {
+        self.tree
+            .memory_reservations
+            .push(MemoryReservation::new(address, size));
+        self
+    }
+
+    /// Returns the device tree built so far.
+    #[must_use]
+    pub fn build(self) -> DeviceTree {
+        self.tree
+    }
+
+    /// Serializes the device tree built so far into a blob.
+    #[must_use]
+    pub fn to_dtb(&self) -> Vec<u8> {
+        self.tree.to_dtb()
+    }
+
+    fn node_mut(&mut self, path: &str) -> &mut DeviceTreeNode {
+        let mut node = &mut self.tree.root;
+        for segment in DtPath::new(path).segments() {
+            if node.child(segment).is_none() {
+                node.add_child(DeviceTreeNode::new(segment));
+            }
+            let Some(child) = node.child_mut(segment) else {
+                unreachable!("the child node exists");
+            };
+            node = child;
+        }
+        node
+    }
+}
+
+/// Returns a blob with nothing but an empty root node.
+#[must_use]
+pub fn root_only_dtb() -> Vec<u8> {
+    DeviceTree::new().to_dtb()
+}
+
+/// Returns a blob describing a minimal system: 1 GiB of memory at
+/// `0x8000_0000` and a single CPU.
+#[must_use]
+pub fn minimal_system_dtb() -> Vec<u8> {
+    TestDtb::new()
+        .memory(0x8000_0000, 0x4000_0000)
+        .cpus(1)
+        .to_dtb()
+}
+
+/// Returns a description of every difference between two device trees, each
+/// prefixed with the path of the node it is in.
+///
+/// Like the [`PartialEq`] implementation of [`DeviceTree`], this ignores the
+/// order of properties and children. An empty list means the trees are equal.
+///
+/// # Examples
+///
+/// ```
+/// # use dtoolkit::test_utils::{TestDtb, tree_differences};
+/// let left = TestDtb::new().property_u32("/soc", "value", 1).build();
+/// let right = TestDtb::new().property_u32("/soc", "value", 2).build();
+/// assert_eq!(
+///     tree_differences(&left, &right),
+///     ["/soc: property value differs, left: <0x01>, right: <0x02>"]
+/// );
+/// ```
+#[must_use]
+pub fn tree_differences(left: &DeviceTree, right: &DeviceTree) -> Vec<String> {
+    let mut differences = Vec::new();
+    if left.memory_reservations != right.memory_reservations {
+        differences.push(format!(
+            "memory reservations differ, left: [{}], right: [{}]",
+            format_reservations(&left.memory_reservations),
+            format_reservations(&right.memory_reservations)
+        ));
+    }
+    node_differences(
+        &DtPathBuf::root(),
+        &left.root,
+        &right.root,
+        &mut differences,
+    );
+    differences
+}
+
+fn format_reservations(reservations: &[MemoryReservation]) -> String {
+    let reservations: Vec<_> = reservations
+        .iter()
+        .map(|reservation| {
+            format!(
+                "{:#x} ({:#x} bytes)",
+                reservation.address(),
+                reservation.size()
+            )
+        })
+        .collect();
+    reservations.join(", ")
+}
+
+fn node_differences(
+    path: &DtPathBuf,
+    left: &DeviceTreeNode,
+    right: &DeviceTreeNode,
+    differences: &mut Vec<String>,
+) {
+    for property in left.properties() {
+        match right.property(property.name()) {
+            None => differences.push(format!("{path}: property {} only in left", property.name())),
+            Some(other) if other.value() != property.value() => differences.push(format!(
+                "{path}: property {} differs, left: {}, right: {}",
+                property.name(),
+                property.decode(),
+                other.decode()
+            )),
+            Some(_) => {}
+        }
+    }
+    for property in right.properties() {
+        if left.property(property.name()).is_none() {
+            differences.push(format!(
+                "{path}: property {} only in right",
+                property.name()
+            ));
+        }
+    }
+
+    for child in left.children() {
+        match right.child(child.name()) {
+            None => differences.push(format!("{path}: child {} only in left", child.name())),
+            Some(other) => {
+                let mut child_path = path.clone();
+                child_path.push(child.name());
+                node_differences(&child_path, child, other, differences);
+            }
+        }
+    }
+    for child in right.children() {
+        if left.child(child.name()).is_none() {
+            differences.push(format!("{path}: child {} only in right", child.name()));
+        }
+    }
+}
+
+/// Asserts that two [`DeviceTree`]s are equal, listing the differences
+/// between them with their node paths if they aren't.
+///
+/// See [`tree_differences`] for how the trees are compared.
+///
+/// # Examples
+///
+/// ```
+/// # use dtoolkit::assert_tree_eq;
+/// # use dtoolkit::fdt::Fdt;
+/// # use dtoolkit::model::DeviceTree;
+/// # use dtoolkit::test_utils::TestDtb;
+/// let builder = TestDtb::new().cpus(4);
+/// let dtb = builder.to_dtb();
+/// let parsed = DeviceTree::from_fdt(&Fdt::new(&dtb).unwrap()).unwrap();
+/// assert_tree_eq!(parsed, builder.build());
+/// ```
+#[macro_export]
+macro_rules! assert_tree_eq {
+    ($left:expr, $right:expr $(,)?) => {
+        match (&$left, &$right) {
+            (left, right) => {
+                let differences = $crate::test_utils::tree_differences(left, right);
+                if !differences.is_empty() {
+                    panic!("device trees differ:\n{}", differences.join("\n"));
+                }
+            }
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fdt::Fdt;
+
+    #[test]
+    fn canned_blobs() {
+        let dtb = root_only_dtb();
+        let fdt = Fdt::new(&dtb).unwrap();
+        assert_eq!(fdt.root().unwrap().children().count(), 0);
+
+        let dtb = minimal_system_dtb();
+        let fdt = Fdt::new(&dtb).unwrap();
+        let memory = fdt.memory().unwrap();
+        let regions: Vec<_> = memory.reg().unwrap().unwrap().collect();
+        assert_eq!(regions.len(), 1);
+        assert_eq!(fdt.cpus().unwrap().cpus().count(), 1);
+    }
+
+    #[test]
+    fn differences_have_paths() {
+        let left = TestDtb::new()
+            .cpus(2)
+            .property_str("/chosen", "bootargs", "quiet")
+            .reserve(0x1000, 0x1000)
+            .build();
+        let right = TestDtb::new()
+            .cpus(1)
+            .property_u32("/cpus/cpu@0", "reg", 1)
+            .build();
+        assert_eq!(
+            tree_differences(&left, &right),
+            [
+                "memory reservations differ, left: [0x1000 (0x1000 bytes)], right: []",
+                "/cpus/cpu@0: property reg differs, left: <0x00>, right: <0x01>",
+                "/cpus: child cpu@1 only in left",
+                "/: child chosen only in left",
+            ]
+        );
+        assert!(tree_differences(&left, &left.clone()).is_empty());
+    }
+
+    #[test]
+    #[should_panic(expected = "/cpus: child cpu@1 only in right")]
+    fn assert_tree_eq_panics() {
+        assert_tree_eq!(
+            TestDtb::new().cpus(1).build(),
+            TestDtb::new().cpus(2).build()
+        );
+    }
+}
@@ -0,0 +1,208 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! JavaScript bindings for inspecting device tree blobs.
+//!
+//! This module exposes a [`DeviceTreeBlob`] class through `wasm-bindgen`,
+//! which owns a copy of a DTB and can print it as DTS or export it as JSON.
+//! It is meant as the core of browser-based DTB inspectors:
+//!
+//! ```js
+//! import { DeviceTreeBlob } from "dtoolkit";
+//!
+//! const dtb = new DeviceTreeBlob(new Uint8Array(await file.arrayBuffer()));
+//! console.log(dtb.toDts());
+//! const tree = JSON.parse(dtb.toJson());
+//! ```
+//!
+//! The JSON document has the following shape, with addresses and sizes
+//! encoded as hexadecimal strings since they may not fit in a JavaScript
+//! number:
+//!
+//! ```json
+//! {
+//!   "version": 17,
+//!   "bootCpuidPhys": 0,
+//!   "memoryReservations": [{ "address": "0x1000", "size": "0x100" }],
+//!   "root": {
+//!     "name": "",
+//!     "properties": [{ "name": "model", "value": [116, 101, 115, 116, 0] }],
+//!     "children": []
+//!   }
+//! }
+//! ```
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::FdtParseError;
+use crate::fdt::{Fdt, FdtBuf, FdtNode};
+
+/// A device tree blob owned by JavaScript code.
+#[wasm_bindgen]
+#[derive(Clone, Debug)]
+pub struct DeviceTreeBlob {
+    buf: FdtBuf,
+}
+
+#[wasm_bindgen]
+impl DeviceTreeBlob {
+    /// Parses a copy of the given device tree blob.
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if the blob is invalid.
+    #[wasm_bindgen(constructor)]
+    pub fn new(data: &[u8]) -> Result<DeviceTreeBlob, JsError> {
+        let buf = FdtBuf::new(data).map_err(|e| to_js_error(&e))?;
+        Ok(Self { buf })
+    }
+
+    /// Returns the version of the device tree.
+    #[must_use]
+    pub fn version(&self) -> u32 {
+        self.buf.fdt().version()
+    }
+
+    /// Returns the size of the device tree blob in bytes.
+    #[must_use]
+    pub fn size(&self) -> usize {
+        self.buf.data().len()
+    }
+
+    /// Returns a copy of the device tree blob.
+    #[must_use]
+    pub fn data(&self) -> Vec<u8> {
+        self.buf.data().to_vec()
+    }
+
+    /// Returns the device tree in DTS source format.
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if the device tree structure is invalid.
+    #[wasm_bindgen(js_name = toDts)]
+    pub fn to_dts(&self) -> Result<String, JsError> {
+        let mut dts = String::new();
+        write!(dts, "{}", self.buf.fdt())
+            .map_err(|_| JsError::new("failed to format device tree"))?;
+        Ok(dts)
+    }
+
+    /// Returns the device tree as a JSON document.
+    ///
+    /// # Errors
+    ///
+    /// Throws an error if the device tree structure is invalid.
+    #[wasm_bindgen(js_name = toJson)]
+    pub fn to_json(&self) -> Result<String, JsError> {
+        fdt_to_json(self.buf.fdt()).map_err(|e| to_js_error(&e))
+    }
+}
+
+fn to_js_error(error: &FdtParseError) -> JsError {
+    let mut message = String::new();
+    let _ = write!(message, "{error}");
+    JsError::new(&message)
+}
+
+fn fdt_to_json(fdt: Fdt) -> Result<String, FdtParseError> {
+    let mut json = String::new();
+    let _ = write!(
+        json,
+        r#"{{"version":{},"bootCpuidPhys":{},"memoryReservations":["#,
+        fdt.version(),
+        fdt.boot_cpuid_phys()
+    );
+    for (i, reservation) in fdt.memory_reservations().enumerate() {
+        let reservation = reservation?;
+        if i > 0 {
+            json.push(',');
+        }
+        let _ = write!(
+            json,
+            r#"{{"address":"{:#x}","size":"{:#x}"}}"#,
+            reservation.address(),
+            reservation.size()
+        );
+    }
+    json.push_str(r#"],"root":"#);
+    node_to_json(&mut json, fdt.root()?)?;
+    json.push('}');
+    Ok(json)
+}
+
+fn node_to_json(json: &mut String, node: FdtNode) -> Result<(), FdtParseError> {
+    json.push_str(r#"{"name":"#);
+    push_json_string(json, node.name()?);
+    json.push_str(r#","properties":["#);
+    for (i, property) in node.properties().enumerate() {
+        let property = property?;
+        if i > 0 {
+            json.push(',');
+        }
+        json.push_str(r#"{"name":"#);
+        push_json_string(json, property.name());
+        json.push_str(r#","value":["#);
+        for (j, byte) in property.value().iter().enumerate() {
+            if j > 0 {
+                json.push(',');
+            }
+            let _ = write!(json, "{byte}");
+        }
+        json.push_str("]}");
+    }
+    json.push_str(r#"],"children":["#);
+    for (i, child) in node.children().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        node_to_json(json, child?)?;
+    }
+    json.push_str("]}");
+    Ok(())
+}
+
+fn push_json_string(json: &mut String, s: &str) {
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str(r#"\""#),
+            '\\' => json.push_str(r"\\"),
+            c if u32::from(c) < 0x20 => {
+                let _ = write!(json, "\\u{:04x}", u32::from(c));
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn json() {
+        let dtb = include_bytes!("../tests/dtb/test_children.dtb");
+        let json = fdt_to_json(Fdt::new(dtb).unwrap()).unwrap();
+        assert!(json.starts_with(r#"{"version":17,"bootCpuidPhys":0,"memoryReservations":["#));
+        assert!(json.contains(r#"{"name":"child1","properties":["#));
+        assert!(json.ends_with("]}]}}"));
+    }
+
+    #[test]
+    fn json_string() {
+        let mut json = String::new();
+        push_json_string(&mut json, "a\"b\\c\n");
+        assert_eq!(json, r#""a\"b\\c\u000a""#);
+    }
+}
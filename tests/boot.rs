@@ -0,0 +1,137 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "write")]
+
+use dtoolkit::error::FdtError;
+use dtoolkit::fdt::Fdt;
+use dtoolkit::model::{DeviceTree, DeviceTreeProperty};
+use dtoolkit::standard::UefiMemoryMap;
+
+#[test]
+fn chosen_fixups() {
+    let mut tree = DeviceTree::new();
+    tree.set_bootargs("console=ttyAMA0");
+    tree.set_initrd(0x1_0000_0000, 0x1_0080_0000).unwrap();
+    tree.set_kaslr_seed(0x0102_0304_0506_0708);
+    tree.set_rng_seed(&[0xaa; 8]);
+    tree.set_usable_memory_range(&[(0x8000_0000, 0x1000_0000)])
+        .unwrap();
+
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+    let chosen = fdt.find_node("/chosen").unwrap().unwrap();
+    let property = |name| chosen.property(name).unwrap().unwrap();
+
+    assert_eq!(property("bootargs").as_str().unwrap(), "console=ttyAMA0");
+    assert_eq!(
+        property("linux,initrd-start").as_u64().unwrap(),
+        0x1_0000_0000
+    );
+    assert_eq!(
+        property("linux,initrd-end").as_u64().unwrap(),
+        0x1_0080_0000
+    );
+    assert_eq!(
+        property("kaslr-seed").as_u64().unwrap(),
+        0x0102_0304_0506_0708
+    );
+    assert_eq!(property("rng-seed").value(), &[0xaa; 8]);
+    assert_eq!(
+        property("linux,usable-memory-range").value(),
+        &[0, 0, 0, 0, 0x80, 0, 0, 0, 0x10, 0, 0, 0]
+    );
+}
+
+#[test]
+fn bogus_address_cells() {
+    let mut tree = DeviceTree::new();
+    tree.root.add_property(DeviceTreeProperty::new(
+        "#address-cells",
+        u32::MAX.to_be_bytes(),
+    ));
+
+    assert_eq!(
+        tree.set_initrd(0x4800_0000, 0x4900_0000),
+        Err(FdtError::TooManyCells {
+            cells: u32::MAX as usize
+        })
+    );
+    assert_eq!(
+        tree.set_usable_memory_range(&[(0x8000_0000, 0x1000_0000)]),
+        Err(FdtError::TooManyCells {
+            cells: u32::MAX as usize
+        })
+    );
+    assert_eq!(
+        tree.fixup_memory(&[(0x8000_0000, 0x1000_0000)]),
+        Err(FdtError::TooManyCells {
+            cells: u32::MAX as usize
+        })
+    );
+}
+
+#[test]
+fn initrd_uses_root_address_cells() {
+    let mut tree = DeviceTree::new();
+    tree.root.add_property(DeviceTreeProperty::new(
+        "#address-cells",
+        1u32.to_be_bytes(),
+    ));
+
+    tree.set_initrd(0x4800_0000, 0x4900_0000).unwrap();
+    let chosen = tree.root.child("chosen").unwrap();
+    assert_eq!(
+        chosen.property("linux,initrd-start").unwrap().as_u32(),
+        Ok(0x4800_0000)
+    );
+
+    assert_eq!(
+        tree.set_initrd(0x1_0000_0000, 0x1_0001_0000),
+        Err(FdtError::ValueTooBig {
+            value: 0x1_0000_0000,
+            cells: 1
+        })
+    );
+}
+
+#[test]
+fn uefi_properties() {
+    let memory_map = UefiMemoryMap {
+        start: 0x8_1234_0000,
+        size: 0x3000,
+        descriptor_size: 48,
+        descriptor_version: 1,
+    };
+    let mut tree = DeviceTree::new();
+    tree.set_uefi_system_table(0x8_fff0_0000);
+    tree.set_uefi_memory_map(&memory_map);
+
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+    let chosen = fdt.chosen().unwrap().unwrap();
+    assert_eq!(chosen.uefi_system_table().unwrap(), Some(0x8_fff0_0000));
+    assert_eq!(chosen.uefi_memory_map().unwrap(), Some(memory_map));
+    let chosen = tree.chosen().unwrap();
+    assert_eq!(chosen.uefi_system_table().unwrap(), Some(0x8_fff0_0000));
+    assert_eq!(chosen.uefi_memory_map().unwrap(), Some(memory_map));
+
+    tree.root
+        .child_mut("chosen")
+        .unwrap()
+        .remove_property("linux,uefi-mmap-desc-ver");
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+    assert_eq!(
+        fdt.chosen().unwrap().unwrap().uefi_memory_map(),
+        Err(FdtError::MissingProperty {
+            name: "linux,uefi-mmap-desc-ver"
+        })
+    );
+    assert!(tree.chosen().unwrap().uefi_memory_map().is_err());
+}
@@ -0,0 +1,75 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use dtoolkit::dtbo::DtTable;
+use dtoolkit::error::FdtErrorKind;
+#[cfg(feature = "write")]
+use dtoolkit::fdt::Fdt;
+#[cfg(feature = "write")]
+use dtoolkit::model::DeviceTree;
+
+#[test]
+fn read_entries() {
+    let image = include_bytes!("dtb/test_dtbo.img");
+    let table = DtTable::new(image).unwrap();
+    assert_eq!(table.version(), 0);
+    assert_eq!(table.page_size(), 2048);
+    assert_eq!(table.len(), 3);
+
+    let entries = table.entries().collect::<Result<Vec<_>, _>>().unwrap();
+    assert_eq!(entries[0].id(), 0x100);
+    assert_eq!(entries[0].rev(), 1);
+    assert_eq!(entries[0].data(), include_bytes!("dtb/test.dtb"));
+    assert_eq!(entries[1].id(), 0x200);
+    assert_eq!(entries[1].rev(), 2);
+    assert_eq!(entries[1].custom(), [1, 2, 3, 4]);
+    assert_eq!(entries[1].data(), include_bytes!("dtb/test_children.dtb"));
+    assert_eq!(entries[2].data(), entries[0].data());
+
+    let fdt = entries[1].fdt().unwrap();
+    assert!(fdt.find_node("/child1").unwrap().is_some());
+    assert!(table.entry(3).is_none());
+}
+
+#[test]
+fn invalid_image() {
+    let image = include_bytes!("dtb/test_dtbo.img");
+    assert!(matches!(
+        DtTable::new(&image[..16]),
+        Err(e) if e.kind == FdtErrorKind::InvalidLength
+    ));
+    assert!(matches!(
+        DtTable::new(&image[..image.len() - 1]),
+        Err(e) if e.kind == FdtErrorKind::InvalidLength
+    ));
+    assert!(matches!(
+        DtTable::new(include_bytes!("dtb/test.dtb")),
+        Err(e) if e.kind == FdtErrorKind::InvalidMagic
+    ));
+}
+
+#[test]
+#[cfg(feature = "write")]
+fn build_round_trip() {
+    let dtb = include_bytes!("dtb/test_props.dtb");
+    let tree = DeviceTree::from_fdt(&Fdt::new(dtb).unwrap()).unwrap();
+    let image = DtTable::builder()
+        .page_size(4096)
+        .entry(&tree, 1, 2, [3, 4, 5, 6])
+        .entry_dtb(include_bytes!("dtb/test.dtb").as_slice(), 7, 8, [0; 4])
+        .build();
+
+    let table = DtTable::new(&image).unwrap();
+    assert_eq!(table.page_size(), 4096);
+    assert_eq!(table.len(), 2);
+    let entry = table.entry(0).unwrap().unwrap();
+    assert_eq!(entry.data(), dtb);
+    assert_eq!(entry.custom(), [3, 4, 5, 6]);
+    let entry = table.entry(1).unwrap().unwrap();
+    assert_eq!((entry.id(), entry.rev()), (7, 8));
+}
@@ -6,10 +6,10 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
-use dtoolkit::fdt::Fdt;
+use dtoolkit::fdt::{Fdt, PropertyValue};
 #[cfg(feature = "write")]
 use dtoolkit::model::DeviceTree;
-use dtoolkit::standard::{InitialMappedArea, Status};
+use dtoolkit::standard::{InitialMappedArea, Phandle, Status};
 
 #[test]
 fn read_child_nodes() {
@@ -93,6 +93,164 @@ fn get_property_by_name() {
     assert!(node.property("non-existent-prop").unwrap().is_none());
 }
 
+#[test]
+fn raw_properties() {
+    let dtb = include_bytes!("dtb/test_props.dtb");
+    let fdt = Fdt::new(dtb).unwrap();
+    for path in ["/", "/test-props", "/standard-props"] {
+        let node = fdt.find_node(path).unwrap().unwrap();
+        let raw: Vec<_> = node.raw_properties().map(Result::unwrap).collect();
+        let validated: Vec<_> = node.properties().map(Result::unwrap).collect();
+        assert_eq!(raw.len(), validated.len());
+        for (raw, validated) in raw.iter().zip(&validated) {
+            assert_eq!(raw.name(), validated.name().as_bytes());
+            assert_eq!(raw.value(), validated.value());
+        }
+    }
+}
+
+#[test]
+fn integer_arrays() {
+    let dtb = include_bytes!("dtb/test_props.dtb");
+    let fdt = Fdt::new(dtb).unwrap();
+    let node = fdt.find_node("/standard-props").unwrap().unwrap();
+
+    let reg = node.property("reg").unwrap().unwrap();
+    assert_eq!(reg.as_u32_array().unwrap().len(), 8);
+    assert!(
+        reg.as_u64_array()
+            .unwrap()
+            .eq([0x1234_5678_0000_3000, 0x20, 0xfe00, 0x100])
+    );
+
+    let phandle = node.property("phandle").unwrap().unwrap();
+    assert!(phandle.as_u32_array().unwrap().eq([0x1234]));
+    assert!(phandle.as_u64_array().is_err());
+
+    let status = node.property("status").unwrap().unwrap();
+    assert!(status.as_u32_array().is_err());
+}
+
+#[test]
+fn prop_encoded_array_dyn() {
+    let dtb = include_bytes!("dtb/test_props.dtb");
+    let fdt = Fdt::new(dtb).unwrap();
+    let node = fdt.find_node("/standard-props").unwrap().unwrap();
+    let reg = node.property("reg").unwrap().unwrap();
+
+    let fields = [1, 1];
+    let elements: Vec<Vec<u32>> = reg
+        .as_prop_encoded_array_dyn(&fields)
+        .unwrap()
+        .map(|element| {
+            element
+                .iter()
+                .map(|cells| cells.to_int().unwrap())
+                .collect()
+        })
+        .collect();
+    assert_eq!(
+        elements,
+        [[0x1234_5678, 0x3000], [0, 0x20], [0, 0xfe00], [0, 0x100]]
+    );
+
+    assert!(reg.as_prop_encoded_array_dyn(&[3]).is_err());
+    assert!(reg.as_prop_encoded_array_dyn(&[]).is_err());
+    let element = reg
+        .as_prop_encoded_array_dyn(&[2, 2])
+        .unwrap()
+        .next()
+        .unwrap();
+    assert!(element.get(2).is_none());
+}
+
+#[test]
+fn error_context() {
+    let dtb = include_bytes!("dtb/test_props.dtb");
+    let fdt = Fdt::new(dtb).unwrap();
+    let node = fdt.find_node("/standard-props").unwrap().unwrap();
+    let error = node
+        .property("reg")
+        .unwrap()
+        .unwrap()
+        .as_prop_encoded_array_dyn(&[3])
+        .map(|_| ())
+        .unwrap_err()
+        .with_context(node, Some("reg"));
+    assert_eq!(error.node_path(), Some("/standard-props@1"));
+    assert_eq!(
+        error.to_string(),
+        "reg property of node /standard-props@1: prop-encoded-array property was 32 bytes, but \
+         should have been a multiple of 3 cells"
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn required_properties() {
+    let dtb = include_bytes!("dtb/test_props.dtb");
+    let fdt = Fdt::new(dtb).unwrap();
+    let node = fdt.find_node("/test-props").unwrap().unwrap();
+    assert_eq!(node.required_u32("u32-prop").unwrap(), 0x1234_5678);
+    assert_eq!(node.required_str("str-prop").unwrap(), "hello world");
+    assert_eq!(
+        node.required_u32("missing").unwrap_err().to_string(),
+        "missing property of node /test-props: required property missing missing"
+    );
+    assert_eq!(
+        node.required_str("u32-prop").unwrap_err().to_string(),
+        "u32-prop property of node /test-props: Invalid string in FDT at offset 124"
+    );
+}
+
+#[test]
+fn decode_property_values() {
+    let dtb = include_bytes!("dtb/test_props.dtb");
+    let fdt = Fdt::new(dtb).unwrap();
+    let decode = |path, name| {
+        let node = fdt.find_node(path).unwrap().unwrap();
+        node.property(name).unwrap().unwrap().decode()
+    };
+
+    assert_eq!(
+        decode("/test-props", "u32-prop"),
+        PropertyValue::U32(0x1234_5678)
+    );
+    let PropertyValue::U32Array(array) = decode("/test-props", "u64-prop") else {
+        panic!("u64-prop should be decoded as a u32 array");
+    };
+    assert!(array.iter().eq([0x1122_3344, 0x5566_7788]));
+    let PropertyValue::StringList(strings) = decode("/test-props", "str-list-prop") else {
+        panic!("str-list-prop should be decoded as a string list");
+    };
+    assert!(strings.iter().eq(["first", "second", "third"]));
+
+    assert_eq!(
+        decode("/standard-props", "status"),
+        PropertyValue::String("fail")
+    );
+    assert_eq!(
+        decode("/standard-props", "dma-coherent"),
+        PropertyValue::Empty
+    );
+    assert_eq!(
+        PropertyValue::decode("mac-address", &[1, 2, 3, 4]),
+        PropertyValue::Bytes(&[1, 2, 3, 4])
+    );
+    assert_eq!(
+        PropertyValue::decode("#address-cells", b"ab\0"),
+        PropertyValue::String("ab")
+    );
+    assert_eq!(
+        PropertyValue::guess(&[1, 2, 3]),
+        PropertyValue::Bytes(&[1, 2, 3])
+    );
+    assert_eq!(
+        PropertyValue::decode("cpu-release-addr", &[0, 0, 0, 1, 0, 0, 0, 2]).to_string(),
+        "/bits/ 64 <0x100000002>"
+    );
+}
+
 #[test]
 fn standard_properties() {
     let dtb = include_bytes!("dtb/test_props.dtb");
@@ -119,7 +277,10 @@ fn standard_properties() {
     assert_eq!(standard_props_node.status().unwrap(), Status::Fail);
     assert_eq!(standard_props_node.model().unwrap(), Some("Some Model"));
     assert!(standard_props_node.dma_coherent().unwrap());
-    assert_eq!(standard_props_node.phandle().unwrap(), Some(0x1234));
+    assert_eq!(
+        standard_props_node.phandle().unwrap().map(Phandle::get),
+        Some(0x1234)
+    );
     assert_eq!(standard_props_node.virtual_reg().unwrap(), Some(0xabcd));
     assert_eq!(
         standard_props_node
@@ -236,6 +397,379 @@ fn find_node_by_path() {
     assert!(fdt.find_node("").unwrap().is_none());
 }
 
+#[test]
+fn path_into_buffer() {
+    use dtoolkit::error::FdtError;
+
+    let dtb = include_bytes!("dtb/test_traversal.dtb");
+    let fdt = Fdt::new(dtb).unwrap();
+    let mut buffer = [0; 8];
+
+    let root = fdt.root().unwrap();
+    assert_eq!(root.path_into(&mut buffer).unwrap(), "/");
+    let c = fdt.find_node("/a/b/c").unwrap().unwrap();
+    assert_eq!(c.path_into(&mut buffer).unwrap(), "/a/b/c");
+    assert_eq!(
+        c.path_into(&mut buffer[..5]),
+        Err(FdtError::BufferTooSmall { needed: 6 })
+    );
+    assert_eq!(
+        root.path_into(&mut []),
+        Err(FdtError::BufferTooSmall { needed: 1 })
+    );
+
+    let mut path = String::new();
+    root.write_path(&mut path).unwrap();
+    assert_eq!(path, "/");
+    path.clear();
+    c.write_path(&mut path).unwrap();
+    assert_eq!(path, "/a/b/c");
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn parent() {
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+
+    let cells =
+        |cells: &[u32]| -> Vec<u8> { cells.iter().flat_map(|cell| cell.to_be_bytes()).collect() };
+    let mut tree = DeviceTree::new();
+    tree.root
+        .add_property(DeviceTreeProperty::new("#address-cells", cells(&[1])));
+    tree.root
+        .add_property(DeviceTreeProperty::new("#size-cells", cells(&[1])));
+    tree.root.add_child(
+        DeviceTreeNode::builder("bus@1000")
+            .property(DeviceTreeProperty::new("reg", cells(&[0x1000, 0x100])))
+            .property(DeviceTreeProperty::new("#address-cells", cells(&[2])))
+            .property(DeviceTreeProperty::new("#size-cells", cells(&[0])))
+            .child(
+                DeviceTreeNode::builder("device@0,2")
+                    .property(DeviceTreeProperty::new("reg", cells(&[0, 2])))
+                    .build(),
+            )
+            .build(),
+    );
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+
+    let device = fdt.find_node("/bus@1000/device@0,2").unwrap().unwrap();
+    let bus = device.parent().unwrap().unwrap();
+    assert_eq!(bus.name(), Ok("bus@1000"));
+    // The parent is decoded with the cells of its own parent, not the defaults
+    // or the cells it defines for its children.
+    let reg = bus.reg().unwrap().unwrap().next().unwrap();
+    assert_eq!(reg.address::<u32>().unwrap(), 0x1000);
+    assert_eq!(reg.size::<u32>().unwrap(), 0x100);
+    let root = bus.parent().unwrap().unwrap();
+    assert_eq!(root.name(), Ok("/"));
+    assert!(root.parent().unwrap().is_none());
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn descendants() {
+    use dtoolkit::error::FdtErrorKind;
+    use dtoolkit::fdt::MAX_DESCENDANTS_DEPTH;
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+
+    let cells =
+        |cells: &[u32]| -> Vec<u8> { cells.iter().flat_map(|cell| cell.to_be_bytes()).collect() };
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("soc")
+            .property(DeviceTreeProperty::new("#address-cells", cells(&[1])))
+            .property(DeviceTreeProperty::new("#size-cells", cells(&[1])))
+            .child(
+                DeviceTreeNode::builder("bus@1000")
+                    .property(DeviceTreeProperty::new("#address-cells", cells(&[1])))
+                    .property(DeviceTreeProperty::new("#size-cells", cells(&[0])))
+                    .child(
+                        DeviceTreeNode::builder("device@2")
+                            .property(DeviceTreeProperty::new("reg", cells(&[2])))
+                            .build(),
+                    )
+                    .build(),
+            )
+            .child(
+                DeviceTreeNode::builder("uart@2000")
+                    .property(DeviceTreeProperty::new("reg", cells(&[0x2000, 0x100])))
+                    .build(),
+            )
+            .build(),
+    );
+    tree.root.add_child(DeviceTreeNode::new("chosen"));
+    let mut deep = DeviceTreeNode::new("leaf");
+    for _ in 0..MAX_DESCENDANTS_DEPTH {
+        deep = DeviceTreeNode::builder("deep").child(deep).build();
+    }
+    tree.root.add_child(deep);
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+
+    let soc = fdt.find_node("/soc").unwrap().unwrap();
+    let names: Vec<_> = soc
+        .descendants()
+        .map(|node| node.unwrap().name().unwrap())
+        .collect();
+    assert_eq!(names, ["bus@1000", "device@2", "uart@2000"]);
+    let regs: Vec<Vec<(u32, u32)>> = soc
+        .descendants()
+        .map(|node| {
+            node.unwrap()
+                .reg()
+                .unwrap()
+                .into_iter()
+                .flatten()
+                .map(|reg| (reg.address().unwrap(), reg.size().unwrap()))
+                .collect()
+        })
+        .collect();
+    assert_eq!(regs, [vec![], vec![(2, 0)], vec![(0x2000, 0x100)]]);
+
+    let chosen = fdt.find_node("/chosen").unwrap().unwrap();
+    assert!(chosen.descendants().next().is_none());
+
+    // The leaf is too deep to be walked from the root, but not from the node
+    // below it.
+    let deep = fdt.find_node("/deep").unwrap().unwrap();
+    assert_eq!(deep.descendants().count(), MAX_DESCENDANTS_DEPTH);
+    let mut descendants = fdt.root().unwrap().descendants().skip(5);
+    let error = descendants
+        .find_map(Result::err)
+        .expect("the leaf should be too deep");
+    assert_eq!(error.kind, FdtErrorKind::TooDeep(MAX_DESCENDANTS_DEPTH));
+    assert!(descendants.next().is_none());
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn find_all_compatible() {
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+
+    let uart = |name: &str| {
+        DeviceTreeNode::builder(name)
+            .property(DeviceTreeProperty::new("compatible", "ns16550a\0"))
+            .build()
+    };
+    let mut tree = DeviceTree::new();
+    tree.root
+        .add_property(DeviceTreeProperty::new("compatible", "board\0ns16550a\0"));
+    tree.root.add_child(
+        DeviceTreeNode::builder("soc")
+            .child(uart("serial@1000"))
+            .child(
+                DeviceTreeNode::builder("bus")
+                    .child(uart("serial@2000"))
+                    .build(),
+            )
+            .build(),
+    );
+    tree.root.add_child(uart("serial@3000"));
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+
+    let names: Vec<_> = fdt
+        .find_all_compatible("ns16550a")
+        .map(|node| node.unwrap().path().unwrap().to_string())
+        .collect();
+    assert_eq!(
+        names,
+        [
+            "/",
+            "/soc/serial@1000",
+            "/soc/bus/serial@2000",
+            "/serial@3000"
+        ]
+    );
+    assert_eq!(fdt.find_all_compatible("other").count(), 0);
+
+    let soc = fdt.find_node("/soc").unwrap().unwrap();
+    let names: Vec<_> = soc
+        .find_all_compatible("ns16550a")
+        .map(|node| node.unwrap().path().unwrap().to_string())
+        .collect();
+    assert_eq!(names, ["/soc/serial@1000", "/soc/bus/serial@2000"]);
+    assert_eq!(soc.find_compatible("ns16550a").count(), 1);
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn find_nodes() {
+    use dtoolkit::error::FdtError;
+    use dtoolkit::model::DeviceTreeNode;
+
+    let bus = |name: &str, uart: &str| {
+        DeviceTreeNode::builder(name)
+            .child(DeviceTreeNode::new(uart))
+            .build()
+    };
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("soc")
+            .child(bus("bus@1", "uart@1000"))
+            .child(bus("bus@2", "uart@2000"))
+            .child(DeviceTreeNode::new("uart@3000"))
+            .build(),
+    );
+    tree.root.add_child(DeviceTreeNode::new("uart@4000"));
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+    let paths = |pattern| -> Vec<String> {
+        fdt.find_nodes(pattern)
+            .unwrap()
+            .map(|node| node.unwrap().path().unwrap().to_string())
+            .collect()
+    };
+
+    assert_eq!(
+        paths("/soc/*/uart@*"),
+        ["/soc/bus@1/uart@1000", "/soc/bus@2/uart@2000"]
+    );
+    assert_eq!(
+        paths("/soc/*/uart"),
+        ["/soc/bus@1/uart@1000", "/soc/bus@2/uart@2000"]
+    );
+    assert_eq!(paths("/soc/bus@2/*"), ["/soc/bus@2/uart@2000"]);
+    assert_eq!(
+        paths("/**/uart@*000"),
+        [
+            "/soc/bus@1/uart@1000",
+            "/soc/bus@2/uart@2000",
+            "/soc/uart@3000",
+            "/uart@4000"
+        ]
+    );
+    assert_eq!(paths("/**/bus/**/uart@2*"), ["/soc/bus@2/uart@2000"]);
+    assert_eq!(paths("/soc/**/*@3*"), ["/soc/uart@3000"]);
+    assert_eq!(paths("/"), ["/"]);
+    assert_eq!(paths("/**").len(), 8);
+    assert!(paths("/nope/*").is_empty());
+    assert!(paths("/*/*/*/*").is_empty());
+
+    let pattern = "/*".repeat(64);
+    assert!(matches!(
+        fdt.find_nodes(&pattern).map(|_| ()),
+        Err(FdtError::CapacityExceeded(_))
+    ));
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn walk() {
+    use dtoolkit::error::FdtErrorKind;
+    use dtoolkit::fdt::{FdtNode, FdtProperty, MAX_DESCENDANTS_DEPTH, Visitor, Walk};
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+
+    /// A visitor which records the events of a walk, and skips or stops at the
+    /// given node or property.
+    #[derive(Default)]
+    struct Recorder {
+        events: Vec<String>,
+        skip: &'static str,
+        stop: &'static str,
+    }
+
+    impl<'a> Visitor<'a> for Recorder {
+        fn enter_node(&mut self, node: FdtNode<'a>) -> Walk {
+            let name = node.name().unwrap();
+            self.events.push(format!("enter {name}"));
+            self.control(name)
+        }
+
+        fn property(&mut self, _node: FdtNode<'a>, property: FdtProperty<'a>) -> Walk {
+            self.events.push(format!("property {}", property.name()));
+            self.control(property.name())
+        }
+
+        fn leave_node(&mut self, node: FdtNode<'a>) -> Walk {
+            self.events.push(format!("leave {}", node.name().unwrap()));
+            Walk::Continue
+        }
+    }
+
+    impl Recorder {
+        fn control(&self, name: &str) -> Walk {
+            if name == self.skip {
+                Walk::SkipSubtree
+            } else if name == self.stop {
+                Walk::Stop
+            } else {
+                Walk::Continue
+            }
+        }
+    }
+
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("a")
+            .property(DeviceTreeProperty::new("p", []))
+            .property(DeviceTreeProperty::new("q", []))
+            .child(DeviceTreeNode::new("b"))
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("c")
+            .child(DeviceTreeNode::new("d"))
+            .build(),
+    );
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+    let walk = |skip, stop| {
+        let mut recorder = Recorder {
+            skip,
+            stop,
+            ..Recorder::default()
+        };
+        fdt.walk(&mut recorder).unwrap();
+        recorder.events.join(", ")
+    };
+
+    assert_eq!(
+        walk("", ""),
+        "enter /, enter a, property p, property q, enter b, leave b, leave a, \
+         enter c, enter d, leave d, leave c, leave /"
+    );
+    assert_eq!(
+        walk("p", ""),
+        "enter /, enter a, property p, leave a, enter c, enter d, leave d, leave c, leave /"
+    );
+    assert_eq!(
+        walk("c", ""),
+        "enter /, enter a, property p, property q, enter b, leave b, leave a, enter c, \
+         leave c, leave /"
+    );
+    assert_eq!(
+        walk("", "b"),
+        "enter /, enter a, property p, property q, enter b"
+    );
+
+    let mut recorder = Recorder::default();
+    let c = fdt.find_node("/c").unwrap().unwrap();
+    c.walk(&mut recorder).unwrap();
+    assert_eq!(
+        recorder.events,
+        ["enter c", "enter d", "leave d", "leave c"]
+    );
+
+    let mut deep = DeviceTreeNode::new("leaf");
+    for _ in 0..MAX_DESCENDANTS_DEPTH {
+        deep = DeviceTreeNode::builder("deep").child(deep).build();
+    }
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(deep);
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+    let deep = fdt.find_node("/deep").unwrap().unwrap();
+    let mut recorder = Recorder::default();
+    deep.walk(&mut recorder).unwrap();
+    assert_eq!(recorder.events.len(), 2 * (MAX_DESCENDANTS_DEPTH + 1));
+    assert_eq!(
+        fdt.walk(&mut Recorder::default()).unwrap_err().kind,
+        FdtErrorKind::TooDeep(MAX_DESCENDANTS_DEPTH)
+    );
+}
+
 #[test]
 fn memory() {
     let dtb = include_bytes!("dtb/test_pretty_print.dtb");
@@ -246,6 +780,12 @@ fn memory() {
     assert_eq!(reg.len(), 1);
     assert_eq!(reg[0].address::<u32>().unwrap(), 0x8000_0000);
     assert_eq!(reg[0].size::<u32>().unwrap(), 0x2000_0000);
+    assert!(
+        memory
+            .regions()
+            .unwrap()
+            .eq([Ok((0x8000_0000, 0x2000_0000))])
+    );
     assert!(memory.hotpluggable().unwrap());
     assert_eq!(
         memory
@@ -261,51 +801,479 @@ fn memory() {
     );
 }
 
-#[macro_export]
-macro_rules! load_dtb_dts_pair {
-    ($name:expr) => {
-        (
-            include_bytes!(concat!("dtb/", $name, ".dtb")).as_slice(),
-            include_str!(concat!("dts/", $name, ".dts")),
-            $name,
-        )
-    };
+#[cfg(feature = "write")]
+#[test]
+fn memory_regions_too_big() {
+    use dtoolkit::error::FdtError;
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+
+    let cells =
+        |cells: &[u32]| -> Vec<u8> { cells.iter().flat_map(|cell| cell.to_be_bytes()).collect() };
+    let mut tree = DeviceTree::new();
+    tree.root
+        .add_property(DeviceTreeProperty::new("#address-cells", cells(&[3])));
+    tree.root.add_child(
+        DeviceTreeNode::builder("memory")
+            .property(DeviceTreeProperty::new("reg", cells(&[1, 0, 0, 0x1000])))
+            .build(),
+    );
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+
+    assert!(
+        fdt.memory()
+            .unwrap()
+            .regions()
+            .unwrap()
+            .eq([Err(FdtError::TooManyCells { cells: 3 })])
+    );
 }
 
-const ALL_DT_FILES: &[(&[u8], &str, &str)] = &[
-    load_dtb_dts_pair!("test_children_nested"),
-    load_dtb_dts_pair!("test_children"),
-    load_dtb_dts_pair!("test_memreserve"),
-    load_dtb_dts_pair!("test_pretty_print"),
-    load_dtb_dts_pair!("test_props"),
-    load_dtb_dts_pair!("test_traversal"),
-    load_dtb_dts_pair!("test"),
-];
+#[cfg(feature = "write")]
+#[test]
+fn symbols() {
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+
+    let dtb = include_bytes!("dtb/test_props.dtb");
+    assert!(Fdt::new(dtb).unwrap().symbols().unwrap().is_none());
+
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("soc")
+            .child(DeviceTreeNode::new("uart@1000"))
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("__symbols__")
+            .property(DeviceTreeProperty::new("uart0", "/soc/uart@1000\0"))
+            .build(),
+    );
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+
+    let symbols = fdt.symbols().unwrap().unwrap();
+    assert_eq!(
+        symbols.labels().collect::<Result<Vec<_>, _>>().unwrap(),
+        vec![("uart0", "/soc/uart@1000")]
+    );
+    assert_eq!(symbols.path("uart0").unwrap(), Some("/soc/uart@1000"));
+    assert_eq!(symbols.path("uart1").unwrap(), None);
+
+    let uart = fdt.resolve_label("uart0").unwrap().unwrap();
+    assert_eq!(uart.name(), Ok("uart@1000"));
+    assert!(fdt.resolve_label("uart1").unwrap().is_none());
+}
 
+#[cfg(feature = "write")]
 #[test]
-fn pretty_print() {
-    for (dtb, expected_dts, name) in ALL_DT_FILES {
-        let fdt = Fdt::new(dtb).unwrap();
-        let s = fdt.to_string();
-        let expected = expected_dts
-            // account for Windows newlines, if needed
-            .replace("\r\n", "\n");
-        assert_eq!(s, expected, "Mismatch for {name}");
+fn aliases() {
+    use dtoolkit::fdt::FdtIndex;
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("soc")
+            .child(
+                DeviceTreeNode::builder("serial@1000")
+                    .child(DeviceTreeNode::new("child"))
+                    .build(),
+            )
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("aliases")
+            .property(DeviceTreeProperty::new("serial0", "/soc/serial@1000\0"))
+            .property(DeviceTreeProperty::new("relative", "serial0\0"))
+            .build(),
+    );
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+
+    let aliases = fdt.aliases().unwrap().unwrap();
+    assert_eq!(
+        aliases.aliases().collect::<Result<Vec<_>, _>>().unwrap(),
+        vec![("serial0", "/soc/serial@1000"), ("relative", "serial0")]
+    );
+    assert_eq!(aliases.path("serial0").unwrap(), Some("/soc/serial@1000"));
+    let serial = fdt.resolve_alias("serial0").unwrap().unwrap();
+    assert_eq!(serial.name(), Ok("serial@1000"));
+    assert!(fdt.resolve_alias("relative").unwrap().is_none());
+
+    let index = FdtIndex::new(fdt).unwrap();
+    for (path, expected) in [
+        ("serial0", Some("serial@1000")),
+        ("serial0/", Some("serial@1000")),
+        ("serial0/child", Some("child")),
+        ("serial0/missing", None),
+        ("serial1", None),
+        ("relative", None),
+    ] {
+        let node = fdt.find_node(path).unwrap();
+        assert_eq!(node.map(|node| node.name().unwrap()), expected, "{path}");
+        let node = index.find_node(path);
+        assert_eq!(node.map(|node| node.name().unwrap()), expected, "{path}");
+        let node = tree.find_node_mut(path);
+        assert_eq!(node.map(|node| node.name()), expected, "{path}");
     }
 }
 
+#[cfg(feature = "write")]
 #[test]
+fn reserved_memory() {
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+
+    let cells =
+        |cells: &[u32]| -> Vec<u8> { cells.iter().flat_map(|cell| cell.to_be_bytes()).collect() };
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("reserved-memory")
+            .property(DeviceTreeProperty::new("#address-cells", cells(&[2])))
+            .property(DeviceTreeProperty::new("#size-cells", cells(&[2])))
+            .property(DeviceTreeProperty::new("ranges", []))
+            .child(
+                DeviceTreeNode::builder("secmon@80000000")
+                    .property(DeviceTreeProperty::new(
+                        "reg",
+                        cells(&[0, 0x8000_0000, 0, 0x1000]),
+                    ))
+                    .property(DeviceTreeProperty::new("no-map", []))
+                    .build(),
+            )
+            .child(
+                DeviceTreeNode::builder("linux,cma")
+                    .property(DeviceTreeProperty::new("compatible", "shared-dma-pool\0"))
+                    .property(DeviceTreeProperty::new("reusable", []))
+                    .property(DeviceTreeProperty::new("size", cells(&[0, 0x400_0000])))
+                    .property(DeviceTreeProperty::new("alignment", cells(&[0, 0x2000])))
+                    .property(DeviceTreeProperty::new(
+                        "alloc-ranges",
+                        cells(&[0, 0x4000_0000, 0, 0x4000_0000]),
+                    ))
+                    .build(),
+            )
+            .child(
+                DeviceTreeNode::builder("bad")
+                    .property(DeviceTreeProperty::new("size", cells(&[0x1000])))
+                    .build(),
+            )
+            .build(),
+    );
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+    assert!(
+        Fdt::new(include_bytes!("dtb/test_props.dtb"))
+            .unwrap()
+            .reserved_memory()
+            .unwrap()
+            .is_none()
+    );
+
+    let reserved_memory = fdt.reserved_memory().unwrap().unwrap();
+    let regions: Vec<_> = reserved_memory.regions().map(Result::unwrap).collect();
+    assert_eq!(regions.len(), 3);
+
+    let secmon = regions[0];
+    let reg: Vec<_> = secmon.reg().unwrap().unwrap().collect();
+    assert_eq!(reg[0].address::<u64>().unwrap(), 0x8000_0000);
+    assert_eq!(reg[0].size::<u64>().unwrap(), 0x1000);
+    assert!(secmon.no_map().unwrap());
+    assert!(!secmon.reusable().unwrap());
+    assert!(!secmon.is_dynamic().unwrap());
+    assert!(!secmon.is_shared_dma_pool().unwrap());
+    assert!(secmon.size().unwrap().is_none());
+
+    let cma = regions[1];
+    assert!(cma.is_dynamic().unwrap());
+    assert!(cma.is_shared_dma_pool().unwrap());
+    assert!(cma.reusable().unwrap());
+    assert!(!cma.no_map().unwrap());
+    assert_eq!(
+        cma.size().unwrap().unwrap().to_int::<u64>().unwrap(),
+        0x400_0000
+    );
+    assert_eq!(
+        cma.alignment().unwrap().unwrap().to_int::<u64>().unwrap(),
+        0x2000
+    );
+    let alloc_ranges: Vec<_> = cma.alloc_ranges().unwrap().unwrap().collect();
+    assert_eq!(alloc_ranges.len(), 1);
+    assert_eq!(alloc_ranges[0].address::<u64>().unwrap(), 0x4000_0000);
+
+    assert!(regions[2].size().is_err());
+
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("reserved-memory")
+            .property(DeviceTreeProperty::new("#address-cells", cells(&[1])))
+            .property(DeviceTreeProperty::new("#size-cells", cells(&[0])))
+            .child(
+                DeviceTreeNode::builder("empty")
+                    .property(DeviceTreeProperty::new("size", []))
+                    .build(),
+            )
+            .build(),
+    );
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+    let region = fdt
+        .reserved_memory()
+        .unwrap()
+        .unwrap()
+        .regions()
+        .next()
+        .unwrap()
+        .unwrap();
+    assert!(region.size().is_err());
+}
+
+/// Returns a device tree with interrupt controllers, a nexus and devices
+/// using them.
 #[cfg(feature = "write")]
-fn round_trip() {
-    for (dtb, _dts, name) in ALL_DT_FILES {
-        let fdt = Fdt::new(dtb).unwrap();
-        let ir = DeviceTree::from_fdt(&fdt).unwrap();
-        let new_dtb = ir.to_dtb();
-        assert_eq!(dtb.to_vec(), new_dtb, "Mismatch for {name}");
-    }
+fn interrupts_dtb() -> Vec<u8> {
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+
+    let cells =
+        |cells: &[u32]| -> Vec<u8> { cells.iter().flat_map(|cell| cell.to_be_bytes()).collect() };
+    let mut tree = DeviceTree::new();
+    tree.root
+        .add_property(DeviceTreeProperty::new("interrupt-parent", cells(&[1])));
+    tree.root.add_child(
+        DeviceTreeNode::builder("interrupt-controller@1000")
+            .property(DeviceTreeProperty::new("phandle", cells(&[1])))
+            .property(DeviceTreeProperty::new("interrupt-controller", []))
+            .property(DeviceTreeProperty::new("#interrupt-cells", cells(&[3])))
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("soc")
+            .child(
+                DeviceTreeNode::builder("serial@2000")
+                    .property(DeviceTreeProperty::new(
+                        "interrupts",
+                        cells(&[0, 5, 4, 0, 6, 4]),
+                    ))
+                    .build(),
+            )
+            .child(
+                DeviceTreeNode::builder("gpio@3000")
+                    .property(DeviceTreeProperty::new("phandle", cells(&[2])))
+                    .property(DeviceTreeProperty::new("interrupt-controller", []))
+                    .property(DeviceTreeProperty::new("#interrupt-cells", cells(&[2])))
+                    .property(DeviceTreeProperty::new("interrupts", cells(&[0, 7, 4])))
+                    .build(),
+            )
+            .child(
+                DeviceTreeNode::builder("button")
+                    .property(DeviceTreeProperty::new("interrupt-parent", cells(&[2])))
+                    .property(DeviceTreeProperty::new("interrupts", cells(&[3, 1, 4])))
+                    .build(),
+            )
+            .child(
+                DeviceTreeNode::builder("nexus")
+                    .property(DeviceTreeProperty::new("phandle", cells(&[4])))
+                    .property(DeviceTreeProperty::new("#interrupt-cells", cells(&[1])))
+                    .child(
+                        DeviceTreeNode::builder("device")
+                            .property(DeviceTreeProperty::new("interrupts", cells(&[1])))
+                            .build(),
+                    )
+                    .build(),
+            )
+            .child(
+                DeviceTreeNode::builder("loop")
+                    .property(DeviceTreeProperty::new("phandle", cells(&[3])))
+                    .property(DeviceTreeProperty::new("interrupt-parent", cells(&[3])))
+                    .child(DeviceTreeNode::new("looped"))
+                    .build(),
+            )
+            .build(),
+    );
+    tree.to_dtb()
 }
 
-#[test]
+#[cfg(feature = "write")]
+#[test]
+fn interrupts() {
+    use dtoolkit::error::{FdtError, FdtErrorKind};
+
+    let dtb = interrupts_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+
+    let gic = fdt.find_node("/interrupt-controller").unwrap().unwrap();
+    assert!(gic.is_interrupt_controller().unwrap());
+    assert_eq!(gic.interrupt_cells().unwrap(), Some(3));
+    assert!(gic.interrupts().unwrap().is_none());
+
+    let serial = fdt.find_node("/soc/serial").unwrap().unwrap();
+    let parent = serial.interrupt_parent().unwrap().unwrap();
+    assert_eq!(parent.name(), Ok("interrupt-controller@1000"));
+    let interrupts: Vec<Vec<u32>> = serial
+        .interrupts()
+        .unwrap()
+        .unwrap()
+        .map(|specifier| specifier.iter().collect())
+        .collect();
+    assert_eq!(interrupts, [[0, 5, 4], [0, 6, 4]]);
+
+    let gpio = fdt.find_node("/soc/gpio").unwrap().unwrap();
+    assert_eq!(gpio.interrupts().unwrap().unwrap().count(), 1);
+
+    let button = fdt.find_node("/soc/button").unwrap().unwrap();
+    let parent = button.interrupt_parent().unwrap().unwrap();
+    assert_eq!(parent.name(), Ok("gpio@3000"));
+    assert!(matches!(
+        button.interrupts().map(|_| ()),
+        Err(FdtError::PropEncodedArraySizeMismatch { size: 12, chunk: 2 })
+    ));
+
+    let looped = fdt.find_node("/soc/loop/looped").unwrap().unwrap();
+    assert_eq!(
+        looped.interrupt_parent().unwrap_err().kind,
+        FdtErrorKind::TooDeep(64)
+    );
+    assert!(gic.interrupt_parent().unwrap().is_none());
+    let root = fdt.root().unwrap().interrupt_parent().unwrap().unwrap();
+    assert_eq!(root.name(), Ok("interrupt-controller@1000"));
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn interrupt_controller() {
+    let dtb = interrupts_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+    let gic = fdt.find_node("/interrupt-controller").unwrap().unwrap();
+    let gpio = fdt.find_node("/soc/gpio").unwrap().unwrap();
+    let button = fdt.find_node("/soc/button").unwrap().unwrap();
+
+    let controller = button.interrupt_controller().unwrap().unwrap();
+    assert_eq!(controller.name(), Ok("gpio@3000"));
+    let controller = gpio.interrupt_controller().unwrap().unwrap();
+    assert_eq!(controller.name(), Ok("interrupt-controller@1000"));
+    let device = fdt.find_node("/soc/nexus/device").unwrap().unwrap();
+    let parent = device.interrupt_parent().unwrap().unwrap();
+    assert_eq!(parent.name(), Ok("nexus"));
+    let controller = device.interrupt_controller().unwrap().unwrap();
+    assert_eq!(controller.name(), Ok("interrupt-controller@1000"));
+    assert!(gic.interrupt_controller().unwrap().is_none());
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn interrupt_map() {
+    use dtoolkit::error::FdtError;
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+
+    let cells =
+        |cells: &[u32]| -> Vec<u8> { cells.iter().flat_map(|cell| cell.to_be_bytes()).collect() };
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("interrupt-controller@1000")
+            .property(DeviceTreeProperty::new("phandle", cells(&[1])))
+            .property(DeviceTreeProperty::new("interrupt-controller", []))
+            .property(DeviceTreeProperty::new("#interrupt-cells", cells(&[3])))
+            .build(),
+    );
+    // A PCI host bridge routing INTA of slots 0 and 1 to different SPIs.
+    tree.root.add_child(
+        DeviceTreeNode::builder("pcie@2000")
+            .property(DeviceTreeProperty::new("#address-cells", cells(&[3])))
+            .property(DeviceTreeProperty::new("#interrupt-cells", cells(&[1])))
+            .property(DeviceTreeProperty::new(
+                "interrupt-map-mask",
+                cells(&[0xf800, 0, 0, 7]),
+            ))
+            .property(DeviceTreeProperty::new(
+                "interrupt-map",
+                cells(&[
+                    0x0000, 0, 0, 1, 1, 0, 100, 4, //
+                    0x0800, 0, 0, 1, 1, 0, 101, 4,
+                ]),
+            ))
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("broken")
+            .property(DeviceTreeProperty::new("#address-cells", cells(&[0])))
+            .property(DeviceTreeProperty::new("#interrupt-cells", cells(&[1])))
+            .property(DeviceTreeProperty::new("interrupt-map", cells(&[1, 1, 0])))
+            .build(),
+    );
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+
+    let pcie = fdt.find_node("/pcie").unwrap().unwrap();
+    let mapped = pcie
+        .map_interrupt(&[0x0800 | 0x300, 0, 0], &[1])
+        .unwrap()
+        .unwrap();
+    assert_eq!(mapped.parent.name(), Ok("interrupt-controller@1000"));
+    assert!(mapped.unit_address.is_empty());
+    assert!(mapped.specifier.iter().eq([0, 101, 4]));
+    let mapped = pcie.map_interrupt(&[0, 0, 0], &[1]).unwrap().unwrap();
+    assert!(mapped.specifier.iter().eq([0, 100, 4]));
+    assert!(pcie.map_interrupt(&[0, 0, 0], &[2]).unwrap().is_none());
+    assert!(pcie.map_interrupt(&[0], &[1]).unwrap().is_none());
+
+    let broken = fdt.find_node("/broken").unwrap().unwrap();
+    assert_eq!(
+        broken.map_interrupt(&[], &[1]).unwrap_err(),
+        FdtError::InvalidInterruptMap("entry is truncated")
+    );
+    let controller = fdt.find_node("/interrupt-controller").unwrap().unwrap();
+    assert_eq!(
+        controller.map_interrupt(&[], &[0, 0, 0]).unwrap_err(),
+        FdtError::MissingProperty {
+            name: "interrupt-map"
+        }
+    );
+}
+
+#[macro_export]
+macro_rules! load_dtb_dts_pair {
+    ($name:expr) => {
+        (
+            include_bytes!(concat!("dtb/", $name, ".dtb")).as_slice(),
+            include_str!(concat!("dts/", $name, ".dts")),
+            $name,
+        )
+    };
+}
+
+const ALL_DT_FILES: &[(&[u8], &str, &str)] = &[
+    load_dtb_dts_pair!("test_children_nested"),
+    load_dtb_dts_pair!("test_children"),
+    load_dtb_dts_pair!("test_memreserve"),
+    load_dtb_dts_pair!("test_pretty_print"),
+    load_dtb_dts_pair!("test_props"),
+    load_dtb_dts_pair!("test_strings"),
+    load_dtb_dts_pair!("test_traversal"),
+    load_dtb_dts_pair!("test"),
+];
+
+#[test]
+fn pretty_print() {
+    for (dtb, expected_dts, name) in ALL_DT_FILES {
+        let fdt = Fdt::new(dtb).unwrap();
+        let s = fdt.to_string();
+        let expected = expected_dts
+            // account for Windows newlines, if needed
+            .replace("\r\n", "\n");
+        assert_eq!(s, expected, "Mismatch for {name}");
+    }
+}
+
+#[test]
+#[cfg(feature = "write")]
+fn round_trip() {
+    for (dtb, _dts, name) in ALL_DT_FILES {
+        let fdt = Fdt::new(dtb).unwrap();
+        let ir = DeviceTree::from_fdt(&fdt).unwrap();
+        let new_dtb = ir.to_dtb();
+        assert_eq!(dtb.to_vec(), new_dtb, "Mismatch for {name}");
+    }
+}
+
+#[test]
 #[cfg(feature = "write")]
 fn round_trip_raw() {
     for (dtb, _dts, name) in ALL_DT_FILES {
@@ -315,3 +1283,1814 @@ fn round_trip_raw() {
         assert_eq!(dtb.to_vec(), new_dtb, "Mismatch for {name}");
     }
 }
+
+#[test]
+fn lenient_parsing() {
+    let dtb = include_bytes!("dtb/test_props.dtb");
+    let field = |data: &[u8], offset: usize| {
+        u32::from_be_bytes(data[offset..offset + 4].try_into().unwrap()) as usize
+    };
+
+    let mut padded = dtb.to_vec();
+    padded.extend_from_slice(&[0xff; 100]);
+    assert!(Fdt::new(&padded).is_err());
+    let (fdt, warnings) = Fdt::new_lenient(&padded).unwrap();
+    assert_eq!(warnings.trailing_bytes, 100);
+    assert!(!warnings.strings_before_struct);
+    assert_eq!(fdt.data(), dtb);
+    let (_, warnings) = Fdt::new_lenient(dtb).unwrap();
+    assert!(warnings.is_empty());
+    assert!(Fdt::new_lenient(&dtb[..dtb.len() - 1]).is_err());
+    let mut tiny = padded.clone();
+    tiny[4..8].copy_from_slice(&8u32.to_be_bytes());
+    assert!(Fdt::new_lenient(&tiny).is_err());
+
+    // Move the strings block before the struct block.
+    let (off_struct, off_strings) = (field(dtb, 8), field(dtb, 12));
+    let (size_strings, size_struct) = (field(dtb, 32), field(dtb, 36));
+    let mut reordered = dtb[..off_struct].to_vec();
+    let new_off_strings = reordered.len();
+    reordered.extend_from_slice(&dtb[off_strings..off_strings + size_strings]);
+    reordered.resize(reordered.len().next_multiple_of(4), 0);
+    let new_off_struct = reordered.len();
+    reordered.extend_from_slice(&dtb[off_struct..off_struct + size_struct]);
+    let totalsize = u32::try_from(reordered.len()).unwrap();
+    reordered[4..8].copy_from_slice(&totalsize.to_be_bytes());
+    reordered[8..12].copy_from_slice(&u32::try_from(new_off_struct).unwrap().to_be_bytes());
+    reordered[12..16].copy_from_slice(&u32::try_from(new_off_strings).unwrap().to_be_bytes());
+    assert!(Fdt::new(&reordered).is_err());
+    let (fdt, warnings) = Fdt::new_lenient(&reordered).unwrap();
+    assert!(warnings.strings_before_struct);
+    assert_eq!(warnings.trailing_bytes, 0);
+    assert_eq!(fdt.to_string(), Fdt::new(dtb).unwrap().to_string());
+
+    // Overlapping blocks are still rejected.
+    reordered[12..16].copy_from_slice(&u32::try_from(new_off_struct - 4).unwrap().to_be_bytes());
+    assert!(Fdt::new_lenient(&reordered).is_err());
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn parse_limits() {
+    use dtoolkit::error::FdtErrorKind;
+    use dtoolkit::fdt::ParseLimits;
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("a")
+            .property(DeviceTreeProperty::new("big", [0u8; 64]))
+            .child(
+                DeviceTreeNode::builder("b")
+                    .child(DeviceTreeNode::new("c"))
+                    .build(),
+            )
+            .build(),
+    );
+    tree.root.add_child(DeviceTreeNode::new("d"));
+    let dtb = tree.to_dtb();
+    let limits = ParseLimits {
+        max_depth: 3,
+        max_nodes: 5,
+        max_property_size: 64,
+    };
+    assert!(Fdt::new_with_limits(&dtb, limits).is_ok());
+    assert!(Fdt::new_with_limits(&dtb, ParseLimits::default()).is_ok());
+
+    let kind = |limits| Fdt::new_with_limits(&dtb, limits).unwrap_err().kind;
+    assert_eq!(
+        kind(ParseLimits {
+            max_depth: 2,
+            ..limits
+        }),
+        FdtErrorKind::TooDeep(2)
+    );
+    assert_eq!(
+        kind(ParseLimits {
+            max_nodes: 4,
+            ..limits
+        }),
+        FdtErrorKind::TooManyNodes(4)
+    );
+    assert_eq!(
+        kind(ParseLimits {
+            max_property_size: 63,
+            ..limits
+        }),
+        FdtErrorKind::PropertyTooBig(63)
+    );
+
+    // The structure block is checked even without limits.
+    let mut malformed = dtb.clone();
+    let off_dt_struct = u32::from_be_bytes(malformed[8..12].try_into().unwrap()) as usize;
+    malformed[off_dt_struct + 3] = 0x2;
+    assert!(Fdt::new(&malformed).is_ok());
+    assert!(Fdt::new_with_limits(&malformed, ParseLimits::UNLIMITED).is_err());
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn compare_blobs() {
+    use dtoolkit::fdt::Difference;
+    use dtoolkit::memreserve::MemoryReservation;
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+
+    let dtb = include_bytes!("dtb/test_props.dtb");
+    let left = Fdt::new(dtb).unwrap();
+    let mut tree = DeviceTree::from_fdt(&left).unwrap();
+    tree.memory_reservations
+        .push(MemoryReservation::new(0x1000, 0x2000));
+    let node = tree.find_node_mut("/test-props").unwrap();
+    node.property_mut("u32-prop")
+        .unwrap()
+        .set_value(1u32.to_be_bytes());
+    node.remove_property("str-prop");
+    node.add_property(DeviceTreeProperty::new("new-prop", []));
+    tree.root.remove_child("standard-props@1");
+    tree.root.add_child(DeviceTreeNode::new("new-node"));
+    let dtb = tree.to_dtb();
+    let right = Fdt::new(&dtb).unwrap();
+
+    let differences: Vec<_> = dtoolkit::compare(&left, &right)
+        .map(Result::unwrap)
+        .collect();
+    assert_eq!(differences.len(), 6, "{differences:#?}");
+    assert!(matches!(
+        differences[0],
+        Difference::MemoryReservation {
+            index: 0,
+            left: None,
+            right: Some(_)
+        }
+    ));
+    assert!(matches!(
+        differences[1],
+        Difference::NodeOnlyInRight(node) if node.name() == Ok("new-node")
+    ));
+    assert!(matches!(
+        differences[2],
+        Difference::PropertyChanged { left, right, .. }
+            if left.as_u32() == Ok(0x1234_5678) && right.as_u32() == Ok(1)
+    ));
+    assert!(matches!(
+        differences[3],
+        Difference::PropertyOnlyInLeft { property, .. } if property.name() == "str-prop"
+    ));
+    let Difference::PropertyOnlyInRight { node, property } = differences[4] else {
+        panic!("unexpected difference {:?}", differences[4]);
+    };
+    assert_eq!(property.name(), "new-prop");
+    assert_eq!(node.path().unwrap().as_str(), "/test-props");
+    assert!(matches!(
+        differences[5],
+        Difference::NodeOnlyInLeft(node) if node.name() == Ok("standard-props@1")
+    ));
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn clocks() {
+    use dtoolkit::error::FdtError;
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+
+    let cells =
+        |cells: &[u32]| -> Vec<u8> { cells.iter().flat_map(|cell| cell.to_be_bytes()).collect() };
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("oscillator")
+            .property(DeviceTreeProperty::new("phandle", cells(&[1])))
+            .property(DeviceTreeProperty::new("#clock-cells", cells(&[0])))
+            .property(DeviceTreeProperty::new(
+                "clock-frequency",
+                cells(&[24_000_000]),
+            ))
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("clock-controller@1000")
+            .property(DeviceTreeProperty::new("phandle", cells(&[2])))
+            .property(DeviceTreeProperty::new("#clock-cells", cells(&[1])))
+            .property(DeviceTreeProperty::new(
+                "clock-frequency",
+                0x1_0000_0000_u64.to_be_bytes(),
+            ))
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("serial@2000")
+            .property(DeviceTreeProperty::new("clocks", cells(&[2, 5, 0, 1])))
+            .property(DeviceTreeProperty::new(
+                "clock-names",
+                b"baud\0unused\0apb\0",
+            ))
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("broken")
+            .property(DeviceTreeProperty::new("clocks", cells(&[1, 9])))
+            .property(DeviceTreeProperty::new("clock-frequency", [0; 2]))
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("truncated")
+            .property(DeviceTreeProperty::new("clocks", cells(&[2])))
+            .build(),
+    );
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+
+    let oscillator = fdt.find_node("/oscillator").unwrap().unwrap();
+    assert_eq!(oscillator.clock_frequency().unwrap(), Some(24_000_000));
+    assert!(oscillator.clocks().unwrap().is_none());
+    let controller = fdt.find_node("/clock-controller").unwrap().unwrap();
+    assert_eq!(controller.clock_frequency().unwrap(), Some(0x1_0000_0000));
+
+    let serial = fdt.find_node("/serial").unwrap().unwrap();
+    assert!(serial.clock_frequency().unwrap().is_none());
+    let clocks: Vec<_> = serial
+        .clocks()
+        .unwrap()
+        .unwrap()
+        .map(|clock| {
+            let clock = clock.unwrap();
+            (clock.name, clock.provider.name(), clock.specifier.get(0))
+        })
+        .collect();
+    assert_eq!(
+        clocks,
+        [
+            (Some("baud"), Ok("clock-controller@1000"), Some(5)),
+            (Some("apb"), Ok("oscillator"), None),
+        ]
+    );
+    let apb = serial.clock("apb").unwrap().unwrap();
+    assert_eq!(apb.provider.name(), Ok("oscillator"));
+    assert!(serial.clock("unused").unwrap().is_none());
+    assert!(serial.clock("missing").unwrap().is_none());
+
+    let broken = fdt.find_node("/broken").unwrap().unwrap();
+    assert!(broken.clock_frequency().is_err());
+    let mut clocks = broken.clocks().unwrap().unwrap();
+    let clock = clocks.next().unwrap().unwrap();
+    assert_eq!(clock.name, None);
+    assert_eq!(
+        clocks.next().unwrap().unwrap_err(),
+        FdtError::PhandleNotFound(9)
+    );
+    assert!(clocks.next().is_none());
+    assert_eq!(
+        broken.clock("any").unwrap_err(),
+        FdtError::PhandleNotFound(9)
+    );
+
+    let truncated = fdt.find_node("/truncated").unwrap().unwrap();
+    assert_eq!(
+        truncated
+            .clocks()
+            .unwrap()
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap_err(),
+        FdtError::PropEncodedArraySizeMismatch { size: 4, chunk: 2 }
+    );
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn gpios() {
+    use dtoolkit::error::FdtError;
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+    use dtoolkit::standard::GpioFlags;
+
+    let cells =
+        |cells: &[u32]| -> Vec<u8> { cells.iter().flat_map(|cell| cell.to_be_bytes()).collect() };
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("pinctrl@1000")
+            .property(DeviceTreeProperty::new("phandle", cells(&[1])))
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("gpio@2000")
+            .property(DeviceTreeProperty::new("phandle", cells(&[2])))
+            .property(DeviceTreeProperty::new("gpio-controller", []))
+            .property(DeviceTreeProperty::new("#gpio-cells", cells(&[2])))
+            .property(DeviceTreeProperty::new(
+                "gpio-ranges",
+                cells(&[1, 0, 16, 8, 1, 8, 0, 0]),
+            ))
+            .property(DeviceTreeProperty::new(
+                "gpio-ranges-group-names",
+                b"\0uart\0",
+            ))
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("spi@3000")
+            .property(DeviceTreeProperty::new(
+                "cs-gpios",
+                cells(&[2, 4, 1, 0, 2, 5, 6]),
+            ))
+            .property(DeviceTreeProperty::new("reset-gpio", cells(&[2, 7, 0])))
+            .property(DeviceTreeProperty::new("gpios", cells(&[2, 8, 0])))
+            .property(DeviceTreeProperty::new("led-gpios", cells(&[2, 9])))
+            .build(),
+    );
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+
+    let spi = fdt.find_node("/spi").unwrap().unwrap();
+    let cs: Vec<_> = spi
+        .gpios(Some("cs"))
+        .unwrap()
+        .unwrap()
+        .map(|gpio| {
+            let gpio = gpio.unwrap();
+            assert_eq!(gpio.controller.name(), Ok("gpio@2000"));
+            (gpio.pin().unwrap(), gpio.flags().unwrap())
+        })
+        .collect();
+    assert_eq!(cs, [(4, GpioFlags(1)), (5, GpioFlags(6))]);
+    assert!(cs[0].1.is_active_low());
+    assert!(cs[1].1.is_open_drain());
+    assert!(!cs[1].1.is_open_source());
+
+    let reset = spi.gpios(Some("reset")).unwrap().unwrap().next();
+    assert_eq!(reset.unwrap().unwrap().pin(), Some(7));
+    let gpio = spi.gpios(None).unwrap().unwrap().next();
+    assert_eq!(gpio.unwrap().unwrap().pin(), Some(8));
+    assert!(spi.gpios(Some("missing")).unwrap().is_none());
+    assert_eq!(
+        spi.gpios(Some("led"))
+            .unwrap()
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap_err(),
+        FdtError::PropEncodedArraySizeMismatch { size: 8, chunk: 3 }
+    );
+
+    let controller = fdt.find_node("/gpio").unwrap().unwrap();
+    assert!(controller.is_gpio_controller().unwrap());
+    assert!(!spi.is_gpio_controller().unwrap());
+    let ranges: Vec<_> = controller
+        .gpio_ranges()
+        .unwrap()
+        .unwrap()
+        .map(|range| {
+            let range = range.unwrap();
+            assert_eq!(range.pin_controller.name(), Ok("pinctrl@1000"));
+            (
+                range.gpio_offset,
+                range.pin_offset,
+                range.count,
+                range.group,
+            )
+        })
+        .collect();
+    assert_eq!(ranges, [(0, 16, 8, None), (8, 0, 0, Some("uart"))]);
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn phandle_specifiers() {
+    use dtoolkit::error::FdtError;
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+
+    let cells =
+        |cells: &[u32]| -> Vec<u8> { cells.iter().flat_map(|cell| cell.to_be_bytes()).collect() };
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("reset-controller@1000")
+            .property(DeviceTreeProperty::new("phandle", cells(&[1])))
+            .property(DeviceTreeProperty::new("#reset-cells", cells(&[1])))
+            .property(DeviceTreeProperty::new("#power-domain-cells", cells(&[0])))
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("dma-controller@2000")
+            .property(DeviceTreeProperty::new("phandle", cells(&[2])))
+            .property(DeviceTreeProperty::new("#dma-cells", cells(&[2])))
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("mmc@3000")
+            .property(DeviceTreeProperty::new("resets", cells(&[1, 3, 1, 4])))
+            .property(DeviceTreeProperty::new("reset-names", b"ahb\0mmc\0"))
+            .property(DeviceTreeProperty::new("power-domains", cells(&[1])))
+            .property(DeviceTreeProperty::new("dmas", cells(&[2, 0, 1, 2, 1, 1])))
+            .property(DeviceTreeProperty::new("dma-names", b"rx\0tx\0"))
+            .property(DeviceTreeProperty::new("mboxes", cells(&[2, 0])))
+            .build(),
+    );
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+    let mmc = fdt.find_node("/mmc").unwrap().unwrap();
+
+    let resets: Vec<_> = mmc
+        .phandle_specifiers("resets", "reset-names", "#reset-cells")
+        .unwrap()
+        .unwrap()
+        .map(|reset| {
+            let reset = reset.unwrap();
+            (reset.name, reset.provider.name(), reset.specifier.get(0))
+        })
+        .collect();
+    assert_eq!(
+        resets,
+        [
+            (Some("ahb"), Ok("reset-controller@1000"), Some(3)),
+            (Some("mmc"), Ok("reset-controller@1000"), Some(4)),
+        ]
+    );
+
+    let mut domains = mmc
+        .phandle_specifiers("power-domains", "power-domain-names", "#power-domain-cells")
+        .unwrap()
+        .unwrap();
+    let domain = domains.next().unwrap().unwrap();
+    assert_eq!(domain.name, None);
+    assert!(domain.specifier.is_empty());
+    assert!(domains.next().is_none());
+
+    let tx = mmc
+        .phandle_specifier("dmas", "dma-names", "#dma-cells", "tx")
+        .unwrap()
+        .unwrap();
+    assert_eq!(tx.provider.name(), Ok("dma-controller@2000"));
+    assert!(tx.specifier.iter().eq([1, 1]));
+    assert!(
+        mmc.phandle_specifier("dmas", "dma-names", "#dma-cells", "missing")
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        mmc.phandle_specifiers("interconnects", "interconnect-names", "#interconnect-cells")
+            .unwrap()
+            .is_none()
+    );
+    assert_eq!(
+        mmc.phandle_specifier("mboxes", "mbox-names", "#mbox-cells", "any")
+            .unwrap_err(),
+        FdtError::MissingProperty {
+            name: "#mbox-cells"
+        }
+    );
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn pinctrl_states() {
+    use dtoolkit::error::FdtError;
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+
+    let cells =
+        |cells: &[u32]| -> Vec<u8> { cells.iter().flat_map(|cell| cell.to_be_bytes()).collect() };
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("pinctrl@1000")
+            .child(
+                DeviceTreeNode::builder("uart-pins")
+                    .property(DeviceTreeProperty::new("phandle", cells(&[1])))
+                    .build(),
+            )
+            .child(
+                DeviceTreeNode::builder("uart-rts-pins")
+                    .property(DeviceTreeProperty::new("phandle", cells(&[2])))
+                    .build(),
+            )
+            .child(
+                DeviceTreeNode::builder("uart-sleep-pins")
+                    .property(DeviceTreeProperty::new("phandle", cells(&[3])))
+                    .build(),
+            )
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("serial@2000")
+            .property(DeviceTreeProperty::new(
+                "pinctrl-names",
+                b"default\0sleep\0",
+            ))
+            .property(DeviceTreeProperty::new("pinctrl-1", cells(&[3])))
+            .property(DeviceTreeProperty::new("pinctrl-0", cells(&[1, 0, 2])))
+            .property(DeviceTreeProperty::new("pinctrl-3", cells(&[9])))
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("broken")
+            .property(DeviceTreeProperty::new("pinctrl-0", cells(&[9])))
+            .build(),
+    );
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+    let serial = fdt.find_node("/serial").unwrap().unwrap();
+
+    let states: Vec<_> = serial
+        .pinctrl_states()
+        .map(|state| {
+            let state = state.unwrap();
+            let configs: Vec<_> = state
+                .configs()
+                .map(|config| config.unwrap().name().unwrap())
+                .collect();
+            (state.index, state.name, configs)
+        })
+        .collect();
+    assert_eq!(
+        states,
+        [
+            (0, Some("default"), vec!["uart-pins", "uart-rts-pins"]),
+            (1, Some("sleep"), vec!["uart-sleep-pins"]),
+        ]
+    );
+
+    let sleep = serial.pinctrl_state("sleep").unwrap().unwrap();
+    assert_eq!(sleep.index, 1);
+    assert!(serial.pinctrl_state("idle").unwrap().is_none());
+    let root = fdt.root().unwrap();
+    assert!(root.pinctrl_states().next().is_none());
+    assert!(root.pinctrl_state("default").unwrap().is_none());
+
+    let broken = fdt.find_node("/broken").unwrap().unwrap();
+    let state = broken.pinctrl_states().next().unwrap().unwrap();
+    assert_eq!(state.name, None);
+    let mut configs = state.configs();
+    assert_eq!(
+        configs.next().unwrap().unwrap_err(),
+        FdtError::PhandleNotFound(9)
+    );
+    assert!(configs.next().is_none());
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn iommus() {
+    use dtoolkit::error::FdtError;
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+
+    let cells =
+        |cells: &[u32]| -> Vec<u8> { cells.iter().flat_map(|cell| cell.to_be_bytes()).collect() };
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("iommu@1000")
+            .property(DeviceTreeProperty::new("phandle", cells(&[1])))
+            .property(DeviceTreeProperty::new("#iommu-cells", cells(&[1])))
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("iommu@2000")
+            .property(DeviceTreeProperty::new("phandle", cells(&[2])))
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("ethernet@3000")
+            .property(DeviceTreeProperty::new(
+                "iommus",
+                cells(&[1, 0x10, 1, 0x11]),
+            ))
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("broken")
+            .property(DeviceTreeProperty::new("iommus", cells(&[2, 0x12])))
+            .build(),
+    );
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+
+    let ethernet = fdt.find_node("/ethernet").unwrap().unwrap();
+    let iommus: Vec<_> = ethernet
+        .iommus()
+        .unwrap()
+        .unwrap()
+        .map(|iommu| {
+            let iommu = iommu.unwrap();
+            (iommu.provider.name(), iommu.specifier.get(0))
+        })
+        .collect();
+    assert_eq!(
+        iommus,
+        [
+            (Ok("iommu@1000"), Some(0x10)),
+            (Ok("iommu@1000"), Some(0x11))
+        ]
+    );
+    assert!(fdt.root().unwrap().iommus().unwrap().is_none());
+
+    let broken = fdt.find_node("/broken").unwrap().unwrap();
+    assert_eq!(
+        broken
+            .iommus()
+            .unwrap()
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap_err(),
+        FdtError::MissingProperty {
+            name: "#iommu-cells"
+        }
+    );
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn msi() {
+    use dtoolkit::error::FdtError;
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+
+    let cells =
+        |cells: &[u32]| -> Vec<u8> { cells.iter().flat_map(|cell| cell.to_be_bytes()).collect() };
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("msi-controller@1000")
+            .property(DeviceTreeProperty::new("phandle", cells(&[1])))
+            .property(DeviceTreeProperty::new("msi-controller", []))
+            .property(DeviceTreeProperty::new("#msi-cells", cells(&[1])))
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("msi-controller@2000")
+            .property(DeviceTreeProperty::new("phandle", cells(&[2])))
+            .property(DeviceTreeProperty::new("msi-controller", []))
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("pcie@3000")
+            .property(DeviceTreeProperty::new("msi-parent", cells(&[1, 0x40, 2])))
+            .property(DeviceTreeProperty::new(
+                "msi-map",
+                cells(&[0x0000, 1, 0x10000, 0x100, 0x0100, 2, 0x0, 0x100]),
+            ))
+            .property(DeviceTreeProperty::new("msi-map-mask", cells(&[0xfff8])))
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("broken")
+            .property(DeviceTreeProperty::new("msi-map", cells(&[0, 9, 0, 1])))
+            .build(),
+    );
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+    let pcie = fdt.find_node("/pcie").unwrap().unwrap();
+
+    let parents: Vec<_> = pcie
+        .msi_parents()
+        .unwrap()
+        .unwrap()
+        .map(|parent| {
+            let parent = parent.unwrap();
+            (parent.provider.name(), parent.specifier.get(0))
+        })
+        .collect();
+    assert_eq!(
+        parents,
+        [
+            (Ok("msi-controller@1000"), Some(0x40)),
+            (Ok("msi-controller@2000"), None),
+        ]
+    );
+
+    assert_eq!(pcie.msi_map().unwrap().unwrap().count(), 2);
+    assert_eq!(pcie.msi_map_mask().unwrap(), Some(0xfff8));
+    let mapped = pcie.map_msi(0x0013).unwrap().unwrap();
+    assert_eq!(mapped.controller.name(), Ok("msi-controller@1000"));
+    assert_eq!(mapped.msi_id, 0x10010);
+    let mapped = pcie.map_msi(0x0105).unwrap().unwrap();
+    assert_eq!(mapped.controller.name(), Ok("msi-controller@2000"));
+    assert_eq!(mapped.msi_id, 0x0);
+    assert!(pcie.map_msi(0x0200).unwrap().is_none());
+    assert!(fdt.root().unwrap().map_msi(0).unwrap().is_none());
+
+    let broken = fdt.find_node("/broken").unwrap().unwrap();
+    assert_eq!(broken.map_msi(0).unwrap_err(), FdtError::PhandleNotFound(9));
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn nvmem_cells() {
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+    use dtoolkit::standard::NvmemBits;
+
+    let cells =
+        |cells: &[u32]| -> Vec<u8> { cells.iter().flat_map(|cell| cell.to_be_bytes()).collect() };
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("eeprom@50")
+            .property(DeviceTreeProperty::new("#address-cells", cells(&[1])))
+            .property(DeviceTreeProperty::new("#size-cells", cells(&[1])))
+            .child(
+                DeviceTreeNode::builder("mac@0")
+                    .property(DeviceTreeProperty::new("phandle", cells(&[1])))
+                    .property(DeviceTreeProperty::new("reg", cells(&[0, 6])))
+                    .build(),
+            )
+            .child(
+                DeviceTreeNode::builder("calibration@6")
+                    .property(DeviceTreeProperty::new("phandle", cells(&[2])))
+                    .property(DeviceTreeProperty::new("reg", cells(&[6, 2])))
+                    .property(DeviceTreeProperty::new("bits", cells(&[3, 5])))
+                    .build(),
+            )
+            .child(DeviceTreeNode::new("not-a-cell"))
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("efuse@1000")
+            .child(
+                DeviceTreeNode::builder("nvmem-layout")
+                    .property(DeviceTreeProperty::new("#address-cells", cells(&[1])))
+                    .property(DeviceTreeProperty::new("#size-cells", cells(&[1])))
+                    .child(
+                        DeviceTreeNode::builder("serial-number@10")
+                            .property(DeviceTreeProperty::new("reg", cells(&[0x10, 4])))
+                            .build(),
+                    )
+                    .build(),
+            )
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("ethernet@2000")
+            .property(DeviceTreeProperty::new("nvmem-cells", cells(&[1, 2])))
+            .property(DeviceTreeProperty::new(
+                "nvmem-cell-names",
+                b"mac-address\0calibration\0",
+            ))
+            .build(),
+    );
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+
+    let ethernet = fdt.find_node("/ethernet").unwrap().unwrap();
+    let names: Vec<_> = ethernet
+        .nvmem_cells()
+        .unwrap()
+        .unwrap()
+        .map(|cell| cell.unwrap().name)
+        .collect();
+    assert_eq!(names, [Some("mac-address"), Some("calibration")]);
+    let mac = ethernet.nvmem_cell("mac-address").unwrap().unwrap();
+    let mac = mac.provider.as_nvmem_cell().unwrap().unwrap();
+    assert_eq!((mac.offset, mac.length, mac.bits), (0, 6, None));
+    let calibration = ethernet.nvmem_cell("calibration").unwrap().unwrap();
+    let calibration = calibration.provider.as_nvmem_cell().unwrap().unwrap();
+    assert_eq!(calibration.node.name(), Ok("calibration@6"));
+    assert_eq!(
+        calibration.bits,
+        Some(NvmemBits {
+            offset: 3,
+            count: 5
+        })
+    );
+    assert!(ethernet.nvmem_cell("serial").unwrap().is_none());
+
+    let eeprom = fdt.find_node("/eeprom").unwrap().unwrap();
+    let offsets: Vec<_> = eeprom
+        .nvmem_provider_cells()
+        .unwrap()
+        .map(|cell| cell.unwrap().offset)
+        .collect();
+    assert_eq!(offsets, [0, 6]);
+    let efuse = fdt.find_node("/efuse").unwrap().unwrap();
+    let cells: Vec<_> = efuse
+        .nvmem_provider_cells()
+        .unwrap()
+        .map(|cell| {
+            let cell = cell.unwrap();
+            (cell.node.name(), cell.offset, cell.length)
+        })
+        .collect();
+    assert_eq!(cells, [(Ok("serial-number@10"), 0x10, 4)]);
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn graph() {
+    use dtoolkit::error::FdtError;
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+
+    let cells =
+        |cells: &[u32]| -> Vec<u8> { cells.iter().flat_map(|cell| cell.to_be_bytes()).collect() };
+    let endpoint = |name: &str, phandle: u32, remote: u32| {
+        DeviceTreeNode::builder(name)
+            .property(DeviceTreeProperty::new("phandle", cells(&[phandle])))
+            .property(DeviceTreeProperty::new("remote-endpoint", cells(&[remote])))
+            .build()
+    };
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("display@1000")
+            .child(
+                DeviceTreeNode::builder("ports")
+                    .property(DeviceTreeProperty::new("#address-cells", cells(&[1])))
+                    .property(DeviceTreeProperty::new("#size-cells", cells(&[0])))
+                    .child(
+                        DeviceTreeNode::builder("port@0")
+                            .property(DeviceTreeProperty::new("reg", cells(&[0])))
+                            .build(),
+                    )
+                    .child(
+                        DeviceTreeNode::builder("port@1")
+                            .property(DeviceTreeProperty::new("reg", cells(&[1])))
+                            .child(endpoint("endpoint", 1, 2))
+                            .build(),
+                    )
+                    .build(),
+            )
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("panel")
+            .child(
+                DeviceTreeNode::builder("port")
+                    .child(endpoint("endpoint", 2, 1))
+                    .child(endpoint("endpoint@1", 3, 9))
+                    .build(),
+            )
+            .build(),
+    );
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+
+    let display = fdt.find_node("/display").unwrap().unwrap();
+    let ports: Vec<_> = display
+        .graph_ports()
+        .unwrap()
+        .map(|port| port.unwrap().graph_id().unwrap())
+        .collect();
+    assert_eq!(ports, [0, 1]);
+    let port = display.graph_port(1).unwrap().unwrap();
+    let endpoint = port.graph_endpoints().next().unwrap().unwrap();
+    let remote = endpoint.remote_endpoint().unwrap().unwrap();
+    assert_eq!(remote.name(), Ok("endpoint"));
+    let panel = endpoint.remote_port_parent().unwrap().unwrap();
+    assert_eq!(panel.name(), Ok("panel"));
+    assert!(display.graph_port(2).unwrap().is_none());
+
+    let port = panel.graph_port(0).unwrap().unwrap();
+    let endpoints: Vec<_> = port
+        .graph_endpoints()
+        .map(|endpoint| endpoint.unwrap().name().unwrap())
+        .collect();
+    assert_eq!(endpoints, ["endpoint", "endpoint@1"]);
+    let endpoint = port.graph_endpoints().next().unwrap().unwrap();
+    let display_parent = endpoint.remote_port_parent().unwrap().unwrap();
+    assert_eq!(display_parent.name(), Ok("display@1000"));
+    let dangling = port.graph_endpoints().nth(1).unwrap().unwrap();
+    assert_eq!(
+        dangling.remote_endpoint().unwrap_err(),
+        FdtError::PhandleNotFound(9)
+    );
+    assert!(port.remote_endpoint().unwrap().is_none());
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn pci_addresses() {
+    use dtoolkit::error::FdtError;
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+    use dtoolkit::standard::{PciAddress, PciSpace};
+
+    let cells =
+        |cells: &[u32]| -> Vec<u8> { cells.iter().flat_map(|cell| cell.to_be_bytes()).collect() };
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("pcie@10000000")
+            .property(DeviceTreeProperty::new("#address-cells", cells(&[3])))
+            .property(DeviceTreeProperty::new("#size-cells", cells(&[2])))
+            .property(DeviceTreeProperty::new(
+                "ranges",
+                cells(&[
+                    0x0100_0000,
+                    0,
+                    0,
+                    0,
+                    0x3eff_0000,
+                    0,
+                    0x1_0000, //
+                    0x4300_0000,
+                    0x80,
+                    0,
+                    0x80,
+                    0,
+                    0x80,
+                    0,
+                ]),
+            ))
+            .child(
+                DeviceTreeNode::builder("ethernet@1,0")
+                    .property(DeviceTreeProperty::new(
+                        "reg",
+                        cells(&[0x0000_0800, 0, 0, 0, 0]),
+                    ))
+                    .property(DeviceTreeProperty::new(
+                        "assigned-addresses",
+                        cells(&[0x8200_0810, 0, 0x1000_0000, 0, 0x4000]),
+                    ))
+                    .build(),
+            )
+            .build(),
+    );
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+
+    let pcie = fdt.find_node("/pcie").unwrap().unwrap();
+    let spaces: Vec<_> = pcie
+        .ranges()
+        .unwrap()
+        .unwrap()
+        .map(|range| {
+            let address = range.pci_child_bus_address().unwrap();
+            (address.space, address.prefetchable, address.address)
+        })
+        .collect();
+    assert_eq!(
+        spaces,
+        [
+            (PciSpace::Io, false, 0),
+            (PciSpace::Memory64, true, 0x80_0000_0000)
+        ]
+    );
+
+    let ethernet = fdt.find_node("/pcie/ethernet").unwrap().unwrap();
+    let reg = ethernet.reg().unwrap().unwrap().next().unwrap();
+    let address = reg.pci_address().unwrap();
+    assert_eq!((address.bus, address.device, address.function), (0, 1, 0));
+    assert_eq!(address.space, PciSpace::Configuration);
+    let bar = ethernet
+        .assigned_addresses()
+        .unwrap()
+        .unwrap()
+        .next()
+        .unwrap();
+    let address = bar.pci_address().unwrap();
+    assert_eq!(address.to_string(), "mem 00:01.0 0x0000000010000000");
+    assert!(!address.relocatable);
+    assert_eq!(address.register, 0x10);
+    assert_eq!(bar.size::<u64>().unwrap(), 0x4000);
+
+    assert_eq!(
+        pcie.ranges()
+            .unwrap()
+            .unwrap()
+            .next()
+            .unwrap()
+            .parent_bus_address
+            .try_into(),
+        Err::<PciAddress, _>(FdtError::InvalidPciAddress { cells: 2 })
+    );
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn pci_interrupts() {
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+    use dtoolkit::standard::PciAddress;
+
+    let cells =
+        |cells: &[u32]| -> Vec<u8> { cells.iter().flat_map(|cell| cell.to_be_bytes()).collect() };
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("interrupt-controller@1000")
+            .property(DeviceTreeProperty::new("phandle", cells(&[1])))
+            .property(DeviceTreeProperty::new("interrupt-controller", []))
+            .property(DeviceTreeProperty::new("#interrupt-cells", cells(&[3])))
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("nexus")
+            .property(DeviceTreeProperty::new("phandle", cells(&[2])))
+            .property(DeviceTreeProperty::new("#address-cells", cells(&[0])))
+            .property(DeviceTreeProperty::new("#interrupt-cells", cells(&[1])))
+            .property(DeviceTreeProperty::new(
+                "interrupt-map",
+                cells(&[7, 1, 0, 50, 4]),
+            ))
+            .build(),
+    );
+    // Slot 0 has its INTA-INTD routed to SPIs 100-103, and slot 1 its INTA to
+    // the nexus.
+    let mut map = Vec::new();
+    for pin in 1..=4 {
+        map.extend([0, 0, 0, pin, 1, 0, 99 + pin, 4]);
+    }
+    map.extend([0x800, 0, 0, 1, 2, 7]);
+    tree.root.add_child(
+        DeviceTreeNode::builder("pcie@2000")
+            .property(DeviceTreeProperty::new("#address-cells", cells(&[3])))
+            .property(DeviceTreeProperty::new("#interrupt-cells", cells(&[1])))
+            .property(DeviceTreeProperty::new(
+                "interrupt-map-mask",
+                cells(&[0xf800, 0, 0, 7]),
+            ))
+            .property(DeviceTreeProperty::new("interrupt-map", cells(&map)))
+            .build(),
+    );
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+    let pcie = fdt.find_node("/pcie").unwrap().unwrap();
+    let map = |path: &[PciAddress], pin| {
+        pcie.map_pci_interrupt(path, pin).unwrap().map(|interrupt| {
+            (
+                interrupt.parent.name().unwrap(),
+                interrupt.specifier.iter().collect::<Vec<_>>(),
+            )
+        })
+    };
+
+    let root_port = PciAddress::config(0, 0, 0);
+    assert_eq!(
+        map(&[root_port], 1),
+        Some(("interrupt-controller@1000", vec![0, 100, 4]))
+    );
+    // INTB of device 2 behind the root port is swizzled to INTD.
+    assert_eq!(
+        map(&[root_port, PciAddress::config(1, 2, 0)], 2),
+        Some(("interrupt-controller@1000", vec![0, 103, 4]))
+    );
+    assert_eq!(
+        map(&[PciAddress::config(0, 1, 0)], 1),
+        Some(("interrupt-controller@1000", vec![0, 50, 4]))
+    );
+    assert_eq!(map(&[PciAddress::config(0, 1, 0)], 2), None);
+    assert_eq!(map(&[root_port], 0), None);
+    assert_eq!(map(&[], 1), None);
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn find_node_by_address() {
+    use dtoolkit::error::{FdtError, FdtErrorKind};
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+    use dtoolkit::standard::MAX_ADDRESS_MAP_DEPTH;
+
+    let cells =
+        |cells: &[u32]| -> Vec<u8> { cells.iter().flat_map(|cell| cell.to_be_bytes()).collect() };
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("memory@40000000")
+            .property(DeviceTreeProperty::new(
+                "reg",
+                cells(&[0, 0x4000_0000, 0x1000_0000]),
+            ))
+            .build(),
+    );
+    // The soc bus maps its addresses 0x0-0x10000000 to 0x90000000.
+    tree.root.add_child(
+        DeviceTreeNode::builder("soc")
+            .property(DeviceTreeProperty::new("#address-cells", cells(&[1])))
+            .property(DeviceTreeProperty::new("#size-cells", cells(&[1])))
+            .property(DeviceTreeProperty::new(
+                "ranges",
+                cells(&[0, 0, 0x9000_0000, 0x1000_0000]),
+            ))
+            .child(
+                DeviceTreeNode::builder("bus@100000")
+                    .property(DeviceTreeProperty::new("#address-cells", cells(&[1])))
+                    .property(DeviceTreeProperty::new("#size-cells", cells(&[1])))
+                    .property(DeviceTreeProperty::new(
+                        "reg",
+                        cells(&[0x10_0000, 0x10_0000]),
+                    ))
+                    .property(DeviceTreeProperty::new("ranges", []))
+                    .child(
+                        DeviceTreeNode::builder("serial@101000")
+                            .property(DeviceTreeProperty::new("reg", cells(&[0x10_1000, 0x100])))
+                            .build(),
+                    )
+                    .build(),
+            )
+            .build(),
+    );
+    // Devices on the i2c bus aren't memory-mapped.
+    tree.root.add_child(
+        DeviceTreeNode::builder("i2c@0")
+            .property(DeviceTreeProperty::new("#address-cells", cells(&[1])))
+            .property(DeviceTreeProperty::new("#size-cells", cells(&[0])))
+            .property(DeviceTreeProperty::new(
+                "reg",
+                cells(&[0, 0x8000_0000, 0x1000]),
+            ))
+            .child(
+                DeviceTreeNode::builder("eeprom@50")
+                    .property(DeviceTreeProperty::new("reg", cells(&[0x50])))
+                    .build(),
+            )
+            .build(),
+    );
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+    let find = |address| {
+        fdt.find_node_by_address(address)
+            .unwrap()
+            .map(|node| node.name().unwrap())
+    };
+
+    assert_eq!(find(0x4800_0000), Some("memory@40000000"));
+    assert_eq!(find(0x9010_1080), Some("serial@101000"));
+    assert_eq!(find(0x9010_2000), Some("bus@100000"));
+    assert_eq!(find(0x8000_0050), Some("i2c@0"));
+    assert_eq!(find(0x9000_0000), None);
+    assert_eq!(find(0x50), None);
+
+    let serial = fdt.find_node("/soc/bus/serial").unwrap().unwrap();
+    assert_eq!(
+        serial.translate_address(0x10_1000).unwrap(),
+        Some(0x9010_1000)
+    );
+    assert_eq!(serial.translate_address(0x1000_0000).unwrap(), None);
+    let eeprom = fdt.find_node("/i2c/eeprom").unwrap().unwrap();
+    assert!(matches!(
+        eeprom.translate_address(0x50),
+        Err(FdtError::NotTranslatable { .. })
+    ));
+
+    let mut deep = DeviceTreeNode::new("leaf");
+    for _ in 0..MAX_ADDRESS_MAP_DEPTH {
+        deep = DeviceTreeNode::builder("bus")
+            .property(DeviceTreeProperty::new("ranges", []))
+            .child(deep)
+            .build();
+    }
+    tree.root.add_child(deep);
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+    assert!(matches!(
+        fdt.find_node_by_address(0x4800_0000),
+        Err(FdtError::Parse(error)) if error.kind == FdtErrorKind::TooDeep(MAX_ADDRESS_MAP_DEPTH)
+    ));
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn address_map() {
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+
+    let cells =
+        |cells: &[u32]| -> Vec<u8> { cells.iter().flat_map(|cell| cell.to_be_bytes()).collect() };
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("soc")
+            .property(DeviceTreeProperty::new("#address-cells", cells(&[1])))
+            .property(DeviceTreeProperty::new("#size-cells", cells(&[1])))
+            .property(DeviceTreeProperty::new(
+                "ranges",
+                cells(&[0, 0, 0x9000_0000, 0x1000_0000]),
+            ))
+            .child(
+                DeviceTreeNode::builder("serial@1000")
+                    .property(DeviceTreeProperty::new(
+                        "compatible",
+                        b"ns16550a\0ns16550\0",
+                    ))
+                    .property(DeviceTreeProperty::new("reg", cells(&[0x1000, 0x100])))
+                    .build(),
+            )
+            .child(
+                DeviceTreeNode::builder("gpio@2000")
+                    .property(DeviceTreeProperty::new(
+                        "reg",
+                        cells(&[0x2000, 0x100, 0x2000_0000, 0x100]),
+                    ))
+                    .build(),
+            )
+            .child(
+                DeviceTreeNode::builder("i2c@3000")
+                    .property(DeviceTreeProperty::new("#address-cells", cells(&[1])))
+                    .property(DeviceTreeProperty::new("#size-cells", cells(&[0])))
+                    .property(DeviceTreeProperty::new("reg", cells(&[0x3000, 0x100])))
+                    .child(
+                        DeviceTreeNode::builder("eeprom@50")
+                            .property(DeviceTreeProperty::new("reg", cells(&[0x50])))
+                            .build(),
+                    )
+                    .build(),
+            )
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("memory@40000000")
+            .property(DeviceTreeProperty::new(
+                "reg",
+                cells(&[0, 0x4000_0000, 0x1000_0000]),
+            ))
+            .build(),
+    );
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+
+    // The second region of the GPIO controller is outside of the soc ranges.
+    let map: Vec<_> = fdt
+        .address_map()
+        .unwrap()
+        .map(|entry| {
+            let entry = entry.unwrap();
+            (
+                entry.node.path().unwrap().to_string(),
+                entry.compatible,
+                entry.start,
+                entry.size,
+            )
+        })
+        .collect();
+    assert_eq!(
+        map,
+        [
+            (
+                "/soc/serial@1000".to_string(),
+                Some("ns16550a"),
+                0x9000_1000,
+                0x100
+            ),
+            ("/soc/gpio@2000".to_string(), None, 0x9000_2000, 0x100),
+            ("/soc/i2c@3000".to_string(), None, 0x9000_3000, 0x100),
+            (
+                "/memory@40000000".to_string(),
+                None,
+                0x4000_0000,
+                0x1000_0000
+            ),
+        ]
+    );
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn translatable_buses() {
+    use dtoolkit::error::FdtError;
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+
+    let cells =
+        |cells: &[u32]| -> Vec<u8> { cells.iter().flat_map(|cell| cell.to_be_bytes()).collect() };
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("soc")
+            .property(DeviceTreeProperty::new("compatible", b"simple-bus\0"))
+            .property(DeviceTreeProperty::new("#address-cells", cells(&[1])))
+            .property(DeviceTreeProperty::new("#size-cells", cells(&[1])))
+            .property(DeviceTreeProperty::new("ranges", []))
+            .child(
+                DeviceTreeNode::builder("spi@1000")
+                    .property(DeviceTreeProperty::new("#address-cells", cells(&[1])))
+                    .property(DeviceTreeProperty::new("#size-cells", cells(&[0])))
+                    .property(DeviceTreeProperty::new("reg", cells(&[0x1000, 0x100])))
+                    .child(DeviceTreeNode::builder("flash@0").build())
+                    .build(),
+            )
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("pcie@10000000")
+            .property(DeviceTreeProperty::new("#address-cells", cells(&[3])))
+            .property(DeviceTreeProperty::new("#size-cells", cells(&[2])))
+            .property(DeviceTreeProperty::new(
+                "ranges",
+                cells(&[0x0200_0000, 0, 0x4000_0000, 0, 0x4000_0000, 0, 0x1000_0000]),
+            ))
+            .child(
+                DeviceTreeNode::builder("ethernet@0,0")
+                    .property(DeviceTreeProperty::new("reg", cells(&[0, 0, 0, 0, 0])))
+                    .build(),
+            )
+            .build(),
+    );
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+    let node = |path| fdt.find_node(path).unwrap().unwrap();
+
+    assert!(fdt.root().unwrap().is_translatable().unwrap());
+    assert!(node("/soc").is_translatable().unwrap());
+    assert!(!node("/soc/spi").is_translatable().unwrap());
+    assert!(!node("/pcie").is_translatable().unwrap());
+
+    assert_eq!(
+        node("/soc/spi").translate_address(0x1000).unwrap(),
+        Some(0x1000)
+    );
+    assert!(matches!(
+        node("/soc/spi/flash").translate_address(0),
+        Err(FdtError::NotTranslatable { .. })
+    ));
+    assert!(matches!(
+        node("/pcie/ethernet").translate_address(0x4000_0000),
+        Err(FdtError::NotTranslatable { .. })
+    ));
+    assert!(fdt.find_node_by_address(0x4000_0000).unwrap().is_none());
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn check_unit_address() {
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+    use dtoolkit::standard::UnitAddressFinding;
+
+    let cells =
+        |cells: &[u32]| -> Vec<u8> { cells.iter().flat_map(|cell| cell.to_be_bytes()).collect() };
+    let mut soc = DeviceTreeNode::builder("soc")
+        .property(DeviceTreeProperty::new("#address-cells", cells(&[1])))
+        .property(DeviceTreeProperty::new("#size-cells", cells(&[1])))
+        .property(DeviceTreeProperty::new(
+            "ranges",
+            cells(&[0, 0, 0x9000_0000, 0x1000_0000]),
+        ));
+    for (name, reg) in [
+        ("serial@1000", Some(0x1000)),
+        ("serial@2000", Some(0x2001)),
+        ("serial@03000", Some(0x3000)),
+        ("serial@0x4000", Some(0x4000)),
+        ("serial", Some(0x5000)),
+        ("serial@6000", None),
+        ("pci@1,0", Some(0x7000)),
+    ] {
+        let mut child = DeviceTreeNode::builder(name);
+        if let Some(reg) = reg {
+            child = child.property(DeviceTreeProperty::new("reg", cells(&[reg, 0x100])));
+        }
+        soc = soc.child(child.build());
+    }
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(soc.build());
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+
+    let findings: Vec<_> = fdt
+        .find_node("/soc")
+        .unwrap()
+        .unwrap()
+        .children()
+        .filter_map(|child| child.unwrap().check_unit_address().unwrap())
+        .map(|finding| (finding.node().name().unwrap(), finding.to_string()))
+        .collect();
+    assert_eq!(
+        findings,
+        [
+            (
+                "serial@2000",
+                "unit name doesn't match reg, expected \"2001\"".to_string()
+            ),
+            (
+                "serial@03000",
+                "unit name doesn't match reg, expected \"3000\"".to_string()
+            ),
+            (
+                "serial@0x4000",
+                "unit name doesn't match reg, expected \"4000\"".to_string()
+            ),
+            (
+                "serial",
+                "node has a reg or ranges property, but no unit name".to_string()
+            ),
+            (
+                "serial@6000",
+                "node has a unit name, but no reg or ranges property".to_string()
+            ),
+        ]
+    );
+    let soc = fdt.find_node("/soc").unwrap().unwrap();
+    assert!(matches!(
+        soc.check_unit_address().unwrap(),
+        Some(UnitAddressFinding::MissingUnitAddress { .. })
+    ));
+    assert!(fdt.root().unwrap().check_unit_address().unwrap().is_none());
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn cpu_properties() {
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+    use dtoolkit::standard::Status;
+
+    let cells =
+        |cells: &[u32]| -> Vec<u8> { cells.iter().flat_map(|cell| cell.to_be_bytes()).collect() };
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("cpus")
+            .property(DeviceTreeProperty::new("#address-cells", cells(&[1])))
+            .property(DeviceTreeProperty::new("#size-cells", cells(&[0])))
+            .property(DeviceTreeProperty::new(
+                "timebase-frequency",
+                cells(&[25_000_000]),
+            ))
+            .child(
+                DeviceTreeNode::builder("cpu@0")
+                    .property(DeviceTreeProperty::new("reg", cells(&[0])))
+                    .property(DeviceTreeProperty::new("compatible", b"arm,cortex-a53\0"))
+                    .property(DeviceTreeProperty::new("clock-frequency", cells(&[1, 0])))
+                    .property(DeviceTreeProperty::new("enable-method", b"spin-table\0"))
+                    .property(DeviceTreeProperty::new(
+                        "cpu-release-addr",
+                        cells(&[0, 0x8000_fff8]),
+                    ))
+                    .build(),
+            )
+            .child(
+                DeviceTreeNode::builder("cpu@1")
+                    .property(DeviceTreeProperty::new("reg", cells(&[1])))
+                    .property(DeviceTreeProperty::new("status", b"disabled\0"))
+                    .property(DeviceTreeProperty::new(
+                        "timebase-frequency",
+                        cells(&[0, 19_200_000]),
+                    ))
+                    .property(DeviceTreeProperty::new("enable-method", b"psci\0"))
+                    .property(DeviceTreeProperty::new("cpu-release-addr", cells(&[0])))
+                    .build(),
+            )
+            .build(),
+    );
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+    let cpus: Vec<_> = fdt
+        .cpus()
+        .unwrap()
+        .cpus()
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    assert_eq!(cpus[0].clock_frequency().unwrap(), Some(0x1_0000_0000));
+    assert_eq!(cpus[0].timebase_frequency().unwrap(), Some(25_000_000));
+    assert!(cpus[0].enable_method().unwrap().unwrap().eq(["spin-table"]));
+    assert_eq!(cpus[0].cpu_release_addr().unwrap(), Some(0x8000_fff8));
+    assert!(cpus[0].is_compatible("arm,cortex-a53").unwrap());
+    assert_eq!(cpus[0].status().unwrap(), Status::Okay);
+
+    assert_eq!(cpus[1].clock_frequency().unwrap(), None);
+    assert_eq!(cpus[1].timebase_frequency().unwrap(), Some(19_200_000));
+    assert!(cpus[1].enable_method().unwrap().unwrap().eq(["psci"]));
+    assert!(cpus[1].cpu_release_addr().is_err());
+    assert_eq!(cpus[1].status().unwrap(), Status::Disabled);
+
+    // The same helpers work on the model.
+    let cpus: Vec<_> = tree
+        .cpus()
+        .unwrap()
+        .cpus()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(cpus.len(), 2);
+    let ids = |cpu: &dtoolkit::model::Cpu| -> Vec<u32> {
+        cpu.ids()
+            .unwrap()
+            .unwrap()
+            .map(|id| id.to_int().unwrap())
+            .collect()
+    };
+    assert_eq!(ids(&cpus[0]), [0]);
+    assert_eq!(cpus[0].timebase_frequency().unwrap(), Some(25_000_000));
+    assert!(cpus[0].enable_method().unwrap().unwrap().eq(["spin-table"]));
+    assert_eq!(cpus[0].cpu_release_addr().unwrap(), Some(0x8000_fff8));
+    assert_eq!(ids(&cpus[1]), [1]);
+    assert_eq!(cpus[1].timebase_frequency().unwrap(), Some(19_200_000));
+    assert!(cpus[1].enable_method().unwrap().unwrap().eq(["psci"]));
+    assert!(cpus[1].cpu_release_addr().is_err());
+    assert!(DeviceTree::new().cpus().is_none());
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn riscv_cpu_properties() {
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+
+    let cells =
+        |cells: &[u32]| -> Vec<u8> { cells.iter().flat_map(|cell| cell.to_be_bytes()).collect() };
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("cpus")
+            .property(DeviceTreeProperty::new("#address-cells", cells(&[1])))
+            .property(DeviceTreeProperty::new("#size-cells", cells(&[0])))
+            .child(
+                DeviceTreeNode::builder("cpu@0")
+                    .property(DeviceTreeProperty::new("reg", cells(&[0])))
+                    .property(DeviceTreeProperty::new("riscv,isa", b"rv64imac_zicsr\0"))
+                    .property(DeviceTreeProperty::new(
+                        "riscv,isa-extensions",
+                        b"i\0m\0a\0c\0zicsr\0",
+                    ))
+                    .property(DeviceTreeProperty::new("mmu-type", b"riscv,sv39\0"))
+                    .child(
+                        DeviceTreeNode::builder("interrupt-controller")
+                            .property(DeviceTreeProperty::new("phandle", cells(&[1])))
+                            .property(DeviceTreeProperty::new("interrupt-controller", []))
+                            .property(DeviceTreeProperty::new("compatible", b"riscv,cpu-intc\0"))
+                            .build(),
+                    )
+                    .build(),
+            )
+            .child(
+                DeviceTreeNode::builder("cpu@1")
+                    .property(DeviceTreeProperty::new("reg", cells(&[1])))
+                    .build(),
+            )
+            .build(),
+    );
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+    let cpus: Vec<_> = fdt
+        .cpus()
+        .unwrap()
+        .cpus()
+        .collect::<Result<_, _>>()
+        .unwrap();
+
+    assert_eq!(cpus[0].riscv_isa().unwrap(), Some("rv64imac_zicsr"));
+    assert!(
+        cpus[0]
+            .riscv_isa_extensions()
+            .unwrap()
+            .unwrap()
+            .eq(["i", "m", "a", "c", "zicsr"])
+    );
+    assert!(cpus[0].has_riscv_isa_extension("zicsr").unwrap());
+    assert!(!cpus[0].has_riscv_isa_extension("f").unwrap());
+    assert_eq!(cpus[0].mmu_type().unwrap(), Some("riscv,sv39"));
+    let intc = cpus[0].hart_interrupt_controller().unwrap().unwrap();
+    assert!(intc.is_compatible("riscv,cpu-intc").unwrap());
+    assert_eq!(intc.phandle().unwrap().map(Phandle::get), Some(1));
+
+    assert_eq!(cpus[1].riscv_isa().unwrap(), None);
+    assert!(cpus[1].riscv_isa_extensions().unwrap().is_none());
+    assert!(!cpus[1].has_riscv_isa_extension("i").unwrap());
+    assert_eq!(cpus[1].mmu_type().unwrap(), None);
+    assert!(cpus[1].hart_interrupt_controller().unwrap().is_none());
+
+    // The same helpers work on the model.
+    let cpus: Vec<_> = tree
+        .cpus()
+        .unwrap()
+        .cpus()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(cpus[0].riscv_isa(), Ok(Some("rv64imac_zicsr")));
+    assert!(
+        cpus[0]
+            .riscv_isa_extensions()
+            .unwrap()
+            .unwrap()
+            .eq(["i", "m", "a", "c", "zicsr"])
+    );
+    assert_eq!(cpus[0].has_riscv_isa_extension("zicsr"), Ok(true));
+    assert_eq!(cpus[0].mmu_type(), Ok(Some("riscv,sv39")));
+    let intc = cpus[0].hart_interrupt_controller().unwrap().unwrap();
+    assert_eq!(intc.name(), "interrupt-controller");
+    assert_eq!(cpus[1].riscv_isa(), Ok(None));
+    assert_eq!(cpus[1].has_riscv_isa_extension("i"), Ok(false));
+    assert_eq!(cpus[1].mmu_type(), Ok(None));
+    assert_eq!(cpus[1].hart_interrupt_controller(), Ok(None));
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn numa() {
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+    use dtoolkit::standard::NumaDistance;
+
+    let cells =
+        |cells: &[u32]| -> Vec<u8> { cells.iter().flat_map(|cell| cell.to_be_bytes()).collect() };
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("memory@80000000")
+            .property(DeviceTreeProperty::new("device_type", b"memory\0"))
+            .property(DeviceTreeProperty::new(
+                "reg",
+                cells(&[0, 0x8000_0000, 0x8000_0000]),
+            ))
+            .property(DeviceTreeProperty::new("numa-node-id", cells(&[1])))
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("distance-map")
+            .property(DeviceTreeProperty::new(
+                "compatible",
+                b"numa,distance-map-v1\0",
+            ))
+            .property(DeviceTreeProperty::new(
+                "distance-matrix",
+                cells(&[0, 0, 10, 0, 1, 20, 1, 0, 30, 0, 2, 40]),
+            ))
+            .build(),
+    );
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+
+    let memory = fdt.find_node("/memory@80000000").unwrap().unwrap();
+    assert_eq!(memory.numa_node_id().unwrap(), Some(1));
+    assert_eq!(fdt.root().unwrap().numa_node_id().unwrap(), None);
+
+    let map = fdt.distance_map().unwrap().unwrap();
+    assert_eq!(
+        map.entries().unwrap().nth(1),
+        Some(NumaDistance {
+            from: 0,
+            to: 1,
+            distance: 20
+        })
+    );
+    assert_eq!(map.distance(0, 1).unwrap(), Some(20));
+    assert_eq!(map.distance(1, 0).unwrap(), Some(30));
+    assert_eq!(map.distance(2, 0).unwrap(), Some(40));
+    assert_eq!(map.distance(1, 1).unwrap(), Some(10));
+    assert_eq!(map.distance(1, 2).unwrap(), None);
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn mac_address() {
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+
+    let mac = [0x02, 0x00, 0x00, 0x12, 0x34, 0x56];
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("ethernet@1000")
+            .property(DeviceTreeProperty::new("local-mac-address", mac))
+            .property(DeviceTreeProperty::new("address", [0xff; 6]))
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("ethernet@2000")
+            .property(DeviceTreeProperty::new("mac-address", [0; 6]))
+            .property(DeviceTreeProperty::new("address", mac))
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("ethernet@3000")
+            .property(DeviceTreeProperty::new("mac-address", [0x02, 0x00]))
+            .build(),
+    );
+    tree.root.add_child(DeviceTreeNode::new("ethernet@4000"));
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+    let node = |path| fdt.find_node(path).unwrap().unwrap();
+
+    assert_eq!(node("/ethernet@1000").mac_address().unwrap(), Some(mac));
+    assert_eq!(node("/ethernet@2000").mac_address().unwrap(), Some(mac));
+    assert!(node("/ethernet@3000").mac_address().is_err());
+    assert_eq!(node("/ethernet@4000").mac_address().unwrap(), None);
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn network_phys() {
+    use dtoolkit::error::FdtError;
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+    use dtoolkit::standard::{FixedLink, PhyMode};
+
+    let cells =
+        |cells: &[u32]| -> Vec<u8> { cells.iter().flat_map(|cell| cell.to_be_bytes()).collect() };
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("mdio")
+            .property(DeviceTreeProperty::new("#address-cells", cells(&[1])))
+            .property(DeviceTreeProperty::new("#size-cells", cells(&[0])))
+            .child(
+                DeviceTreeNode::builder("ethernet-phy@7")
+                    .property(DeviceTreeProperty::new("reg", cells(&[7])))
+                    .property(DeviceTreeProperty::new("phandle", cells(&[1])))
+                    .build(),
+            )
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("ethernet@1000")
+            .property(DeviceTreeProperty::new("phy-handle", cells(&[1])))
+            .property(DeviceTreeProperty::new("phy-mode", b"rgmii-id\0"))
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("ethernet@2000")
+            .property(DeviceTreeProperty::new("phy", cells(&[2])))
+            .property(DeviceTreeProperty::new("phy-connection-type", b"sgmii\0"))
+            .child(
+                DeviceTreeNode::builder("fixed-link")
+                    .property(DeviceTreeProperty::new("speed", cells(&[1000])))
+                    .property(DeviceTreeProperty::new("full-duplex", []))
+                    .build(),
+            )
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("ethernet@3000")
+            .property(DeviceTreeProperty::new("phy-mode", b"bogus\0"))
+            .property(DeviceTreeProperty::new(
+                "fixed-link",
+                cells(&[0, 1, 100, 0, 1]),
+            ))
+            .build(),
+    );
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+    let node = |path| fdt.find_node(path).unwrap().unwrap();
+
+    let phy = node("/ethernet@1000").phy().unwrap().unwrap();
+    assert_eq!(phy.node.name().unwrap(), "ethernet-phy@7");
+    assert_eq!(phy.address, Some(7));
+    assert_eq!(
+        node("/ethernet@1000").phy_mode().unwrap(),
+        Some(PhyMode::RgmiiId)
+    );
+    assert!(PhyMode::RgmiiId.is_rgmii());
+    assert_eq!(node("/ethernet@1000").fixed_link().unwrap(), None);
+
+    assert_eq!(
+        node("/ethernet@2000").phy().unwrap_err(),
+        FdtError::PhandleNotFound(2)
+    );
+    assert_eq!(
+        node("/ethernet@2000").phy_mode().unwrap(),
+        Some(PhyMode::Sgmii)
+    );
+    assert_eq!(
+        node("/ethernet@2000").fixed_link().unwrap(),
+        Some(FixedLink {
+            speed: 1000,
+            full_duplex: true,
+            pause: false,
+            asym_pause: false,
+        })
+    );
+
+    assert!(node("/ethernet@3000").phy().unwrap().is_none());
+    assert_eq!(
+        node("/ethernet@3000").phy_mode(),
+        Err(FdtError::InvalidPhyMode)
+    );
+    assert_eq!(
+        node("/ethernet@3000").fixed_link().unwrap(),
+        Some(FixedLink {
+            speed: 100,
+            full_duplex: true,
+            pause: false,
+            asym_pause: true,
+        })
+    );
+    assert_eq!("10g-qxgmii".parse(), Ok(PhyMode::Qxgmii10G));
+    assert_eq!(PhyMode::Base1000X.to_string(), "1000base-x");
+}
+
+#[cfg(feature = "write")]
+#[test]
+fn usable_memory() {
+    use dtoolkit::memreserve::MemoryReservation;
+    use dtoolkit::model::{DeviceTreeNode, DeviceTreeProperty};
+
+    let cells =
+        |cells: &[u32]| -> Vec<u8> { cells.iter().flat_map(|cell| cell.to_be_bytes()).collect() };
+    let memory = |name: &str, reg: &[u32]| {
+        DeviceTreeNode::builder(name)
+            .property(DeviceTreeProperty::new("device_type", b"memory\0"))
+            .property(DeviceTreeProperty::new("reg", cells(reg)))
+    };
+    let mut tree = DeviceTree::new();
+    tree.memory_reservations
+        .push(MemoryReservation::new(0x8000_0000, 0x1_0000));
+    tree.root
+        .add_child(memory("memory@80000000", &[0, 0x8000_0000, 0x1000_0000]).build());
+    tree.root
+        .add_child(memory("memory@90000000", &[0, 0x9000_0000, 0x1000_0000]).build());
+    tree.root.add_child(
+        memory("memory@c0000000", &[0, 0xc000_0000, 0x1000_0000])
+            .property(DeviceTreeProperty::new("status", b"disabled\0"))
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("reserved-memory")
+            .property(DeviceTreeProperty::new("#address-cells", cells(&[2])))
+            .property(DeviceTreeProperty::new("#size-cells", cells(&[1])))
+            .property(DeviceTreeProperty::new("ranges", []))
+            .child(
+                DeviceTreeNode::builder("secmon@8fff0000")
+                    .property(DeviceTreeProperty::new(
+                        "reg",
+                        cells(&[0, 0x8fff_0000, 0x2_0000]),
+                    ))
+                    .property(DeviceTreeProperty::new("no-map", []))
+                    .build(),
+            )
+            .child(
+                DeviceTreeNode::builder("framebuffer@98000000")
+                    .property(DeviceTreeProperty::new(
+                        "reg",
+                        cells(&[0, 0x9800_0000, 0x80_0000]),
+                    ))
+                    .build(),
+            )
+            .child(
+                DeviceTreeNode::builder("cma")
+                    .property(DeviceTreeProperty::new("size", cells(&[0x400_0000])))
+                    .property(DeviceTreeProperty::new("reusable", []))
+                    .build(),
+            )
+            .build(),
+    );
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+
+    assert_eq!(
+        fdt.usable_memory().unwrap(),
+        [
+            (0x8001_0000, 0xffe_0000),
+            (0x9001_0000, 0x7ff_0000),
+            (0x9880_0000, 0x780_0000),
+        ]
+    );
+}
@@ -6,8 +6,12 @@
 // option. This file may not be copied, modified, or distributed
 // except according to those terms.
 
+use dtoolkit::error::FdtErrorKind;
 use dtoolkit::fdt::Fdt;
-use dtoolkit::memreserve::MemoryReservation;
+use dtoolkit::memreserve::{
+    MemoryReservation, coalesce_reservations, overlapping_reservations, sort_reservations,
+};
+use zerocopy::IntoBytes;
 
 #[test]
 fn memreserve() {
@@ -28,3 +32,55 @@ fn memreserve() {
     assert!(dts.contains("/memreserve/ 0x1000 0x100;"));
     assert!(dts.contains("/memreserve/ 0x2000 0x200;"));
 }
+
+#[test]
+fn memreserve_not_terminated() {
+    let mut dtb = include_bytes!("dtb/test_memreserve.dtb").to_vec();
+    // Overwrite the terminator with a third reservation.
+    dtb[72..88].copy_from_slice(MemoryReservation::new(0x3000, 0x300).as_bytes());
+    let fdt = Fdt::new(&dtb).unwrap();
+
+    let reservations = fdt.memory_reservations();
+    assert_eq!(reservations.len(), 4);
+    let reservations: Vec<_> = reservations.collect();
+    assert_eq!(reservations[2], Ok(MemoryReservation::new(0x3000, 0x300)));
+    assert_eq!(
+        reservations[3].as_ref().unwrap_err().kind,
+        FdtErrorKind::MemReserveNotTerminated
+    );
+}
+
+#[test]
+fn reservation_hygiene() {
+    let mut reservations = vec![
+        MemoryReservation::new(0x9000, 0x1000),
+        MemoryReservation::new(0x1000, 0x2000),
+        MemoryReservation::new(0x2000, 0x100),
+        MemoryReservation::new(0x3000, 0x1000),
+        MemoryReservation::new(0x5000, 0),
+    ];
+    assert!(overlapping_reservations(&reservations).eq([(
+        MemoryReservation::new(0x1000, 0x2000),
+        MemoryReservation::new(0x2000, 0x100)
+    )]));
+
+    sort_reservations(&mut reservations);
+    assert_eq!(reservations[0], MemoryReservation::new(0x1000, 0x2000));
+    assert_eq!(reservations[4], MemoryReservation::new(0x9000, 0x1000));
+
+    let len = coalesce_reservations(&mut reservations);
+    reservations.truncate(len);
+    assert_eq!(
+        reservations,
+        [
+            MemoryReservation::new(0x1000, 0x3000),
+            MemoryReservation::new(0x9000, 0x1000)
+        ]
+    );
+    assert_eq!(overlapping_reservations(&reservations).count(), 0);
+
+    let memory = [(0x0, 0x2000), (0x8000, 0x1000)];
+    assert!(!reservations[0].is_outside(memory));
+    assert!(reservations[1].is_outside(memory));
+    assert!(MemoryReservation::new(u64::MAX - 1, 0x10).overlaps(u64::MAX - 1, 1));
+}
@@ -8,7 +8,10 @@
 
 #![cfg(feature = "write")]
 
-use dtoolkit::model::{DeviceTree, DeviceTreeNode, DeviceTreeProperty};
+use dtoolkit::error::{FdtError, FdtErrorKind};
+use dtoolkit::fdt::Fdt;
+use dtoolkit::model::{DeviceTree, DeviceTreeNode, DeviceTreeProperty, LazyDeviceTree, NodeMut};
+use dtoolkit::standard::{Phandle, Status};
 
 #[test]
 fn tree_creation() {
@@ -139,3 +142,338 @@ fn device_tree_format() {
 "#
     );
 }
+
+#[test]
+fn deep_nesting() {
+    const DEPTH: usize = 1000;
+
+    let mut node = DeviceTreeNode::new("leaf");
+    for _ in 1..DEPTH {
+        node = DeviceTreeNode::builder("node").child(node).build();
+    }
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(node);
+
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+    assert_eq!(DeviceTree::from_fdt(&fdt).unwrap(), tree);
+    assert_eq!(fdt.to_string().lines().count(), 2 * DEPTH + 4);
+
+    assert!(DeviceTree::from_fdt_with_max_depth(&fdt, DEPTH).is_ok());
+    assert_eq!(
+        DeviceTree::from_fdt_with_max_depth(&fdt, DEPTH - 1)
+            .unwrap_err()
+            .kind,
+        FdtErrorKind::TooDeep(DEPTH - 1)
+    );
+}
+
+#[test]
+fn round_trip_keeps_strings_block() {
+    let dtb = include_bytes!("dtb/test_strings.dtb");
+    let fdt = Fdt::new(dtb).unwrap();
+    let mut tree = DeviceTree::from_fdt(&fdt).unwrap();
+    assert_eq!(tree.to_dtb(), dtb);
+
+    tree.root
+        .add_property(DeviceTreeProperty::new("model", "new\0"));
+    let new_dtb = tree.to_dtb();
+    assert!(new_dtb.ends_with(b"compatible\0linux,phandle\0model\0"));
+    assert_eq!(
+        DeviceTree::from_fdt(&Fdt::new(&new_dtb).unwrap()).unwrap(),
+        tree
+    );
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn dtb_files() {
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/dtb/test_children.dtb");
+    let tree = DeviceTree::from_dtb_file(path).unwrap();
+    assert!(tree.root.child("child2@42").is_some());
+
+    let mut dtb = Vec::new();
+    tree.write_dtb_to(&mut dtb).unwrap();
+    assert_eq!(dtb, include_bytes!("dtb/test_children.dtb"));
+
+    let error = DeviceTree::from_dtb_file("missing.dtb").unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_round_trip() {
+    use dtoolkit::memreserve::MemoryReservation;
+
+    for dtb in [
+        &include_bytes!("dtb/test.dtb")[..],
+        include_bytes!("dtb/test_children_nested.dtb"),
+        include_bytes!("dtb/test_memreserve.dtb"),
+        include_bytes!("dtb/test_pretty_print.dtb"),
+        include_bytes!("dtb/test_props.dtb"),
+        include_bytes!("dtb/test_strings.dtb"),
+    ] {
+        let tree = DeviceTree::from_fdt(&Fdt::new(dtb).unwrap()).unwrap();
+        let json = serde_json::to_string(&tree).unwrap();
+        let parsed: DeviceTree = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, tree, "{json}");
+        assert!(parsed.semantically_equals(&Fdt::new(dtb).unwrap()).unwrap());
+    }
+
+    let mut tree = DeviceTree::new();
+    tree.memory_reservations
+        .push(MemoryReservation::new(0x1000, 0x2000));
+    let mut node = DeviceTreeNode::builder("serial@1000")
+        .property(DeviceTreeProperty::new(
+            "compatible",
+            "ns16550a\0vendor,uart\0",
+        ))
+        .property(DeviceTreeProperty::new("status", "okay\0"))
+        .property(DeviceTreeProperty::new("reg", [0, 0, 0x10, 0, 0, 0, 1, 0]))
+        .property(DeviceTreeProperty::new("mac-address", [2, 0, 0, 0, 0, 1]))
+        .property(DeviceTreeProperty::new("dma-coherent", []))
+        .build();
+    node.add_label("uart0");
+    tree.root.add_child(node);
+    let json = serde_json::json!({
+        "memory_reservations": [{ "address": 0x1000, "size": 0x2000 }],
+        "root": {
+            "name": "/",
+            "children": [{
+                "name": "serial@1000",
+                "labels": ["uart0"],
+                "properties": [
+                    { "name": "compatible", "value": { "strings": ["ns16550a", "vendor,uart"] } },
+                    { "name": "status", "value": { "string": "okay" } },
+                    { "name": "reg", "value": { "cells": [0x1000, 0x100] } },
+                    { "name": "mac-address", "value": { "bytes": [2, 0, 0, 0, 0, 1] } },
+                    { "name": "dma-coherent" },
+                ],
+            }],
+        },
+    });
+    assert_eq!(serde_json::to_value(&tree).unwrap(), json);
+    assert_eq!(serde_json::from_value::<DeviceTree>(json).unwrap(), tree);
+    assert!(serde_json::from_str::<DeviceTree>(r#"{"root": {"name": "/", "other": 1}}"#).is_err());
+
+    let error = serde_json::from_str::<DeviceTree>(
+        r#"{"root": {"name": "/", "properties": [{"name": "model"}, {"name": "model"}]}}"#,
+    )
+    .unwrap_err();
+    assert!(
+        error.to_string().contains("duplicate property `model`"),
+        "{error}"
+    );
+    let error = serde_json::from_str::<DeviceTree>(
+        r#"{"root": {"name": "/", "children": [{"name": "cpus"}, {"name": "cpus"}]}}"#,
+    )
+    .unwrap_err();
+    assert!(
+        error.to_string().contains("duplicate child `cpus`"),
+        "{error}"
+    );
+}
+
+#[test]
+fn semantic_equality() {
+    let dtb = include_bytes!("dtb/test_props.dtb");
+    let fdt = Fdt::new(dtb).unwrap();
+    let mut tree = DeviceTree::from_fdt(&fdt).unwrap();
+
+    // Moving a property to the end of its node changes the encoding but not
+    // the contents.
+    let node = tree.find_node_mut("/test-props").unwrap();
+    let property = node.remove_property("u32-prop").unwrap();
+    node.add_property(property);
+    let reordered = tree.to_dtb();
+    assert_ne!(reordered, dtb);
+    let reordered = Fdt::new(&reordered).unwrap();
+    assert!(fdt.semantically_equals(reordered).unwrap());
+    assert!(reordered.semantically_equals(fdt).unwrap());
+    assert!(tree.semantically_equals(&fdt).unwrap());
+
+    // So does moving a node to the end of its parent, as children are matched
+    // by name like `compare` and `==` do.
+    let child = tree.root.remove_child("test-props").unwrap();
+    tree.root.add_child(child);
+    let reordered = tree.to_dtb();
+    let reordered = Fdt::new(&reordered).unwrap();
+    assert_eq!(
+        reordered
+            .root()
+            .unwrap()
+            .children()
+            .last()
+            .unwrap()
+            .unwrap()
+            .name(),
+        Ok("test-props")
+    );
+    assert_eq!(dtoolkit::compare(&fdt, &reordered).count(), 0);
+    assert!(fdt.semantically_equals(reordered).unwrap());
+    assert!(tree.semantically_equals(&fdt).unwrap());
+    assert_eq!(tree, DeviceTree::from_fdt(&fdt).unwrap());
+
+    tree.find_node_mut("/test-props")
+        .unwrap()
+        .property_mut("u32-prop")
+        .unwrap()
+        .set_value(2u32.to_be_bytes());
+    let modified = tree.to_dtb();
+    assert!(
+        !fdt.semantically_equals(Fdt::new(&modified).unwrap())
+            .unwrap()
+    );
+    assert!(!tree.semantically_equals(&fdt).unwrap());
+
+    tree.memory_reservations
+        .push(dtoolkit::memreserve::MemoryReservation::new(0x1000, 0x1000));
+    assert!(
+        !tree
+            .semantically_equals(&Fdt::new(&modified).unwrap())
+            .unwrap()
+    );
+}
+
+#[test]
+fn node_mut_setters() {
+    fn fixup<N: NodeMut>(node: &mut N) -> Result<(), N::Error> {
+        node.set_compatible(&["vendor,board", "vendor,soc"])?;
+        node.set_model("Board")?;
+        node.set_status(Status::Disabled)?;
+        node.set_mac_address([0x02, 0, 0, 0x12, 0x34, 0x56])?;
+        node.set_phandle(Phandle::new(0x10).unwrap())
+    }
+
+    let dtb = include_bytes!("dtb/test_props.dtb");
+    let fdt = Fdt::new(dtb).unwrap();
+    let mut tree = DeviceTree::from_fdt(&fdt).unwrap();
+    fixup(&mut tree.root).unwrap();
+    let mut lazy = LazyDeviceTree::from_fdt(&fdt).unwrap();
+    fixup(lazy.root()).unwrap();
+    assert_eq!(lazy.into_device_tree().unwrap(), tree);
+
+    let dtb = tree.to_dtb();
+    let root = Fdt::new(&dtb).unwrap().root().unwrap();
+    assert!(
+        root.compatible()
+            .unwrap()
+            .unwrap()
+            .eq(["vendor,board", "vendor,soc"])
+    );
+    assert_eq!(root.required_str("model").unwrap(), "Board");
+    assert_eq!(root.status().unwrap(), Status::Disabled);
+    assert_eq!(root.phandle().unwrap(), Phandle::new(0x10));
+    assert_eq!(
+        root.mac_address().unwrap(),
+        Some([0x02, 0, 0, 0x12, 0x34, 0x56])
+    );
+}
+
+#[test]
+fn canonical_node_names() {
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(DeviceTreeNode::new("reserved-memory"));
+    let reserved_memory = tree.root.child_mut("reserved-memory").unwrap();
+    reserved_memory
+        .add_child_with_reg("framebuffer", &[(0xa000_0000, 0x80_0000)])
+        .unwrap();
+    reserved_memory.add_child(DeviceTreeNode::new("last"));
+
+    let framebuffer = reserved_memory
+        .set_child_reg("framebuffer@a0000000", &[(0x1_0000_0000, 0x80_0000)])
+        .unwrap()
+        .unwrap();
+    assert_eq!(framebuffer.name(), "framebuffer@100000000");
+    assert!(
+        reserved_memory
+            .children()
+            .map(DeviceTreeNode::name)
+            .eq(["framebuffer@100000000", "last"])
+    );
+    assert!(
+        reserved_memory
+            .set_child_reg("missing", &[])
+            .unwrap()
+            .is_none()
+    );
+    reserved_memory.add_child(DeviceTreeNode::new("framebuffer@2000"));
+    assert_eq!(
+        reserved_memory
+            .set_child_reg("framebuffer@100000000", &[(0x2000, 0x1000)])
+            .unwrap_err(),
+        FdtError::DuplicateChild
+    );
+    assert!(reserved_memory.children().map(DeviceTreeNode::name).eq([
+        "framebuffer@100000000",
+        "last",
+        "framebuffer@2000"
+    ]));
+    reserved_memory.remove_child("framebuffer@2000");
+
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+    let framebuffer = fdt
+        .find_node("/reserved-memory/framebuffer")
+        .unwrap()
+        .unwrap();
+    let reg = framebuffer.reg().unwrap().unwrap().next().unwrap();
+    assert_eq!(reg.address::<u64>().unwrap(), 0x1_0000_0000);
+    assert_eq!(
+        framebuffer.unit_address().unwrap(),
+        Some(reg.unit_address().to_string().as_str())
+    );
+}
+
+#[test]
+fn symbols_follow_nodes() {
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("soc")
+            .child(DeviceTreeNode::new("serial@1000"))
+            .child(DeviceTreeNode::new("i2c@2000"))
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("__symbols__")
+            .property(DeviceTreeProperty::new("uart0", "/soc/serial@1000\0"))
+            .property(DeviceTreeProperty::new("i2c0", "/soc/i2c@2000\0"))
+            .property(DeviceTreeProperty::new("soc", "/soc\0"))
+            .build(),
+    );
+    let dtb = tree.to_dtb();
+    let mut tree = DeviceTree::from_fdt(&Fdt::new(&dtb).unwrap()).unwrap();
+    assert!(tree.to_dtb() == dtb);
+
+    let soc = tree.root.child_mut("soc").unwrap();
+    assert!(soc.labels().eq(["soc"]));
+    soc.set_child_reg("serial@1000", &[(0x3000, 0x100)])
+        .unwrap();
+    let i2c = soc.remove_child("i2c@2000").unwrap();
+    assert!(i2c.labels().eq(["i2c0"]));
+    tree.root.add_child(i2c);
+    assert!(tree.root.child_mut("soc").unwrap().remove_label("soc"));
+
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+    let symbols = fdt.find_node("/__symbols__").unwrap().unwrap();
+    assert_eq!(symbols.required_str("uart0").unwrap(), "/soc/serial@3000");
+    assert_eq!(symbols.required_str("i2c0").unwrap(), "/i2c@2000");
+    assert!(symbols.property("soc").unwrap().is_none());
+
+    let mut relabelled = tree.clone();
+    relabelled.root.child_mut("soc").unwrap().add_label("soc");
+    relabelled.update_symbols();
+    let symbols = relabelled.root.child("__symbols__").unwrap();
+    assert_eq!(symbols.property("soc").unwrap().as_str(), Ok("/soc"));
+
+    tree.root.remove_child("soc");
+    tree.update_symbols();
+    let symbols = tree.root.child("__symbols__").unwrap();
+    assert!(
+        symbols
+            .properties()
+            .map(DeviceTreeProperty::name)
+            .eq(["i2c0"])
+    );
+}
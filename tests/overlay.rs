@@ -0,0 +1,557 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(feature = "write")]
+
+use dtoolkit::error::FdtError;
+use dtoolkit::fdt::{self, Fdt};
+use dtoolkit::model::{
+    DeviceTree, DeviceTreeNode, DeviceTreeProperty, Overlay, OverlayError, OverlayTarget,
+};
+
+fn u32_prop(name: &str, value: u32) -> DeviceTreeProperty {
+    DeviceTreeProperty::new(name, value.to_be_bytes())
+}
+
+fn str_prop(name: &str, value: &str) -> DeviceTreeProperty {
+    DeviceTreeProperty::new(name, format!("{value}\0"))
+}
+
+/// Returns a base tree with a UART labelled `uart`.
+fn base_tree() -> DeviceTree {
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("soc")
+            .property(u32_prop("phandle", 1))
+            .child(
+                DeviceTreeNode::builder("uart@1000")
+                    .property(u32_prop("phandle", 2))
+                    .property(str_prop("status", "disabled"))
+                    .build(),
+            )
+            .build(),
+    );
+    tree.root.add_child(
+        DeviceTreeNode::builder("__symbols__")
+            .property(str_prop("uart", "/soc/uart@1000"))
+            .build(),
+    );
+    tree
+}
+
+/// Returns an overlay as compiled by `dtc -@` from:
+///
+/// ```dts
+/// /dts-v1/;
+/// /plugin/;
+///
+/// &uart {
+///     status = "okay";
+///     dev: dev { };
+/// };
+///
+/// / {
+///     consumer { dev = <&dev>; };
+/// };
+/// ```
+fn overlay_dtbo() -> Vec<u8> {
+    let mut overlay = DeviceTree::new();
+    overlay.root.add_child(
+        DeviceTreeNode::builder("fragment@0")
+            .property(u32_prop("target", 0xffff_ffff))
+            .child(
+                DeviceTreeNode::builder("__overlay__")
+                    .property(str_prop("status", "okay"))
+                    .child(
+                        DeviceTreeNode::builder("dev")
+                            .property(u32_prop("phandle", 1))
+                            .build(),
+                    )
+                    .build(),
+            )
+            .build(),
+    );
+    overlay.root.add_child(
+        DeviceTreeNode::builder("fragment@1")
+            .property(str_prop("target-path", "/"))
+            .child(
+                DeviceTreeNode::builder("__overlay__")
+                    .child(
+                        DeviceTreeNode::builder("consumer")
+                            .property(u32_prop("dev", 1))
+                            .build(),
+                    )
+                    .build(),
+            )
+            .build(),
+    );
+    overlay.root.add_child(
+        DeviceTreeNode::builder("__symbols__")
+            .property(str_prop("dev", "/fragment@0/__overlay__/dev"))
+            .build(),
+    );
+    overlay.root.add_child(
+        DeviceTreeNode::builder("__fixups__")
+            .property(str_prop("uart", "/fragment@0:target:0"))
+            .build(),
+    );
+    overlay.root.add_child(
+        DeviceTreeNode::builder("__local_fixups__")
+            .child(
+                DeviceTreeNode::builder("fragment@1")
+                    .child(
+                        DeviceTreeNode::builder("__overlay__")
+                            .child(
+                                DeviceTreeNode::builder("consumer")
+                                    .property(u32_prop("dev", 0))
+                                    .build(),
+                            )
+                            .build(),
+                    )
+                    .build(),
+            )
+            .build(),
+    );
+    overlay.to_dtb()
+}
+
+#[test]
+fn apply_overlay() {
+    let mut tree = base_tree();
+    let dtbo = overlay_dtbo();
+    tree.apply_overlay(&Fdt::new(&dtbo).unwrap()).unwrap();
+
+    let uart = tree.find_node_mut("/soc/uart@1000").unwrap();
+    assert_eq!(uart.property("status").unwrap().as_str(), Ok("okay"));
+    let dev = uart.child("dev").unwrap();
+    assert_eq!(dev.property("phandle").unwrap().as_u32(), Ok(3));
+
+    let consumer = tree.find_node_mut("/consumer").unwrap();
+    assert_eq!(consumer.property("dev").unwrap().as_u32(), Ok(3));
+
+    let symbols = tree.root.child("__symbols__").unwrap();
+    assert_eq!(
+        symbols.property("dev").unwrap().as_str(),
+        Ok("/soc/uart@1000/dev")
+    );
+    assert_eq!(
+        symbols.property("uart").unwrap().as_str(),
+        Ok("/soc/uart@1000")
+    );
+    assert!(tree.root.child("fragment@0").is_none());
+}
+
+#[test]
+fn apply_overlay_errors() {
+    let dtbo = overlay_dtbo();
+    let overlay = Fdt::new(&dtbo).unwrap();
+
+    let mut tree = base_tree();
+    tree.root.remove_child("__symbols__");
+    let unchanged = tree.clone();
+    assert_eq!(
+        tree.apply_overlay(&overlay),
+        Err(OverlayError::UnresolvedSymbol {
+            label: "uart".into()
+        })
+    );
+    assert_eq!(tree, unchanged);
+
+    let mut tree = base_tree();
+    tree.root
+        .child_mut("soc")
+        .unwrap()
+        .remove_child("uart@1000");
+    let unchanged = tree.clone();
+    let error = tree.apply_overlay(&overlay).unwrap_err();
+    assert_eq!(error.to_string(), "symbol uart not found");
+    assert_eq!(error.code(), 302);
+    assert_eq!(tree, unchanged);
+
+    let mut missing_target = DeviceTree::new();
+    missing_target.root.add_child(
+        DeviceTreeNode::builder("fragment@0")
+            .property(str_prop("target-path", "/missing"))
+            .child(DeviceTreeNode::new("__overlay__"))
+            .build(),
+    );
+    let dtbo = missing_target.to_dtb();
+    let mut tree = base_tree();
+    assert_eq!(
+        tree.apply_overlay(&Fdt::new(&dtbo).unwrap()),
+        Err(OverlayError::TargetNotFound {
+            fragment: "fragment@0".into()
+        })
+    );
+
+    // A local reference renumbered to the reserved phandle 0xffffffff.
+    let mut overflowing = DeviceTree::new();
+    overflowing.root.add_child(
+        DeviceTreeNode::builder("fragment@0")
+            .property(str_prop("target-path", "/"))
+            .child(
+                DeviceTreeNode::builder("__overlay__")
+                    .property(u32_prop("dev", 1))
+                    .build(),
+            )
+            .build(),
+    );
+    overflowing.root.add_child(
+        DeviceTreeNode::builder("__local_fixups__")
+            .child(
+                DeviceTreeNode::builder("fragment@0")
+                    .child(
+                        DeviceTreeNode::builder("__overlay__")
+                            .property(u32_prop("dev", 0))
+                            .build(),
+                    )
+                    .build(),
+            )
+            .build(),
+    );
+    let dtbo = overflowing.to_dtb();
+    let mut tree = base_tree();
+    tree.find_node_mut("/soc")
+        .unwrap()
+        .add_property(u32_prop("phandle", 0xffff_fffe));
+    assert_eq!(
+        tree.apply_overlay(&Fdt::new(&dtbo).unwrap()),
+        Err(OverlayError::PhandleOverflow)
+    );
+}
+
+#[test]
+fn apply_overlay_in_place() {
+    let dtbo = overlay_dtbo();
+    let mut tree = base_tree();
+    tree.apply_overlay(&Fdt::new(&dtbo).unwrap()).unwrap();
+
+    let dtb = base_tree().to_dtb();
+    let mut buffer = vec![0; dtb.len() + 256];
+    buffer[..dtb.len()].copy_from_slice(&dtb);
+    let size = fdt::apply_overlay(&mut buffer, &dtbo).unwrap();
+    let fdt = Fdt::new(&buffer[..size]).unwrap();
+    assert!(tree.semantically_equals(&fdt).unwrap());
+
+    let mut buffer = dtb.clone();
+    assert!(matches!(
+        fdt::apply_overlay(&mut buffer, &dtbo),
+        Err(FdtError::BufferTooSmall { .. })
+    ));
+
+    let mut base = base_tree();
+    base.root.remove_child("__symbols__");
+    let mut buffer = base.to_dtb();
+    buffer.resize(buffer.len() + 256, 0);
+    let unchanged = buffer.clone();
+    assert_eq!(
+        fdt::apply_overlay(&mut buffer, &dtbo),
+        Err(FdtError::InvalidOverlay("symbol not found"))
+    );
+    assert_eq!(buffer, unchanged);
+
+    // The strings block is moved up to the structure block when the base is
+    // edited, which mustn't happen before the labels are checked.
+    let dtb = base.to_dtb();
+    let header_field =
+        |offset: usize| u32::from_be_bytes(dtb[offset..offset + 4].try_into().unwrap());
+    let (totalsize, strings) = (header_field(4), header_field(12));
+    let mut buffer = dtb[..strings as usize].to_vec();
+    buffer.extend_from_slice(&[0; 8]);
+    buffer.extend_from_slice(&dtb[strings as usize..]);
+    buffer[4..8].copy_from_slice(&(totalsize + 8).to_be_bytes());
+    buffer[12..16].copy_from_slice(&(strings + 8).to_be_bytes());
+    assert!(
+        Fdt::new(&buffer)
+            .unwrap()
+            .semantically_equals(Fdt::new(&dtb).unwrap())
+            .unwrap()
+    );
+    buffer.resize(buffer.len() + 256, 0);
+    let unchanged = buffer.clone();
+    assert_eq!(
+        fdt::apply_overlay(&mut buffer, &dtbo),
+        Err(FdtError::InvalidOverlay("symbol not found"))
+    );
+    assert_eq!(buffer, unchanged);
+
+    // Shifting a local reference past the phandles of the base overflows.
+    let mut base = base_tree();
+    base.find_node_mut("/soc/uart@1000")
+        .unwrap()
+        .add_property(u32_prop("phandle", 0xffff_fff0));
+    let mut buffer = base.to_dtb();
+    buffer.resize(buffer.len() + 256, 0);
+    let mut overlay = DeviceTree::from_fdt(&Fdt::new(&dtbo).unwrap()).unwrap();
+    overlay
+        .find_node_mut("/fragment@1/__overlay__/consumer")
+        .unwrap()
+        .add_property(u32_prop("dev", 0x20));
+    assert_eq!(
+        fdt::apply_overlay(&mut buffer, &overlay.to_dtb()),
+        Err(FdtError::InvalidOverlay("phandle overflow"))
+    );
+}
+
+#[test]
+fn generate_fixups() {
+    let mut overlay = DeviceTree::new_overlay();
+    let mut dev = DeviceTreeNode::new("dev");
+    dev.add_label("dev");
+    let mut fragment = DeviceTreeNode::builder("fragment@0")
+        .property(u32_prop("target", 0))
+        .child(
+            DeviceTreeNode::builder("__overlay__")
+                .property(str_prop("status", "okay"))
+                .child(dev)
+                .build(),
+        )
+        .build();
+    fragment.add_phandle_reference("target", 0, "uart");
+    overlay.root.add_child(fragment);
+    let mut consumer = DeviceTreeNode::new("consumer");
+    consumer.add_property(u32_prop("dev", 0));
+    consumer.add_phandle_reference("dev", 0, "dev");
+    overlay.root.add_child(
+        DeviceTreeNode::builder("fragment@1")
+            .property(str_prop("target-path", "/"))
+            .child(
+                DeviceTreeNode::builder("__overlay__")
+                    .child(consumer)
+                    .build(),
+            )
+            .build(),
+    );
+    let dtbo = overlay.to_dtb();
+
+    let fdt = Fdt::new(&dtbo).unwrap();
+    let fixups = fdt.find_node("/__fixups__").unwrap().unwrap();
+    assert_eq!(fixups.required_str("uart").unwrap(), "/fragment@0:target:0");
+    let local_fixups = fdt
+        .find_node("/__local_fixups__/fragment@1/__overlay__/consumer")
+        .unwrap()
+        .unwrap();
+    assert_eq!(local_fixups.required_u32("dev").unwrap(), 0);
+    let symbols = fdt.find_node("/__symbols__").unwrap().unwrap();
+    assert_eq!(
+        symbols.required_str("dev").unwrap(),
+        "/fragment@0/__overlay__/dev"
+    );
+    assert!(DeviceTree::from_fdt(&fdt).unwrap().is_overlay());
+
+    let mut tree = base_tree();
+    tree.apply_overlay(&fdt).unwrap();
+    let uart = tree.find_node_mut("/soc/uart@1000").unwrap();
+    assert_eq!(uart.property("status").unwrap().as_str(), Ok("okay"));
+    let phandle = uart.child("dev").unwrap().property("phandle").unwrap();
+    assert_eq!(phandle.as_u32(), Ok(3));
+    let consumer = tree.find_node_mut("/consumer").unwrap();
+    assert_eq!(consumer.property("dev").unwrap().as_u32(), Ok(3));
+}
+
+#[test]
+fn overlay_builder() {
+    let mut overlay = Overlay::new();
+    overlay.add_fragment(
+        OverlayTarget::Label("uart".into()),
+        DeviceTreeNode::builder("uart")
+            .property(str_prop("status", "okay"))
+            .build(),
+    );
+    let root = overlay.add_fragment(OverlayTarget::Path("/".into()), DeviceTreeNode::new("/"));
+    root.add_child(
+        DeviceTreeNode::builder("consumer")
+            .property(u32_prop("value", 1))
+            .build(),
+    );
+    let dtbo = overlay.to_dtbo();
+
+    let fdt = Fdt::new(&dtbo).unwrap();
+    let fragment = fdt.find_node("/fragment@1").unwrap().unwrap();
+    assert_eq!(fragment.required_str("target-path").unwrap(), "/");
+    let mut tree = base_tree();
+    tree.apply_overlay(&fdt).unwrap();
+    let uart = tree.find_node_mut("/soc/uart@1000").unwrap();
+    assert_eq!(uart.property("status").unwrap().as_str(), Ok("okay"));
+    let consumer = tree.find_node_mut("/consumer").unwrap();
+    assert_eq!(consumer.property("value").unwrap().as_u32(), Ok(1));
+}
+
+#[test]
+fn validate_overlay() {
+    let mut base = base_tree();
+    base.find_node_mut("/soc/uart@1000")
+        .unwrap()
+        .add_property(u32_prop("#clock-cells", 1));
+    let dtb = base.to_dtb();
+    let base = Fdt::new(&dtb).unwrap();
+
+    let overlay_with_clocks = |clocks: &[u32], references: &[usize]| {
+        let mut overlay = Overlay::new();
+        let mut consumer = DeviceTreeNode::new("consumer");
+        let value: Vec<u8> = clocks.iter().flat_map(|cell| cell.to_be_bytes()).collect();
+        consumer.add_property(DeviceTreeProperty::new("clocks", value));
+        for &offset in references {
+            consumer.add_phandle_reference("clocks", offset, "uart");
+        }
+        overlay
+            .add_fragment(OverlayTarget::Path("/".into()), DeviceTreeNode::new("/"))
+            .add_child(consumer);
+        overlay
+    };
+    assert_eq!(overlay_with_clocks(&[0, 5], &[0]).validate(&base), Ok(()));
+    let error = overlay_with_clocks(&[0], &[0]).validate(&base).unwrap_err();
+    assert_eq!(
+        error,
+        OverlayError::CellCountMismatch {
+            node: "/fragment@0/__overlay__/consumer".into(),
+            property: "clocks".into(),
+        }
+    );
+    assert_eq!(error.code(), 306);
+    assert!(matches!(
+        overlay_with_clocks(&[0, 0], &[0, 4]).validate(&base),
+        Err(OverlayError::CellCountMismatch { .. })
+    ));
+
+    let mut overlay = Overlay::new();
+    overlay.add_fragment(
+        OverlayTarget::Path("/missing".into()),
+        DeviceTreeNode::new("missing"),
+    );
+    assert_eq!(
+        overlay.validate(&base),
+        Err(OverlayError::TargetNotFound {
+            fragment: "fragment@0".into()
+        })
+    );
+
+    // Target paths must name nodes exactly, as when the overlay is applied.
+    let mut overlay = Overlay::new();
+    overlay.add_fragment(
+        OverlayTarget::Path("/soc/uart".into()),
+        DeviceTreeNode::new("uart"),
+    );
+    let mut tree = base_tree();
+    assert!(matches!(
+        tree.apply_overlay(&Fdt::new(&overlay.to_dtbo()).unwrap()),
+        Err(OverlayError::TargetNotFound { .. })
+    ));
+    assert_eq!(
+        overlay.validate(&base),
+        Err(OverlayError::TargetNotFound {
+            fragment: "fragment@0".into()
+        })
+    );
+
+    let mut overlay = Overlay::new();
+    overlay.add_fragment(
+        OverlayTarget::Label("missing".into()),
+        DeviceTreeNode::new("missing"),
+    );
+    assert_eq!(
+        overlay.validate(&base),
+        Err(OverlayError::UnresolvedSymbol {
+            label: "missing".into()
+        })
+    );
+
+    // Fragment targets are only looked up in the base tree, even if the
+    // overlay has a node with the same label.
+    let mut overlay = Overlay::new();
+    let mut local = DeviceTreeNode::new("local");
+    local.add_label("local");
+    overlay
+        .add_fragment(OverlayTarget::Path("/".into()), DeviceTreeNode::new("/"))
+        .add_child(local);
+    overlay.add_fragment(
+        OverlayTarget::Label("local".into()),
+        DeviceTreeNode::new("local"),
+    );
+    assert_eq!(
+        overlay.validate(&base),
+        Err(OverlayError::UnresolvedSymbol {
+            label: "local".into()
+        })
+    );
+    assert!(
+        base_tree()
+            .apply_overlay(&Fdt::new(&overlay.to_dtbo()).unwrap())
+            .is_err()
+    );
+}
+
+#[test]
+fn revert_overlay() {
+    let dtbo = overlay_dtbo();
+    let overlay = Fdt::new(&dtbo).unwrap();
+    let mut tree = base_tree();
+    let changeset = tree.apply_overlay_with_changeset(&overlay).unwrap();
+    assert!(
+        changeset
+            .added_nodes()
+            .map(|path| path.as_str())
+            .eq(["/soc/uart@1000/dev", "/consumer"])
+    );
+    let (path, name, old) = changeset.changed_properties().next().unwrap();
+    assert_eq!((path.as_str(), name), ("/soc/uart@1000", "status"));
+    assert_eq!(old.unwrap().as_str(), Ok("disabled"));
+
+    let mut reverted = tree.clone();
+    reverted.revert(changeset.clone()).unwrap();
+    assert_eq!(reverted, base_tree());
+
+    tree.root.remove_child("consumer");
+    let unchanged = tree.clone();
+    let error = tree.revert(changeset).unwrap_err();
+    assert_eq!(
+        error,
+        OverlayError::StaleChangeset {
+            path: "/consumer".into()
+        }
+    );
+    assert_eq!(error.code(), 307);
+    assert_eq!(tree, unchanged);
+}
+
+#[test]
+fn apply_overlays_atomically() {
+    let dtbo = overlay_dtbo();
+    let mut dev_overlay = Overlay::new();
+    dev_overlay.add_fragment(
+        OverlayTarget::Label("dev".into()),
+        DeviceTreeNode::builder("dev")
+            .property(str_prop("status", "okay"))
+            .build(),
+    );
+    let dev_dtbo = dev_overlay.to_dtbo();
+    let mut missing_overlay = Overlay::new();
+    missing_overlay.add_fragment(
+        OverlayTarget::Path("/missing".into()),
+        DeviceTreeNode::new("missing"),
+    );
+    let missing_dtbo = missing_overlay.to_dtbo();
+
+    let overlay = Fdt::new(&dtbo).unwrap();
+    let dev_overlay = Fdt::new(&dev_dtbo).unwrap();
+    let missing_overlay = Fdt::new(&missing_dtbo).unwrap();
+
+    let mut tree = base_tree();
+    tree.apply_overlays(&[overlay, dev_overlay]).unwrap();
+    let dev = tree.find_node_mut("/soc/uart@1000/dev").unwrap();
+    assert_eq!(dev.property("status").unwrap().as_str(), Ok("okay"));
+
+    let mut tree = base_tree();
+    assert_eq!(
+        tree.apply_overlays(&[overlay, dev_overlay, missing_overlay]),
+        Err(OverlayError::TargetNotFound {
+            fragment: "fragment@0".into()
+        })
+    );
+    assert_eq!(tree, base_tree());
+}
@@ -0,0 +1,173 @@
+// Copyright 2025 Google LLC
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// https://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or https://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+#![cfg(all(feature = "query", feature = "write"))]
+
+use dtoolkit::error::FdtErrorKind;
+use dtoolkit::fdt::{Fdt, MAX_DESCENDANTS_DEPTH};
+use dtoolkit::model::{DeviceTree, DeviceTreeNode, DeviceTreeProperty};
+use dtoolkit::query::{Query, QueryError};
+
+fn tree() -> DeviceTree {
+    let uart = |name: &str, compatible: &str, status: Option<&str>| {
+        let mut node = DeviceTreeNode::builder(name)
+            .property(DeviceTreeProperty::new("compatible", compatible))
+            .property(DeviceTreeProperty::new(
+                "clock-frequency",
+                0x1c2_0000u32.to_be_bytes(),
+            ))
+            .build();
+        if let Some(status) = status {
+            node.add_property(DeviceTreeProperty::new("status", status));
+        }
+        node
+    };
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(
+        DeviceTreeNode::builder("soc")
+            .child(uart("serial@1000", "vendor,uart\0ns16550a\0", None))
+            .child(uart("serial@2000", "ns16550a\0", Some("disabled\0")))
+            .child(
+                DeviceTreeNode::builder("bus@3000")
+                    .child(uart("serial@3000", "ns16550a\0", Some("okay\0")))
+                    .child(uart("serial@4000", "other\0", Some("okay\0")))
+                    .build(),
+            )
+            .build(),
+    );
+    tree
+}
+
+#[test]
+fn select() {
+    let tree = tree();
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+    // Evaluates the query on both the blob and the model, checking that they
+    // agree, and returns the paths of the selected nodes.
+    let select = |query: &str| -> Vec<String> {
+        let query = Query::new(query).unwrap();
+        let nodes = query.select(fdt).unwrap();
+        let names: Vec<&str> = nodes.iter().map(|node| node.name().unwrap()).collect();
+        let tree_names: Vec<&str> = query
+            .select_tree(&tree)
+            .iter()
+            .map(|node| node.name())
+            .collect();
+        assert_eq!(names, tree_names);
+        nodes
+            .iter()
+            .map(|node| node.path().unwrap().to_string())
+            .collect()
+    };
+
+    assert_eq!(select("/"), ["/"]);
+    assert_eq!(select("/soc"), ["/soc"]);
+    assert_eq!(
+        select("/soc/serial"),
+        ["/soc/serial@1000", "/soc/serial@2000"]
+    );
+    assert_eq!(select("/soc/*/serial@3*"), ["/soc/bus@3000/serial@3000"]);
+    assert_eq!(
+        select("//serial[@compatible='ns16550a']"),
+        [
+            "/soc/serial@1000",
+            "/soc/serial@2000",
+            "/soc/bus@3000/serial@3000"
+        ]
+    );
+    assert_eq!(
+        select("//*[@compatible = \"ns16550a\" and @status = 'okay']"),
+        ["/soc/bus@3000/serial@3000"]
+    );
+    assert_eq!(
+        select("//*[@compatible='ns16550a'][@status != 'disabled']"),
+        ["/soc/serial@1000", "/soc/bus@3000/serial@3000"]
+    );
+    assert_eq!(
+        select("//*[not(@status) or @compatible='other']"),
+        [
+            "/soc",
+            "/soc/serial@1000",
+            "/soc/bus@3000",
+            "/soc/bus@3000/serial@4000"
+        ]
+    );
+    assert_eq!(
+        select("//bus//*[@clock-frequency = 0x1c20000]"),
+        ["/soc/bus@3000/serial@3000", "/soc/bus@3000/serial@4000"]
+    );
+    assert_eq!(select("//*[@clock-frequency = 29491200]").len(), 4);
+    assert_eq!(select("//*[@clock-frequency = 1]").len(), 0);
+    assert_eq!(
+        select(
+            "//soc//serial[(@status = 'okay' or @status = 'disabled') and not(@compatible = 'other')]"
+        ),
+        ["/soc/serial@2000", "/soc/bus@3000/serial@3000"]
+    );
+    assert!(select("/serial").is_empty());
+}
+
+#[test]
+fn invalid() {
+    let error = |query: &str| Query::new(query).unwrap_err();
+    let QueryError {
+        offset, message, ..
+    } = error("soc");
+    assert_eq!((offset, message), (0, "expected `/`"));
+    assert_eq!(error("soc").code(), 400);
+    assert_eq!(error("//").offset, 2);
+    assert_eq!(error("/soc/").message, "expected a node name");
+    assert_eq!(error("/soc[@status").message, "expected `]`");
+    assert_eq!(
+        error("/soc[@status = 'okay]").message,
+        "unterminated string"
+    );
+    assert_eq!(
+        error("/soc[@status = okay]").message,
+        "expected a string or number"
+    );
+    assert_eq!(error("/soc[status]").message, "expected `@`, `(` or `not`");
+    assert_eq!(error("/soc[not @status]").message, "expected `(`");
+    assert_eq!(error("/soc[(@status]").message, "expected `)`");
+    assert_eq!(error("/soc]").offset, 4);
+    assert_eq!(error(&"/a".repeat(64)).message, "too many steps");
+    assert!(Query::new(&"/a".repeat(63)).is_ok());
+    assert!("//*[@a and(@b)]".parse::<Query>().is_ok());
+
+    let nested = |depth| format!("//*[{}@a{}]", "not(".repeat(depth), ")".repeat(depth));
+    assert!(Query::new(&nested(32)).is_ok());
+    let error = error(&nested(33));
+    assert_eq!(error.message, "predicate nested too deeply");
+    assert_eq!(error.code(), 410);
+    let chain = format!("//*[@a{}]", " and @a or @b".repeat(10_000));
+    assert!(Query::new(&chain).is_ok());
+}
+
+#[test]
+fn too_deep() {
+    let mut deep = DeviceTreeNode::new("leaf");
+    for _ in 0..MAX_DESCENDANTS_DEPTH {
+        deep = DeviceTreeNode::builder("deep").child(deep).build();
+    }
+    let mut tree = DeviceTree::new();
+    tree.root.add_child(deep);
+    let dtb = tree.to_dtb();
+    let fdt = Fdt::new(&dtb).unwrap();
+
+    // The leaf is too deep to be walked from the root.
+    let query = Query::new("//*").unwrap();
+    let error = query.select(fdt).unwrap_err();
+    assert_eq!(error.kind, FdtErrorKind::TooDeep(MAX_DESCENDANTS_DEPTH));
+    assert_eq!(
+        Query::new("/deep/deep").unwrap().select(fdt).unwrap().len(),
+        1
+    );
+
+    assert_eq!(query.select_tree(&tree).len(), MAX_DESCENDANTS_DEPTH + 1);
+}